@@ -0,0 +1,712 @@
+//! # Asynchronous validation
+//!
+//! ## Feature
+//!
+//! Enable the `async_validate` feature to use `ValidAsync<E>`.
+//!
+//! ## Usage
+//!
+//! Some rules can only be checked against external data, e.g. a name must be unique within a store.
+//! Such rules are expressed by implementing `HasAsyncValidate` for your data type:
+//!
+//! 1. Choose the `Context` the rule needs. It is extracted from the request parts, so it can be the router
+//!    state (`State<T>`), anything derived from the request (e.g. a tenant id taken from a header),
+//!    or a tuple of these.
+//! 2. Implement `validate_async`, returning an error which implements `IntoResponse` when the rule fails.
+//! 3. In your handler function, use `ValidAsync<E>` as the extractor, e.g. `ValidAsync<Json<T>>`.
+//!
+//! `ValidAsync` runs the inner extractor first, then extracts the context and awaits `validate_async`.
+//! It can be combined with the synchronous extractors, e.g. `ValidAsync<Valid<Json<T>>>`
+//! runs the synchronous rules before the asynchronous ones.
+//!
+//! ## Timeouts
+//!
+//! To bound how long `validate_async` may take, use `TimedValidAsync<E>` instead, and make an `AsyncTimeout`
+//! available from your router state (implement `FromRef<YourState>` for it, or use it as the state directly).
+//! A validation which does not complete in time is abandoned, and the request is rejected with the status and
+//! message of the `AsyncTimeout` (`504 Gateway Timeout` by default), so clients can tell it apart from a failed
+//! validation and retry.
+//!
+//! ## Example
+//!
+//! Names are unique per tenant, the tenant being identified by the `X-Tenant-Id` header:
+//!
+//! ```no_run
+//! use std::collections::{HashMap, HashSet};
+//! use std::sync::{Arc, Mutex};
+//! use axum::async_trait;
+//! use axum::extract::{FromRequestParts, State};
+//! use axum::http::request::Parts;
+//! use axum::http::StatusCode;
+//! use axum::routing::post;
+//! use axum::{Json, Router};
+//! use axum_valid::async_validate::{HasAsyncValidate, ValidAsync};
+//! use serde::Deserialize;
+//!
+//! #[derive(Clone, Default)]
+//! pub struct Names(Arc<Mutex<HashMap<String, HashSet<String>>>>);
+//!
+//! pub struct Tenant(String);
+//!
+//! #[async_trait]
+//! impl<S: Send + Sync> FromRequestParts<S> for Tenant {
+//!     type Rejection = StatusCode;
+//!
+//!     async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+//!         let tenant = parts.headers.get("x-tenant-id").and_then(|v| v.to_str().ok());
+//!         tenant.map(|t| Tenant(t.to_owned())).ok_or(StatusCode::UNAUTHORIZED)
+//!     }
+//! }
+//!
+//! #[derive(Deserialize)]
+//! pub struct Project {
+//!     pub name: String,
+//! }
+//!
+//! #[async_trait]
+//! impl HasAsyncValidate for Project {
+//!     type Context = (State<Names>, Tenant);
+//!     type Error = (StatusCode, &'static str);
+//!
+//!     async fn validate_async(&self, (names, tenant): &Self::Context) -> Result<(), Self::Error> {
+//!         let names = names.0 .0.lock().expect("poisoned");
+//!         match names.get(&tenant.0) {
+//!             Some(taken) if taken.contains(&self.name) => {
+//!                 Err((StatusCode::CONFLICT, "name is already taken"))
+//!             }
+//!             _ => Ok(()),
+//!         }
+//!     }
+//! }
+//!
+//! async fn handler(ValidAsync(Json(project)): ValidAsync<Json<Project>>) {
+//!     assert!(!project.name.is_empty());
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new()
+//! #         .route("/projects", post(handler))
+//! #         .with_state(Names::default());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::HasValidate;
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::time::Duration;
+
+/// Trait for data types with validation rules which have to be awaited.
+///
+/// Data types implementing this trait can be validated by `ValidAsync`.
+///
+#[async_trait]
+pub trait HasAsyncValidate {
+    /// What the rules are checked against, extracted from the request parts, e.g. `State<T>`
+    type Context: Send + Sync;
+    /// Error returned when the rules are not satisfied
+    type Error: IntoResponse;
+    /// Check the rules against the context
+    async fn validate_async(&self, context: &Self::Context) -> Result<(), Self::Error>;
+}
+
+/// # `ValidAsync` data extractor
+///
+/// `ValidAsync` runs the inner extractor, then awaits the `HasAsyncValidate` rules
+/// of its inner data.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidAsync<E>(pub E);
+
+impl<E> Deref for ValidAsync<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for ValidAsync<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for ValidAsync<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> ValidAsync<E> {
+    /// Consumes the `ValidAsync` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for ValidAsync<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `ValidAsyncRejection` is returned when the `ValidAsync` extractor fails.
+///
+#[derive(Debug)]
+pub enum ValidAsyncRejection<V, C, E> {
+    /// `Valid` variant captures errors returned by `validate_async`.
+    Valid(V),
+    /// `Context` variant captures errors raised while extracting the validation context.
+    Context(C),
+    /// `Inner` variant represents potential errors that might occur within the inner extractor.
+    Inner(E),
+}
+
+impl<V: Display, C: Display, E: Display> Display for ValidAsyncRejection<V, C, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidAsyncRejection::Valid(error) => write!(f, "{error}"),
+            ValidAsyncRejection::Context(error) => write!(f, "{error}"),
+            ValidAsyncRejection::Inner(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl<V, C, E> Error for ValidAsyncRejection<V, C, E>
+where
+    V: Error + 'static,
+    C: Error + 'static,
+    E: Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ValidAsyncRejection::Valid(error) => Some(error),
+            ValidAsyncRejection::Context(error) => Some(error),
+            ValidAsyncRejection::Inner(error) => Some(error),
+        }
+    }
+}
+
+impl<V: IntoResponse, C: IntoResponse, E: IntoResponse> IntoResponse
+    for ValidAsyncRejection<V, C, E>
+{
+    fn into_response(self) -> Response {
+        match self {
+            ValidAsyncRejection::Valid(error) => error.into_response(),
+            ValidAsyncRejection::Context(error) => error.into_response(),
+            ValidAsyncRejection::Inner(error) => error.into_response(),
+        }
+    }
+}
+
+type Rejection<Extractor, State, Inner> = ValidAsyncRejection<
+    <<Extractor as HasValidate>::Validate as HasAsyncValidate>::Error,
+    <<<Extractor as HasValidate>::Validate as HasAsyncValidate>::Context as FromRequestParts<
+        State,
+    >>::Rejection,
+    Inner,
+>;
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for ValidAsync<Extractor>
+where
+    State: Send + Sync,
+    Extractor: HasValidate + FromRequest<State> + Send,
+    Extractor::Validate: HasAsyncValidate + Sync,
+    <Extractor::Validate as HasAsyncValidate>::Context: FromRequestParts<State>,
+{
+    type Rejection = Rejection<Extractor, State, <Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let (parts, body) = req.into_parts();
+        let mut context_parts = parts.clone();
+        let inner = Extractor::from_request(Request::from_parts(parts, body), state)
+            .await
+            .map_err(ValidAsyncRejection::Inner)?;
+        let context = FromRequestParts::from_request_parts(&mut context_parts, state)
+            .await
+            .map_err(ValidAsyncRejection::Context)?;
+        inner
+            .get_validate()
+            .validate_async(&context)
+            .await
+            .map_err(ValidAsyncRejection::Valid)?;
+        Ok(ValidAsync(inner))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequestParts<State> for ValidAsync<Extractor>
+where
+    State: Send + Sync,
+    Extractor: HasValidate + FromRequestParts<State> + Send,
+    Extractor::Validate: HasAsyncValidate + Sync,
+    <Extractor::Validate as HasAsyncValidate>::Context: FromRequestParts<State>,
+{
+    type Rejection = Rejection<Extractor, State, <Extractor as FromRequestParts<State>>::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let inner = Extractor::from_request_parts(parts, state)
+            .await
+            .map_err(ValidAsyncRejection::Inner)?;
+        let context = FromRequestParts::from_request_parts(parts, state)
+            .await
+            .map_err(ValidAsyncRejection::Context)?;
+        inner
+            .get_validate()
+            .validate_async(&context)
+            .await
+            .map_err(ValidAsyncRejection::Valid)?;
+        Ok(ValidAsync(inner))
+    }
+}
+
+/// Time limit of `TimedValidAsync`, with the response sent when it is exceeded.
+///
+/// `TimedValidAsync` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsyncTimeout {
+    /// How long `validate_async` may take.
+    pub duration: Duration,
+    /// Status of the response sent when `validate_async` does not complete in time.
+    pub status: StatusCode,
+    /// Message of the response sent when `validate_async` does not complete in time.
+    pub message: Cow<'static, str>,
+}
+
+impl AsyncTimeout {
+    /// Create an `AsyncTimeout` responding with `504 Gateway Timeout` once `duration` has elapsed.
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            status: StatusCode::GATEWAY_TIMEOUT,
+            message: Cow::Borrowed("Validation timed out"),
+        }
+    }
+
+    /// Sets the status of the response sent on timeout.
+    pub fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Sets the message of the response sent on timeout.
+    pub fn with_message(mut self, message: impl Into<Cow<'static, str>>) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    async fn run<F: Future>(&self, validation: F) -> Result<F::Output, Self> {
+        tokio::time::timeout(self.duration, validation)
+            .await
+            .map_err(|_| self.clone())
+    }
+}
+
+impl Display for AsyncTimeout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for AsyncTimeout {}
+
+impl IntoResponse for AsyncTimeout {
+    fn into_response(self) -> Response {
+        (self.status, self.message).into_response()
+    }
+}
+
+/// # `TimedValidAsync` data extractor
+///
+/// `TimedValidAsync` works like `ValidAsync`, but rejects the request with the `AsyncTimeout`
+/// taken from the state when `validate_async` does not complete in time.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimedValidAsync<E>(pub E);
+
+impl<E> Deref for TimedValidAsync<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for TimedValidAsync<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for TimedValidAsync<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> TimedValidAsync<E> {
+    /// Consumes the `TimedValidAsync` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for TimedValidAsync<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `TimedValidAsyncRejection` is returned when the `TimedValidAsync` extractor fails.
+///
+#[derive(Debug)]
+pub enum TimedValidAsyncRejection<V, C, E> {
+    /// `Timeout` variant is returned when `validate_async` does not complete in time.
+    Timeout(AsyncTimeout),
+    /// `ValidAsync` variant captures the errors `ValidAsync` would have returned.
+    ValidAsync(ValidAsyncRejection<V, C, E>),
+}
+
+impl<V, C, E> From<ValidAsyncRejection<V, C, E>> for TimedValidAsyncRejection<V, C, E> {
+    fn from(rejection: ValidAsyncRejection<V, C, E>) -> Self {
+        TimedValidAsyncRejection::ValidAsync(rejection)
+    }
+}
+
+impl<V: Display, C: Display, E: Display> Display for TimedValidAsyncRejection<V, C, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimedValidAsyncRejection::Timeout(timeout) => write!(f, "{timeout}"),
+            TimedValidAsyncRejection::ValidAsync(rejection) => write!(f, "{rejection}"),
+        }
+    }
+}
+
+impl<V, C, E> Error for TimedValidAsyncRejection<V, C, E>
+where
+    V: Error + 'static,
+    C: Error + 'static,
+    E: Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TimedValidAsyncRejection::Timeout(timeout) => Some(timeout),
+            TimedValidAsyncRejection::ValidAsync(rejection) => Some(rejection),
+        }
+    }
+}
+
+impl<V: IntoResponse, C: IntoResponse, E: IntoResponse> IntoResponse
+    for TimedValidAsyncRejection<V, C, E>
+{
+    fn into_response(self) -> Response {
+        match self {
+            TimedValidAsyncRejection::Timeout(timeout) => timeout.into_response(),
+            TimedValidAsyncRejection::ValidAsync(rejection) => rejection.into_response(),
+        }
+    }
+}
+
+type TimedRejection<Extractor, State, Inner> = TimedValidAsyncRejection<
+    <<Extractor as HasValidate>::Validate as HasAsyncValidate>::Error,
+    <<<Extractor as HasValidate>::Validate as HasAsyncValidate>::Context as FromRequestParts<
+        State,
+    >>::Rejection,
+    Inner,
+>;
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for TimedValidAsync<Extractor>
+where
+    State: Send + Sync,
+    AsyncTimeout: FromRef<State>,
+    Extractor: HasValidate + FromRequest<State> + Send,
+    Extractor::Validate: HasAsyncValidate + Sync,
+    <Extractor::Validate as HasAsyncValidate>::Context: FromRequestParts<State>,
+{
+    type Rejection = TimedRejection<Extractor, State, <Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let timeout = AsyncTimeout::from_ref(state);
+        let (parts, body) = req.into_parts();
+        let mut context_parts = parts.clone();
+        let inner = Extractor::from_request(Request::from_parts(parts, body), state)
+            .await
+            .map_err(ValidAsyncRejection::Inner)?;
+        let context = FromRequestParts::from_request_parts(&mut context_parts, state)
+            .await
+            .map_err(ValidAsyncRejection::Context)?;
+        timeout
+            .run(inner.get_validate().validate_async(&context))
+            .await
+            .map_err(TimedValidAsyncRejection::Timeout)?
+            .map_err(ValidAsyncRejection::Valid)?;
+        Ok(TimedValidAsync(inner))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequestParts<State> for TimedValidAsync<Extractor>
+where
+    State: Send + Sync,
+    AsyncTimeout: FromRef<State>,
+    Extractor: HasValidate + FromRequestParts<State> + Send,
+    Extractor::Validate: HasAsyncValidate + Sync,
+    <Extractor::Validate as HasAsyncValidate>::Context: FromRequestParts<State>,
+{
+    type Rejection =
+        TimedRejection<Extractor, State, <Extractor as FromRequestParts<State>>::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let timeout = AsyncTimeout::from_ref(state);
+        let inner = Extractor::from_request_parts(parts, state)
+            .await
+            .map_err(ValidAsyncRejection::Inner)?;
+        let context = FromRequestParts::from_request_parts(parts, state)
+            .await
+            .map_err(ValidAsyncRejection::Context)?;
+        timeout
+            .run(inner.get_validate().validate_async(&context))
+            .await
+            .map_err(TimedValidAsyncRejection::Timeout)?
+            .map_err(ValidAsyncRejection::Valid)?;
+        Ok(TimedValidAsync(inner))
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::Json;
+    use serde::Deserialize;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct Names(Arc<HashSet<(String, String)>>);
+
+    struct Tenant(String);
+
+    #[async_trait]
+    impl<S: Send + Sync> FromRequestParts<S> for Tenant {
+        type Rejection = StatusCode;
+
+        async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+            parts
+                .headers
+                .get("x-tenant-id")
+                .and_then(|value| value.to_str().ok())
+                .map(|tenant| Tenant(tenant.to_owned()))
+                .ok_or(StatusCode::UNAUTHORIZED)
+        }
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Project {
+        name: String,
+    }
+
+    #[async_trait]
+    impl HasAsyncValidate for Project {
+        type Context = (State<Names>, Tenant);
+        type Error = StatusCode;
+
+        async fn validate_async(&self, (names, tenant): &Self::Context) -> Result<(), Self::Error> {
+            if names.0 .0.contains(&(tenant.0.clone(), self.name.clone())) {
+                return Err(StatusCode::CONFLICT);
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn valid_async_tenant_scoped_unique_name() {
+        let names = Names(Arc::new(HashSet::from([(
+            String::from("acme"),
+            String::from("apollo"),
+        )])));
+        let request = |tenant: Option<&str>, name: &str| {
+            let mut builder = Request::builder()
+                .uri("/")
+                .header("content-type", "application/json");
+            if let Some(tenant) = tenant {
+                builder = builder.header("x-tenant-id", tenant);
+            }
+            builder
+                .body(Body::from(format!(r#"{{ "name": "{name}" }}"#)))
+                .expect("invalid request")
+        };
+
+        let rejection =
+            ValidAsync::<Json<Project>>::from_request(request(Some("acme"), "apollo"), &names)
+                .await
+                .expect_err("name taken within the tenant accepted");
+        assert_eq!(rejection.into_response().status(), StatusCode::CONFLICT);
+
+        let ValidAsync(Json(project)) =
+            ValidAsync::<Json<Project>>::from_request(request(Some("globex"), "apollo"), &names)
+                .await
+                .expect("name taken by another tenant rejected");
+        assert_eq!(project.name, "apollo");
+
+        assert!(
+            ValidAsync::<Json<Project>>::from_request(request(Some("acme"), "gemini"), &names)
+                .await
+                .is_ok()
+        );
+
+        let rejection = ValidAsync::<Json<Project>>::from_request(request(None, "gemini"), &names)
+            .await
+            .expect_err("missing tenant accepted");
+        assert!(matches!(rejection, ValidAsyncRejection::Context(_)));
+        assert_eq!(rejection.into_response().status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[derive(Clone, Default)]
+    struct Usernames(Arc<std::sync::Mutex<HashSet<String>>>);
+
+    #[derive(Debug, Deserialize)]
+    struct Signup {
+        username: String,
+    }
+
+    #[async_trait]
+    impl HasAsyncValidate for Signup {
+        type Context = State<Usernames>;
+        type Error = (StatusCode, &'static str);
+
+        async fn validate_async(&self, usernames: &Self::Context) -> Result<(), Self::Error> {
+            let usernames = usernames.0 .0.lock().expect("poisoned");
+            if usernames.contains(&self.username) {
+                return Err((StatusCode::BAD_REQUEST, "username is already taken"));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn valid_async_unique_username_from_state() {
+        let usernames = Usernames::default();
+        usernames
+            .0
+            .lock()
+            .expect("poisoned")
+            .insert(String::from("ferris"));
+        let request = |username: &str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(format!(r#"{{ "username": "{username}" }}"#)))
+                .expect("invalid request")
+        };
+
+        let rejection = ValidAsync::<Json<Signup>>::from_request(request("ferris"), &usernames)
+            .await
+            .expect_err("taken username accepted");
+        assert!(matches!(rejection, ValidAsyncRejection::Valid(_)));
+        assert_eq!(rejection.into_response().status(), StatusCode::BAD_REQUEST);
+
+        let ValidAsync(Json(signup)) =
+            ValidAsync::<Json<Signup>>::from_request(request("corro"), &usernames)
+                .await
+                .expect("free username rejected");
+        assert_eq!(signup.username, "corro");
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Lookup {
+        delay_ms: u64,
+        found: bool,
+    }
+
+    #[async_trait]
+    impl HasAsyncValidate for Lookup {
+        type Context = ();
+        type Error = StatusCode;
+
+        async fn validate_async(&self, _: &Self::Context) -> Result<(), Self::Error> {
+            tokio::time::sleep(Duration::from_millis(self.delay_ms)).await;
+            if self.found {
+                Ok(())
+            } else {
+                Err(StatusCode::BAD_REQUEST)
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn timed_valid_async_distinguishes_timeouts() {
+        let timeout = AsyncTimeout::new(Duration::from_millis(50))
+            .with_message("Lookup service unavailable, retry later");
+        let request = |delay_ms: u64, found: bool| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(format!(
+                    r#"{{ "delay_ms": {delay_ms}, "found": {found} }}"#
+                )))
+                .expect("invalid request")
+        };
+
+        let TimedValidAsync(Json(lookup)) =
+            TimedValidAsync::<Json<Lookup>>::from_request(request(0, true), &timeout)
+                .await
+                .expect("fast valid lookup rejected");
+        assert!(lookup.found);
+
+        let rejection = TimedValidAsync::<Json<Lookup>>::from_request(request(0, false), &timeout)
+            .await
+            .expect_err("failed lookup accepted");
+        assert_eq!(rejection.into_response().status(), StatusCode::BAD_REQUEST);
+
+        let rejection =
+            TimedValidAsync::<Json<Lookup>>::from_request(request(5_000, true), &timeout)
+                .await
+                .expect_err("slow lookup accepted");
+        assert!(matches!(rejection, TimedValidAsyncRejection::Timeout(_)));
+        let response = rejection.into_response();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("unreadable body");
+        assert_eq!(body, "Lookup service unavailable, retry later");
+
+        let timeout = timeout.with_status(StatusCode::SERVICE_UNAVAILABLE);
+        let rejection =
+            TimedValidAsync::<Json<Lookup>>::from_request(request(5_000, true), &timeout)
+                .await
+                .expect_err("slow lookup accepted");
+        assert_eq!(
+            rejection.into_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+}
@@ -0,0 +1,297 @@
+//! # Audit log of validated writes
+//!
+//! ## Feature
+//!
+//! Enable the `audit` feature to use `Audited<E>`.
+//!
+//! ## Usage
+//!
+//! `Audited<E>` validates like `Valid<E>`, and records an `AuditEvent` for every request which passes validation.
+//!
+//! 1. Implement `AuditSink` to send events wherever your audit log lives.
+//! 2. Make an `Auditor` wrapping your sink available from your router state
+//!    (implement `FromRef<YourState>` for it, or use it as the state directly).
+//! 3. Implement `Auditable` for your data type, returning a summary with sensitive values redacted.
+//! 4. In your handler function, use `Audited<E>` as the extractor, e.g. `Audited<Json<T>>`.
+//!
+//! The actor of an event is resolved by the `Auditor`. By default it is the `AuditActor` found in the request
+//! extensions (usually inserted by an authentication middleware), or `anonymous`. Use `Auditor::with_actor`
+//! to resolve it differently.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use axum::routing::post;
+//! use axum::Json;
+//! use axum::Router;
+//! use axum_valid::audit::{AuditEvent, AuditSink, Auditable, Audited, Auditor};
+//! use serde::Deserialize;
+//! use validator::Validate;
+//!
+//! struct StdoutSink;
+//!
+//! impl AuditSink for StdoutSink {
+//!     fn record(&self, event: AuditEvent) {
+//!         println!("{} {} {}: {}", event.actor, event.method, event.path, event.summary);
+//!     }
+//! }
+//!
+//! #[derive(Validate, Deserialize)]
+//! pub struct Transfer {
+//!     #[validate(length(equal = 22))]
+//!     pub iban: String,
+//!     #[validate(range(min = 1))]
+//!     pub amount: u64,
+//! }
+//!
+//! impl Auditable for Transfer {
+//!     fn audit_summary(&self) -> String {
+//!         format!("transfer of {} to ****{}", self.amount, &self.iban[18..])
+//!     }
+//! }
+//!
+//! async fn handler(Audited(Json(transfer)): Audited<Json<Transfer>>) {
+//!     assert!(transfer.validate().is_ok());
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new()
+//! #         .route("/transfers", post(handler))
+//! #         .with_state(Auditor::new(StdoutSink));
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::{HasValidate, ValidRejection};
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, Request};
+use axum::http::request::Parts;
+use axum::http::Method;
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::time::SystemTime;
+use validator::Validate;
+
+/// An entry of the audit log, recorded for every request which passes validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEvent {
+    /// Who made the request.
+    pub actor: String,
+    /// Method of the request.
+    pub method: Method,
+    /// Path of the request.
+    pub path: String,
+    /// Redacted summary of the validated data, see `Auditable`.
+    pub summary: String,
+    /// When the request was validated.
+    pub at: SystemTime,
+}
+
+/// Destination of audit events.
+pub trait AuditSink: Send + Sync + 'static {
+    /// Record an event
+    fn record(&self, event: AuditEvent);
+}
+
+/// Trait for data types which can be summarized in the audit log.
+pub trait Auditable {
+    /// Summary of the data, with sensitive values redacted
+    fn audit_summary(&self) -> String;
+}
+
+/// Identity of the caller, inserted into the request extensions by an authentication layer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditActor(pub String);
+
+/// Records audit events into an `AuditSink`.
+///
+/// `Audited` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Clone)]
+pub struct Auditor {
+    sink: Arc<dyn AuditSink>,
+    actor: Arc<dyn Fn(&Parts) -> String + Send + Sync>,
+}
+
+impl Auditor {
+    /// Create an `Auditor` recording events into `sink`.
+    pub fn new(sink: impl AuditSink) -> Self {
+        Self {
+            sink: Arc::new(sink),
+            actor: Arc::new(|parts: &Parts| {
+                parts
+                    .extensions
+                    .get::<AuditActor>()
+                    .map_or_else(|| String::from("anonymous"), |actor| actor.0.clone())
+            }),
+        }
+    }
+
+    /// Resolve the actor of events with `actor` instead of the `AuditActor` extension.
+    pub fn with_actor(mut self, actor: impl Fn(&Parts) -> String + Send + Sync + 'static) -> Self {
+        self.actor = Arc::new(actor);
+        self
+    }
+
+    fn record(&self, parts: &Parts, summary: String) {
+        self.sink.record(AuditEvent {
+            actor: (self.actor)(parts),
+            method: parts.method.clone(),
+            path: parts.uri.path().to_owned(),
+            summary,
+            at: SystemTime::now(),
+        });
+    }
+}
+
+impl Debug for Auditor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Auditor").finish_non_exhaustive()
+    }
+}
+
+/// # `Audited` data extractor
+///
+/// `Audited` validates the inner data like `Valid`, then records an `AuditEvent`
+/// into the `Auditor` taken from the state.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Audited<E>(pub E);
+
+impl<E> Deref for Audited<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for Audited<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for Audited<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> Audited<E> {
+    /// Consumes the `Audited` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for Audited<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for Audited<Extractor>
+where
+    State: Send + Sync,
+    Auditor: FromRef<State>,
+    Extractor: HasValidate + FromRequest<State>,
+    Extractor::Validate: Validate + Auditable,
+{
+    type Rejection = ValidRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let (parts, body) = req.into_parts();
+        let audit_parts = parts.clone();
+        let inner = Extractor::from_request(Request::from_parts(parts, body), state)
+            .await
+            .map_err(ValidRejection::Inner)?;
+        let data = inner.get_validate();
+        data.validate()?;
+        Auditor::from_ref(state).record(&audit_parts, data.audit_summary());
+        Ok(Audited(inner))
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::Json;
+    use serde::Deserialize;
+    use std::sync::Mutex;
+
+    #[derive(Clone, Default)]
+    struct Events(Arc<Mutex<Vec<AuditEvent>>>);
+
+    impl AuditSink for Events {
+        fn record(&self, event: AuditEvent) {
+            self.0.lock().expect("poisoned").push(event);
+        }
+    }
+
+    #[derive(Validate, Deserialize)]
+    struct Login {
+        #[validate(length(min = 1))]
+        user: String,
+        #[validate(length(min = 8))]
+        password: String,
+    }
+
+    impl Auditable for Login {
+        fn audit_summary(&self) -> String {
+            format!("user={} password=***", self.user)
+        }
+    }
+
+    #[tokio::test]
+    async fn audited_write_is_recorded() {
+        let events = Events::default();
+        let auditor = Auditor::new(events.clone());
+        let request = |password: &str| {
+            let mut request = Request::builder()
+                .method(Method::POST)
+                .uri("/login?next=/")
+                .header("content-type", "application/json")
+                .body(Body::from(format!(
+                    r#"{{ "user": "axum", "password": "{password}" }}"#
+                )))
+                .expect("invalid request");
+            request
+                .extensions_mut()
+                .insert(AuditActor(String::from("admin")));
+            request
+        };
+
+        assert!(
+            Audited::<Json<Login>>::from_request(request("short"), &auditor)
+                .await
+                .is_err()
+        );
+        assert!(events.0.lock().expect("poisoned").is_empty());
+
+        assert!(
+            Audited::<Json<Login>>::from_request(request("correct horse"), &auditor)
+                .await
+                .is_ok()
+        );
+        let recorded = events.0.lock().expect("poisoned");
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].actor, "admin");
+        assert_eq!(recorded[0].method, Method::POST);
+        assert_eq!(recorded[0].path, "/login");
+        assert_eq!(recorded[0].summary, "user=axum password=***");
+    }
+}
@@ -0,0 +1,67 @@
+//! Internal helpers for extractors that need to inspect the request body
+//! before handing the request to their inner extractor.
+
+use axum::body::{Body, Bytes};
+use axum::extract::rejection::BytesRejection;
+use axum::extract::{FromRequest, Request};
+
+/// Buffers the request body, returning the bytes and an equivalent request
+/// that can still be passed to an inner extractor.
+pub(crate) async fn buffer<S: Send + Sync>(
+    req: Request,
+    state: &S,
+) -> Result<(Request, Bytes), BytesRejection> {
+    let (parts, body) = req.into_parts();
+    let bytes = Bytes::from_request(Request::from_parts(parts.clone(), body), state).await?;
+    Ok((Request::from_parts(parts, Body::from(bytes.clone())), bytes))
+}
+
+/// Streams the request body (honoring the default body limit), passing every chunk to `inspect`,
+/// and returns an equivalent request that can still be passed to an inner extractor.
+#[cfg(any(feature = "guard_hash", feature = "guard_crc32"))]
+pub(crate) async fn inspect(
+    req: Request,
+    mut inspect: impl FnMut(&[u8]) + Send,
+) -> Result<Request, axum::Error> {
+    use axum::RequestExt;
+    use http_body_util::BodyExt;
+
+    let (parts, mut body) = req.with_limited_body().into_parts();
+    let mut buffer = Vec::new();
+    while let Some(frame) = body.frame().await {
+        if let Ok(data) = frame?.into_data() {
+            inspect(&data);
+            buffer.extend_from_slice(&data);
+        }
+    }
+    Ok(Request::from_parts(parts, Body::from(buffer)))
+}
+
+/// Reads the whole request body (honoring the default body limit), returning the request parts and the bytes.
+#[cfg(any(feature = "guard_compression", feature = "guard_mime"))]
+pub(crate) async fn read(req: Request) -> Result<(axum::http::request::Parts, Bytes), axum::Error> {
+    use axum::RequestExt;
+    use http_body_util::BodyExt;
+
+    let (parts, body) = req.with_limited_body().into_parts();
+    let bytes = body.collect().await?.to_bytes();
+    Ok((parts, bytes))
+}
+
+/// Returns `true` if the error was caused by the request body exceeding the body limit.
+#[cfg(any(
+    feature = "guard_hash",
+    feature = "guard_crc32",
+    feature = "guard_compression",
+    feature = "guard_mime"
+))]
+pub(crate) fn is_length_limit_error(error: &axum::Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(error);
+    while let Some(error) = source {
+        if error.is::<http_body_util::LengthLimitError>() {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
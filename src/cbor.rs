@@ -176,4 +176,36 @@ mod tests {
                 .body(vec)
         }
     }
+
+    #[cfg(feature = "validator")]
+    #[tokio::test]
+    async fn missing_content_type_is_unsupported() {
+        use crate::Valid;
+        use axum::body::Body;
+        use axum::extract::{FromRequest, Request};
+        use axum::response::IntoResponse;
+        use validator::Validate;
+
+        #[derive(Debug, Validate, serde::Deserialize, Serialize)]
+        struct Reading {
+            #[validate(range(min = 0))]
+            value: i32,
+        }
+
+        let mut body = Vec::new();
+        ciborium::ser::into_writer(&Reading { value: 1 }, &mut body)
+            .expect("Failed to serialize parameters to cbor");
+        let request = Request::builder()
+            .uri("/")
+            .body(Body::from(body))
+            .expect("invalid request");
+        let rejection = Valid::<Cbor<Reading>>::from_request(request, &())
+            .await
+            .expect_err("cbor body without content type accepted");
+        assert!(matches!(rejection, crate::ValidRejection::Inner(_)));
+        assert_eq!(
+            rejection.into_response().status(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+    }
 }
@@ -0,0 +1,338 @@
+//! # Validation rules by client IP
+//!
+//! ## Feature
+//!
+//! Enable the `client_ip` feature to use `ValidFromIp<E>`.
+//!
+//! ## Usage
+//!
+//! Some rules depend on where the request comes from, e.g. stricter limits for address ranges known for abuse.
+//! `ValidFromIp` validates with a `ClientSource` as validation context, describing the client IP address
+//! (taken from `ConnectInfo<SocketAddr>`) and whether it belongs to a flagged range.
+//!
+//! 1. Serve your router with `into_make_service_with_connect_info::<SocketAddr>()`.
+//! 2. Make an `IpPolicy` listing the flagged ranges available from your router state
+//!    (implement `FromRef<YourState>` for it, or use it as the state directly).
+//! 3. Declare `#[validate(context = ClientSource)]` on your data type, and use `custom(..., use_context)`
+//!    rules which read the `ClientSource`.
+//! 4. In your handler function, use `ValidFromIp<E>` as the extractor, e.g. `ValidFromIp<Json<T>>`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+//! use axum::routing::post;
+//! use axum::Json;
+//! use axum::Router;
+//! use axum_valid::client_ip::{ClientSource, IpPolicy, ValidFromIp};
+//! use serde::Deserialize;
+//! use validator::{Validate, ValidationError};
+//!
+//! #[derive(Validate, Deserialize)]
+//! #[validate(context = ClientSource)]
+//! pub struct Batch {
+//!     #[validate(custom(function = "batch_size", use_context))]
+//!     pub items: Vec<String>,
+//! }
+//!
+//! // Flagged clients may only send 10 items at once, others 1000.
+//! fn batch_size(items: &[String], source: &ClientSource) -> Result<(), ValidationError> {
+//!     let max = if source.flagged { 10 } else { 1000 };
+//!     if items.len() > max {
+//!         return Err(ValidationError::new("batch_size"));
+//!     }
+//!     Ok(())
+//! }
+//!
+//! async fn handler(ValidFromIp(Json(batch)): ValidFromIp<Json<Batch>>) {
+//!     assert!(batch.items.len() <= 1000);
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use tokio::net::TcpListener;
+//! let policy = IpPolicy::new().flag(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 0)), 24);
+//! let router = Router::new()
+//!     .route("/batches", post(handler))
+//!     .with_state(policy);
+//! let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! axum::serve(
+//!     listener,
+//!     router.into_make_service_with_connect_info::<SocketAddr>(),
+//! )
+//! .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::HasValidateArgs;
+use axum::async_trait;
+use axum::extract::rejection::ExtensionRejection;
+use axum::extract::{ConnectInfo, FromRef, FromRequest, FromRequestParts, Request};
+use axum::response::{IntoResponse, Response};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::net::{IpAddr, SocketAddr};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use validator::{ValidateArgs, ValidationErrors};
+
+/// Address ranges whose clients are flagged.
+///
+/// `ValidFromIp` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IpPolicy(Arc<Vec<(IpAddr, u8)>>);
+
+impl IpPolicy {
+    /// Create a policy without flagged ranges.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Flag the range of `network` with a prefix length of `prefix`, e.g. `203.0.113.0` and `24`.
+    pub fn flag(mut self, network: IpAddr, prefix: u8) -> Self {
+        Arc::make_mut(&mut self.0).push((network, prefix));
+        self
+    }
+
+    /// Returns `true` if `ip` belongs to a flagged range.
+    pub fn is_flagged(&self, ip: IpAddr) -> bool {
+        self.0
+            .iter()
+            .any(|&(network, prefix)| in_range(ip, network, prefix))
+    }
+}
+
+fn in_range(ip: IpAddr, network: IpAddr, prefix: u8) -> bool {
+    let ip = match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        IpAddr::V4(_) => ip,
+    };
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => {
+            let mask = u32::MAX
+                .checked_shl(32 - u32::from(prefix.min(32)))
+                .unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) => {
+            let mask = u128::MAX
+                .checked_shl(128 - u32::from(prefix.min(128)))
+                .unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+/// Validation context of `ValidFromIp`, describing where the request comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientSource {
+    /// IP address of the client.
+    pub ip: IpAddr,
+    /// Whether the address belongs to a range flagged by the `IpPolicy`.
+    pub flagged: bool,
+}
+
+/// # `ValidFromIp` data extractor
+///
+/// `ValidFromIp` validates the inner data with a `ClientSource` built from the client address
+/// and the `IpPolicy` taken from the state.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidFromIp<E>(pub E);
+
+impl<E> Deref for ValidFromIp<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for ValidFromIp<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for ValidFromIp<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> ValidFromIp<E> {
+    /// Consumes the `ValidFromIp` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for ValidFromIp<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `ValidFromIpRejection` is returned when the `ValidFromIp` extractor fails.
+///
+#[derive(Debug)]
+pub enum ValidFromIpRejection<E> {
+    /// `Valid` variant captures errors related to the validation logic.
+    Valid(ValidationErrors),
+    /// `ConnectInfo` variant is returned when the router is not served with `ConnectInfo<SocketAddr>`.
+    ConnectInfo(ExtensionRejection),
+    /// `Inner` variant represents potential errors that might occur within the inner extractor.
+    Inner(E),
+}
+
+impl<E: Display> Display for ValidFromIpRejection<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidFromIpRejection::Valid(errors) => write!(f, "{errors}"),
+            ValidFromIpRejection::ConnectInfo(error) => write!(f, "{error}"),
+            ValidFromIpRejection::Inner(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for ValidFromIpRejection<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ValidFromIpRejection::Valid(errors) => Some(errors),
+            ValidFromIpRejection::ConnectInfo(error) => Some(error),
+            ValidFromIpRejection::Inner(error) => Some(error),
+        }
+    }
+}
+
+impl<E: IntoResponse> IntoResponse for ValidFromIpRejection<E> {
+    fn into_response(self) -> Response {
+        match self {
+            ValidFromIpRejection::Valid(errors) => crate::validation_errors_response(&errors),
+            ValidFromIpRejection::ConnectInfo(error) => error.into_response(),
+            ValidFromIpRejection::Inner(error) => error.into_response(),
+        }
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for ValidFromIp<Extractor>
+where
+    State: Send + Sync,
+    IpPolicy: FromRef<State>,
+    Extractor: for<'v> HasValidateArgs<'v> + FromRequest<State>,
+    for<'v> <Extractor as HasValidateArgs<'v>>::ValidateArgs:
+        ValidateArgs<'v, Args = &'v ClientSource>,
+{
+    type Rejection = ValidFromIpRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let (mut parts, body) = req.into_parts();
+        let ConnectInfo(address) = ConnectInfo::<SocketAddr>::from_request_parts(&mut parts, state)
+            .await
+            .map_err(ValidFromIpRejection::ConnectInfo)?;
+        let source = ClientSource {
+            ip: address.ip(),
+            flagged: IpPolicy::from_ref(state).is_flagged(address.ip()),
+        };
+        let inner = Extractor::from_request(Request::from_parts(parts, body), state)
+            .await
+            .map_err(ValidFromIpRejection::Inner)?;
+        inner
+            .get_validate_args()
+            .validate_with_args(&source)
+            .map_err(ValidFromIpRejection::Valid)?;
+        Ok(ValidFromIp(inner))
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::Json;
+    use serde::Deserialize;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+    use validator::{Validate, ValidationError};
+
+    #[derive(Debug, Validate, Deserialize)]
+    #[validate(context = ClientSource)]
+    struct Batch {
+        #[validate(custom(function = "batch_size", use_context))]
+        items: Vec<String>,
+    }
+
+    fn batch_size(items: &[String], source: &ClientSource) -> Result<(), ValidationError> {
+        let max = if source.flagged { 1 } else { 3 };
+        if items.len() > max {
+            return Err(ValidationError::new("batch_size"));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn ip_policy_ranges() {
+        let policy = IpPolicy::new()
+            .flag(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 0)), 24)
+            .flag(
+                IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0)),
+                32,
+            );
+        assert!(policy.is_flagged(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7))));
+        assert!(!policy.is_flagged(IpAddr::V4(Ipv4Addr::new(203, 0, 114, 7))));
+        assert!(policy.is_flagged(IpAddr::V6(Ipv4Addr::new(203, 0, 113, 7).to_ipv6_mapped())));
+        assert!(policy.is_flagged(IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 1, 0, 0, 0, 0, 1))));
+        assert!(!policy.is_flagged(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+        assert!(IpPolicy::new()
+            .flag(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0)
+            .is_flagged(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+    }
+
+    #[tokio::test]
+    async fn stricter_rules_for_flagged_ranges() {
+        let policy = IpPolicy::new().flag(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 0)), 24);
+        let request = |client: [u8; 4]| {
+            let mut request = Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{ "items": ["a", "b"] }"#))
+                .expect("invalid request");
+            request
+                .extensions_mut()
+                .insert(ConnectInfo(SocketAddr::from((client, 4000))));
+            request
+        };
+
+        assert!(
+            ValidFromIp::<Json<Batch>>::from_request(request([198, 51, 100, 1]), &policy)
+                .await
+                .is_ok()
+        );
+        let rejection =
+            ValidFromIp::<Json<Batch>>::from_request(request([203, 0, 113, 9]), &policy)
+                .await
+                .expect_err("flagged client accepted");
+        assert!(matches!(rejection, ValidFromIpRejection::Valid(_)));
+        assert_eq!(
+            rejection.into_response().status(),
+            crate::VALIDATION_ERROR_STATUS
+        );
+
+        let without_connect_info = Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{ "items": [] }"#))
+            .expect("invalid request");
+        assert!(matches!(
+            ValidFromIp::<Json<Batch>>::from_request(without_connect_info, &policy).await,
+            Err(ValidFromIpRejection::ConnectInfo(_))
+        ));
+    }
+}
@@ -0,0 +1,168 @@
+//! # Deprecation headers for responses
+//!
+//! ## Feature
+//!
+//! Enable the `deprecation` feature to use `Deprecated<T>`.
+//!
+//! ## Usage
+//!
+//! Deprecated endpoints keep validating their input as usual, but should tell clients when they are going away.
+//! Return `Deprecated<T>` from the handler of such an endpoint, where `T` is the response the handler
+//! would normally return.
+//!
+//! `Deprecated` sets the `Sunset` header ([RFC 8594](https://www.rfc-editor.org/rfc/rfc8594)) and the
+//! `Deprecation` header ([RFC 9745](https://www.rfc-editor.org/rfc/rfc9745)) on the inner response.
+//! The headers are also set when the inner response is an error.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use std::time::{Duration, UNIX_EPOCH};
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::deprecation::Deprecated;
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/v1/json", post(handler))
+//!     }
+//!
+//!     async fn handler(Valid(Json(parameter)): Valid<Json<Parameter>>) -> Deprecated<String> {
+//!         let sunset = UNIX_EPOCH + Duration::from_secs(1_767_225_600); // 2026-01-01
+//!         Deprecated::new(parameter.v0.to_string(), sunset)
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Parameter {
+//!         #[validate(range(min = 5, max = 10))]
+//!         pub v0: i32,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use axum::http::{HeaderName, HeaderValue};
+use axum::response::{IntoResponse, Response};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Name of the `Sunset` header.
+pub const SUNSET: HeaderName = HeaderName::from_static("sunset");
+/// Name of the `Deprecation` header.
+pub const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+
+/// # `Deprecated` response
+///
+/// `Deprecated` wraps the response of a deprecated endpoint,
+/// adding the `Sunset` and `Deprecation` headers to it.
+///
+#[derive(Debug, Clone, Copy)]
+pub struct Deprecated<T> {
+    /// The response of the endpoint.
+    pub inner: T,
+    /// When the endpoint was deprecated. If `None`, the `Deprecation` header is set to `true`.
+    pub deprecated_at: Option<SystemTime>,
+    /// When the endpoint will stop working.
+    pub sunset: SystemTime,
+}
+
+impl<T> Deprecated<T> {
+    /// Wraps the response of an endpoint which is already deprecated and will stop working at `sunset`.
+    pub fn new(inner: T, sunset: SystemTime) -> Self {
+        Self {
+            inner,
+            deprecated_at: None,
+            sunset,
+        }
+    }
+
+    /// Consumes the `Deprecated` and returns the inner response.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: IntoResponse> IntoResponse for Deprecated<T> {
+    fn into_response(self) -> Response {
+        let deprecation = match self.deprecated_at {
+            Some(time) => {
+                let since = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+                format!("@{}", since.as_secs())
+            }
+            None => String::from("true"),
+        };
+        let mut response = self.inner.into_response();
+        let headers = response.headers_mut();
+        if let Ok(sunset) = HeaderValue::from_str(&httpdate::fmt_http_date(self.sunset)) {
+            headers.insert(SUNSET, sunset);
+        }
+        if let Ok(deprecation) = HeaderValue::from_str(&deprecation) {
+            headers.insert(DEPRECATION, deprecation);
+        }
+        response
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationOutput for Deprecated<T>
+where
+    T: aide::OperationOutput,
+{
+    type Inner = T::Inner;
+
+    fn operation_response(
+        ctx: &mut aide::gen::GenContext,
+        operation: &mut aide::openapi::Operation,
+    ) -> Option<aide::openapi::Response> {
+        T::operation_response(ctx, operation)
+    }
+
+    fn inferred_responses(
+        ctx: &mut aide::gen::GenContext,
+        operation: &mut aide::openapi::Operation,
+    ) -> Vec<(Option<u16>, aide::openapi::Response)> {
+        T::inferred_responses(ctx, operation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use std::time::Duration;
+
+    #[test]
+    fn deprecated_into_response() {
+        let sunset = UNIX_EPOCH + Duration::from_secs(1_767_225_600);
+        let response = Deprecated::new((StatusCode::CREATED, "created"), sunset).into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()[SUNSET], "Thu, 01 Jan 2026 00:00:00 GMT");
+        assert_eq!(response.headers()[DEPRECATION], "true");
+
+        let response = Deprecated {
+            inner: StatusCode::BAD_REQUEST,
+            deprecated_at: Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+            sunset,
+        }
+        .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers()[DEPRECATION], "@1700000000");
+    }
+}
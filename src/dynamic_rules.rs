@@ -0,0 +1,852 @@
+//! # Validation against rules loaded at runtime
+//!
+//! ## Feature
+//!
+//! Enable the `dynamic_rules` feature to use `DynamicValid<E>`.
+//!
+//! ## Usage
+//!
+//! Rules declared with derives are fixed at compile time. When bounds have to change without a redeploy,
+//! e.g. from a configuration store, `DynamicValid<E>` checks the data against the `RuleSet` found in the
+//! router state instead:
+//!
+//! 1. Derive `Serialize` for your data type.
+//! 2. Make a `RuleSet` available from your router state (implement `FromRef<YourState>` for it,
+//!    or use it as the state directly). Clones share the same rules, so updating one updates them all.
+//! 3. In your handler function, use `DynamicValid<E>` as the extractor, e.g. `DynamicValid<Json<T>>`.
+//!
+//! Every request is checked against the rules in place when it arrives. A `FieldRule` bounds numbers with
+//! `min` and `max` (code `range`), and the number of characters of strings with `min_length` and `max_length`
+//! (code `length`). Rules can be built in code, or deserialized, e.g. from `{ "age": { "min": 18 } }`.
+//!
+//! Fields are named using dots, e.g. `owner.age`, following the serialized form of the data. Array indices are
+//! left out, so `items.price` names the `price` field of every element of `items`, and `tags` every element of
+//! the `tags` array. Missing and `null` fields are not checked. Violations are reported with
+//! `VALIDATION_ERROR_STATUS`, by the path of the offending field, e.g. `items[2].price`.
+//!
+//! ### Per-tenant rules
+//!
+//! In multi-tenant applications, each tenant may define its own custom fields and rules. Use `TenantValid<E>`
+//! with `TenantRules` in the router state instead: it holds one `RuleSet` per tenant, and checks each request
+//! against the rules of the tenant named by its `x-tenant-id` header (see `TenantRules::with_header`).
+//! Requests without the header, or from a tenant unknown to the `TenantRules`, are rejected with
+//! `400 Bad Request`, since clients choose the header and could otherwise skip validation by leaving it out.
+//! Use `TenantRules::accept_unknown_tenants` to accept them without checking them instead.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use axum::extract::State;
+//! use axum::routing::{post, put};
+//! use axum::Json;
+//! use axum::Router;
+//! use axum_valid::dynamic_rules::{DynamicValid, FieldRule, RuleSet};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Deserialize, Serialize)]
+//! pub struct Order {
+//!     pub quantity: u32,
+//!     pub note: String,
+//! }
+//!
+//! async fn create(DynamicValid(Json(order)): DynamicValid<Json<Order>>) {
+//!     assert!(order.quantity > 0);
+//! }
+//!
+//! async fn update_rules(State(rules): State<RuleSet>, Json(quantity): Json<FieldRule>) {
+//!     rules.set("quantity", quantity);
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! let rules = RuleSet::default();
+//! rules.set("quantity", FieldRule::default().min(1.0).max(100.0));
+//! rules.set("note", FieldRule::default().max_length(280));
+//! let router = Router::new()
+//!     .route("/orders", post(create))
+//!     .route("/rules/quantity", put(update_rules))
+//!     .with_state(rules);
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::{HasValidate, ValidationRejection};
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::{HeaderMap, HeaderName, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, RwLock};
+
+/// Bounds of a field. Unset bounds are not checked.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FieldRule {
+    /// Minimum value of numbers.
+    pub min: Option<f64>,
+    /// Maximum value of numbers.
+    pub max: Option<f64>,
+    /// Minimum number of characters of strings.
+    pub min_length: Option<usize>,
+    /// Maximum number of characters of strings.
+    pub max_length: Option<usize>,
+}
+
+impl FieldRule {
+    /// Sets the minimum value of numbers.
+    pub fn min(mut self, min: f64) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    /// Sets the maximum value of numbers.
+    pub fn max(mut self, max: f64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Sets the minimum number of characters of strings.
+    pub fn min_length(mut self, min_length: usize) -> Self {
+        self.min_length = Some(min_length);
+        self
+    }
+
+    /// Sets the maximum number of characters of strings.
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    fn check(&self, value: &Value, path: &str, violations: &mut Vec<RuleViolation>) {
+        let mut violate = |code, message| {
+            violations.push(RuleViolation {
+                field: path.to_owned(),
+                code,
+                message,
+            })
+        };
+        match value {
+            Value::Number(number) => {
+                let Some(number) = number.as_f64() else {
+                    return;
+                };
+                if let Some(min) = self.min.filter(|min| number < *min) {
+                    violate("range", format!("must be at least {min}"));
+                }
+                if let Some(max) = self.max.filter(|max| number > *max) {
+                    violate("range", format!("must be at most {max}"));
+                }
+            }
+            Value::String(s) => {
+                let length = s.chars().count();
+                if let Some(min) = self.min_length.filter(|min| length < *min) {
+                    violate("length", format!("must be at least {min} characters long"));
+                }
+                if let Some(max) = self.max_length.filter(|max| length > *max) {
+                    violate("length", format!("must be at most {max} characters long"));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The rules `DynamicValid` checks, by field.
+///
+/// `DynamicValid` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet(Arc<RwLock<Arc<BTreeMap<String, FieldRule>>>>);
+
+impl RuleSet {
+    /// Create a `RuleSet` with the given rules. Nested fields are named using dots, e.g. `owner.age`.
+    pub fn new(rules: BTreeMap<String, FieldRule>) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(rules))))
+    }
+
+    /// Returns the rules currently in place.
+    pub fn rules(&self) -> Arc<BTreeMap<String, FieldRule>> {
+        self.0
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Replaces all the rules.
+    pub fn replace(&self, rules: BTreeMap<String, FieldRule>) {
+        *self
+            .0
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(rules);
+    }
+
+    /// Sets the rule of a field.
+    pub fn set(&self, field: impl Into<String>, rule: FieldRule) {
+        self.update(|rules| {
+            rules.insert(field.into(), rule);
+        });
+    }
+
+    /// Removes the rule of a field, returning it.
+    pub fn remove(&self, field: &str) -> Option<FieldRule> {
+        let mut removed = None;
+        self.update(|rules| removed = rules.remove(field));
+        removed
+    }
+
+    fn update(&self, update: impl FnOnce(&mut BTreeMap<String, FieldRule>)) {
+        let mut rules = self
+            .0
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        update(Arc::make_mut(&mut rules));
+    }
+
+    /// Checks serializable data against the rules currently in place.
+    pub fn check<T: Serialize + ?Sized>(&self, data: &T) -> Result<(), RuleViolations> {
+        let rules = self.rules();
+        let mut violations = Vec::new();
+        if !rules.is_empty() {
+            let value = serde_json::to_value(data).map_err(|error| {
+                RuleViolations(vec![RuleViolation {
+                    field: String::new(),
+                    code: "serialize",
+                    message: error.to_string(),
+                }])
+            })?;
+            visit(&rules, &value, "", "", &mut violations);
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(RuleViolations(violations))
+        }
+    }
+}
+
+/// `path` is reported in violations, `field` (which has no array indices) is matched against the rules.
+fn visit(
+    rules: &BTreeMap<String, FieldRule>,
+    value: &Value,
+    path: &str,
+    field: &str,
+    violations: &mut Vec<RuleViolation>,
+) {
+    match value {
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                visit(rules, item, &format!("{path}[{index}]"), field, violations);
+            }
+        }
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                let join = |prefix: &str| {
+                    if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{prefix}.{key}")
+                    }
+                };
+                visit(rules, value, &join(path), &join(field), violations);
+            }
+        }
+        value => {
+            if let Some(rule) = rules.get(field) {
+                rule.check(value, path, violations);
+            }
+        }
+    }
+}
+
+/// A field which violates its rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleViolation {
+    /// Path of the offending field, e.g. `items[2].price`.
+    pub field: String,
+    /// `range` or `length`.
+    pub code: &'static str,
+    /// Human-readable error message.
+    pub message: String,
+}
+
+impl Display for RuleViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// The rule violations found by `DynamicValid`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleViolations(pub Vec<RuleViolation>);
+
+impl RuleViolations {
+    /// Returns the violations found for the given field path.
+    pub fn get<'a>(&'a self, field: &'a str) -> impl Iterator<Item = &'a RuleViolation> + 'a {
+        self.0
+            .iter()
+            .filter(move |violation| violation.field == field)
+    }
+}
+
+impl Display for RuleViolations {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, violation) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{violation}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for RuleViolations {}
+
+/// Serialized like `validator`'s field errors: a map from field path to the list of violations of that field.
+impl Serialize for RuleViolations {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Entry<'a> {
+            code: &'a str,
+            message: &'a str,
+        }
+
+        let mut fields: BTreeMap<&str, Vec<Entry>> = BTreeMap::new();
+        for violation in &self.0 {
+            fields.entry(&violation.field).or_default().push(Entry {
+                code: violation.code,
+                message: &violation.message,
+            });
+        }
+        fields.serialize(serializer)
+    }
+}
+
+impl IntoResponse for RuleViolations {
+    fn into_response(self) -> Response {
+        crate::validation_errors_response(&self)
+    }
+}
+
+/// # `DynamicValid` data extractor
+///
+/// `DynamicValid` checks the data of the inner extractor against the `RuleSet` taken from the state.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DynamicValid<E>(pub E);
+
+impl<E> Deref for DynamicValid<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for DynamicValid<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for DynamicValid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> DynamicValid<E> {
+    /// Consumes the `DynamicValid` and returns the checked data within.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for DynamicValid<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `DynamicValidRejection` is returned when the `DynamicValid` extractor fails.
+///
+pub type DynamicValidRejection<E> = ValidationRejection<RuleViolations, E>;
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for DynamicValid<Extractor>
+where
+    State: Send + Sync,
+    RuleSet: FromRef<State>,
+    Extractor: HasValidate + FromRequest<State>,
+    Extractor::Validate: Serialize,
+{
+    type Rejection = DynamicValidRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let rules = RuleSet::from_ref(state);
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(DynamicValidRejection::Inner)?;
+        rules
+            .check(inner.get_validate())
+            .map_err(DynamicValidRejection::Valid)?;
+        Ok(DynamicValid(inner))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequestParts<State> for DynamicValid<Extractor>
+where
+    State: Send + Sync,
+    RuleSet: FromRef<State>,
+    Extractor: HasValidate + FromRequestParts<State>,
+    Extractor::Validate: Serialize,
+{
+    type Rejection = DynamicValidRejection<<Extractor as FromRequestParts<State>>::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let rules = RuleSet::from_ref(state);
+        let inner = Extractor::from_request_parts(parts, state)
+            .await
+            .map_err(DynamicValidRejection::Inner)?;
+        rules
+            .check(inner.get_validate())
+            .map_err(DynamicValidRejection::Valid)?;
+        Ok(DynamicValid(inner))
+    }
+}
+
+/// Header naming the tenant of a request by default.
+pub const DEFAULT_TENANT_HEADER: HeaderName = HeaderName::from_static("x-tenant-id");
+
+/// The rules `TenantValid` checks, by tenant.
+///
+/// `TenantValid` reads it from the router state, so it must implement `FromRef<YourState>`.
+/// Clones share the same tenants, so adding a tenant to one adds it to them all.
+///
+#[derive(Debug, Clone)]
+pub struct TenantRules {
+    header: HeaderName,
+    accept_unknown: bool,
+    tenants: Arc<RwLock<BTreeMap<String, RuleSet>>>,
+}
+
+impl Default for TenantRules {
+    fn default() -> Self {
+        Self {
+            header: DEFAULT_TENANT_HEADER,
+            accept_unknown: false,
+            tenants: Default::default(),
+        }
+    }
+}
+
+impl TenantRules {
+    /// Read the tenant id from the given header instead of `x-tenant-id`.
+    pub fn with_header(mut self, header: HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Accept requests without a tenant, or from an unknown tenant, without checking them,
+    /// instead of rejecting them with `400 Bad Request`.
+    pub fn accept_unknown_tenants(mut self) -> Self {
+        self.accept_unknown = true;
+        self
+    }
+
+    /// Returns the rules of a tenant, adding an empty `RuleSet` if it has none yet.
+    /// The returned `RuleSet` shares its rules with the `TenantRules`, so it can be used to update them.
+    pub fn tenant(&self, tenant: &str) -> RuleSet {
+        if let Some(rules) = self.get(tenant) {
+            return rules;
+        }
+        self.tenants
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(tenant.to_owned())
+            .or_default()
+            .clone()
+    }
+
+    /// Returns the rules of a tenant, if it has any.
+    pub fn get(&self, tenant: &str) -> Option<RuleSet> {
+        self.tenants
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(tenant)
+            .cloned()
+    }
+
+    /// Removes the rules of a tenant, returning them.
+    pub fn remove(&self, tenant: &str) -> Option<RuleSet> {
+        self.tenants
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(tenant)
+    }
+
+    /// Returns the rules of the tenant named by the headers of a request,
+    /// or `None` if unknown tenants are accepted and the tenant is unknown.
+    fn for_request(&self, headers: &HeaderMap) -> Result<Option<RuleSet>, TenantError> {
+        let Some(tenant) = headers.get(&self.header) else {
+            if self.accept_unknown {
+                return Ok(None);
+            }
+            return Err(TenantError::Missing(self.header.clone()));
+        };
+        let tenant = tenant.to_str().unwrap_or("<non-ASCII>").trim();
+        match self.get(tenant) {
+            Some(rules) => Ok(Some(rules)),
+            None if self.accept_unknown => Ok(None),
+            None => Err(TenantError::Unknown(tenant.to_owned())),
+        }
+    }
+}
+
+/// Errors raised by `TenantValid` when the tenant of a request has no rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TenantError {
+    /// The request does not name its tenant.
+    Missing(HeaderName),
+    /// The tenant named by the request is unknown.
+    Unknown(String),
+}
+
+impl Display for TenantError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TenantError::Missing(header) => write!(f, "Missing `{header}` header"),
+            TenantError::Unknown(tenant) => write!(f, "Unknown tenant `{tenant}`"),
+        }
+    }
+}
+
+impl Error for TenantError {}
+
+impl IntoResponse for TenantError {
+    fn into_response(self) -> Response {
+        (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+    }
+}
+
+/// # `TenantValid` data extractor
+///
+/// `TenantValid` checks the data of the inner extractor against the `RuleSet` of the tenant of the request,
+/// taken from the `TenantRules` of the state.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TenantValid<E>(pub E);
+
+impl<E> Deref for TenantValid<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for TenantValid<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for TenantValid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> TenantValid<E> {
+    /// Consumes the `TenantValid` and returns the checked data within.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for TenantValid<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `TenantValidRejection` is returned when the `TenantValid` extractor fails.
+///
+#[derive(Debug)]
+pub enum TenantValidRejection<E> {
+    /// `Tenant` variant captures requests whose tenant has no rules.
+    Tenant(TenantError),
+    /// `Valid` variant captures the violations of the rules of the tenant.
+    Valid(RuleViolations),
+    /// `Inner` variant represents potential errors that might occur within the inner extractor.
+    Inner(E),
+}
+
+impl<E: Display> Display for TenantValidRejection<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TenantValidRejection::Tenant(error) => write!(f, "{error}"),
+            TenantValidRejection::Valid(violations) => write!(f, "{violations}"),
+            TenantValidRejection::Inner(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for TenantValidRejection<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TenantValidRejection::Tenant(error) => Some(error),
+            TenantValidRejection::Valid(violations) => Some(violations),
+            TenantValidRejection::Inner(error) => Some(error),
+        }
+    }
+}
+
+impl<E: IntoResponse> IntoResponse for TenantValidRejection<E> {
+    fn into_response(self) -> Response {
+        match self {
+            TenantValidRejection::Tenant(error) => error.into_response(),
+            TenantValidRejection::Valid(violations) => violations.into_response(),
+            TenantValidRejection::Inner(error) => error.into_response(),
+        }
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for TenantValid<Extractor>
+where
+    State: Send + Sync,
+    TenantRules: FromRef<State>,
+    Extractor: HasValidate + FromRequest<State>,
+    Extractor::Validate: Serialize,
+{
+    type Rejection = TenantValidRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let rules = TenantRules::from_ref(state)
+            .for_request(req.headers())
+            .map_err(TenantValidRejection::Tenant)?;
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(TenantValidRejection::Inner)?;
+        if let Some(rules) = rules {
+            rules
+                .check(inner.get_validate())
+                .map_err(TenantValidRejection::Valid)?;
+        }
+        Ok(TenantValid(inner))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequestParts<State> for TenantValid<Extractor>
+where
+    State: Send + Sync,
+    TenantRules: FromRef<State>,
+    Extractor: HasValidate + FromRequestParts<State>,
+    Extractor::Validate: Serialize,
+{
+    type Rejection = TenantValidRejection<<Extractor as FromRequestParts<State>>::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let rules = TenantRules::from_ref(state)
+            .for_request(&parts.headers)
+            .map_err(TenantValidRejection::Tenant)?;
+        let inner = Extractor::from_request_parts(parts, state)
+            .await
+            .map_err(TenantValidRejection::Inner)?;
+        if let Some(rules) = rules {
+            rules
+                .check(inner.get_validate())
+                .map_err(TenantValidRejection::Valid)?;
+        }
+        Ok(TenantValid(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn rule_set_check() {
+        let rules: BTreeMap<String, FieldRule> = serde_json::from_value(json!({
+            "quantity": { "min": 1, "max": 10 },
+            "items.name": { "min_length": 2, "max_length": 4 },
+            "scores": { "max": 100 }
+        }))
+        .expect("invalid rules");
+        let rules = RuleSet::new(rules);
+
+        let valid = json!({
+            "quantity": 10,
+            "items": [{ "name": "ab" }, { "name": "abcd" }],
+            "scores": [0, 100],
+            "note": null
+        });
+        assert_eq!(rules.check(&valid), Ok(()));
+
+        let invalid = json!({
+            "quantity": 0,
+            "items": [{ "name": "a" }, { "name": "ééééé" }],
+            "scores": [101]
+        });
+        let violations = rules.check(&invalid).expect_err("invalid data accepted");
+        assert_eq!(violations.0.len(), 4);
+        assert_eq!(violations.get("quantity").count(), 1);
+        assert_eq!(violations.get("items[0].name").count(), 1);
+        assert_eq!(violations.get("items[1].name").count(), 1);
+        assert_eq!(violations.get("scores[0]").count(), 1);
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn dynamic_valid_follows_rule_updates() {
+        use axum::body::Body;
+        use axum::Json;
+
+        #[derive(Debug, Deserialize, Serialize)]
+        struct Order {
+            quantity: u32,
+        }
+
+        let request = || {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{ "quantity": 50 }"#))
+                .expect("invalid request")
+        };
+        let rules = RuleSet::default();
+
+        DynamicValid::<Json<Order>>::from_request(request(), &rules)
+            .await
+            .expect("order rejected without rules");
+
+        rules.set("quantity", FieldRule::default().max(10.0));
+        let rejection = DynamicValid::<Json<Order>>::from_request(request(), &rules.clone())
+            .await
+            .expect_err("order accepted above the maximum");
+        assert!(matches!(
+            &rejection,
+            DynamicValidRejection::Valid(violations) if violations.get("quantity").any(|v| v.code == "range")
+        ));
+        assert_eq!(
+            rejection.into_response().status(),
+            crate::VALIDATION_ERROR_STATUS
+        );
+
+        rules.set("quantity", FieldRule::default().max(100.0));
+        let DynamicValid(Json(order)) =
+            DynamicValid::<Json<Order>>::from_request(request(), &rules)
+                .await
+                .expect("order rejected after raising the maximum");
+        assert_eq!(order.quantity, 50);
+        assert!(rules.remove("quantity").is_some());
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn tenant_valid_uses_tenant_rules() {
+        use axum::body::Body;
+        use axum::Json;
+
+        #[derive(Debug, Deserialize, Serialize)]
+        struct Ticket {
+            title: String,
+            custom: BTreeMap<String, Value>,
+        }
+
+        let request = |tenant: Option<&'static str>| {
+            let builder = Request::builder()
+                .uri("/")
+                .header("content-type", "application/json");
+            match tenant {
+                Some(tenant) => builder.header("x-tenant-id", tenant),
+                None => builder,
+            }
+            .body(Body::from(
+                r#"{ "title": "Broken", "custom": { "severity": 7, "team": "ops" } }"#,
+            ))
+            .expect("invalid request")
+        };
+        let tenants = TenantRules::default();
+        tenants
+            .tenant("acme")
+            .set("custom.severity", FieldRule::default().min(1.0).max(5.0));
+        tenants
+            .tenant("globex")
+            .set("custom.team", FieldRule::default().min_length(2));
+
+        let rejection = TenantValid::<Json<Ticket>>::from_request(request(Some("acme")), &tenants)
+            .await
+            .expect_err("ticket accepted above the tenant maximum");
+        assert!(matches!(
+            &rejection,
+            TenantValidRejection::Valid(violations) if violations.get("custom.severity").any(|v| v.code == "range")
+        ));
+        assert_eq!(
+            rejection.into_response().status(),
+            crate::VALIDATION_ERROR_STATUS
+        );
+
+        let TenantValid(Json(ticket)) =
+            TenantValid::<Json<Ticket>>::from_request(request(Some("globex")), &tenants)
+                .await
+                .expect("ticket rejected by the rules of another tenant");
+        assert_eq!(ticket.custom["severity"], 7);
+
+        // Requests cannot skip validation by leaving out or changing their tenant
+        let rejection =
+            TenantValid::<Json<Ticket>>::from_request(request(Some("initech")), &tenants)
+                .await
+                .expect_err("ticket accepted for an unknown tenant");
+        assert!(matches!(
+            &rejection,
+            TenantValidRejection::Tenant(TenantError::Unknown(tenant)) if tenant == "initech"
+        ));
+        assert_eq!(rejection.into_response().status(), StatusCode::BAD_REQUEST);
+        assert!(matches!(
+            TenantValid::<Json<Ticket>>::from_request(request(None), &tenants).await,
+            Err(TenantValidRejection::Tenant(TenantError::Missing(_)))
+        ));
+
+        assert!(tenants.remove("acme").is_some());
+        assert!(matches!(
+            TenantValid::<Json<Ticket>>::from_request(request(Some("acme")), &tenants).await,
+            Err(TenantValidRejection::Tenant(TenantError::Unknown(_)))
+        ));
+
+        let tenants = tenants.accept_unknown_tenants();
+        TenantValid::<Json<Ticket>>::from_request(request(Some("initech")), &tenants)
+            .await
+            .expect("ticket rejected for an unknown tenant");
+        TenantValid::<Json<Ticket>>::from_request(request(None), &tenants)
+            .await
+            .expect("ticket rejected without a tenant");
+        TenantValid::<Json<Ticket>>::from_request(request(Some("globex")), &tenants)
+            .await
+            .expect("ticket rejected by the rules of another tenant");
+    }
+}
@@ -0,0 +1,343 @@
+//! # Encryption at rest of tagged fields
+//!
+//! ## Feature
+//!
+//! Enable the `encrypt_fields` feature to use `EncryptedAtRest<E>`.
+//!
+//! ## Usage
+//!
+//! Some fields, e.g. national ids, must never be stored in plaintext. `EncryptedAtRest<E>` validates the data,
+//! encrypts its tagged fields, and checks that none of them is left in plaintext before the handler sees it:
+//!
+//! 1. Implement `FieldCipher` for your encryption scheme, holding its key.
+//! 2. Make a `FieldEncryptor` wrapping your cipher available from your router state
+//!    (implement `FromRef<YourState>` for it, or use it as the state directly).
+//! 3. Implement `EncryptAtRest` for your data type, tagging the fields to encrypt.
+//! 4. In your handler function, use `EncryptedAtRest<E>` as the extractor, e.g. `EncryptedAtRest<Json<T>>`.
+//!
+//! The `validator` rules are checked against the plaintext, so tagged fields can be validated as usual.
+//! Tagged fields are always encrypted, even when they already look encrypted, since request input is untrusted:
+//! a client could otherwise send a forged ciphertext to store a value which was never encrypted.
+//! A tagged field still in plaintext after encryption is reported with the `plaintext` code, like a
+//! validation error.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use axum::routing::post;
+//! use axum::Json;
+//! use axum::Router;
+//! use axum_valid::encrypt_fields::{EncryptAtRest, EncryptedAtRest, FieldCipher, FieldEncryptor};
+//! use serde::Deserialize;
+//! use validator::Validate;
+//!
+//! struct MyCipher {
+//!     key: Vec<u8>,
+//! }
+//!
+//! impl FieldCipher for MyCipher {
+//!     fn encrypt(&self, plaintext: &str) -> String {
+//!         // Encrypt `plaintext` with `self.key`, e.g. into `enc:<base64 ciphertext>`.
+//!         # let _ = &self.key;
+//!         # format!("enc:{plaintext}")
+//!     }
+//!
+//!     fn is_encrypted(&self, value: &str) -> bool {
+//!         value.starts_with("enc:")
+//!     }
+//! }
+//!
+//! #[derive(Validate, Deserialize)]
+//! pub struct Patient {
+//!     #[validate(length(min = 1, max = 64))]
+//!     pub name: String,
+//!     #[validate(length(equal = 11))]
+//!     pub national_id: String,
+//! }
+//!
+//! impl EncryptAtRest for Patient {
+//!     fn tagged_fields(&mut self) -> Vec<(&'static str, &mut String)> {
+//!         vec![("national_id", &mut self.national_id)]
+//!     }
+//! }
+//!
+//! async fn handler(EncryptedAtRest(Json(patient)): EncryptedAtRest<Json<Patient>>) {
+//!     assert!(patient.national_id.starts_with("enc:"));
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! let cipher = MyCipher { key: b"secret".to_vec() };
+//! let router = Router::new()
+//!     .route("/patients", post(handler))
+//!     .with_state(FieldEncryptor::new(cipher));
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::ValidationRejection;
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use validator::{Validate, ValidationError, ValidationErrors};
+
+/// An encryption scheme for fields stored at rest.
+pub trait FieldCipher: Send + Sync + 'static {
+    /// Encrypt the value of a field.
+    fn encrypt(&self, plaintext: &str) -> String;
+    /// Returns `true` if the value is a ciphertext produced by `encrypt`.
+    fn is_encrypted(&self, value: &str) -> bool;
+}
+
+/// Trait for data types with fields which must be encrypted at rest.
+///
+/// Data types implementing this trait can be extracted with `EncryptedAtRest`.
+///
+pub trait EncryptAtRest {
+    /// The tagged fields, with the names used to report them.
+    fn tagged_fields(&mut self) -> Vec<(&'static str, &mut String)>;
+}
+
+/// The `FieldCipher` used by `EncryptedAtRest`.
+///
+/// `EncryptedAtRest` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Clone)]
+pub struct FieldEncryptor(Arc<dyn FieldCipher>);
+
+impl FieldEncryptor {
+    /// Create a `FieldEncryptor` encrypting with `cipher`.
+    pub fn new(cipher: impl FieldCipher) -> Self {
+        Self(Arc::new(cipher))
+    }
+
+    /// Encrypts the tagged fields of `data`.
+    pub fn encrypt<T: EncryptAtRest + ?Sized>(&self, data: &mut T) {
+        for (_, value) in data.tagged_fields() {
+            *value = self.0.encrypt(value);
+        }
+    }
+
+    /// Checks that no tagged field of `data` is in plaintext.
+    pub fn verify<T: EncryptAtRest + ?Sized>(&self, data: &mut T) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        for (field, value) in data.tagged_fields() {
+            if !self.0.is_encrypted(value) {
+                let mut error = ValidationError::new("plaintext");
+                error.message = Some("must be encrypted".into());
+                errors.add(field, error);
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Validates `data`, encrypts its tagged fields, and checks that none is left in plaintext.
+    fn seal<T: Validate + EncryptAtRest + ?Sized>(
+        &self,
+        data: &mut T,
+    ) -> Result<(), ValidationErrors> {
+        data.validate()?;
+        self.encrypt(data);
+        self.verify(data)
+    }
+}
+
+impl Debug for FieldEncryptor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FieldEncryptor").finish_non_exhaustive()
+    }
+}
+
+/// # `EncryptedAtRest` data extractor
+///
+/// `EncryptedAtRest` runs the inner extractor, validates its data, then encrypts its tagged fields
+/// with the `FieldEncryptor` taken from the state.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EncryptedAtRest<E>(pub E);
+
+impl<E> Deref for EncryptedAtRest<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for EncryptedAtRest<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for EncryptedAtRest<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> EncryptedAtRest<E> {
+    /// Consumes the `EncryptedAtRest` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for EncryptedAtRest<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `EncryptedAtRestRejection` is returned when the `EncryptedAtRest` extractor fails.
+///
+pub type EncryptedAtRestRejection<E> = ValidationRejection<ValidationErrors, E>;
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for EncryptedAtRest<Extractor>
+where
+    State: Send + Sync,
+    FieldEncryptor: FromRef<State>,
+    Extractor: DerefMut + FromRequest<State>,
+    Extractor::Target: Validate + EncryptAtRest,
+{
+    type Rejection = EncryptedAtRestRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let mut inner = Extractor::from_request(req, state)
+            .await
+            .map_err(EncryptedAtRestRejection::Inner)?;
+        FieldEncryptor::from_ref(state)
+            .seal(&mut *inner)
+            .map_err(EncryptedAtRestRejection::Valid)?;
+        Ok(EncryptedAtRest(inner))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequestParts<State> for EncryptedAtRest<Extractor>
+where
+    State: Send + Sync,
+    FieldEncryptor: FromRef<State>,
+    Extractor: DerefMut + FromRequestParts<State>,
+    Extractor::Target: Validate + EncryptAtRest,
+{
+    type Rejection = EncryptedAtRestRejection<<Extractor as FromRequestParts<State>>::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let mut inner = Extractor::from_request_parts(parts, state)
+            .await
+            .map_err(EncryptedAtRestRejection::Inner)?;
+        FieldEncryptor::from_ref(state)
+            .seal(&mut *inner)
+            .map_err(EncryptedAtRestRejection::Valid)?;
+        Ok(EncryptedAtRest(inner))
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::Json;
+    use serde::Deserialize;
+
+    struct Reverse;
+
+    impl FieldCipher for Reverse {
+        fn encrypt(&self, plaintext: &str) -> String {
+            format!("enc:{}", plaintext.chars().rev().collect::<String>())
+        }
+
+        fn is_encrypted(&self, value: &str) -> bool {
+            value.starts_with("enc:")
+        }
+    }
+
+    struct Forgetful;
+
+    impl FieldCipher for Forgetful {
+        fn encrypt(&self, plaintext: &str) -> String {
+            plaintext.to_owned()
+        }
+
+        fn is_encrypted(&self, value: &str) -> bool {
+            value.starts_with("enc:")
+        }
+    }
+
+    #[derive(Debug, Validate, Deserialize)]
+    struct Patient {
+        #[validate(length(min = 1))]
+        name: String,
+        #[validate(length(equal = 4))]
+        national_id: String,
+    }
+
+    impl EncryptAtRest for Patient {
+        fn tagged_fields(&mut self) -> Vec<(&'static str, &mut String)> {
+            vec![("national_id", &mut self.national_id)]
+        }
+    }
+
+    fn request(national_id: &str) -> Request {
+        Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(format!(
+                r#"{{ "name": "Ada", "national_id": "{national_id}" }}"#
+            )))
+            .expect("invalid request")
+    }
+
+    #[tokio::test]
+    async fn encrypted_at_rest() {
+        let encryptor = FieldEncryptor::new(Reverse);
+
+        let EncryptedAtRest(Json(patient)) =
+            EncryptedAtRest::<Json<Patient>>::from_request(request("1234"), &encryptor)
+                .await
+                .expect("valid patient rejected");
+        assert_eq!(patient.name, "Ada");
+        assert_eq!(patient.national_id, "enc:4321");
+
+        // A value which looks encrypted is still encrypted, not trusted as a ciphertext
+        let EncryptedAtRest(Json(patient)) =
+            EncryptedAtRest::<Json<Patient>>::from_request(request("enc:"), &encryptor)
+                .await
+                .expect("valid patient rejected");
+        assert_eq!(patient.national_id, "enc::cne");
+
+        assert!(matches!(
+            EncryptedAtRest::<Json<Patient>>::from_request(request("123"), &encryptor).await,
+            Err(EncryptedAtRestRejection::Valid(errors)) if errors.field_errors()["national_id"][0].code == "length"
+        ));
+
+        let rejection = EncryptedAtRest::<Json<Patient>>::from_request(
+            request("1234"),
+            &FieldEncryptor::new(Forgetful),
+        )
+        .await
+        .expect_err("plaintext field accepted");
+        assert!(matches!(
+            rejection,
+            EncryptedAtRestRejection::Valid(errors) if errors.field_errors()["national_id"][0].code == "plaintext"
+        ));
+    }
+}
@@ -0,0 +1,351 @@
+//! # Support for encrypted query parameters
+//!
+//! ## Feature
+//!
+//! Enable the `encrypted_query` feature to use `EncryptedQuery<T>`.
+//!
+//! ## Usage
+//!
+//! Some links carry their parameters as a single encrypted blob, e.g. `/unsubscribe?token=<ciphertext>`,
+//! where the plaintext is itself a query string such as `user=42&list=news`.
+//!
+//! 1. Implement `QueryCipher` for your decryption scheme, holding its key.
+//! 2. Make a `QueryDecryptor`, naming the encrypted parameter and wrapping your cipher, available from your
+//!    router state (implement `FromRef<YourState>` for it, or use it as the state directly).
+//! 3. Implement `Deserialize` and `Validate` for your data type `T`.
+//! 4. In your handler function, use `Valid<EncryptedQuery<T>>` as some parameter's type.
+//!
+//! A missing parameter, a blob that does not decrypt, or a plaintext that does not deserialize into `T` is
+//! rejected with `400 Bad Request` by `EncryptedQuery` itself (the `Inner` variant of the rejection), while data
+//! that decrypts but does not pass validation is rejected like any other `Valid` extractor (the `Valid` variant).
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::get;
+//!     use axum::Router;
+//!     use axum_valid::encrypted_query::{EncryptedQuery, QueryCipher, QueryDecryptor};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     struct MyCipher {
+//!         key: Vec<u8>,
+//!     }
+//!
+//!     impl QueryCipher for MyCipher {
+//!         fn decrypt(&self, ciphertext: &str) -> Option<String> {
+//!             // Decrypt `ciphertext` with `self.key`, returning `None` if it is not authentic.
+//!             # let _ = &self.key;
+//!             # Some(ciphertext.to_owned())
+//!         }
+//!     }
+//!
+//!     pub fn router() -> Router {
+//!         let cipher = MyCipher { key: b"secret".to_vec() };
+//!         Router::new()
+//!             .route("/unsubscribe", get(handler))
+//!             .with_state(QueryDecryptor::new("token", cipher))
+//!     }
+//!
+//!     async fn handler(Valid(EncryptedQuery(parameter)): Valid<EncryptedQuery<Parameter>>) {
+//!         assert!(parameter.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Parameter {
+//!         #[validate(range(min = 1))]
+//!         pub user: u64,
+//!         #[validate(length(min = 1, max = 16))]
+//!         pub list: String,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::HasValidate;
+#[cfg(feature = "validator")]
+use crate::HasValidateArgs;
+use axum::async_trait;
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{FromRef, FromRequestParts, Query};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+#[cfg(feature = "validator")]
+use validator::ValidateArgs;
+
+/// A decryption scheme for query parameters.
+pub trait QueryCipher: Send + Sync + 'static {
+    /// Decrypt the (already percent-decoded) value of the parameter into a query string,
+    /// returning `None` if it cannot be decrypted.
+    fn decrypt(&self, ciphertext: &str) -> Option<String>;
+}
+
+/// Names the encrypted query parameter and how to decrypt it.
+///
+/// `EncryptedQuery` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Clone)]
+pub struct QueryDecryptor {
+    param: Arc<str>,
+    cipher: Arc<dyn QueryCipher>,
+}
+
+impl QueryDecryptor {
+    /// Create a `QueryDecryptor` decrypting the `param` query parameter with `cipher`.
+    pub fn new(param: impl Into<Arc<str>>, cipher: impl QueryCipher) -> Self {
+        Self {
+            param: param.into(),
+            cipher: Arc::new(cipher),
+        }
+    }
+}
+
+impl Debug for QueryDecryptor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueryDecryptor")
+            .field("param", &self.param)
+            .finish_non_exhaustive()
+    }
+}
+
+/// # `EncryptedQuery` extractor
+///
+/// `EncryptedQuery` decrypts the query parameter named by the `QueryDecryptor` taken from the state,
+/// and deserializes the resulting query string into `T`, like `Query<T>`.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EncryptedQuery<T>(pub T);
+
+impl<T> Deref for EncryptedQuery<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for EncryptedQuery<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// `EncryptedQueryRejection` is returned when the `EncryptedQuery` extractor fails.
+///
+#[derive(Debug)]
+pub enum EncryptedQueryRejection {
+    /// The encrypted parameter is missing.
+    Missing(Arc<str>),
+    /// The encrypted parameter could not be decrypted into a query string.
+    Decrypt(Arc<str>),
+    /// The query string of the request could not be parsed.
+    Query(QueryRejection),
+    /// The decrypted query string could not be deserialized.
+    Deserialize(serde_urlencoded::de::Error),
+}
+
+impl Display for EncryptedQueryRejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncryptedQueryRejection::Missing(param) => {
+                write!(f, "Missing `{param}` query parameter")
+            }
+            EncryptedQueryRejection::Decrypt(param) => {
+                write!(f, "Failed to decrypt the `{param}` query parameter")
+            }
+            EncryptedQueryRejection::Query(error) => write!(f, "{error}"),
+            EncryptedQueryRejection::Deserialize(error) => {
+                write!(
+                    f,
+                    "Failed to deserialize the decrypted query string: {error}"
+                )
+            }
+        }
+    }
+}
+
+impl Error for EncryptedQueryRejection {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EncryptedQueryRejection::Query(error) => Some(error),
+            EncryptedQueryRejection::Deserialize(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for EncryptedQueryRejection {
+    fn into_response(self) -> Response {
+        match self {
+            EncryptedQueryRejection::Query(error) => error.into_response(),
+            _ => (StatusCode::BAD_REQUEST, self.to_string()).into_response(),
+        }
+    }
+}
+
+#[async_trait]
+impl<State, T> FromRequestParts<State> for EncryptedQuery<T>
+where
+    State: Send + Sync,
+    QueryDecryptor: FromRef<State>,
+    T: DeserializeOwned,
+{
+    type Rejection = EncryptedQueryRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let decryptor = QueryDecryptor::from_ref(state);
+        let Query(mut params) = Query::<HashMap<String, String>>::try_from_uri(&parts.uri)
+            .map_err(EncryptedQueryRejection::Query)?;
+        let ciphertext = params
+            .remove(decryptor.param.as_ref())
+            .ok_or_else(|| EncryptedQueryRejection::Missing(decryptor.param.clone()))?;
+        let plaintext = decryptor
+            .cipher
+            .decrypt(&ciphertext)
+            .ok_or_else(|| EncryptedQueryRejection::Decrypt(decryptor.param.clone()))?;
+        let value =
+            serde_urlencoded::from_str(&plaintext).map_err(EncryptedQueryRejection::Deserialize)?;
+        Ok(EncryptedQuery(value))
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for EncryptedQuery<T> {}
+
+impl<T> HasValidate for EncryptedQuery<T> {
+    type Validate = T;
+    fn get_validate(&self) -> &T {
+        &self.0
+    }
+}
+
+#[cfg(feature = "validator")]
+impl<'v, T: ValidateArgs<'v>> HasValidateArgs<'v> for EncryptedQuery<T> {
+    type ValidateArgs = T;
+    fn get_validate_args(&self) -> &Self::ValidateArgs {
+        &self.0
+    }
+}
+
+#[cfg(feature = "validify")]
+impl<T: validify::Modify> crate::HasModify for EncryptedQuery<T> {
+    type Modify = T;
+
+    fn get_modify(&mut self) -> &mut Self::Modify {
+        &mut self.0
+    }
+}
+
+#[cfg(all(test, feature = "validator"))]
+mod tests {
+    use super::*;
+    use crate::{Valid, ValidRejection};
+    use axum::extract::Request;
+    use serde::Deserialize;
+    use validator::Validate;
+
+    /// Reverses the plaintext, which is enough to tell decryption apart from deserialization.
+    struct Reverse;
+
+    impl QueryCipher for Reverse {
+        fn decrypt(&self, ciphertext: &str) -> Option<String> {
+            ciphertext
+                .starts_with('!')
+                .then(|| ciphertext[1..].chars().rev().collect())
+        }
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Parameter {
+        #[validate(range(min = 1))]
+        user: u64,
+        #[validate(length(min = 1, max = 16))]
+        list: String,
+    }
+
+    async fn extract(
+        query: &str,
+    ) -> Result<Valid<EncryptedQuery<Parameter>>, ValidRejection<EncryptedQueryRejection>> {
+        let (mut parts, _) = Request::builder()
+            .uri(format!("/unsubscribe?{query}"))
+            .body(())
+            .expect("invalid request")
+            .into_parts();
+        let decryptor = QueryDecryptor::new("token", Reverse);
+        Valid::<EncryptedQuery<Parameter>>::from_request_parts(&mut parts, &decryptor).await
+    }
+
+    /// Reverses the plaintext, percent-encoded to fit in the URI.
+    fn encrypt(plaintext: &str) -> String {
+        let ciphertext = format!("!{}", plaintext.chars().rev().collect::<String>());
+        ciphertext
+            .bytes()
+            .map(|byte| match byte {
+                b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'Z' => char::from(byte).to_string(),
+                _ => format!("%{byte:02X}"),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn encrypted_query() {
+        let Valid(EncryptedQuery(parameter)) =
+            extract(&format!("token={}", encrypt("user=42&list=news")))
+                .await
+                .expect("valid encrypted query rejected");
+        assert_eq!(parameter.user, 42);
+        assert_eq!(parameter.list, "news");
+
+        assert!(matches!(
+            extract(&format!("token={}", encrypt("user=0&list=news"))).await,
+            Err(ValidRejection::Valid(_))
+        ));
+        assert!(matches!(
+            extract("token=garbage").await,
+            Err(ValidRejection::Inner(EncryptedQueryRejection::Decrypt(_)))
+        ));
+        assert!(matches!(
+            extract("user=42").await,
+            Err(ValidRejection::Inner(EncryptedQueryRejection::Missing(_)))
+        ));
+        assert!(matches!(
+            extract(&format!("token={}", encrypt("user=many"))).await,
+            Err(ValidRejection::Inner(EncryptedQueryRejection::Deserialize(
+                _
+            )))
+        ));
+    }
+
+    #[tokio::test]
+    async fn encrypted_query_plaintext_outside_uri_charset() {
+        let Valid(EncryptedQuery(parameter)) =
+            extract(&format!("token={}", encrypt("user=7&list=café news")))
+                .await
+                .expect("decrypted plaintext with spaces and accents rejected");
+        assert_eq!(parameter.user, 7);
+        assert_eq!(parameter.list, "café news");
+    }
+}
@@ -8,6 +8,7 @@
 //!
 //! * [`self`] : `Cache<T>`
 //! * [`self`] : `WithRejection<T, R>`
+//! * [`either`] : `ValidEither<L, R>`
 //! * [`form`] : `Form<T>`
 //! * [`protobuf`] : `Protobuf<T>`
 //! * [`query`] : `Query<T>`
@@ -435,6 +436,8 @@
 //! }
 //! ```
 
+#[cfg(feature = "validator")]
+pub mod either;
 #[cfg(feature = "extra_form")]
 pub mod form;
 #[cfg(feature = "extra_protobuf")]
@@ -671,4 +674,79 @@ mod tests {
             T::set_invalid_request(builder)
         }
     }
+
+    #[cfg(all(feature = "validator", feature = "json"))]
+    #[tokio::test]
+    async fn custom_rejection_for_parse_and_validation_errors() {
+        use crate::ValidRejection;
+        use axum::body::Body;
+        use axum::extract::rejection::JsonRejection;
+        use axum::extract::{FromRequest, Request};
+        use axum::response::{IntoResponse, Response};
+        use axum::Json;
+        use validator::Validate;
+
+        #[derive(Debug, serde::Deserialize, Validate)]
+        struct Name {
+            #[validate(length(min = 1))]
+            name: String,
+        }
+
+        #[derive(Debug)]
+        enum MyError {
+            Parse,
+            Invalid,
+        }
+
+        impl From<JsonRejection> for MyError {
+            fn from(_: JsonRejection) -> Self {
+                MyError::Parse
+            }
+        }
+
+        impl From<ValidRejection<JsonRejection>> for MyError {
+            fn from(rejection: ValidRejection<JsonRejection>) -> Self {
+                match rejection {
+                    ValidRejection::Valid(_) => MyError::Invalid,
+                    ValidRejection::Inner(_) => MyError::Parse,
+                }
+            }
+        }
+
+        impl IntoResponse for MyError {
+            fn into_response(self) -> Response {
+                StatusCode::IM_A_TEAPOT.into_response()
+            }
+        }
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+
+        let WithRejection(Valid(Json(name)), _) =
+            WithRejection::<Valid<Json<Name>>, MyError>::from_request(
+                request(r#"{"name": "axum"}"#),
+                &(),
+            )
+            .await
+            .expect("valid body rejected");
+        assert_eq!(name.name, "axum");
+
+        assert!(matches!(
+            WithRejection::<Valid<Json<Name>>, MyError>::from_request(request("{"), &()).await,
+            Err(MyError::Parse)
+        ));
+        assert!(matches!(
+            WithRejection::<Valid<Json<Name>>, MyError>::from_request(
+                request(r#"{"name": ""}"#),
+                &()
+            )
+            .await,
+            Err(MyError::Invalid)
+        ));
+    }
 }
@@ -0,0 +1,287 @@
+//! # Support for `Either<L, R>` from `axum-extra`
+//!
+//! ## Feature
+//!
+//! Enable the `extra` and `validator` features to use `ValidEither<L, R>`.
+//!
+//! ## Usage
+//!
+//! An endpoint may accept several shapes of data, e.g. two JSON schemas. `Valid<Either<L, R>>` cannot express
+//! this: `HasValidate` exposes a single type to validate, and `Either` only extracts from the request parts,
+//! so it cannot be used with body extractors such as `Json`. Use `ValidEither<L, R>` instead:
+//!
+//! 1. Implement `Deserialize` and `Validate` for both data types.
+//! 2. In your handler function, use `ValidEither<L, R>` as some parameter's type, e.g. `ValidEither<Json<A>, Json<B>>`,
+//!    and match on the `Either` within.
+//!
+//! `L` is tried first, and `R` only if `L` fails to extract the request. Whichever variant is extracted
+//! is then validated, and its validation errors are returned as the matching variant of `ValidEitherRejection`,
+//! without trying the other one. If neither variant is extracted, the rejection of `R` is returned, like `Either` does.
+//!
+//! Since `serde` ignores unknown fields by default, make sure a payload of `R` cannot be extracted as `L`,
+//! e.g. with fields which are required by `L` only, or with `#[serde(deny_unknown_fields)]`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use axum::routing::post;
+//! use axum::Json;
+//! use axum::Router;
+//! use axum_extra::either::Either;
+//! use axum_valid::extra::either::ValidEither;
+//! use serde::Deserialize;
+//! use validator::Validate;
+//!
+//! #[derive(Validate, Deserialize)]
+//! #[serde(deny_unknown_fields)]
+//! pub struct ByEmail {
+//!     #[validate(email)]
+//!     pub email: String,
+//! }
+//!
+//! #[derive(Validate, Deserialize)]
+//! #[serde(deny_unknown_fields)]
+//! pub struct ByPhone {
+//!     #[validate(length(min = 8, max = 15))]
+//!     pub phone: String,
+//! }
+//!
+//! async fn handler(ValidEither(lookup): ValidEither<Json<ByEmail>, Json<ByPhone>>) {
+//!     match lookup {
+//!         Either::E1(Json(by_email)) => assert!(by_email.validate().is_ok()),
+//!         Either::E2(Json(by_phone)) => assert!(by_phone.validate().is_ok()),
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new().route("/lookup", post(handler));
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::{HasValidate, ValidRejection};
+use axum::async_trait;
+use axum::body::{Body, Bytes};
+use axum::extract::rejection::BytesRejection;
+use axum::extract::{FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::response::{IntoResponse, Response};
+use axum_extra::either::Either;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use validator::Validate;
+
+/// # `ValidEither` data extractor
+///
+/// `ValidEither` extracts either `L` or `R`, then validates the variant which was extracted like `Valid` would do.
+///
+#[derive(Debug, Clone)]
+pub struct ValidEither<L, R>(pub Either<L, R>);
+
+impl<L, R> Deref for ValidEither<L, R> {
+    type Target = Either<L, R>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<L, R> DerefMut for ValidEither<L, R> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<L, R> ValidEither<L, R> {
+    /// Consumes the `ValidEither` and returns the extracted variant.
+    pub fn into_inner(self) -> Either<L, R> {
+        self.0
+    }
+}
+
+/// `ValidEitherRejection` is returned when the `ValidEither` extractor fails.
+///
+/// `Left` and `Right` tell which variant was extracted, and failed validation,
+/// or which one was last tried when neither could be extracted.
+///
+#[derive(Debug)]
+pub enum ValidEitherRejection<L, R> {
+    /// `Left` variant holds the rejection of `L`.
+    Left(ValidRejection<L>),
+    /// `Right` variant holds the rejection of `R`.
+    Right(ValidRejection<R>),
+    /// `Body` variant represents errors that occur while buffering the request body.
+    Body(BytesRejection),
+}
+
+impl<L: Display, R: Display> Display for ValidEitherRejection<L, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidEitherRejection::Left(rejection) => write!(f, "{rejection}"),
+            ValidEitherRejection::Right(rejection) => write!(f, "{rejection}"),
+            ValidEitherRejection::Body(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl<L: Error + 'static, R: Error + 'static> Error for ValidEitherRejection<L, R> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ValidEitherRejection::Left(rejection) => Some(rejection),
+            ValidEitherRejection::Right(rejection) => Some(rejection),
+            ValidEitherRejection::Body(error) => Some(error),
+        }
+    }
+}
+
+impl<L: IntoResponse, R: IntoResponse> IntoResponse for ValidEitherRejection<L, R> {
+    fn into_response(self) -> Response {
+        match self {
+            ValidEitherRejection::Left(rejection) => rejection.into_response(),
+            ValidEitherRejection::Right(rejection) => rejection.into_response(),
+            ValidEitherRejection::Body(error) => error.into_response(),
+        }
+    }
+}
+
+#[async_trait]
+impl<State, L, R> FromRequest<State> for ValidEither<L, R>
+where
+    State: Send + Sync,
+    L: HasValidate + FromRequest<State>,
+    L::Validate: Validate,
+    R: HasValidate + FromRequest<State>,
+    R::Validate: Validate,
+{
+    type Rejection = ValidEitherRejection<
+        <L as FromRequest<State>>::Rejection,
+        <R as FromRequest<State>>::Rejection,
+    >;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let (parts, body) = req.into_parts();
+        let bytes = Bytes::from_request(Request::from_parts(parts.clone(), body), state)
+            .await
+            .map_err(ValidEitherRejection::Body)?;
+        let left = Request::from_parts(parts.clone(), Body::from(bytes.clone()));
+        if let Ok(left) = L::from_request(left, state).await {
+            left.get_validate()
+                .validate()
+                .map_err(|errors| ValidEitherRejection::Left(ValidRejection::Valid(errors)))?;
+            return Ok(ValidEither(Either::E1(left)));
+        }
+        let right = R::from_request(Request::from_parts(parts, Body::from(bytes)), state)
+            .await
+            .map_err(|rejection| ValidEitherRejection::Right(ValidRejection::Inner(rejection)))?;
+        right
+            .get_validate()
+            .validate()
+            .map_err(|errors| ValidEitherRejection::Right(ValidRejection::Valid(errors)))?;
+        Ok(ValidEither(Either::E2(right)))
+    }
+}
+
+#[async_trait]
+impl<State, L, R> FromRequestParts<State> for ValidEither<L, R>
+where
+    State: Send + Sync,
+    L: HasValidate + FromRequestParts<State>,
+    L::Validate: Validate,
+    R: HasValidate + FromRequestParts<State>,
+    R::Validate: Validate,
+{
+    type Rejection = ValidEitherRejection<
+        <L as FromRequestParts<State>>::Rejection,
+        <R as FromRequestParts<State>>::Rejection,
+    >;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        if let Ok(left) = L::from_request_parts(parts, state).await {
+            left.get_validate()
+                .validate()
+                .map_err(|errors| ValidEitherRejection::Left(ValidRejection::Valid(errors)))?;
+            return Ok(ValidEither(Either::E1(left)));
+        }
+        let right = R::from_request_parts(parts, state)
+            .await
+            .map_err(|rejection| ValidEitherRejection::Right(ValidRejection::Inner(rejection)))?;
+        right
+            .get_validate()
+            .validate()
+            .map_err(|errors| ValidEitherRejection::Right(ValidRejection::Valid(errors)))?;
+        Ok(ValidEither(Either::E2(right)))
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use axum::Json;
+    use serde::Deserialize;
+
+    #[derive(Debug, Validate, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct ByEmail {
+        #[validate(email)]
+        email: String,
+    }
+
+    #[derive(Debug, Validate, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct ByPhone {
+        #[validate(length(min = 8, max = 15))]
+        phone: String,
+    }
+
+    type Lookup = ValidEither<Json<ByEmail>, Json<ByPhone>>;
+
+    fn request(body: &'static str) -> Request {
+        Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .expect("invalid request")
+    }
+
+    #[tokio::test]
+    async fn valid_either_validates_extracted_variant() {
+        let ValidEither(lookup) =
+            Lookup::from_request(request(r#"{ "phone": "0123456789" }"#), &())
+                .await
+                .expect("valid phone rejected");
+        assert!(matches!(lookup, Either::E2(Json(ByPhone { phone })) if phone == "0123456789"));
+
+        let rejection = Lookup::from_request(request(r#"{ "phone": "012" }"#), &())
+            .await
+            .expect_err("short phone accepted");
+        assert!(matches!(
+            &rejection,
+            ValidEitherRejection::Right(ValidRejection::Valid(errors))
+                if errors.field_errors()["phone"][0].code == "length"
+        ));
+        assert_eq!(
+            rejection.into_response().status(),
+            crate::VALIDATION_ERROR_STATUS
+        );
+
+        assert!(matches!(
+            Lookup::from_request(request(r#"{ "email": "nope" }"#), &()).await,
+            Err(ValidEitherRejection::Left(ValidRejection::Valid(_)))
+        ));
+        assert!(matches!(
+            Lookup::from_request(request(r#"{ "email": "a@b.c" }"#), &()).await,
+            Ok(ValidEither(Either::E1(_)))
+        ));
+        assert!(matches!(
+            Lookup::from_request(request(r#"{ "name": "a" }"#), &()).await,
+            Err(ValidEitherRejection::Right(ValidRejection::Inner(_)))
+        ));
+    }
+}
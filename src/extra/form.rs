@@ -157,4 +157,38 @@ mod tests {
             builder.form(T::invalid())
         }
     }
+
+    #[cfg(feature = "validator")]
+    #[tokio::test]
+    async fn repeated_keys_are_validated() {
+        use crate::{Valid, ValidRejection};
+        use axum::body::Body;
+        use axum::extract::{FromRequest, Request};
+        use validator::Validate;
+
+        #[derive(Debug, serde::Deserialize, Validate)]
+        struct Tags {
+            #[validate(length(min = 2))]
+            a: Vec<u32>,
+        }
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .method("POST")
+                .uri("/")
+                .header("content-type", "application/x-www-form-urlencoded")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+
+        let Valid(Form(tags)) = Valid::<Form<Tags>>::from_request(request("a=1&a=2"), &())
+            .await
+            .expect("repeated keys rejected");
+        assert_eq!(tags.a, [1, 2]);
+
+        assert!(matches!(
+            Valid::<Form<Tags>>::from_request(request("a=1"), &()).await,
+            Err(ValidRejection::Valid(_))
+        ));
+    }
 }
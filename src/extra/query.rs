@@ -158,4 +158,39 @@ mod tests {
             builder.query(&T::invalid())
         }
     }
+
+    #[cfg(feature = "validator")]
+    #[tokio::test]
+    async fn repeated_keys_are_validated() {
+        use crate::{Valid, ValidRejection};
+        use axum::extract::FromRequestParts;
+        use axum::http::Request;
+        use validator::Validate;
+
+        #[derive(Debug, serde::Deserialize, Validate)]
+        struct Ids {
+            #[validate(length(min = 2))]
+            x: Vec<u32>,
+        }
+
+        let parts = |uri: &'static str| {
+            Request::builder()
+                .uri(uri)
+                .body(())
+                .expect("invalid request")
+                .into_parts()
+                .0
+        };
+
+        let Valid(Query(ids)) =
+            Valid::<Query<Ids>>::from_request_parts(&mut parts("/?x=1&x=2"), &())
+                .await
+                .expect("repeated keys rejected");
+        assert_eq!(ids.x, [1, 2]);
+
+        assert!(matches!(
+            Valid::<Query<Ids>>::from_request_parts(&mut parts("/?x=1"), &()).await,
+            Err(ValidRejection::Valid(_))
+        ));
+    }
 }
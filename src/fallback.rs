@@ -0,0 +1,300 @@
+//! # Default values for invalid non-critical fields
+//!
+//! ## Feature
+//!
+//! Enable the `fallback` feature to use `WithFallback<E>`.
+//!
+//! ## Usage
+//!
+//! Some fields are not worth failing a request for, e.g. a display preference or an optional referral code.
+//! `WithFallback<E>` modifies and validates data like `Validated<E>` with `validify`, but replaces the invalid
+//! fields listed in a `FallbackFields` policy with their default value instead of rejecting the request:
+//!
+//! 1. Derive `Default`, `Serialize` and `Deserialize` for your data type, in addition to `Validify`.
+//!    The default value of a field is its value in `T::default()`.
+//! 2. Build a `FallbackFields` policy listing the fields which may fall back, and make it available from your
+//!    router state (implement `FromRef<YourState>` for it, or use it as the state directly).
+//! 3. In your handler function, use `WithFallback<E>` as the extractor, e.g. `WithFallback<Json<T>>`.
+//!
+//! The fields are top-level fields of the data, named as they are serialized. A listed field falls back as a
+//! whole when it, or any field nested in it, is invalid. The data is validated again after the fallback,
+//! and the request is rejected as usual if any other field is invalid. The names of the fields which fell
+//! back are returned alongside the inner extractor, e.g. to be logged.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use axum::routing::post;
+//! use axum::Json;
+//! use axum::Router;
+//! use axum_valid::fallback::{FallbackFields, WithFallback};
+//! use serde::{Deserialize, Serialize};
+//! use validify::Validify;
+//!
+//! #[derive(Default, Clone, Validify, Deserialize, Serialize)]
+//! pub struct Signup {
+//!     #[validate(email)]
+//!     pub email: String,
+//!     #[modify(trim)]
+//!     #[validate(length(max = 16))]
+//!     pub referral: Option<String>,
+//! }
+//!
+//! async fn handler(WithFallback(Json(signup), fell_back): WithFallback<Json<Signup>>) {
+//!     assert!(signup.referral.is_none() || fell_back.is_empty());
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! let router = Router::new()
+//!     .route("/signup", post(handler))
+//!     .with_state(FallbackFields::new(["referral"]));
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::{HasModify, ValidifyRejection};
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use validify::{Modify, Validate, ValidationErrors};
+
+/// The fields `WithFallback` replaces with their default value when they are invalid.
+///
+/// `WithFallback` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FallbackFields(Arc<BTreeSet<String>>);
+
+impl FallbackFields {
+    /// Create a policy for the given top-level fields.
+    pub fn new<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self(Arc::new(fields.into_iter().map(Into::into).collect()))
+    }
+
+    /// Returns `true` if the given field falls back to its default value when invalid.
+    pub fn contains(&self, field: &str) -> bool {
+        self.0.contains(field)
+    }
+
+    /// Modifies and validates `data`, replacing its invalid listed fields with their default value.
+    /// Returns the names of the fields which fell back.
+    pub fn apply<T>(&self, data: &mut T) -> Result<Vec<String>, ValidationErrors>
+    where
+        T: Modify + Validate + Default + Serialize + DeserializeOwned,
+    {
+        data.modify();
+        let Err(errors) = data.validate() else {
+            return Ok(Vec::new());
+        };
+        let fields = errors
+            .errors()
+            .iter()
+            .filter_map(|error| error.location().split('/').nth(1))
+            .filter(|field| self.contains(field))
+            .map(ToOwned::to_owned)
+            .collect::<BTreeSet<_>>();
+        if fields.is_empty() {
+            return Err(errors);
+        }
+        let (Ok(Value::Object(mut document)), Ok(Value::Object(defaults))) = (
+            serde_json::to_value(&*data),
+            serde_json::to_value(T::default()),
+        ) else {
+            return Err(errors);
+        };
+        for field in &fields {
+            let default = defaults.get(field).cloned().unwrap_or(Value::Null);
+            document.insert(field.clone(), default);
+        }
+        *data = serde_json::from_value(Value::Object(document)).map_err(|_| errors)?;
+        data.validate()?;
+        Ok(fields.into_iter().collect())
+    }
+}
+
+/// # `WithFallback` data extractor
+///
+/// `WithFallback` modifies and validates the data of the inner extractor, replacing the invalid fields listed
+/// in the `FallbackFields` policy taken from the state with their default value, and holds the inner extractor
+/// and the names of the fields which fell back.
+///
+#[derive(Debug, Clone, Default)]
+pub struct WithFallback<E>(pub E, pub Vec<String>);
+
+impl<E> WithFallback<E> {
+    /// Consumes the `WithFallback` and returns the inner extractor and the fields which fell back.
+    pub fn into_inner(self) -> (E, Vec<String>) {
+        (self.0, self.1)
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for WithFallback<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for WithFallback<Extractor>
+where
+    State: Send + Sync,
+    FallbackFields: FromRef<State>,
+    Extractor: HasModify + FromRequest<State>,
+    Extractor::Modify: Validate + Default + Serialize + DeserializeOwned,
+{
+    type Rejection = ValidifyRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let policy = FallbackFields::from_ref(state);
+        let mut inner = Extractor::from_request(req, state)
+            .await
+            .map_err(ValidifyRejection::Inner)?;
+        let fell_back = policy.apply(inner.get_modify())?;
+        Ok(WithFallback(inner, fell_back))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequestParts<State> for WithFallback<Extractor>
+where
+    State: Send + Sync,
+    FallbackFields: FromRef<State>,
+    Extractor: HasModify + FromRequestParts<State>,
+    Extractor::Modify: Validate + Default + Serialize + DeserializeOwned,
+{
+    type Rejection = ValidifyRejection<<Extractor as FromRequestParts<State>>::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let policy = FallbackFields::from_ref(state);
+        let mut inner = Extractor::from_request_parts(parts, state)
+            .await
+            .map_err(ValidifyRejection::Inner)?;
+        let fell_back = policy.apply(inner.get_modify())?;
+        Ok(WithFallback(inner, fell_back))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use validify::Validify;
+
+    #[derive(Debug, Clone, PartialEq, Validify, Serialize, Deserialize)]
+    struct Preferences {
+        #[validate(length(min = 2, max = 2))]
+        language: String,
+    }
+
+    #[derive(Debug, Default, Clone, PartialEq, Validify, Serialize, Deserialize)]
+    struct Signup {
+        #[validate(length(min = 1))]
+        name: String,
+        #[modify(trim)]
+        #[validate(length(max = 8))]
+        referral: Option<String>,
+        #[validate]
+        preferences: Preferences,
+    }
+
+    impl Default for Preferences {
+        fn default() -> Self {
+            Self {
+                language: String::from("en"),
+            }
+        }
+    }
+
+    #[test]
+    fn fall_back_to_defaults() {
+        let policy = FallbackFields::new(["referral", "preferences"]);
+
+        let mut signup = Signup {
+            name: String::from("axum"),
+            referral: Some(String::from(" FRIEND ")),
+            preferences: Preferences::default(),
+        };
+        assert_eq!(policy.apply(&mut signup), Ok(Vec::new()));
+        assert_eq!(signup.referral.as_deref(), Some("FRIEND"));
+
+        let mut signup = Signup {
+            name: String::from("axum"),
+            referral: Some(String::from("a referral code far too long")),
+            preferences: Preferences {
+                language: String::from("english"),
+            },
+        };
+        assert_eq!(
+            policy.apply(&mut signup),
+            Ok(vec![String::from("preferences"), String::from("referral")])
+        );
+        assert_eq!(signup.referral, None);
+        assert_eq!(signup.preferences.language, "en");
+
+        let mut signup = Signup {
+            name: String::new(),
+            referral: Some(String::from("a referral code far too long")),
+            preferences: Preferences::default(),
+        };
+        let errors = policy.apply(&mut signup).expect_err("empty name accepted");
+        assert_eq!(errors.errors().len(), 1);
+        assert_eq!(errors.errors()[0].location(), "/name");
+        assert_eq!(signup.referral, None);
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn invalid_optional_field_falls_back() {
+        use axum::body::Body;
+        use axum::Json;
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+        let policy = FallbackFields::new(["referral"]);
+
+        let WithFallback(Json(signup), fell_back) = WithFallback::<Json<Signup>>::from_request(
+            request(
+                r#"{ "name": "axum", "referral": "not a referral code", "preferences": { "language": "fr" } }"#,
+            ),
+            &policy,
+        )
+        .await
+        .expect("invalid optional field rejected");
+        assert_eq!(signup.referral, None);
+        assert_eq!(signup.preferences.language, "fr");
+        assert_eq!(fell_back, [String::from("referral")]);
+
+        assert!(matches!(
+            WithFallback::<Json<Signup>>::from_request(
+                request(r#"{ "name": "axum", "preferences": { "language": "french" } }"#),
+                &policy,
+            )
+            .await,
+            Err(ValidifyRejection::Valid(_))
+        ));
+    }
+}
@@ -0,0 +1,394 @@
+//! # Metrics of failing fields
+//!
+//! ## Feature
+//!
+//! Enable the `field_metrics` feature to use `Measured<E>`.
+//!
+//! ## Usage
+//!
+//! `Measured<E>` validates like `Valid<E>`, and counts the fields which fail validation, to find out which fields
+//! clients get wrong most often. Only the names of the fields are recorded, never their values.
+//!
+//! 1. Pick a `FailureRecorder`: `MetricsCounter` increments the `validation_failures` counter of the
+//!    [`metrics`](https://docs.rs/metrics) crate, labelled with the `field`, and `FailureCounts` keeps the counts
+//!    in memory. Implement `FailureRecorder` to send the counts anywhere else.
+//! 2. Make a `FieldMetrics` wrapping your recorder available from your router state
+//!    (implement `FromRef<YourState>` for it, or use it as the state directly).
+//! 3. In your handler function, use `Measured<E>` as the extractor, e.g. `Measured<Json<T>>`.
+//!
+//! Each failing field is counted once per rejected request, however many of its rules failed.
+//! Nested fields are named using dots, e.g. `owner.email`, and array indices are left out, so `items.name`
+//! counts the failures of the `name` field of every element of `items`, keeping the number of counters bounded.
+//! Requests which the inner extractor rejects are not counted.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use axum::extract::State;
+//! use axum::routing::{get, post};
+//! use axum::Json;
+//! use axum::Router;
+//! use axum_valid::field_metrics::{FailureCounts, FieldMetrics, Measured};
+//! use serde::Deserialize;
+//! use std::collections::BTreeMap;
+//! use validator::Validate;
+//!
+//! #[derive(Clone)]
+//! struct AppState {
+//!     counts: FailureCounts,
+//!     metrics: FieldMetrics,
+//! }
+//!
+//! impl axum::extract::FromRef<AppState> for FieldMetrics {
+//!     fn from_ref(state: &AppState) -> Self {
+//!         state.metrics.clone()
+//!     }
+//! }
+//!
+//! #[derive(Validate, Deserialize)]
+//! pub struct Signup {
+//!     #[validate(email)]
+//!     pub email: String,
+//!     #[validate(length(min = 8))]
+//!     pub password: String,
+//! }
+//!
+//! async fn signup(Measured(Json(signup)): Measured<Json<Signup>>) {
+//!     assert!(signup.validate().is_ok());
+//! }
+//!
+//! async fn failures(State(state): State<AppState>) -> Json<BTreeMap<String, u64>> {
+//!     Json(state.counts.snapshot())
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! #     let counts = FailureCounts::default();
+//! #     let state = AppState { metrics: FieldMetrics::new(counts.clone()), counts };
+//! #     let router = Router::new()
+//! #         .route("/signup", post(signup))
+//! #         .route("/failures", get(failures))
+//! #         .with_state(state);
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::{HasValidate, ValidRejection};
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use validator::{Validate, ValidationErrors};
+
+/// Destination of the failure counts.
+pub trait FailureRecorder: Send + Sync + 'static {
+    /// Count one failure of `field`
+    fn record_failure(&self, field: &str);
+}
+
+/// In-memory `FailureRecorder`, counting the failures of each field.
+#[derive(Debug, Clone, Default)]
+pub struct FailureCounts(Arc<Mutex<BTreeMap<String, u64>>>);
+
+impl FailureCounts {
+    /// Returns the number of failures of a field.
+    pub fn get(&self, field: &str) -> u64 {
+        self.lock().get(field).copied().unwrap_or_default()
+    }
+
+    /// Returns the number of failures of every field which failed at least once.
+    pub fn snapshot(&self) -> BTreeMap<String, u64> {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, u64>> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl FailureRecorder for FailureCounts {
+    fn record_failure(&self, field: &str) {
+        *self.lock().entry(field.to_owned()).or_default() += 1;
+    }
+}
+
+/// Name of the counter incremented by `MetricsCounter`.
+pub const VALIDATION_FAILURES: &str = "validation_failures";
+
+/// `FailureRecorder` incrementing the `validation_failures` counter of the `metrics` crate,
+/// labelled with the failing `field`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetricsCounter;
+
+impl FailureRecorder for MetricsCounter {
+    fn record_failure(&self, field: &str) {
+        metrics::counter!(VALIDATION_FAILURES, "field" => field.to_owned()).increment(1);
+    }
+}
+
+/// Records the failing fields of rejected requests into a `FailureRecorder`.
+///
+/// `Measured` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Clone)]
+pub struct FieldMetrics {
+    recorder: Arc<dyn FailureRecorder>,
+}
+
+impl FieldMetrics {
+    /// Create a `FieldMetrics` recording failures into `recorder`.
+    pub fn new(recorder: impl FailureRecorder) -> Self {
+        Self {
+            recorder: Arc::new(recorder),
+        }
+    }
+
+    fn record(&self, errors: &ValidationErrors) {
+        let mut fields = BTreeSet::new();
+        failing_fields(errors, &mut fields);
+        for field in fields {
+            self.recorder.record_failure(&field);
+        }
+    }
+}
+
+impl Debug for FieldMetrics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FieldMetrics").finish_non_exhaustive()
+    }
+}
+
+/// Collects the dotted names of the failing fields, leaving array indices out.
+fn failing_fields(errors: &ValidationErrors, fields: &mut BTreeSet<String>) {
+    crate::flatten_errors(errors, &mut |path, _| {
+        fields.insert(crate::format_error_path(path, false));
+    });
+}
+
+/// # `Measured` data extractor
+///
+/// `Measured` validates the inner data like `Valid`, recording its failing fields
+/// into the `FieldMetrics` taken from the state when validation fails.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Measured<E>(pub E);
+
+impl<E> Deref for Measured<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for Measured<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for Measured<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> Measured<E> {
+    /// Consumes the `Measured` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for Measured<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for Measured<Extractor>
+where
+    State: Send + Sync,
+    FieldMetrics: FromRef<State>,
+    Extractor: HasValidate + FromRequest<State>,
+    Extractor::Validate: Validate,
+{
+    type Rejection = ValidRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(ValidRejection::Inner)?;
+        if let Err(errors) = inner.get_validate().validate() {
+            FieldMetrics::from_ref(state).record(&errors);
+            return Err(ValidRejection::Valid(errors));
+        }
+        Ok(Measured(inner))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequestParts<State> for Measured<Extractor>
+where
+    State: Send + Sync,
+    FieldMetrics: FromRef<State>,
+    Extractor: HasValidate + FromRequestParts<State>,
+    Extractor::Validate: Validate,
+{
+    type Rejection = ValidRejection<<Extractor as FromRequestParts<State>>::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let inner = Extractor::from_request_parts(parts, state)
+            .await
+            .map_err(ValidRejection::Inner)?;
+        if let Err(errors) = inner.get_validate().validate() {
+            FieldMetrics::from_ref(state).record(&errors);
+            return Err(ValidRejection::Valid(errors));
+        }
+        Ok(Measured(inner))
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::Json;
+    use serde::Deserialize;
+
+    #[derive(Debug, Validate, Deserialize)]
+    struct Item {
+        #[validate(length(min = 1))]
+        name: String,
+    }
+
+    #[derive(Debug, Validate, Deserialize)]
+    struct Order {
+        #[validate(email)]
+        email: String,
+        #[validate(range(min = 1), custom(function = "even"))]
+        quantity: u32,
+        #[validate(nested)]
+        items: Vec<Item>,
+    }
+
+    fn even(quantity: u32) -> Result<(), validator::ValidationError> {
+        match quantity % 2 {
+            0 => Ok(()),
+            _ => Err(validator::ValidationError::new("even")),
+        }
+    }
+
+    #[tokio::test]
+    async fn failing_fields_are_counted() {
+        let counts = FailureCounts::default();
+        let metrics = FieldMetrics::new(counts.clone());
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+
+        assert!(Measured::<Json<Order>>::from_request(
+            request(r#"{ "email": "a@b.c", "quantity": 2, "items": [{ "name": "x" }] }"#),
+            &metrics,
+        )
+        .await
+        .is_ok());
+        assert!(counts.snapshot().is_empty());
+
+        let rejection = Measured::<Json<Order>>::from_request(
+            request(
+                r#"{ "email": "nope", "quantity": 0, "items": [{ "name": "" }, { "name": "" }] }"#,
+            ),
+            &metrics,
+        )
+        .await
+        .expect_err("invalid order accepted");
+        assert!(matches!(rejection, ValidRejection::Valid(_)));
+        assert!(Measured::<Json<Order>>::from_request(
+            request(r#"{ "email": "nope", "quantity": 2, "items": [] }"#),
+            &metrics,
+        )
+        .await
+        .is_err());
+        assert!(
+            Measured::<Json<Order>>::from_request(request("{"), &metrics)
+                .await
+                .is_err()
+        );
+
+        assert_eq!(
+            counts.snapshot(),
+            BTreeMap::from([
+                (String::from("email"), 2),
+                (String::from("items.name"), 1),
+                (String::from("quantity"), 1),
+            ])
+        );
+        assert_eq!(counts.get("email"), 2);
+        assert_eq!(counts.get("items"), 0);
+    }
+
+    #[test]
+    fn metrics_counter_increments_counters() {
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let field_metrics = FieldMetrics::new(MetricsCounter);
+        let order = Order {
+            email: String::from("nope"),
+            quantity: 0,
+            items: vec![Item {
+                name: String::new(),
+            }],
+        };
+        let errors = order.validate().expect_err("invalid order accepted");
+        metrics::with_local_recorder(&recorder, || {
+            field_metrics.record(&errors);
+            field_metrics.record(&errors);
+        });
+
+        let counters = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| {
+                let key = key.key();
+                assert_eq!(key.name(), VALIDATION_FAILURES);
+                let field = key
+                    .labels()
+                    .find(|label| label.key() == "field")
+                    .map(|label| label.value().to_owned())
+                    .expect("missing field label");
+                (field, value)
+            })
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(
+            counters,
+            BTreeMap::from([
+                (String::from("email"), DebugValue::Counter(2)),
+                (String::from("items.name"), DebugValue::Counter(2)),
+                (String::from("quantity"), DebugValue::Counter(2)),
+            ])
+        );
+    }
+}
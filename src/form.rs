@@ -157,4 +157,53 @@ mod tests {
             builder.form(T::invalid())
         }
     }
+
+    #[cfg(feature = "validify")]
+    #[tokio::test]
+    async fn missing_fields_are_validation_errors() {
+        use crate::{ValidationRejection, Validified};
+        use axum::body::Body;
+        use axum::extract::{FromRequest, Request};
+        use axum::response::IntoResponse;
+        use axum::Form;
+        use validify::{Payload, Validify};
+
+        #[derive(Debug, Clone, serde::Deserialize, Validify, Payload)]
+        struct Login {
+            #[validate(length(min = 1))]
+            user: String,
+            #[validate(length(min = 8))]
+            password: String,
+        }
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .method("POST")
+                .uri("/")
+                .header("content-type", "application/x-www-form-urlencoded")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+
+        let Validified(Form(login)) =
+            Validified::<Form<Login>>::from_request(request("user=axum&password=secret42"), &())
+                .await
+                .expect("complete form rejected");
+        assert_eq!(login.user, "axum");
+
+        let rejection = Validified::<Form<Login>>::from_request(request("user=axum"), &())
+            .await
+            .expect_err("missing field accepted");
+        let ValidationRejection::Valid(errors) = &rejection else {
+            panic!("missing field should be a validation error");
+        };
+        assert!(errors
+            .field_errors()
+            .iter()
+            .any(|error| error.field_name() == Some("password") && error.code() == "required"));
+        assert_eq!(
+            rejection.into_response().status(),
+            crate::VALIDATION_ERROR_STATUS
+        );
+    }
 }
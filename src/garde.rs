@@ -4,9 +4,13 @@
 //!
 //! Enable the `garde` feature to use `Garde<E>`.
 //!
+//! With the `into_json` feature, validation errors are rendered as a JSON object mapping the dotted path
+//! of each invalid field to its messages, e.g. `{ "outer.inner": ["length is lower than 3"], "items[0]": [...] }`.
+//! Errors concerning the whole value are reported under `__all__`.
+//!
 
 #[cfg(test)]
-pub mod test;
+mod test;
 
 use crate::{HasValidate, ValidationRejection};
 use axum::async_trait;
@@ -55,6 +59,19 @@ impl<E> Garde<E> {
     pub fn into_inner(self) -> E {
         self.0
     }
+
+    /// Maps the validated value with `f`, keeping it wrapped in `Garde`, like `Valid::map`.
+    pub fn map<U>(self, f: impl FnOnce(E) -> U) -> Garde<U> {
+        Garde(f(self.0))
+    }
+
+    /// Maps the validated value with the fallible `f`, keeping it wrapped in `Garde` on success.
+    pub fn try_map<U, Error>(
+        self,
+        f: impl FnOnce(E) -> Result<U, Error>,
+    ) -> Result<Garde<U>, Error> {
+        f(self.0).map(Garde)
+    }
 }
 
 #[cfg(feature = "aide")]
@@ -67,13 +84,67 @@ where
     }
 }
 
+/// Validation errors reported by garde.
+///
+/// It dereferences to the `garde::Report`, and serializes as a map from the dotted path of each invalid field
+/// to its messages, so clients can map errors back to form fields.
+///
+#[derive(Debug, Clone)]
+pub struct GardeReport(pub Report);
+
+impl Deref for GardeReport {
+    type Target = Report;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl GardeReport {
+    /// Consumes the `GardeReport` and returns the `garde::Report` within.
+    pub fn into_inner(self) -> Report {
+        self.0
+    }
+}
+
+impl From<Report> for GardeReport {
+    fn from(value: Report) -> Self {
+        Self(value)
+    }
+}
+
+impl Display for GardeReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for GardeReport {}
+
+#[cfg(feature = "into_json")]
+impl serde::Serialize for GardeReport {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut fields = std::collections::BTreeMap::<String, Vec<&str>>::new();
+        for (path, error) in self.0.iter() {
+            let path = path.to_string();
+            let path = if path.is_empty() {
+                String::from("__all__")
+            } else {
+                path
+            };
+            fields.entry(path).or_default().push(error.message());
+        }
+        fields.serialize(serializer)
+    }
+}
+
 /// `GardeRejection` is returned when the `Garde` extractor fails.
 ///
-pub type GardeRejection<E> = ValidationRejection<Report, E>;
+pub type GardeRejection<E> = ValidationRejection<GardeReport, E>;
 
 impl<E> From<Report> for GardeRejection<E> {
     fn from(value: Report) -> Self {
-        Self::Valid(value)
+        Self::Valid(GardeReport(value))
     }
 }
 
@@ -145,7 +216,7 @@ mod tests {
         let mut report = Report::new();
         report.append(Path::empty(), garde::Error::new(GARDE));
         let s = report.to_string();
-        let vr = GardeRejection::<String>::Valid(report);
+        let vr = GardeRejection::<String>::from(report);
         assert_eq!(vr.to_string(), s);
 
         // GardeRejection::Inner Display
@@ -156,13 +227,91 @@ mod tests {
         // GardeRejection::Valid Error
         let mut report = Report::new();
         report.append(Path::empty(), garde::Error::new(GARDE));
-        let vr = GardeRejection::<io::Error>::Valid(report);
-        assert!(matches!(vr.source(), Some(source) if source.downcast_ref::<Report>().is_some()));
+        let vr = GardeRejection::<io::Error>::from(report);
+        assert!(
+            matches!(vr.source(), Some(source) if source.downcast_ref::<GardeReport>().is_some())
+        );
 
         // GardeRejection::Valid Error
-        let vr = GardeRejection::<io::Error>::Inner(io::Error::new(io::ErrorKind::Other, GARDE));
+        let vr = GardeRejection::<io::Error>::Inner(io::Error::other(GARDE));
         assert!(
             matches!(vr.source(), Some(source) if source.downcast_ref::<io::Error>().is_some())
         );
     }
+
+    #[test]
+    fn rejection_into_status_and_message() {
+        use axum::extract::rejection::MissingJsonContentType;
+        use axum::http::StatusCode;
+
+        let mut report = Report::new();
+        report.append(Path::new("name"), garde::Error::new(GARDE));
+        let (status, valid) =
+            <(StatusCode, String)>::from(GardeRejection::<MissingJsonContentType>::from(report));
+        assert_eq!(status, crate::VALIDATION_ERROR_STATUS);
+        assert_eq!(valid, "name: garde");
+
+        let (status, inner) =
+            <(StatusCode, String)>::from(GardeRejection::Inner(MissingJsonContentType::default()));
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(inner, MissingJsonContentType::default().to_string());
+        assert_ne!(valid, inner);
+    }
+
+    #[cfg(feature = "into_json")]
+    #[tokio::test]
+    async fn nested_paths_in_json_body() {
+        use axum::body::Body;
+        use axum::response::IntoResponse;
+        use axum::Json;
+        use serde_json::{json, Value};
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        struct Inner {
+            #[garde(length(min = 3))]
+            inner: String,
+        }
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        struct Outer {
+            #[garde(dive)]
+            outer: Inner,
+            #[garde(length(max = 1), inner(range(max = 9)))]
+            items: Vec<u32>,
+        }
+
+        let request = Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                r#"{ "outer": { "inner": "ax" }, "items": [1, 10] }"#,
+            ))
+            .expect("invalid request");
+        let response = Garde::<Json<Outer>>::from_request(request, &())
+            .await
+            .expect_err("invalid data accepted")
+            .into_response();
+        assert_eq!(response.status(), crate::VALIDATION_ERROR_STATUS);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("failed to read body");
+        let errors: Value = serde_json::from_slice(&body).expect("invalid json");
+        let messages = |path: &str| errors[path].as_array().map(Vec::len);
+        assert_eq!(messages("outer.inner"), Some(1));
+        assert_eq!(messages("items"), Some(1));
+        assert_eq!(messages("items[1]"), Some(1));
+        assert_eq!(
+            errors.as_object().map(|fields| fields.len()),
+            Some(3),
+            "{errors}"
+        );
+        assert!(errors["outer.inner"][0].is_string());
+
+        let mut report = Report::new();
+        report.append(Path::empty(), garde::Error::new(GARDE));
+        assert_eq!(
+            serde_json::to_value(GardeReport(report)).expect("failed to serialize report"),
+            json!({ "__all__": [GARDE] })
+        );
+    }
 }
@@ -0,0 +1,99 @@
+//! # Request guards
+//!
+//! ## Feature
+//!
+//! Enable the `guard` feature to use the guard extractors.
+//!
+//! A guard checks some property of the incoming request before delegating to an inner extractor,
+//! which is usually one of the validation extractors, e.g. `FreshSignature<Valid<Json<T>>>`.
+//! If the check fails, the inner extractor is never run.
+//!
+//! ## Modules
+//!
+//! * [`compression`] : `Compressed<E, THRESHOLD>` (requires the `guard_compression` feature)
+//! * [`crc32`] : `Crc32Verified<E>` (requires the `guard_crc32` feature)
+//! * [`encodings`] : `AllowedEncodings<E, L>` (requires the `guard_compression` feature)
+//! * [`freshness`] : `FreshSignature<E>`
+//! * [`headers`] : `RequireHeaders<E, H>`
+//! * [`hash`] : `HashVerified<E>` (requires the `guard_hash` feature)
+//! * [`mime`] : `MimeSniffed<E>` (requires the `guard_mime` feature)
+//! * [`min_size`] : `MinBodySize<E, N>`
+//! * [`origin`] : `OriginGuard<E>`
+//! * [`sequence`] : `InSequence<E>`
+//!
+
+#[cfg(feature = "guard_compression")]
+pub mod compression;
+#[cfg(feature = "guard_crc32")]
+pub mod crc32;
+#[cfg(feature = "guard_compression")]
+pub mod encodings;
+pub mod freshness;
+#[cfg(feature = "guard_hash")]
+pub mod hash;
+pub mod headers;
+#[cfg(feature = "guard_mime")]
+pub mod mime;
+pub mod min_size;
+pub mod origin;
+pub mod sequence;
+
+#[cfg(feature = "guard_compression")]
+pub use compression::{Compressed, CompressionError, DecompressedLimit};
+#[cfg(feature = "guard_crc32")]
+pub use crc32::{Crc32Error, Crc32Verified, CONTENT_CRC32_HEADER};
+#[cfg(feature = "guard_compression")]
+pub use encodings::{AllowedEncodings, EncodingList};
+pub use freshness::{FreshSignature, FreshnessError, MaxSkew, TIMESTAMP_HEADER};
+#[cfg(feature = "guard_hash")]
+pub use hash::{HashError, HashVerified, CONTENT_SHA256_HEADER};
+pub use headers::{HeaderSet, HeadersError, RequireHeaders};
+#[cfg(feature = "guard_mime")]
+pub use mime::{MimeError, MimeSniffed};
+pub use min_size::{MinBodySize, MinBodySizeError};
+pub use origin::{AllowedOrigins, OriginError, OriginGuard};
+pub use sequence::{ClientId, InSequence, SequenceError, SequenceTracker, SEQUENCE_HEADER};
+
+use axum::response::{IntoResponse, Response};
+use std::error::Error;
+use std::fmt::Display;
+
+/// `GuardRejection` is returned when a guard extractor fails.
+///
+/// This enumeration captures two types of errors: errors raised by the guard's own check (represented by `Guard`),
+/// and errors that may arise within the inner extractor (represented by `Inner`).
+///
+#[derive(Debug)]
+pub enum GuardRejection<G, E> {
+    /// `Guard` variant captures errors related to the guard's check.
+    Guard(G),
+    /// `Inner` variant represents potential errors that might occur within the inner extractor.
+    Inner(E),
+}
+
+impl<G: Display, E: Display> Display for GuardRejection<G, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GuardRejection::Guard(error) => write!(f, "{error}"),
+            GuardRejection::Inner(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl<G: Error + 'static, E: Error + 'static> Error for GuardRejection<G, E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GuardRejection::Guard(ge) => Some(ge),
+            GuardRejection::Inner(e) => Some(e),
+        }
+    }
+}
+
+impl<G: IntoResponse, E: IntoResponse> IntoResponse for GuardRejection<G, E> {
+    fn into_response(self) -> Response {
+        match self {
+            GuardRejection::Guard(g) => g.into_response(),
+            GuardRejection::Inner(e) => e.into_response(),
+        }
+    }
+}
@@ -0,0 +1,429 @@
+//! # Body compression guard
+//!
+//! ## Feature
+//!
+//! Enable the `guard_compression` feature to use `Compressed<E, THRESHOLD>`.
+//!
+//! ## Usage
+//!
+//! 1. Have clients compress request bodies larger than `THRESHOLD` bytes with `gzip` or `deflate`,
+//!    and send the `Content-Encoding` header accordingly.
+//! 2. In your handler function, wrap your extractor with `Compressed`,
+//!    e.g. `Compressed<Valid<Json<T>>, 65536>`.
+//!
+//! Uncompressed bodies larger than `THRESHOLD` bytes are rejected with `400 Bad Request`,
+//! and bodies with an unsupported `Content-Encoding` with `415 Unsupported Media Type`,
+//! before the inner extractor runs. Compressed bodies are decompressed, so the inner extractor
+//! sees the original payload, whatever its size.
+//!
+//! Decompressed bodies are limited like any other body, to axum's default body limit or the one set with
+//! `DefaultBodyLimit`, so that a small compressed body cannot expand into an arbitrarily large one.
+//! Insert a `DecompressedLimit` into the request extensions, e.g. with `Extension(DecompressedLimit(n))`,
+//! to lower the limit of decompressed bodies to `n` bytes. It cannot raise it, since the inner extractor
+//! still enforces the body limit. Bodies are decompressed on a blocking thread, and only until the limit
+//! is reached.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::guard::Compressed;
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/batch", post(handler))
+//!     }
+//!
+//!     async fn handler(
+//!         Compressed(Valid(Json(batch))): Compressed<Valid<Json<Batch>>, 65536>,
+//!     ) {
+//!         assert!(batch.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Batch {
+//!         #[validate(length(min = 1, max = 10000))]
+//!         pub items: Vec<String>,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::guard::GuardRejection;
+use axum::async_trait;
+use axum::body::{Body, Bytes};
+use axum::extract::{FromRequest, Request};
+use axum::http::request::Parts;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::RequestExt;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use http_body_util::{BodyExt, Limited};
+use std::convert::Infallible;
+use std::fmt::{Display, Formatter};
+use std::io::{Cursor, Read};
+use std::ops::{Deref, DerefMut};
+
+/// Size of the chunks a body is decompressed in, in bytes.
+const CHUNK_SIZE: usize = 16 * 1024;
+
+/// Maximum size of a decompressed body, in bytes.
+///
+/// Insert it into the request extensions to limit decompressed bodies below the body limit.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecompressedLimit(pub usize);
+
+/// # `Compressed` guard extractor
+///
+/// `Compressed` requires bodies larger than `THRESHOLD` bytes to be compressed,
+/// decompresses the body, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Compressed<E, const THRESHOLD: usize>(pub E);
+
+impl<E, const THRESHOLD: usize> Deref for Compressed<E, THRESHOLD> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E, const THRESHOLD: usize> DerefMut for Compressed<E, THRESHOLD> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display, const THRESHOLD: usize> Display for Compressed<T, THRESHOLD> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E, const THRESHOLD: usize> Compressed<E, THRESHOLD> {
+    /// Consumes the `Compressed` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T, const THRESHOLD: usize> aide::OperationInput for Compressed<T, THRESHOLD>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// Errors raised by `Compressed`.
+#[derive(Debug)]
+pub enum CompressionError {
+    /// The body is larger than the threshold but not compressed.
+    Required {
+        /// Size above which bodies must be compressed, in bytes.
+        threshold: usize,
+        /// Size of the uncompressed body, in bytes.
+        actual: usize,
+    },
+    /// The `Content-Encoding` is neither `gzip` nor `deflate`.
+    Unsupported(String),
+    /// The `Content-Encoding` is not allowed on this route.
+    Disallowed(String),
+    /// The body could not be decompressed.
+    Decompress(std::io::Error),
+    /// The decompressed body is larger than the body limit, or the `DecompressedLimit`.
+    TooLarge,
+    /// The body could not be read.
+    Body(axum::Error),
+}
+
+impl Display for CompressionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompressionError::Required { threshold, actual } => write!(
+                f,
+                "Request bodies larger than {threshold} bytes must be compressed, got {actual} uncompressed bytes"
+            ),
+            CompressionError::Unsupported(encoding) => {
+                write!(f, "Unsupported content encoding `{encoding}`")
+            }
+            CompressionError::Disallowed(encoding) => {
+                write!(f, "Content encoding `{encoding}` is not allowed")
+            }
+            CompressionError::Decompress(error) => {
+                write!(f, "Failed to decompress the request body: {error}")
+            }
+            CompressionError::TooLarge => write!(f, "Decompressed request body is too large"),
+            CompressionError::Body(error) => write!(f, "Failed to read the request body: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for CompressionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CompressionError::Decompress(error) => Some(error),
+            CompressionError::Body(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for CompressionError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            CompressionError::Unsupported(_) | CompressionError::Disallowed(_) => {
+                StatusCode::UNSUPPORTED_MEDIA_TYPE
+            }
+            CompressionError::TooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            CompressionError::Body(error) if crate::body::is_length_limit_error(error) => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            _ => StatusCode::BAD_REQUEST,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// `CompressedRejection` is returned when the `Compressed` extractor fails.
+///
+pub type CompressedRejection<E> = GuardRejection<CompressionError, E>;
+
+/// Reads the `Content-Encoding` of a request, in lowercase.
+pub(super) fn content_encoding(req: &Request) -> Result<Option<String>, CompressionError> {
+    req.headers()
+        .get(header::CONTENT_ENCODING)
+        .map(|value| {
+            value
+                .to_str()
+                .map(|value| value.trim().to_ascii_lowercase())
+                .map_err(|_| CompressionError::Unsupported(String::from("<non-ASCII>")))
+        })
+        .transpose()
+}
+
+/// Decompresses a body encoded with `encoding`, which must not be `identity`,
+/// and returns the request with the decompressed body.
+///
+/// The body is decompressed on a blocking thread, which stops once the decompressed body limit is reached.
+pub(super) async fn decompress(
+    mut parts: Parts,
+    bytes: Bytes,
+    encoding: &str,
+) -> Result<Request, CompressionError> {
+    let mut reader: Box<dyn Read + Send> = match encoding {
+        "gzip" | "x-gzip" => Box::new(GzDecoder::new(Cursor::new(bytes))),
+        "deflate" => Box::new(ZlibDecoder::new(Cursor::new(bytes))),
+        encoding => return Err(CompressionError::Unsupported(encoding.to_owned())),
+    };
+    let (sender, mut receiver) = tokio::sync::mpsc::channel(1);
+    let decoder = tokio::task::spawn_blocking(move || -> std::io::Result<()> {
+        let mut chunk = vec![0; CHUNK_SIZE];
+        loop {
+            let read = reader.read(&mut chunk)?;
+            // Stop once the body is decompressed, or no longer read
+            if read == 0
+                || sender
+                    .blocking_send(Bytes::copy_from_slice(&chunk[..read]))
+                    .is_err()
+            {
+                return Ok(());
+            }
+        }
+    });
+    let chunks = futures_util::stream::poll_fn(move |cx| {
+        receiver
+            .poll_recv(cx)
+            .map(|chunk| chunk.map(Ok::<_, Infallible>))
+    });
+
+    parts.headers.remove(header::CONTENT_ENCODING);
+    parts.headers.remove(header::CONTENT_LENGTH);
+    let req = Request::from_parts(parts, Body::from_stream(chunks));
+    let (parts, body) = match req.extensions().get::<DecompressedLimit>() {
+        Some(&DecompressedLimit(limit)) => req.map(|body| Body::new(Limited::new(body, limit))),
+        None => req.with_limited_body(),
+    }
+    .into_parts();
+    let body = body.collect().await.map_err(|error| {
+        if crate::body::is_length_limit_error(&error) {
+            CompressionError::TooLarge
+        } else {
+            CompressionError::Body(error)
+        }
+    })?;
+    decoder
+        .await
+        .map_err(|error| CompressionError::Decompress(std::io::Error::other(error)))?
+        .map_err(CompressionError::Decompress)?;
+    Ok(Request::from_parts(parts, Body::from(body.to_bytes())))
+}
+
+async fn check_compression(req: Request, threshold: usize) -> Result<Request, CompressionError> {
+    let encoding = content_encoding(&req)?;
+    let (parts, bytes) = crate::body::read(req)
+        .await
+        .map_err(CompressionError::Body)?;
+    match encoding.as_deref() {
+        None | Some("identity") if bytes.len() > threshold => Err(CompressionError::Required {
+            threshold,
+            actual: bytes.len(),
+        }),
+        None | Some("identity") => Ok(Request::from_parts(parts, Body::from(bytes))),
+        Some(encoding) => decompress(parts, bytes, encoding).await,
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, const THRESHOLD: usize> FromRequest<State>
+    for Compressed<Extractor, THRESHOLD>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = CompressedRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let req = check_compression(req, THRESHOLD)
+            .await
+            .map_err(CompressedRejection::Guard)?;
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(CompressedRejection::Inner)?;
+        Ok(Compressed(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    fn request(encoding: Option<&str>, body: Vec<u8>) -> Request {
+        let mut builder = Request::builder().uri("/");
+        if let Some(encoding) = encoding {
+            builder = builder.header(header::CONTENT_ENCODING, encoding);
+        }
+        builder.body(Body::from(body)).expect("invalid request")
+    }
+
+    fn gzip(body: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(body).expect("failed to compress");
+        encoder.finish().expect("failed to compress")
+    }
+
+    #[tokio::test]
+    async fn compressed() {
+        let small = "a".repeat(16);
+        let large = "a".repeat(1024);
+
+        let Compressed(body) =
+            Compressed::<String, 64>::from_request(request(None, small.clone().into()), &())
+                .await
+                .expect("small uncompressed body rejected");
+        assert_eq!(body, small);
+
+        let rejection =
+            Compressed::<String, 64>::from_request(request(None, large.clone().into()), &())
+                .await
+                .expect_err("large uncompressed body accepted");
+        assert!(matches!(
+            rejection,
+            CompressedRejection::Guard(CompressionError::Required {
+                threshold: 64,
+                actual: 1024
+            })
+        ));
+        assert_eq!(rejection.into_response().status(), StatusCode::BAD_REQUEST);
+
+        let Compressed(body) = Compressed::<String, 64>::from_request(
+            request(Some("gzip"), gzip(large.as_bytes())),
+            &(),
+        )
+        .await
+        .expect("large compressed body rejected");
+        assert_eq!(body, large);
+
+        let rejection =
+            Compressed::<String, 64>::from_request(request(Some("br"), large.clone().into()), &())
+                .await
+                .expect_err("unsupported encoding accepted");
+        assert_eq!(
+            rejection.into_response().status(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+
+        assert!(matches!(
+            Compressed::<String, 64>::from_request(request(Some("gzip"), large.into()), &()).await,
+            Err(CompressedRejection::Guard(CompressionError::Decompress(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn decompressed_limit() {
+        // Decompressed bodies are limited to axum's default body limit of 2 MiB
+        let bomb = "a".repeat(3 * 1024 * 1024);
+        let rejection = Compressed::<String, 64>::from_request(
+            request(Some("gzip"), gzip(bomb.as_bytes())),
+            &(),
+        )
+        .await
+        .expect_err("body larger than the body limit accepted");
+        assert!(matches!(
+            rejection,
+            CompressedRejection::Guard(CompressionError::TooLarge)
+        ));
+        assert_eq!(
+            rejection.into_response().status(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+
+        // or to the `DecompressedLimit`, if set
+        let deflate = {
+            let mut encoder =
+                flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder
+                .write_all(&[b'a'; 1024])
+                .expect("failed to compress");
+            encoder.finish().expect("failed to compress")
+        };
+        let mut req = request(Some("deflate"), deflate.clone());
+        req.extensions_mut().insert(DecompressedLimit(2048));
+        let Compressed(body) = Compressed::<String, 64>::from_request(req, &())
+            .await
+            .expect("body within the decompressed limit rejected");
+        assert_eq!(body.len(), 1024);
+
+        let mut req = request(Some("deflate"), deflate);
+        req.extensions_mut().insert(DecompressedLimit(512));
+        assert!(matches!(
+            Compressed::<String, 64>::from_request(req, &()).await,
+            Err(CompressedRejection::Guard(CompressionError::TooLarge))
+        ));
+    }
+}
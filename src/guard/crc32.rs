@@ -0,0 +1,280 @@
+//! # Body CRC32 verification guard
+//!
+//! ## Feature
+//!
+//! Enable the `guard_crc32` feature to use `Crc32Verified<E>`.
+//!
+//! ## Usage
+//!
+//! 1. Have clients send the hex-encoded CRC32 checksum of the request body in the `X-Content-CRC32` header.
+//! 2. In your handler function, wrap your extractor with `Crc32Verified`, e.g. `Crc32Verified<Valid<Json<T>>>`.
+//!
+//! CRC32 detects accidental corruption at a lower cost than `HashVerified`, but offers no protection
+//! against deliberate tampering. The checksum is computed while the body is read. Requests without the header,
+//! with a header which is not a hex-encoded 32-bit checksum, or whose body does not match it,
+//! are rejected with `400 Bad Request` before the inner extractor runs.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::guard::Crc32Verified;
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/json", post(handler))
+//!     }
+//!
+//!     async fn handler(Crc32Verified(Valid(Json(parameter))): Crc32Verified<Valid<Json<Parameter>>>) {
+//!         assert!(parameter.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Parameter {
+//!         #[validate(range(min = 5, max = 10))]
+//!         pub v0: i32,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::guard::GuardRejection;
+use axum::async_trait;
+use axum::extract::{FromRequest, Request};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use crc32fast::Hasher;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// Name of the header carrying the hex-encoded CRC32 checksum of the request body.
+pub const CONTENT_CRC32_HEADER: &str = "x-content-crc32";
+
+/// # `Crc32Verified` guard extractor
+///
+/// `Crc32Verified` checks that the CRC32 checksum of the request body matches the
+/// `X-Content-CRC32` header, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Crc32Verified<E>(pub E);
+
+impl<E> Deref for Crc32Verified<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for Crc32Verified<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for Crc32Verified<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> Crc32Verified<E> {
+    /// Consumes the `Crc32Verified` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for Crc32Verified<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// Errors raised by `Crc32Verified`.
+#[derive(Debug)]
+pub enum Crc32Error {
+    /// The `X-Content-CRC32` header is missing.
+    Missing,
+    /// The `X-Content-CRC32` header is not a hex-encoded 32-bit checksum.
+    Invalid,
+    /// The checksum of the body does not match the `X-Content-CRC32` header.
+    Mismatch {
+        /// The checksum sent in the header.
+        expected: u32,
+        /// The checksum of the body.
+        actual: u32,
+    },
+    /// The body could not be read.
+    Body(axum::Error),
+}
+
+impl Display for Crc32Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Crc32Error::Missing => write!(f, "Missing `{CONTENT_CRC32_HEADER}` header"),
+            Crc32Error::Invalid => write!(
+                f,
+                "Invalid `{CONTENT_CRC32_HEADER}` header, expected a hex-encoded CRC32 checksum"
+            ),
+            Crc32Error::Mismatch { expected, actual } => write!(
+                f,
+                "Request body checksum {actual:08x} does not match the `{CONTENT_CRC32_HEADER}` header {expected:08x}"
+            ),
+            Crc32Error::Body(error) => write!(f, "Failed to read the request body: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for Crc32Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Crc32Error::Body(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for Crc32Error {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Crc32Error::Body(error) if crate::body::is_length_limit_error(error) => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            _ => StatusCode::BAD_REQUEST,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// `Crc32VerifiedRejection` is returned when the `Crc32Verified` extractor fails.
+///
+pub type Crc32VerifiedRejection<E> = GuardRejection<Crc32Error, E>;
+
+/// Parses a hex-encoded checksum, with an optional `0x` prefix.
+fn parse_checksum(value: &str) -> Option<u32> {
+    let value = value.trim();
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    if digits.is_empty() || digits.len() > 8 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(digits, 16).ok()
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for Crc32Verified<Extractor>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = Crc32VerifiedRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let header = req
+            .headers()
+            .get(CONTENT_CRC32_HEADER)
+            .ok_or(Crc32VerifiedRejection::Guard(Crc32Error::Missing))?;
+        let expected = header
+            .to_str()
+            .ok()
+            .and_then(parse_checksum)
+            .ok_or(Crc32VerifiedRejection::Guard(Crc32Error::Invalid))?;
+        let mut hasher = Hasher::new();
+        let req = crate::body::inspect(req, |chunk| hasher.update(chunk))
+            .await
+            .map_err(|error| Crc32VerifiedRejection::Guard(Crc32Error::Body(error)))?;
+        let actual = hasher.finalize();
+        if actual != expected {
+            return Err(Crc32VerifiedRejection::Guard(Crc32Error::Mismatch {
+                expected,
+                actual,
+            }));
+        }
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(Crc32VerifiedRejection::Inner)?;
+        Ok(Crc32Verified(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    const BODY: &str = "axum-valid";
+
+    fn request(checksum: Option<&str>) -> Request {
+        let mut builder = Request::builder().uri("/");
+        if let Some(checksum) = checksum {
+            builder = builder.header(CONTENT_CRC32_HEADER, checksum);
+        }
+        builder.body(Body::from(BODY)).expect("invalid request")
+    }
+
+    #[test]
+    fn checksum_header() {
+        assert_eq!(parse_checksum("cbf43926"), Some(0xcbf43926));
+        assert_eq!(parse_checksum(" 0xCBF43926 "), Some(0xcbf43926));
+        assert_eq!(parse_checksum("1f"), Some(0x1f));
+        assert_eq!(parse_checksum(""), None);
+        assert_eq!(parse_checksum("+1f"), None);
+        assert_eq!(parse_checksum("123456789"), None);
+        assert_eq!(crc32fast::hash(b"123456789"), 0xcbf43926);
+    }
+
+    #[tokio::test]
+    async fn crc32_verified() {
+        let checksum = format!("{:08x}", crc32fast::hash(BODY.as_bytes()));
+
+        let Crc32Verified(body) =
+            Crc32Verified::<String>::from_request(request(Some(&checksum)), &())
+                .await
+                .expect("matching checksum rejected");
+        assert_eq!(body, BODY);
+
+        let rejection = Crc32Verified::<String>::from_request(request(Some("00000000")), &())
+            .await
+            .expect_err("mismatching checksum accepted");
+        assert!(matches!(
+            rejection,
+            Crc32VerifiedRejection::Guard(Crc32Error::Mismatch { expected: 0, .. })
+        ));
+        assert_eq!(rejection.into_response().status(), StatusCode::BAD_REQUEST);
+
+        assert!(matches!(
+            Crc32Verified::<String>::from_request(request(Some("not-a-checksum")), &()).await,
+            Err(Crc32VerifiedRejection::Guard(Crc32Error::Invalid))
+        ));
+        assert!(matches!(
+            Crc32Verified::<String>::from_request(request(None), &()).await,
+            Err(Crc32VerifiedRejection::Guard(Crc32Error::Missing))
+        ));
+    }
+}
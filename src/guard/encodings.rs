@@ -0,0 +1,233 @@
+//! # Allowed content encodings guard
+//!
+//! ## Feature
+//!
+//! Enable the `guard_compression` feature to use `AllowedEncodings<E, L>`.
+//!
+//! ## Usage
+//!
+//! 1. Declare the content encodings a route accepts with a type implementing `EncodingList`.
+//! 2. In your handler function, wrap your extractor with `AllowedEncodings`,
+//!    e.g. `AllowedEncodings<Valid<Json<T>>, GzipOnly>`.
+//!
+//! Requests with a `Content-Encoding` missing from the list are rejected with `415 Unsupported Media Type`
+//! before the inner extractor runs. Uncompressed bodies, without a `Content-Encoding` or with `identity`,
+//! are always accepted. Bodies with an allowed encoding are decompressed, so the inner extractor sees
+//! the original payload, and are limited like with `Compressed` once decompressed.
+//!
+//! Only `gzip` (or `x-gzip`) and `deflate` can be decompressed, so other encodings are rejected
+//! with `415 Unsupported Media Type` even when listed.
+//!
+//! Encoding lists are types rather than const generic parameters, since slices of strings cannot be used
+//! as const generic parameters on stable Rust.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::guard::{AllowedEncodings, EncodingList};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/upload", post(handler))
+//!     }
+//!
+//!     pub struct GzipOnly;
+//!
+//!     impl EncodingList for GzipOnly {
+//!         const ENCODINGS: &'static [&'static str] = &["gzip"];
+//!     }
+//!
+//!     async fn handler(
+//!         AllowedEncodings(Valid(Json(upload)), _): AllowedEncodings<Valid<Json<Upload>>, GzipOnly>,
+//!     ) {
+//!         assert!(upload.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Upload {
+//!         #[validate(length(min = 1, max = 10000))]
+//!         pub lines: Vec<String>,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::guard::compression::{content_encoding, decompress, CompressionError};
+use crate::guard::GuardRejection;
+use axum::async_trait;
+use axum::extract::{FromRequest, Request};
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+/// A list of content encodings accepted by a route.
+pub trait EncodingList {
+    /// Names of the accepted encodings, in lowercase, e.g. `gzip`.
+    const ENCODINGS: &'static [&'static str];
+}
+
+/// # `AllowedEncodings` guard extractor
+///
+/// `AllowedEncodings` rejects requests whose `Content-Encoding` is not listed in `L`,
+/// decompresses the body, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllowedEncodings<E, L>(pub E, pub PhantomData<L>);
+
+impl<E, L> Deref for AllowedEncodings<E, L> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E, L> DerefMut for AllowedEncodings<E, L> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display, L> Display for AllowedEncodings<T, L> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E, L> AllowedEncodings<E, L> {
+    /// Consumes the `AllowedEncodings` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T, L> aide::OperationInput for AllowedEncodings<T, L>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `AllowedEncodingsRejection` is returned when the `AllowedEncodings` extractor fails.
+///
+pub type AllowedEncodingsRejection<E> = GuardRejection<CompressionError, E>;
+
+async fn check_encoding(req: Request, allowed: &[&str]) -> Result<Request, CompressionError> {
+    let encoding = match content_encoding(&req)? {
+        None => return Ok(req),
+        Some(encoding) if encoding == "identity" => return Ok(req),
+        Some(encoding) if !allowed.contains(&encoding.as_str()) => {
+            return Err(CompressionError::Disallowed(encoding));
+        }
+        Some(encoding) => encoding,
+    };
+    let (parts, bytes) = crate::body::read(req)
+        .await
+        .map_err(CompressionError::Body)?;
+    decompress(parts, bytes, &encoding).await
+}
+
+#[async_trait]
+impl<State, Extractor, L> FromRequest<State> for AllowedEncodings<Extractor, L>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+    L: EncodingList,
+{
+    type Rejection = AllowedEncodingsRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let req = check_encoding(req, L::ENCODINGS)
+            .await
+            .map_err(AllowedEncodingsRejection::Guard)?;
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(AllowedEncodingsRejection::Inner)?;
+        Ok(AllowedEncodings(inner, PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::header;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    #[derive(Debug)]
+    struct GzipOnly;
+
+    impl EncodingList for GzipOnly {
+        const ENCODINGS: &'static [&'static str] = &["gzip"];
+    }
+
+    fn request(encoding: Option<&str>, body: Vec<u8>) -> Request {
+        let mut builder = Request::builder().uri("/");
+        if let Some(encoding) = encoding {
+            builder = builder.header(header::CONTENT_ENCODING, encoding);
+        }
+        builder.body(Body::from(body)).expect("invalid request")
+    }
+
+    #[tokio::test]
+    async fn allowed_encodings() {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"payload").expect("failed to compress");
+        let gzipped = encoder.finish().expect("failed to compress");
+
+        let AllowedEncodings(body, _) =
+            AllowedEncodings::<String, GzipOnly>::from_request(request(Some("gzip"), gzipped), &())
+                .await
+                .expect("gzip body rejected");
+        assert_eq!(body, "payload");
+
+        let AllowedEncodings(body, _) = AllowedEncodings::<String, GzipOnly>::from_request(
+            request(None, b"plain".to_vec()),
+            &(),
+        )
+        .await
+        .expect("uncompressed body rejected");
+        assert_eq!(body, "plain");
+
+        let rejection = AllowedEncodings::<String, GzipOnly>::from_request(
+            request(Some("br"), b"payload".to_vec()),
+            &(),
+        )
+        .await
+        .expect_err("disallowed encoding accepted");
+        assert!(matches!(
+            &rejection,
+            AllowedEncodingsRejection::Guard(CompressionError::Disallowed(encoding)) if encoding == "br"
+        ));
+        assert_eq!(
+            rejection.into_response().status(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+    }
+}
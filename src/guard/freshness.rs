@@ -0,0 +1,277 @@
+//! # Request freshness guard
+//!
+//! ## Feature
+//!
+//! Enable the `guard` feature to use `FreshSignature<E>`.
+//!
+//! ## Usage
+//!
+//! 1. Make `MaxSkew` available from your router state (implement `FromRef<YourState>` for it,
+//!    or use it as the state directly).
+//! 2. Have clients send the unix timestamp (in seconds) of the request in the `X-Timestamp` header.
+//! 3. In your handler function, wrap your extractor with `FreshSignature`,
+//!    e.g. `FreshSignature<Valid<Json<T>>>`.
+//!
+//! Requests without a timestamp, with a malformed one, or with one further than the allowed skew
+//! from the server clock are rejected with `401 Unauthorized` before the inner extractor runs.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use std::time::Duration;
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::guard::{FreshSignature, MaxSkew};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new()
+//!             .route("/webhook", post(handler))
+//!             .with_state(MaxSkew(Duration::from_secs(300)))
+//!     }
+//!
+//!     async fn handler(
+//!         FreshSignature(Valid(Json(event))): FreshSignature<Valid<Json<Event>>>,
+//!     ) {
+//!         assert!(event.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Event {
+//!         #[validate(length(min = 1, max = 64))]
+//!         pub id: String,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::guard::GuardRejection;
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Name of the header carrying the unix timestamp (in seconds) of the request.
+pub const TIMESTAMP_HEADER: &str = "x-timestamp";
+
+/// Maximum allowed difference between the request timestamp and the server clock.
+///
+/// `FreshSignature` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaxSkew(pub Duration);
+
+impl Default for MaxSkew {
+    fn default() -> Self {
+        MaxSkew(Duration::from_secs(300))
+    }
+}
+
+/// # `FreshSignature` guard extractor
+///
+/// `FreshSignature` checks that the `X-Timestamp` header of the request is within the
+/// `MaxSkew` taken from the state, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FreshSignature<E>(pub E);
+
+impl<E> Deref for FreshSignature<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for FreshSignature<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for FreshSignature<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> FreshSignature<E> {
+    /// Consumes the `FreshSignature` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for FreshSignature<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// Errors raised by `FreshSignature` when the request is not fresh.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FreshnessError {
+    /// The `X-Timestamp` header is missing.
+    Missing,
+    /// The `X-Timestamp` header is not a unix timestamp in seconds.
+    Malformed,
+    /// The timestamp is further from the server clock than the allowed skew.
+    Stale,
+}
+
+impl Display for FreshnessError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FreshnessError::Missing => write!(f, "Missing `{TIMESTAMP_HEADER}` header"),
+            FreshnessError::Malformed => write!(f, "Malformed `{TIMESTAMP_HEADER}` header"),
+            FreshnessError::Stale => write!(f, "Request timestamp is outside the allowed skew"),
+        }
+    }
+}
+
+impl std::error::Error for FreshnessError {}
+
+impl IntoResponse for FreshnessError {
+    fn into_response(self) -> Response {
+        (StatusCode::UNAUTHORIZED, self.to_string()).into_response()
+    }
+}
+
+/// `FreshSignatureRejection` is returned when the `FreshSignature` extractor fails.
+///
+pub type FreshSignatureRejection<E> = GuardRejection<FreshnessError, E>;
+
+fn check_freshness(headers: &HeaderMap, max_skew: MaxSkew) -> Result<(), FreshnessError> {
+    let timestamp = headers
+        .get(TIMESTAMP_HEADER)
+        .ok_or(FreshnessError::Missing)?
+        .to_str()
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok())
+        .ok_or(FreshnessError::Malformed)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    if now.abs_diff(timestamp) > max_skew.0.as_secs() {
+        return Err(FreshnessError::Stale);
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for FreshSignature<Extractor>
+where
+    State: Send + Sync,
+    MaxSkew: FromRef<State>,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = FreshSignatureRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        check_freshness(req.headers(), MaxSkew::from_ref(state))
+            .map_err(FreshSignatureRejection::Guard)?;
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(FreshSignatureRejection::Inner)?;
+        Ok(FreshSignature(inner))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequestParts<State> for FreshSignature<Extractor>
+where
+    State: Send + Sync,
+    MaxSkew: FromRef<State>,
+    Extractor: FromRequestParts<State>,
+{
+    type Rejection = FreshSignatureRejection<<Extractor as FromRequestParts<State>>::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        check_freshness(&parts.headers, MaxSkew::from_ref(state))
+            .map_err(FreshSignatureRejection::Guard)?;
+        let inner = Extractor::from_request_parts(parts, state)
+            .await
+            .map_err(FreshSignatureRejection::Inner)?;
+        Ok(FreshSignature(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    fn request(timestamp: Option<String>) -> Request {
+        let mut builder = Request::builder().uri("/");
+        if let Some(timestamp) = timestamp {
+            builder = builder.header(TIMESTAMP_HEADER, timestamp);
+        }
+        builder.body(Body::empty()).expect("invalid request")
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock before unix epoch")
+            .as_secs()
+    }
+
+    #[tokio::test]
+    async fn fresh_signature() {
+        let state = MaxSkew(Duration::from_secs(60));
+
+        let fresh = request(Some(now().to_string()));
+        assert!(FreshSignature::<String>::from_request(fresh, &state)
+            .await
+            .is_ok());
+
+        let stale = request(Some((now() - 120).to_string()));
+        let rejection = FreshSignature::<String>::from_request(stale, &state)
+            .await
+            .expect_err("stale request accepted");
+        assert!(matches!(
+            rejection,
+            FreshSignatureRejection::Guard(FreshnessError::Stale)
+        ));
+        assert_eq!(rejection.into_response().status(), StatusCode::UNAUTHORIZED);
+
+        let missing = request(None);
+        assert!(matches!(
+            FreshSignature::<String>::from_request(missing, &state).await,
+            Err(FreshSignatureRejection::Guard(FreshnessError::Missing))
+        ));
+
+        let malformed = request(Some(String::from("yesterday")));
+        assert!(matches!(
+            FreshSignature::<String>::from_request(malformed, &state).await,
+            Err(FreshSignatureRejection::Guard(FreshnessError::Malformed))
+        ));
+    }
+}
@@ -0,0 +1,231 @@
+//! # Body hash verification guard
+//!
+//! ## Feature
+//!
+//! Enable the `guard_hash` feature to use `HashVerified<E>`.
+//!
+//! ## Usage
+//!
+//! 1. Have clients send the hex-encoded SHA-256 digest of the request body in the `X-Content-SHA256` header.
+//! 2. In your handler function, wrap your extractor with `HashVerified`, e.g. `HashVerified<Valid<Json<T>>>`.
+//!
+//! The digest is computed while the body is read. Requests without the header, or whose body does not
+//! match it, are rejected with `400 Bad Request` before the inner extractor runs.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::guard::HashVerified;
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/json", post(handler))
+//!     }
+//!
+//!     async fn handler(HashVerified(Valid(Json(parameter))): HashVerified<Valid<Json<Parameter>>>) {
+//!         assert!(parameter.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Parameter {
+//!         #[validate(range(min = 5, max = 10))]
+//!         pub v0: i32,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::guard::GuardRejection;
+use axum::async_trait;
+use axum::extract::{FromRequest, Request};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use sha2::{Digest, Sha256};
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// Name of the header carrying the hex-encoded SHA-256 digest of the request body.
+pub const CONTENT_SHA256_HEADER: &str = "x-content-sha256";
+
+/// # `HashVerified` guard extractor
+///
+/// `HashVerified` checks that the SHA-256 digest of the request body matches the
+/// `X-Content-SHA256` header, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HashVerified<E>(pub E);
+
+impl<E> Deref for HashVerified<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for HashVerified<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for HashVerified<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> HashVerified<E> {
+    /// Consumes the `HashVerified` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for HashVerified<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// Errors raised by `HashVerified`.
+#[derive(Debug)]
+pub enum HashError {
+    /// The `X-Content-SHA256` header is missing.
+    Missing,
+    /// The digest of the body does not match the `X-Content-SHA256` header.
+    Mismatch,
+    /// The body could not be read.
+    Body(axum::Error),
+}
+
+impl Display for HashError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HashError::Missing => write!(f, "Missing `{CONTENT_SHA256_HEADER}` header"),
+            HashError::Mismatch => write!(
+                f,
+                "Request body does not match the `{CONTENT_SHA256_HEADER}` header"
+            ),
+            HashError::Body(error) => write!(f, "Failed to read the request body: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for HashError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HashError::Body(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for HashError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            HashError::Body(error) if crate::body::is_length_limit_error(error) => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            _ => StatusCode::BAD_REQUEST,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// `HashVerifiedRejection` is returned when the `HashVerified` extractor fails.
+///
+pub type HashVerifiedRejection<E> = GuardRejection<HashError, E>;
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for HashVerified<Extractor>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = HashVerifiedRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let expected = req
+            .headers()
+            .get(CONTENT_SHA256_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim().to_owned())
+            .ok_or(HashVerifiedRejection::Guard(HashError::Missing))?;
+        let mut hasher = Sha256::new();
+        let req = crate::body::inspect(req, |chunk| hasher.update(chunk))
+            .await
+            .map_err(|error| HashVerifiedRejection::Guard(HashError::Body(error)))?;
+        if !format!("{:x}", hasher.finalize()).eq_ignore_ascii_case(&expected) {
+            return Err(HashVerifiedRejection::Guard(HashError::Mismatch));
+        }
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(HashVerifiedRejection::Inner)?;
+        Ok(HashVerified(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    const BODY: &str = "axum-valid";
+
+    fn request(digest: Option<String>) -> Request {
+        let mut builder = Request::builder().uri("/");
+        if let Some(digest) = digest {
+            builder = builder.header(CONTENT_SHA256_HEADER, digest);
+        }
+        builder.body(Body::from(BODY)).expect("invalid request")
+    }
+
+    #[tokio::test]
+    async fn hash_verified() {
+        let digest = format!("{:x}", Sha256::digest(BODY));
+
+        let HashVerified(body) = HashVerified::<String>::from_request(request(Some(digest)), &())
+            .await
+            .expect("matching digest rejected");
+        assert_eq!(body, BODY);
+
+        let rejection = HashVerified::<String>::from_request(request(Some("0".repeat(64))), &())
+            .await
+            .expect_err("mismatching digest accepted");
+        assert!(matches!(
+            rejection,
+            HashVerifiedRejection::Guard(HashError::Mismatch)
+        ));
+        assert_eq!(rejection.into_response().status(), StatusCode::BAD_REQUEST);
+
+        assert!(matches!(
+            HashVerified::<String>::from_request(request(None), &()).await,
+            Err(HashVerifiedRejection::Guard(HashError::Missing))
+        ));
+    }
+}
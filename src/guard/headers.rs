@@ -0,0 +1,292 @@
+//! # Required headers guard
+//!
+//! ## Feature
+//!
+//! Enable the `guard` feature to use `RequireHeaders<E, H>`.
+//!
+//! ## Usage
+//!
+//! 1. Declare the headers a route requires with a type implementing `HeaderSet`. Override `HeaderSet::check`
+//!    to also require their values to match a pattern.
+//! 2. In your handler function, wrap your extractor with `RequireHeaders`,
+//!    e.g. `RequireHeaders<Valid<Json<T>>, PartnerHeaders>`.
+//!
+//! Requests missing any of the headers, or with a value rejected by `check`, are rejected with
+//! `400 Bad Request` before the inner extractor runs. The response lists every missing and invalid header,
+//! not just the first one.
+//!
+//! Header sets are types rather than const generic parameters, since slices of strings cannot be used
+//! as const generic parameters on stable Rust.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::guard::{HeaderSet, RequireHeaders};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/orders", post(handler))
+//!     }
+//!
+//!     pub struct PartnerHeaders;
+//!
+//!     impl HeaderSet for PartnerHeaders {
+//!         const HEADERS: &'static [&'static str] = &["x-partner-id", "x-request-id"];
+//!
+//!         fn check(name: &str, value: &str) -> bool {
+//!             name != "x-partner-id" || value.bytes().all(|b| b.is_ascii_digit())
+//!         }
+//!     }
+//!
+//!     async fn handler(
+//!         RequireHeaders(Valid(Json(order)), _): RequireHeaders<Valid<Json<Order>>, PartnerHeaders>,
+//!     ) {
+//!         assert!(order.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Order {
+//!         #[validate(range(min = 1, max = 100))]
+//!         pub quantity: u32,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::guard::GuardRejection;
+use axum::async_trait;
+use axum::extract::{FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+/// A set of headers required by a route.
+pub trait HeaderSet {
+    /// Names of the required headers, in lowercase
+    const HEADERS: &'static [&'static str];
+
+    /// Check the value of a required header. Accepts any value by default.
+    fn check(name: &str, value: &str) -> bool {
+        let _ = (name, value);
+        true
+    }
+}
+
+/// # `RequireHeaders` guard extractor
+///
+/// `RequireHeaders` checks that the request has every header of the `HeaderSet` `H`,
+/// then delegates to the inner extractor.
+///
+pub struct RequireHeaders<E, H>(pub E, pub PhantomData<H>);
+
+impl<E, H> Deref for RequireHeaders<E, H> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E, H> DerefMut for RequireHeaders<E, H> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display, H> Display for RequireHeaders<T, H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E: std::fmt::Debug, H> std::fmt::Debug for RequireHeaders<E, H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RequireHeaders").field(&self.0).finish()
+    }
+}
+
+impl<E, H> RequireHeaders<E, H> {
+    /// Consumes the `RequireHeaders` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T, H> aide::OperationInput for RequireHeaders<T, H>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// Error raised by `RequireHeaders` when required headers are missing or invalid.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeadersError {
+    /// Required headers which are missing.
+    pub missing: Vec<&'static str>,
+    /// Required headers whose value was rejected by `HeaderSet::check`, or is not visible ASCII.
+    pub invalid: Vec<&'static str>,
+}
+
+impl Display for HeadersError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut groups = Vec::new();
+        if !self.missing.is_empty() {
+            groups.push(format!(
+                "Missing required headers: {}",
+                self.missing.join(", ")
+            ));
+        }
+        if !self.invalid.is_empty() {
+            groups.push(format!("Invalid headers: {}", self.invalid.join(", ")));
+        }
+        write!(f, "{}", groups.join("\n"))
+    }
+}
+
+impl std::error::Error for HeadersError {}
+
+impl IntoResponse for HeadersError {
+    fn into_response(self) -> Response {
+        (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+    }
+}
+
+/// `RequireHeadersRejection` is returned when the `RequireHeaders` extractor fails.
+///
+pub type RequireHeadersRejection<E> = GuardRejection<HeadersError, E>;
+
+fn check_headers<H: HeaderSet>(headers: &HeaderMap) -> Result<(), HeadersError> {
+    let mut error = HeadersError::default();
+    for &name in H::HEADERS {
+        match headers.get(name).map(|value| value.to_str()) {
+            None => error.missing.push(name),
+            Some(Ok(value)) if H::check(name, value) => {}
+            Some(_) => error.invalid.push(name),
+        }
+    }
+    if error.missing.is_empty() && error.invalid.is_empty() {
+        Ok(())
+    } else {
+        Err(error)
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, H> FromRequest<State> for RequireHeaders<Extractor, H>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+    H: HeaderSet,
+{
+    type Rejection = RequireHeadersRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        check_headers::<H>(req.headers()).map_err(RequireHeadersRejection::Guard)?;
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(RequireHeadersRejection::Inner)?;
+        Ok(RequireHeaders(inner, PhantomData))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, H> FromRequestParts<State> for RequireHeaders<Extractor, H>
+where
+    State: Send + Sync,
+    Extractor: FromRequestParts<State>,
+    H: HeaderSet,
+{
+    type Rejection = RequireHeadersRejection<<Extractor as FromRequestParts<State>>::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        check_headers::<H>(&parts.headers).map_err(RequireHeadersRejection::Guard)?;
+        let inner = Extractor::from_request_parts(parts, state)
+            .await
+            .map_err(RequireHeadersRejection::Inner)?;
+        Ok(RequireHeaders(inner, PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    struct PartnerHeaders;
+
+    impl HeaderSet for PartnerHeaders {
+        const HEADERS: &'static [&'static str] = &["x-partner-id", "x-request-id", "x-region"];
+
+        fn check(name: &str, value: &str) -> bool {
+            name != "x-partner-id" || value.bytes().all(|b| b.is_ascii_digit())
+        }
+    }
+
+    fn request(headers: &[(&str, &str)]) -> Request {
+        let mut builder = Request::builder().uri("/");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(Body::empty()).expect("invalid request")
+    }
+
+    #[tokio::test]
+    async fn require_headers() {
+        type Extractor = RequireHeaders<String, PartnerHeaders>;
+
+        assert!(Extractor::from_request(
+            request(&[
+                ("x-partner-id", "42"),
+                ("x-request-id", "r1"),
+                ("x-region", "eu")
+            ]),
+            &()
+        )
+        .await
+        .is_ok());
+
+        let rejection = Extractor::from_request(
+            request(&[("x-partner-id", "acme"), ("x-request-id", "r1")]),
+            &(),
+        )
+        .await
+        .expect_err("missing header accepted");
+        let RequireHeadersRejection::Guard(error) = &rejection else {
+            panic!("unexpected rejection: {rejection:?}");
+        };
+        assert_eq!(error.missing, ["x-region"]);
+        assert_eq!(error.invalid, ["x-partner-id"]);
+        assert_eq!(
+            error.to_string(),
+            "Missing required headers: x-region\nInvalid headers: x-partner-id"
+        );
+        assert_eq!(rejection.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+}
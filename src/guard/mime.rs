@@ -0,0 +1,254 @@
+//! # MIME sniffing guard
+//!
+//! ## Feature
+//!
+//! Enable the `guard_mime` feature to use `MimeSniffed<E>`.
+//!
+//! ## Usage
+//!
+//! 1. In your handler function, wrap your extractor with `MimeSniffed`, e.g. `MimeSniffed<Bytes>`.
+//!
+//! The type of the body is detected from its leading bytes (its "magic number") with the `infer` crate,
+//! and compared with the essence of the `Content-Type` header, e.g. `image/png` for `image/png; q=1`.
+//! Requests without the header, whose body is detected as another type, or whose body is not recognized
+//! although the declared type could have been, are rejected with `415 Unsupported Media Type`
+//! before the inner extractor runs.
+//!
+//! Bodies of types `infer` does not detect, like `application/json` or `text/plain`, are passed through
+//! as long as they do not look like a detectable type.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use axum::body::Bytes;
+//! use axum::routing::put;
+//! use axum::Router;
+//! use axum_valid::guard::MimeSniffed;
+//!
+//! async fn handler(MimeSniffed(image): MimeSniffed<Bytes>) {
+//!     assert!(!image.is_empty());
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! let router = Router::new().route("/avatar", put(handler));
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::guard::GuardRejection;
+use axum::async_trait;
+use axum::body::Body;
+use axum::extract::{FromRequest, Request};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// # `MimeSniffed` guard extractor
+///
+/// `MimeSniffed` checks that the type detected from the request body matches the `Content-Type` header,
+/// then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MimeSniffed<E>(pub E);
+
+impl<E> Deref for MimeSniffed<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for MimeSniffed<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for MimeSniffed<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> MimeSniffed<E> {
+    /// Consumes the `MimeSniffed` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for MimeSniffed<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// Errors raised by `MimeSniffed`.
+#[derive(Debug)]
+pub enum MimeError {
+    /// The `Content-Type` header is missing or is not valid ASCII.
+    Missing,
+    /// The type detected from the body does not match the `Content-Type` header.
+    Mismatch {
+        /// The essence of the `Content-Type` header.
+        declared: String,
+        /// The type detected from the body, `None` if it was not recognized.
+        detected: Option<&'static str>,
+    },
+    /// The body could not be read.
+    Body(axum::Error),
+}
+
+impl Display for MimeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MimeError::Missing => write!(f, "Missing `{}` header", header::CONTENT_TYPE),
+            MimeError::Mismatch {
+                declared,
+                detected: Some(detected),
+            } => write!(
+                f,
+                "Request body is `{detected}`, but `{declared}` was declared"
+            ),
+            MimeError::Mismatch {
+                declared,
+                detected: None,
+            } => write!(f, "Request body is not `{declared}`"),
+            MimeError::Body(error) => write!(f, "Failed to read the request body: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for MimeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MimeError::Body(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for MimeError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            MimeError::Body(error) if crate::body::is_length_limit_error(error) => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            MimeError::Body(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// `MimeSniffedRejection` is returned when the `MimeSniffed` extractor fails.
+///
+pub type MimeSniffedRejection<E> = GuardRejection<MimeError, E>;
+
+/// Compares the declared `Content-Type` with the type detected from `body`.
+fn sniff(declared: &str, body: &[u8]) -> Result<(), MimeError> {
+    match infer::get(body).map(|kind| kind.mime_type()) {
+        Some(detected) if detected == declared => Ok(()),
+        None if !infer::is_mime_supported(declared) => Ok(()),
+        detected => Err(MimeError::Mismatch {
+            declared: declared.to_owned(),
+            detected,
+        }),
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for MimeSniffed<Extractor>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = MimeSniffedRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let declared = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(|essence| essence.trim().to_ascii_lowercase())
+            .ok_or(MimeSniffedRejection::Guard(MimeError::Missing))?;
+        let (parts, bytes) = crate::body::read(req)
+            .await
+            .map_err(|error| MimeSniffedRejection::Guard(MimeError::Body(error)))?;
+        sniff(&declared, &bytes).map_err(MimeSniffedRejection::Guard)?;
+        let inner = Extractor::from_request(Request::from_parts(parts, Body::from(bytes)), state)
+            .await
+            .map_err(MimeSniffedRejection::Inner)?;
+        Ok(MimeSniffed(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Bytes;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+    const PDF: &[u8] = b"%PDF-1.7\n";
+
+    fn request(content_type: Option<&str>, body: &'static [u8]) -> Request {
+        let mut builder = Request::builder().uri("/");
+        if let Some(content_type) = content_type {
+            builder = builder.header(header::CONTENT_TYPE, content_type);
+        }
+        builder.body(Body::from(body)).expect("invalid request")
+    }
+
+    #[tokio::test]
+    async fn mime_sniffed() {
+        let MimeSniffed(body) =
+            MimeSniffed::<Bytes>::from_request(request(Some("Image/PNG; q=1"), PNG), &())
+                .await
+                .expect("matching content type rejected");
+        assert_eq!(body, PNG);
+
+        MimeSniffed::<Bytes>::from_request(request(Some("application/json"), b"{}"), &())
+            .await
+            .expect("undetectable content type rejected");
+
+        let rejection = MimeSniffed::<Bytes>::from_request(request(Some("image/png"), PDF), &())
+            .await
+            .expect_err("spoofed content type accepted");
+        assert!(matches!(
+            &rejection,
+            MimeSniffedRejection::Guard(MimeError::Mismatch {
+                declared,
+                detected: Some("application/pdf"),
+            }) if declared == "image/png"
+        ));
+        assert_eq!(
+            rejection.into_response().status(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+
+        assert!(matches!(
+            MimeSniffed::<Bytes>::from_request(request(Some("image/png"), b"<svg/>"), &()).await,
+            Err(MimeSniffedRejection::Guard(MimeError::Mismatch {
+                detected: None,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            MimeSniffed::<Bytes>::from_request(request(None, PNG), &()).await,
+            Err(MimeSniffedRejection::Guard(MimeError::Missing))
+        ));
+    }
+}
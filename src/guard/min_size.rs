@@ -0,0 +1,241 @@
+//! # Minimum body size guard
+//!
+//! ## Feature
+//!
+//! Enable the `guard` feature to use `MinBodySize<E, N>`.
+//!
+//! ## Usage
+//!
+//! 1. In your handler function, wrap your extractor with `MinBodySize`, e.g. `MinBodySize<Valid<Json<T>>, 64>`.
+//!
+//! Request bodies smaller than `N` bytes are rejected with `400 Bad Request` before the inner extractor runs,
+//! so trivially small payloads never reach deserialization or validation.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::guard::MinBodySize;
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/reports", post(handler))
+//!     }
+//!
+//!     async fn handler(MinBodySize(Valid(Json(report))): MinBodySize<Valid<Json<Report>>, 32>) {
+//!         assert!(report.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Report {
+//!         #[validate(length(min = 1, max = 64))]
+//!         pub title: String,
+//!         #[validate(length(min = 16))]
+//!         pub body: String,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::guard::GuardRejection;
+use axum::async_trait;
+use axum::extract::rejection::BytesRejection;
+use axum::extract::{FromRequest, Request};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// # `MinBodySize` guard extractor
+///
+/// `MinBodySize` checks that the request body is at least `N` bytes long, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinBodySize<E, const N: usize>(pub E);
+
+impl<E, const N: usize> Deref for MinBodySize<E, N> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E, const N: usize> DerefMut for MinBodySize<E, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display, const N: usize> Display for MinBodySize<T, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E, const N: usize> MinBodySize<E, N> {
+    /// Consumes the `MinBodySize` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T, const N: usize> aide::OperationInput for MinBodySize<T, N>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// Errors raised by `MinBodySize`.
+#[derive(Debug)]
+pub enum MinBodySizeError {
+    /// The body is smaller than the minimum size.
+    TooSmall {
+        /// The minimum size, in bytes.
+        min: usize,
+        /// The size of the body, in bytes.
+        actual: usize,
+    },
+    /// The body could not be read.
+    Body(BytesRejection),
+}
+
+impl Display for MinBodySizeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MinBodySizeError::TooSmall { min, actual } => write!(
+                f,
+                "Request body must be at least {min} bytes long, got {actual} bytes"
+            ),
+            MinBodySizeError::Body(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for MinBodySizeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MinBodySizeError::Body(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for MinBodySizeError {
+    fn into_response(self) -> Response {
+        match self {
+            MinBodySizeError::TooSmall { .. } => {
+                (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+            }
+            MinBodySizeError::Body(error) => error.into_response(),
+        }
+    }
+}
+
+/// `MinBodySizeRejection` is returned when the `MinBodySize` extractor fails.
+///
+pub type MinBodySizeRejection<E> = GuardRejection<MinBodySizeError, E>;
+
+#[async_trait]
+impl<State, Extractor, const N: usize> FromRequest<State> for MinBodySize<Extractor, N>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = MinBodySizeRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let (req, bytes) = crate::body::buffer(req, state)
+            .await
+            .map_err(|error| MinBodySizeRejection::Guard(MinBodySizeError::Body(error)))?;
+        if bytes.len() < N {
+            return Err(MinBodySizeRejection::Guard(MinBodySizeError::TooSmall {
+                min: N,
+                actual: bytes.len(),
+            }));
+        }
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(MinBodySizeRejection::Inner)?;
+        Ok(MinBodySize(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    fn request(body: &'static str) -> Request {
+        Request::builder()
+            .uri("/")
+            .body(Body::from(body))
+            .expect("invalid request")
+    }
+
+    #[tokio::test]
+    async fn min_body_size() {
+        let MinBodySize(body) = MinBodySize::<String, 4>::from_request(request("abcd"), &())
+            .await
+            .expect("large enough body rejected");
+        assert_eq!(body, "abcd");
+
+        let rejection = MinBodySize::<String, 4>::from_request(request("abc"), &())
+            .await
+            .expect_err("small body accepted");
+        assert!(matches!(
+            rejection,
+            MinBodySizeRejection::Guard(MinBodySizeError::TooSmall { min: 4, actual: 3 })
+        ));
+        assert_eq!(rejection.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[cfg(all(feature = "validator", feature = "json"))]
+    #[tokio::test]
+    async fn min_body_size_before_validation() {
+        use crate::Valid;
+        use axum::Json;
+        use validator::Validate;
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        struct Report {
+            #[validate(length(min = 16))]
+            body: String,
+        }
+
+        let request = Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"body":""}"#))
+            .expect("invalid request");
+        assert!(matches!(
+            MinBodySize::<Valid<Json<Report>>, 32>::from_request(request, &()).await,
+            Err(MinBodySizeRejection::Guard(
+                MinBodySizeError::TooSmall { .. }
+            ))
+        ));
+    }
+}
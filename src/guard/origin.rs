@@ -0,0 +1,353 @@
+//! # Request origin guard
+//!
+//! ## Feature
+//!
+//! Enable the `guard` feature to use `OriginGuard<E>`.
+//!
+//! ## Usage
+//!
+//! To mitigate cross-site request forgery, browsers send the origin of the page issuing a request in the
+//! `Origin` header, and the server rejects the origins it does not trust.
+//!
+//! 1. Make an `AllowedOrigins` allowlist available from your router state (implement `FromRef<YourState>`
+//!    for it, or use it as the state directly).
+//! 2. In your handler function, wrap your extractor with `OriginGuard`, e.g. `OriginGuard<Valid<Json<T>>>`.
+//!
+//! The origin is read from the `Origin` header, or from the `Referer` header when there is none, and compared
+//! with the allowlist ignoring ASCII case and a trailing slash. Requests from other origins, and requests
+//! carrying neither header, are rejected with `403 Forbidden` before the inner extractor runs.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::guard::{AllowedOrigins, OriginGuard};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new()
+//!             .route("/transfers", post(handler))
+//!             .with_state(AllowedOrigins::new(["https://bank.example"]))
+//!     }
+//!
+//!     async fn handler(OriginGuard(Valid(Json(transfer))): OriginGuard<Valid<Json<Transfer>>>) {
+//!         assert!(transfer.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Transfer {
+//!         #[validate(range(min = 1))]
+//!         pub amount: u64,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::guard::GuardRejection;
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// Origins `OriginGuard` accepts requests from, e.g. `https://example.com`.
+///
+/// `OriginGuard` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AllowedOrigins(Arc<BTreeSet<String>>);
+
+impl AllowedOrigins {
+    /// Create an allowlist of the given origins.
+    pub fn new<I, S>(origins: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self(Arc::new(
+            origins
+                .into_iter()
+                .map(|origin| normalize(origin.as_ref()))
+                .collect(),
+        ))
+    }
+
+    /// Returns `true` if requests from `origin` are accepted.
+    pub fn allows(&self, origin: &str) -> bool {
+        self.0.contains(&normalize(origin))
+    }
+}
+
+fn normalize(origin: &str) -> String {
+    origin.trim().trim_end_matches('/').to_ascii_lowercase()
+}
+
+/// # `OriginGuard` guard extractor
+///
+/// `OriginGuard` checks that the `Origin` (or `Referer`) header of the request is in the
+/// `AllowedOrigins` allowlist, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OriginGuard<E>(pub E);
+
+impl<E> Deref for OriginGuard<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for OriginGuard<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for OriginGuard<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> OriginGuard<E> {
+    /// Consumes the `OriginGuard` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for OriginGuard<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// Errors raised by `OriginGuard` when the request does not come from an allowed origin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OriginError {
+    /// Neither the `Origin` nor the `Referer` header is present.
+    Missing,
+    /// The origin of the request is not in the allowlist.
+    Disallowed(String),
+}
+
+impl Display for OriginError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OriginError::Missing => write!(f, "Missing `Origin` header"),
+            OriginError::Disallowed(origin) => write!(f, "Origin `{origin}` is not allowed"),
+        }
+    }
+}
+
+impl std::error::Error for OriginError {}
+
+impl IntoResponse for OriginError {
+    fn into_response(self) -> Response {
+        (StatusCode::FORBIDDEN, self.to_string()).into_response()
+    }
+}
+
+/// `OriginGuardRejection` is returned when the `OriginGuard` extractor fails.
+///
+pub type OriginGuardRejection<E> = GuardRejection<OriginError, E>;
+
+/// Returns the scheme, host and port of a `Referer` URL.
+fn referer_origin(referer: &str) -> &str {
+    let authority = referer.find("://").map_or(0, |scheme| scheme + 3);
+    referer[authority..]
+        .find(['/', '?', '#'])
+        .map_or(referer, |end| &referer[..authority + end])
+}
+
+fn check_origin(headers: &HeaderMap, allowed: &AllowedOrigins) -> Result<(), OriginError> {
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    let origin = match header(header::ORIGIN) {
+        Some(origin) => origin,
+        None => header(header::REFERER)
+            .map(referer_origin)
+            .ok_or(OriginError::Missing)?,
+    };
+    if allowed.allows(origin) {
+        Ok(())
+    } else {
+        Err(OriginError::Disallowed(origin.to_owned()))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for OriginGuard<Extractor>
+where
+    State: Send + Sync,
+    AllowedOrigins: FromRef<State>,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = OriginGuardRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        check_origin(req.headers(), &AllowedOrigins::from_ref(state))
+            .map_err(OriginGuardRejection::Guard)?;
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(OriginGuardRejection::Inner)?;
+        Ok(OriginGuard(inner))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequestParts<State> for OriginGuard<Extractor>
+where
+    State: Send + Sync,
+    AllowedOrigins: FromRef<State>,
+    Extractor: FromRequestParts<State>,
+{
+    type Rejection = OriginGuardRejection<<Extractor as FromRequestParts<State>>::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        check_origin(&parts.headers, &AllowedOrigins::from_ref(state))
+            .map_err(OriginGuardRejection::Guard)?;
+        let inner = Extractor::from_request_parts(parts, state)
+            .await
+            .map_err(OriginGuardRejection::Inner)?;
+        Ok(OriginGuard(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    fn request(name: &str, value: &str) -> Request {
+        Request::builder()
+            .uri("/")
+            .header(name, value)
+            .body(Body::from("{}"))
+            .expect("invalid request")
+    }
+
+    #[test]
+    fn origin_of_referer() {
+        assert_eq!(
+            referer_origin("https://example.com:8443/pay?to=me#top"),
+            "https://example.com:8443"
+        );
+        assert_eq!(
+            referer_origin("https://example.com?x=1"),
+            "https://example.com"
+        );
+        assert_eq!(referer_origin("https://example.com"), "https://example.com");
+    }
+
+    #[tokio::test]
+    async fn origin_guard() {
+        let allowed = AllowedOrigins::new(["https://example.com/"]);
+
+        for (name, value) in [
+            ("origin", "https://example.com"),
+            ("origin", "HTTPS://Example.com"),
+            ("referer", "https://example.com/checkout?step=2"),
+        ] {
+            assert!(
+                OriginGuard::<String>::from_request(request(name, value), &allowed)
+                    .await
+                    .is_ok(),
+                "{name}: {value} rejected"
+            );
+        }
+
+        let rejection = OriginGuard::<String>::from_request(
+            request("origin", "https://evil.example"),
+            &allowed,
+        )
+        .await
+        .expect_err("cross-origin request accepted");
+        assert!(matches!(
+            &rejection,
+            OriginGuardRejection::Guard(OriginError::Disallowed(origin)) if origin == "https://evil.example"
+        ));
+        assert_eq!(rejection.into_response().status(), StatusCode::FORBIDDEN);
+
+        assert!(matches!(
+            OriginGuard::<String>::from_request(
+                request("referer", "https://example.com.evil.example/"),
+                &allowed
+            )
+            .await,
+            Err(OriginGuardRejection::Guard(OriginError::Disallowed(_)))
+        ));
+        assert!(matches!(
+            OriginGuard::<String>::from_request(request("x-other", "1"), &allowed).await,
+            Err(OriginGuardRejection::Guard(OriginError::Missing))
+        ));
+    }
+
+    #[cfg(all(feature = "validator", feature = "json"))]
+    #[tokio::test]
+    async fn rejected_before_validation() {
+        use crate::{Valid, ValidRejection};
+        use axum::Json;
+        use validator::Validate;
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        struct Transfer {
+            #[validate(range(min = 1))]
+            amount: u64,
+        }
+
+        let allowed = AllowedOrigins::new(["https://example.com"]);
+        let request = |origin: &str| {
+            Request::builder()
+                .uri("/")
+                .header("origin", origin)
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{ "amount": 0 }"#))
+                .expect("invalid request")
+        };
+
+        assert!(matches!(
+            OriginGuard::<Valid<Json<Transfer>>>::from_request(
+                request("https://evil.example"),
+                &allowed
+            )
+            .await,
+            Err(OriginGuardRejection::Guard(OriginError::Disallowed(_)))
+        ));
+        assert!(matches!(
+            OriginGuard::<Valid<Json<Transfer>>>::from_request(
+                request("https://example.com"),
+                &allowed
+            )
+            .await,
+            Err(OriginGuardRejection::Inner(ValidRejection::Valid(_)))
+        ));
+    }
+}
@@ -0,0 +1,431 @@
+//! # Request sequence guard
+//!
+//! ## Feature
+//!
+//! Enable the `guard` feature to use `InSequence<E>`.
+//!
+//! ## Usage
+//!
+//! For ordered event ingestion, every client numbers its requests and the server rejects the ones that
+//! arrive out of order.
+//!
+//! 1. Make a `SequenceTracker` available from your router state (implement `FromRef<YourState>` for it,
+//!    or use it as the state directly). Clones share the same last seen values.
+//! 2. Identify the client of each request: by default, the tracker reads the `ClientId` request extension,
+//!    which your authentication middleware inserts once the client is authenticated. Use
+//!    `SequenceTracker::with_key` to derive the id from the request parts in another way.
+//! 3. Have clients send an increasing number in the `X-Sequence` header.
+//! 4. In your handler function, wrap your extractor with `InSequence`, e.g. `InSequence<Valid<Json<T>>>`.
+//!
+//! The sequence number is only recorded once the inner extractor succeeds, so a request rejected by
+//! validation can be retried with the same number. A sequence number which is not greater than the
+//! last recorded one is rejected with `409 Conflict`; a missing or malformed one, or a request without
+//! a client id, with `400 Bad Request`.
+//!
+//! The client id must come from a trusted source, such as a verified token: any request claiming another
+//! client's id could send `u64::MAX` as its sequence number, rejecting every later request of that client.
+//! The tracker remembers a bounded number of clients, evicting the least recently seen one once it is full,
+//! so an evicted client starts a new sequence.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::guard::{InSequence, SequenceTracker};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use std::num::NonZeroUsize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new()
+//!             .route("/events", post(handler))
+//!             .with_state(SequenceTracker::new(NonZeroUsize::new(10_000).expect("zero capacity")))
+//!     }
+//!
+//!     async fn handler(InSequence(Valid(Json(event))): InSequence<Valid<Json<Event>>>) {
+//!         assert!(event.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Event {
+//!         #[validate(length(min = 1, max = 64))]
+//!         pub kind: String,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::guard::GuardRejection;
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use lru::LruCache;
+use std::fmt::{Debug, Display, Formatter};
+use std::num::NonZeroUsize;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+
+/// Name of the header carrying the sequence number of the request.
+pub const SEQUENCE_HEADER: &str = "x-sequence";
+
+/// Identity of an authenticated client, inserted as a request extension by your authentication middleware.
+///
+/// `SequenceTracker` keys sequences by it unless configured with `SequenceTracker::with_key`.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClientId(pub String);
+
+type KeyFn = dyn Fn(&Parts) -> Option<String> + Send + Sync;
+
+/// Last sequence number accepted from each client.
+///
+/// `InSequence` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Clone)]
+pub struct SequenceTracker {
+    last: Arc<Mutex<LruCache<String, u64>>>,
+    key: Arc<KeyFn>,
+}
+
+impl SequenceTracker {
+    /// Create a `SequenceTracker` remembering up to `capacity` clients, identified by the `ClientId` extension.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self::with_key(capacity, |parts| {
+            parts
+                .extensions
+                .get::<ClientId>()
+                .map(|ClientId(id)| id.clone())
+        })
+    }
+
+    /// Create a `SequenceTracker` remembering up to `capacity` clients, identified by `key`.
+    ///
+    /// `key` must only return ids the request is trusted to act for, e.g. taken from a verified token,
+    /// or `None` to reject the request.
+    pub fn with_key<F>(capacity: NonZeroUsize, key: F) -> Self
+    where
+        F: Fn(&Parts) -> Option<String> + Send + Sync + 'static,
+    {
+        Self {
+            last: Arc::new(Mutex::new(LruCache::new(capacity))),
+            key: Arc::new(key),
+        }
+    }
+
+    /// Returns the last sequence number accepted from `client`.
+    pub fn last(&self, client: &str) -> Option<u64> {
+        self.lock().peek(client).copied()
+    }
+
+    /// Records `sequence` for `client` if it is greater than the last one,
+    /// otherwise returns the last one.
+    pub fn advance(&self, client: &str, sequence: u64) -> Result<(), u64> {
+        let mut last = self.lock();
+        match last.get(client) {
+            Some(&previous) if previous >= sequence => Err(previous),
+            _ => {
+                last.put(client.to_owned(), sequence);
+                Ok(())
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruCache<String, u64>> {
+        self.last
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Debug for SequenceTracker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SequenceTracker")
+            .field("clients", &self.lock().len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// # `InSequence` guard extractor
+///
+/// `InSequence` delegates to the inner extractor, then checks that the `X-Sequence` header
+/// of the request is greater than the last one accepted from the same client.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InSequence<E>(pub E);
+
+impl<E> Deref for InSequence<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for InSequence<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for InSequence<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> InSequence<E> {
+    /// Consumes the `InSequence` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for InSequence<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// Errors raised by `InSequence` when the request is out of order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceError {
+    /// The `X-Sequence` header is missing.
+    Missing,
+    /// The `X-Sequence` header is not an unsigned integer.
+    Malformed,
+    /// The client of the request could not be identified.
+    UnknownClient,
+    /// The sequence number is not greater than the last one accepted from the client.
+    OutOfOrder {
+        /// Last sequence number accepted from the client.
+        last: u64,
+        /// Sequence number of the request.
+        received: u64,
+    },
+}
+
+impl Display for SequenceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SequenceError::Missing => write!(f, "Missing `{SEQUENCE_HEADER}` header"),
+            SequenceError::Malformed => write!(f, "Malformed `{SEQUENCE_HEADER}` header"),
+            SequenceError::UnknownClient => write!(f, "Unknown client"),
+            SequenceError::OutOfOrder { last, received } => write!(
+                f,
+                "Sequence number {received} is out of order, last accepted is {last}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SequenceError {}
+
+impl IntoResponse for SequenceError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            SequenceError::OutOfOrder { .. } => StatusCode::CONFLICT,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// `InSequenceRejection` is returned when the `InSequence` extractor fails.
+///
+pub type InSequenceRejection<E> = GuardRejection<SequenceError, E>;
+
+fn read_sequence(tracker: &SequenceTracker, parts: &Parts) -> Result<(String, u64), SequenceError> {
+    let sequence = parts
+        .headers
+        .get(SEQUENCE_HEADER)
+        .ok_or(SequenceError::Missing)?
+        .to_str()
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok())
+        .ok_or(SequenceError::Malformed)?;
+    let client = (tracker.key)(parts).ok_or(SequenceError::UnknownClient)?;
+    Ok((client, sequence))
+}
+
+fn advance(tracker: &SequenceTracker, client: &str, sequence: u64) -> Result<(), SequenceError> {
+    tracker
+        .advance(client, sequence)
+        .map_err(|last| SequenceError::OutOfOrder {
+            last,
+            received: sequence,
+        })
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for InSequence<Extractor>
+where
+    State: Send + Sync,
+    SequenceTracker: FromRef<State>,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = InSequenceRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let tracker = SequenceTracker::from_ref(state);
+        let (parts, body) = req.into_parts();
+        let (client, sequence) =
+            read_sequence(&tracker, &parts).map_err(InSequenceRejection::Guard)?;
+        let inner = Extractor::from_request(Request::from_parts(parts, body), state)
+            .await
+            .map_err(InSequenceRejection::Inner)?;
+        advance(&tracker, &client, sequence).map_err(InSequenceRejection::Guard)?;
+        Ok(InSequence(inner))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequestParts<State> for InSequence<Extractor>
+where
+    State: Send + Sync,
+    SequenceTracker: FromRef<State>,
+    Extractor: FromRequestParts<State>,
+{
+    type Rejection = InSequenceRejection<<Extractor as FromRequestParts<State>>::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let tracker = SequenceTracker::from_ref(state);
+        let (client, sequence) =
+            read_sequence(&tracker, parts).map_err(InSequenceRejection::Guard)?;
+        let inner = Extractor::from_request_parts(parts, state)
+            .await
+            .map_err(InSequenceRejection::Inner)?;
+        advance(&tracker, &client, sequence).map_err(InSequenceRejection::Guard)?;
+        Ok(InSequence(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    fn request(client: &str, sequence: &str) -> Request {
+        Request::builder()
+            .uri("/")
+            .extension(ClientId(client.to_owned()))
+            .header(SEQUENCE_HEADER, sequence)
+            .body(Body::empty())
+            .expect("invalid request")
+    }
+
+    fn capacity(clients: usize) -> NonZeroUsize {
+        NonZeroUsize::new(clients).expect("zero capacity")
+    }
+
+    #[tokio::test]
+    async fn in_sequence() {
+        let tracker = SequenceTracker::new(capacity(16));
+
+        for sequence in ["1", "2", "5"] {
+            assert!(
+                InSequence::<String>::from_request(request("a", sequence), &tracker)
+                    .await
+                    .is_ok()
+            );
+        }
+        assert!(
+            InSequence::<String>::from_request(request("b", "1"), &tracker)
+                .await
+                .is_ok()
+        );
+
+        let rejection = InSequence::<String>::from_request(request("a", "4"), &tracker)
+            .await
+            .expect_err("out of order request accepted");
+        assert!(matches!(
+            rejection,
+            InSequenceRejection::Guard(SequenceError::OutOfOrder {
+                last: 5,
+                received: 4
+            })
+        ));
+        assert_eq!(rejection.into_response().status(), StatusCode::CONFLICT);
+        assert!(
+            InSequence::<String>::from_request(request("a", "5"), &tracker)
+                .await
+                .is_err()
+        );
+        assert_eq!(tracker.last("a"), Some(5));
+
+        assert!(matches!(
+            InSequence::<String>::from_request(request("a", "six"), &tracker).await,
+            Err(InSequenceRejection::Guard(SequenceError::Malformed))
+        ));
+    }
+
+    #[tokio::test]
+    async fn in_sequence_needs_trusted_client() {
+        let tracker = SequenceTracker::new(capacity(2));
+
+        // A client id sent as a header is not trusted
+        let anonymous = Request::builder()
+            .uri("/")
+            .header("x-client-id", "a")
+            .header(SEQUENCE_HEADER, "1")
+            .body(Body::empty())
+            .expect("invalid request");
+        let rejection = InSequence::<String>::from_request(anonymous, &tracker)
+            .await
+            .expect_err("unidentified request accepted");
+        assert!(matches!(
+            rejection,
+            InSequenceRejection::Guard(SequenceError::UnknownClient)
+        ));
+        assert_eq!(rejection.into_response().status(), StatusCode::BAD_REQUEST);
+
+        // The least recently seen client is evicted once the tracker is full
+        for client in ["a", "b", "c"] {
+            InSequence::<String>::from_request(request(client, "7"), &tracker)
+                .await
+                .expect("first request rejected");
+        }
+        assert_eq!(tracker.last("a"), None);
+        assert_eq!(tracker.last("c"), Some(7));
+
+        let tracker = SequenceTracker::with_key(capacity(2), |parts| {
+            parts
+                .uri
+                .path()
+                .strip_prefix("/tenants/")
+                .map(str::to_owned)
+        });
+        let request = Request::builder()
+            .uri("/tenants/acme")
+            .header(SEQUENCE_HEADER, "3")
+            .body(Body::empty())
+            .expect("invalid request");
+        InSequence::<String>::from_request(request, &tracker)
+            .await
+            .expect("keyed request rejected");
+        assert_eq!(tracker.last("acme"), Some(3));
+    }
+}
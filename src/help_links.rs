@@ -0,0 +1,324 @@
+//! # Documentation links in validation errors
+//!
+//! ## Feature
+//!
+//! Enable the `help_links` feature to use `WithHelp<E>`.
+//!
+//! ## Usage
+//!
+//! Error codes such as `length` or `range` tell a client what went wrong, but not always how to fix it.
+//! `WithHelp<E>` wraps a validation extractor, e.g. `WithHelp<Valid<Json<T>>>`, and adds a link to the
+//! documentation of each error code under the `help` key of the errors:
+//!
+//! 1. Build a `HelpLinks` mapping error codes to documentation URLs, and make it available from your router state
+//!    (implement `FromRef<YourState>` for it, or use it as the state directly).
+//! 2. In your handler function, wrap your validation extractor with `WithHelp`.
+//!
+//! Codes without a URL of their own are linked to `{base}{code}` when a base URL is set with `with_base`,
+//! and left without a `help` key otherwise. Errors are found at any depth, as objects with a string `code`,
+//! so the errors of `validator`, `validify` and the pre-checks are supported; `garde` errors have no code.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::help_links::{HelpLinks, WithHelp};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         let links = HelpLinks::default()
+//!             .with_base("https://docs.example.com/errors#")
+//!             .code("email", "https://docs.example.com/accounts#email");
+//!         Router::new()
+//!             .route("/json", post(handler))
+//!             .with_state(links)
+//!     }
+//!
+//!     async fn handler(WithHelp(Valid(Json(account))): WithHelp<Valid<Json<Account>>>) {
+//!         assert!(account.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Account {
+//!         #[validate(email)]
+//!         pub email: String,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::ValidationRejection;
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// Documentation URLs of error codes, rendered by `WithHelp`.
+///
+/// `WithHelp` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, Default)]
+pub struct HelpLinks {
+    base: Option<Arc<str>>,
+    codes: Arc<BTreeMap<String, String>>,
+}
+
+impl HelpLinks {
+    /// Links the errors with the given code to `url`.
+    pub fn code(mut self, code: impl Into<String>, url: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.codes).insert(code.into(), url.into());
+        self
+    }
+
+    /// Links the errors whose code has no URL of its own to `{base}{code}`.
+    pub fn with_base(mut self, base: impl Into<String>) -> Self {
+        self.base = Some(Arc::from(base.into()));
+        self
+    }
+
+    /// Returns the documentation URL of an error code.
+    pub fn get(&self, code: &str) -> Option<String> {
+        match (self.codes.get(code), &self.base) {
+            (Some(url), _) => Some(url.clone()),
+            (None, Some(base)) => Some(format!("{base}{code}")),
+            (None, None) => None,
+        }
+    }
+
+    /// Adds the `help` key to the errors of serialized validation errors.
+    fn link(&self, value: &mut Value) {
+        match value {
+            Value::Object(fields) => {
+                let help = fields
+                    .get("code")
+                    .and_then(Value::as_str)
+                    .and_then(|code| self.get(code));
+                match help {
+                    Some(url) => {
+                        fields.insert(String::from("help"), Value::String(url));
+                    }
+                    None => fields.values_mut().for_each(|value| self.link(value)),
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.link(item)),
+            _ => {}
+        }
+    }
+}
+
+/// Validation errors linked to their documentation by `WithHelp`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct LinkedErrors(pub Value);
+
+impl Display for LinkedErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for LinkedErrors {}
+
+/// # `WithHelp` data extractor
+///
+/// `WithHelp` runs the inner validation extractor, linking each of its validation errors
+/// to the documentation of its code according to the `HelpLinks` taken from the state.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WithHelp<E>(pub E);
+
+impl<E> Deref for WithHelp<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for WithHelp<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for WithHelp<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> WithHelp<E> {
+    /// Consumes the `WithHelp` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for WithHelp<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `WithHelpRejection` is returned when the `WithHelp` extractor fails.
+///
+pub type WithHelpRejection<E> = ValidationRejection<LinkedErrors, E>;
+
+fn link<V: Serialize, E>(
+    rejection: ValidationRejection<V, E>,
+    links: &HelpLinks,
+) -> WithHelpRejection<E> {
+    match rejection {
+        ValidationRejection::Valid(errors) => {
+            let mut value = serde_json::to_value(&errors).unwrap_or(Value::Null);
+            links.link(&mut value);
+            ValidationRejection::Valid(LinkedErrors(value))
+        }
+        ValidationRejection::Inner(error) => ValidationRejection::Inner(error),
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, V, E> FromRequest<State> for WithHelp<Extractor>
+where
+    State: Send + Sync,
+    HelpLinks: FromRef<State>,
+    Extractor: FromRequest<State, Rejection = ValidationRejection<V, E>>,
+    V: Serialize,
+    WithHelpRejection<E>: axum::response::IntoResponse,
+{
+    type Rejection = WithHelpRejection<E>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        Extractor::from_request(req, state)
+            .await
+            .map(WithHelp)
+            .map_err(|rejection| link(rejection, &HelpLinks::from_ref(state)))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, V, E> FromRequestParts<State> for WithHelp<Extractor>
+where
+    State: Send + Sync,
+    HelpLinks: FromRef<State>,
+    Extractor: FromRequestParts<State, Rejection = ValidationRejection<V, E>>,
+    V: Serialize,
+    WithHelpRejection<E>: axum::response::IntoResponse,
+{
+    type Rejection = WithHelpRejection<E>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        Extractor::from_request_parts(parts, state)
+            .await
+            .map(WithHelp)
+            .map_err(|rejection| link(rejection, &HelpLinks::from_ref(state)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn help_urls() {
+        let links = HelpLinks::default().code("range", "https://example.com/range");
+        assert_eq!(
+            links.get("range").as_deref(),
+            Some("https://example.com/range")
+        );
+        assert_eq!(links.get("length"), None);
+
+        let links = links.with_base("https://example.com/errors#");
+        assert_eq!(
+            links.get("length").as_deref(),
+            Some("https://example.com/errors#length")
+        );
+
+        let mut errors = json!({
+            "code": [{ "code": "range", "params": { "code": "x" } }],
+            "items": { "0": { "name": [{ "code": "unlisted" }] } },
+        });
+        HelpLinks::default()
+            .code("range", "https://example.com/range")
+            .link(&mut errors);
+        assert_eq!(errors["code"][0]["help"], "https://example.com/range");
+        assert!(errors["code"][0]["params"].get("help").is_none());
+        assert!(errors["items"]["0"]["name"][0].get("help").is_none());
+    }
+
+    #[cfg(feature = "validator")]
+    #[tokio::test]
+    async fn errors_carry_help_links() {
+        use crate::Valid;
+        use axum::body::Body;
+        use axum::response::IntoResponse;
+        use axum::Json;
+        use serde::Deserialize;
+        use validator::Validate;
+
+        #[derive(Debug, Validate, Deserialize)]
+        struct Account {
+            #[validate(email)]
+            email: String,
+            #[validate(length(min = 3))]
+            name: String,
+        }
+
+        let links = HelpLinks::default()
+            .with_base("https://docs.example.com/errors#")
+            .code("email", "https://docs.example.com/accounts#email");
+        let request = Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{ "email": "nope", "name": "ax" }"#))
+            .expect("invalid request");
+        let response = WithHelp::<Valid<Json<Account>>>::from_request(request, &links)
+            .await
+            .expect_err("invalid account accepted")
+            .into_response();
+        assert_eq!(response.status(), crate::VALIDATION_ERROR_STATUS);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("failed to read body");
+        let errors: Value = serde_json::from_slice(&body).expect("invalid json");
+        assert_eq!(
+            errors["email"][0]["help"],
+            "https://docs.example.com/accounts#email"
+        );
+        assert_eq!(
+            errors["name"][0]["help"],
+            "https://docs.example.com/errors#length"
+        );
+    }
+}
@@ -0,0 +1,1483 @@
+//! # Validation helpers
+//!
+//! ## Feature
+//!
+//! Enable the `helpers` feature to use the helper validators.
+//!
+//! The helpers are plain functions returning `Result<(), validator::ValidationError>`,
+//! so they can be used with `validator`'s `custom` rule.
+//! Helpers that need a parameter take it as a const generic argument, e.g. `max_bytes::<4096>`.
+//! Helpers that need a closure, such as `sorted_by`, are called from a small function of your own,
+//! which is then passed to `custom` or `schema`.
+//!
+//! Helpers that depend on configuration, such as `no_forbidden_words`, take it as the validation context,
+//! so they are used with `custom(..., use_context)` and the `ValidEx` extractor, which reads the context
+//! from the router state.
+//!
+//! Enable the `decimal` feature for `decimal_max` and `decimal_min`, which bound `rust_decimal::Decimal` values.
+//!
+//! Enable the `unicode-segmentation` feature for `max_graphemes`, which bounds the visible length of strings.
+//!
+//! Enable the `datetime-helpers` feature for `within_duration`, which bounds the time between two `chrono` values.
+//!
+//! `normalize_email` and `deserialize_email` normalize email addresses before they are validated,
+//! with `validify`'s `custom` modifier and `serde`'s `deserialize_with` respectively.
+//!
+//! `custom` rules are skipped for `Option` fields which are `None`, so an `Option<String>` field
+//! validated with `not_empty` accepts a missing field but rejects `""`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use axum::routing::post;
+//! use axum::Json;
+//! use axum::Router;
+//! use axum_valid::Valid;
+//! use serde::Deserialize;
+//! use validator::Validate;
+//!
+//! #[derive(Validate, Deserialize)]
+//! pub struct Profile {
+//!     // At most 256 characters, but no more than 512 bytes once encoded.
+//!     #[validate(length(max = 256), custom(function = "axum_valid::helpers::max_bytes::<512>"))]
+//!     pub bio: String,
+//! }
+//!
+//! async fn handler(Valid(Json(profile)): Valid<Json<Profile>>) {
+//!     assert!(profile.bio.len() <= 512);
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new().route("/profile", post(handler));
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::iter::Sum;
+use std::sync::Arc;
+use validator::ValidationError;
+
+/// Validates that a string is at most `N` bytes long once UTF-8 encoded.
+///
+/// Unlike `length(max = N)`, which counts characters, this bounds the memory used by the value.
+///
+/// Error code: `max_bytes`, with the `max` and `actual` byte counts as parameters.
+pub fn max_bytes<const N: usize>(value: &str) -> Result<(), ValidationError> {
+    if value.len() <= N {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("max_bytes");
+    error.message = Some(Cow::from(format!("must be at most {N} bytes long")));
+    error.add_param(Cow::from("max"), &N);
+    error.add_param(Cow::from("actual"), &value.len());
+    Err(error)
+}
+
+/// Validates that a string is not empty.
+///
+/// On an `Option<String>` field this tells an empty value apart from a missing one:
+/// `None` (field absent or `null`) is not validated, while `Some("")` is rejected.
+///
+/// Error code: `empty`.
+pub fn not_empty(value: &str) -> Result<(), ValidationError> {
+    if !value.is_empty() {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("empty");
+    error.message = Some(Cow::from("must not be empty, omit the field instead"));
+    Err(error)
+}
+
+/// Validates that a string is not empty once leading and trailing whitespace is trimmed,
+/// so `"   "` fails like a missing value would.
+///
+/// Combine it with `required` on an `Option<String>` field to reject missing, `null` and
+/// whitespace-only values alike: `#[validate(required, custom(function = "not_blank"))]`.
+///
+/// Error code: `blank`.
+pub fn not_blank(value: &str) -> Result<(), ValidationError> {
+    if !value.trim().is_empty() {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("blank");
+    error.message = Some(Cow::from("must not be blank"));
+    Err(error)
+}
+
+/// Normalizes an email address, trimming surrounding whitespace and lowercasing both its local part and domain,
+/// so `" Ada@Example.COM "` becomes `"ada@example.com"`.
+///
+/// Its signature fits `validify`'s `custom` modifier, which runs before the validations of `Validified`:
+///
+/// ```
+/// # #[cfg(feature = "validify")]
+/// # {
+/// use validify::Validify;
+///
+/// #[derive(Validify)]
+/// struct Signup {
+///     #[modify(custom(axum_valid::helpers::normalize_email))]
+///     #[validate(email)]
+///     email: String,
+/// }
+///
+/// let mut signup = Signup { email: String::from(" Ada@Example.COM ") };
+/// assert!(signup.validify().is_ok());
+/// assert_eq!(signup.email, "ada@example.com");
+/// # }
+/// ```
+///
+/// `validator` cannot modify values, so pair `validator`'s `email` check with `deserialize_email` instead.
+pub fn normalize_email(email: &mut String) {
+    let normalized = email.trim().to_lowercase();
+    *email = normalized;
+}
+
+/// Deserializes an email address normalized by `normalize_email`, so `validator`'s `email` check runs
+/// on the normalized value, e.g. with `Valid<Json<T>>`:
+///
+/// ```
+/// use serde::Deserialize;
+/// use validator::Validate;
+///
+/// #[derive(Validate, Deserialize)]
+/// struct Signup {
+///     #[serde(deserialize_with = "axum_valid::helpers::deserialize_email")]
+///     #[validate(email)]
+///     email: String,
+/// }
+///
+/// let signup: Signup = serde_json::from_str(r#"{ "email": " Ada@Example.COM " }"#).expect("invalid json");
+/// assert!(signup.validate().is_ok());
+/// assert_eq!(signup.email, "ada@example.com");
+/// ```
+pub fn deserialize_email<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let mut email = String::deserialize(deserializer)?;
+    normalize_email(&mut email);
+    Ok(email)
+}
+
+/// Validates that a string is one of the `allowed` values, e.g. the variants of a string-typed enum.
+///
+/// Since it takes the list of values, call it from a function of your own:
+///
+/// ```
+/// use validator::{Validate, ValidationError};
+///
+/// fn color(value: &str) -> Result<(), ValidationError> {
+///     axum_valid::helpers::one_of(value, &["red", "green", "blue"])
+/// }
+///
+/// #[derive(Validate)]
+/// struct Paint {
+///     #[validate(custom(function = "color"))]
+///     color: String,
+/// }
+/// ```
+///
+/// Values are compared exactly, so `Red` is not one of `["red"]`.
+///
+/// Error code: `one_of`, with the `allowed` values and the `actual` value as parameters.
+pub fn one_of(value: &str, allowed: &[&str]) -> Result<(), ValidationError> {
+    if allowed.contains(&value) {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("one_of");
+    error.message = Some(Cow::from(format!("must be one of: {}", allowed.join(", "))));
+    error.add_param(Cow::from("allowed"), &allowed);
+    error.add_param(Cow::from("actual"), &value);
+    Err(error)
+}
+
+/// A set of characters accepted by `charset`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Charset {
+    /// ASCII characters, including control characters.
+    Ascii,
+    /// Printable ASCII characters, from space to `~`.
+    AsciiPrintable,
+    /// ASCII letters and digits.
+    Alphanumeric,
+    /// ASCII digits.
+    Digits,
+}
+
+impl Charset {
+    /// Returns `true` if `c` belongs to the set.
+    pub fn contains(self, c: char) -> bool {
+        match self {
+            Charset::Ascii => c.is_ascii(),
+            Charset::AsciiPrintable => matches!(c, ' '..='~'),
+            Charset::Alphanumeric => c.is_ascii_alphanumeric(),
+            Charset::Digits => c.is_ascii_digit(),
+        }
+    }
+
+    /// Name of the set, as reported in the `charset` parameter of errors.
+    pub fn name(self) -> &'static str {
+        match self {
+            Charset::Ascii => "ascii",
+            Charset::AsciiPrintable => "ascii_printable",
+            Charset::Alphanumeric => "alphanumeric",
+            Charset::Digits => "digits",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Charset::Ascii => "ASCII characters",
+            Charset::AsciiPrintable => "printable ASCII characters",
+            Charset::Alphanumeric => "ASCII letters and digits",
+            Charset::Digits => "digits",
+        }
+    }
+}
+
+/// Validates that every character of a string belongs to `charset`, e.g. to keep usernames ASCII-only.
+///
+/// Since it takes the character set, call it from a function of your own:
+///
+/// ```
+/// use axum_valid::helpers::Charset;
+/// use validator::{Validate, ValidationError};
+///
+/// fn ascii_only(value: &str) -> Result<(), ValidationError> {
+///     axum_valid::helpers::charset(value, Charset::Alphanumeric)
+/// }
+///
+/// #[derive(Validate)]
+/// struct Account {
+///     #[validate(length(min = 3, max = 32), custom(function = "ascii_only"))]
+///     username: String,
+/// }
+/// ```
+///
+/// Error code: `charset`, with the `charset` name and the first `invalid` character as parameters.
+pub fn charset(value: &str, charset: Charset) -> Result<(), ValidationError> {
+    let Some(invalid) = value.chars().find(|&c| !charset.contains(c)) else {
+        return Ok(());
+    };
+    let mut error = ValidationError::new("charset");
+    error.message = Some(Cow::from(format!(
+        "must only contain {}",
+        charset.description()
+    )));
+    error.add_param(Cow::from("charset"), &charset.name());
+    error.add_param(Cow::from("invalid"), &invalid.to_string());
+    Err(error)
+}
+
+/// A Unicode script counted by `max_scripts`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Script {
+    /// Latin letters, including accented ones, e.g. `é`.
+    Latin,
+    /// Greek letters.
+    Greek,
+    /// Cyrillic letters.
+    Cyrillic,
+    /// Armenian letters.
+    Armenian,
+    /// Hebrew letters.
+    Hebrew,
+    /// Arabic letters.
+    Arabic,
+    /// Devanagari letters.
+    Devanagari,
+    /// Thai letters.
+    Thai,
+    /// Korean Hangul syllables and letters.
+    Hangul,
+    /// Japanese Hiragana.
+    Hiragana,
+    /// Japanese Katakana.
+    Katakana,
+    /// CJK ideographs.
+    Han,
+}
+
+impl Script {
+    /// Returns the script of `c`, or `None` for characters shared by all scripts, such as digits,
+    /// punctuation and whitespace, and for characters of scripts not listed above.
+    pub fn of(c: char) -> Option<Script> {
+        match c {
+            'A'..='Z' | 'a'..='z' | 'ª' | 'º' | '\u{1E00}'..='\u{1EFF}' => Some(Script::Latin),
+            'Ａ'..='Ｚ' | 'ａ'..='ｚ' => Some(Script::Latin),
+            '×' | '÷' => None,
+            'À'..='\u{024F}' => Some(Script::Latin),
+            '\u{0370}'..='\u{03FF}' | '\u{1F00}'..='\u{1FFF}' => Some(Script::Greek),
+            '\u{0400}'..='\u{052F}' | '\u{1C80}'..='\u{1C8F}' | '\u{A640}'..='\u{A69F}' => {
+                Some(Script::Cyrillic)
+            }
+            '\u{0531}'..='\u{058F}' => Some(Script::Armenian),
+            '\u{0591}'..='\u{05FF}' => Some(Script::Hebrew),
+            '\u{0600}'..='\u{06FF}' | '\u{0750}'..='\u{077F}' => Some(Script::Arabic),
+            '\u{0900}'..='\u{097F}' => Some(Script::Devanagari),
+            '\u{0E00}'..='\u{0E7F}' => Some(Script::Thai),
+            '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' | '\u{AC00}'..='\u{D7AF}' => {
+                Some(Script::Hangul)
+            }
+            '\u{3041}'..='\u{309F}' => Some(Script::Hiragana),
+            '\u{30A0}'..='\u{30FF}' => Some(Script::Katakana),
+            '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' => Some(Script::Han),
+            _ => None,
+        }
+    }
+
+    /// Name of the script, as reported in the `scripts` parameter of errors.
+    pub fn name(self) -> &'static str {
+        match self {
+            Script::Latin => "latin",
+            Script::Greek => "greek",
+            Script::Cyrillic => "cyrillic",
+            Script::Armenian => "armenian",
+            Script::Hebrew => "hebrew",
+            Script::Arabic => "arabic",
+            Script::Devanagari => "devanagari",
+            Script::Thai => "thai",
+            Script::Hangul => "hangul",
+            Script::Hiragana => "hiragana",
+            Script::Katakana => "katakana",
+            Script::Han => "han",
+        }
+    }
+}
+
+/// Validates that a string mixes at most `N` Unicode scripts, e.g. to reject usernames such as `pаypal`
+/// whose `а` is Cyrillic, spoofing a Latin name.
+///
+/// Digits, punctuation and whitespace belong to no script, so `user_42` only uses Latin.
+/// Japanese text commonly mixes Han, Hiragana and Katakana, so allow 3 scripts for such fields.
+///
+/// ```
+/// use validator::Validate;
+///
+/// #[derive(Validate)]
+/// struct Account {
+///     #[validate(length(min = 3, max = 32), custom(function = "axum_valid::helpers::max_scripts::<1>"))]
+///     username: String,
+/// }
+/// ```
+///
+/// Error code: `mixed_scripts`, with the `max` count and the names of the `scripts` found, in order, as parameters.
+pub fn max_scripts<const N: usize>(value: &str) -> Result<(), ValidationError> {
+    let scripts = value
+        .chars()
+        .filter_map(Script::of)
+        .collect::<BTreeSet<_>>();
+    if scripts.len() <= N {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("mixed_scripts");
+    error.message = Some(Cow::from(match N {
+        1 => String::from("must not mix scripts"),
+        _ => format!("must not mix more than {N} scripts"),
+    }));
+    error.add_param(Cow::from("max"), &N);
+    error.add_param(
+        Cow::from("scripts"),
+        &scripts.into_iter().map(Script::name).collect::<Vec<_>>(),
+    );
+    Err(error)
+}
+
+/// Validates that a string is not repetitive, i.e. that its Shannon entropy is at least `min` bits per character,
+/// e.g. to reject spam such as `aaaaaaaa` or `abababab`.
+///
+/// Since it takes the threshold, call it from a function of your own:
+///
+/// ```
+/// use validator::{Validate, ValidationError};
+///
+/// fn varied(value: &str) -> Result<(), ValidationError> {
+///     axum_valid::helpers::min_entropy(value, 2.0)
+/// }
+///
+/// #[derive(Validate)]
+/// struct Comment {
+///     #[validate(length(min = 8), custom(function = "varied"))]
+///     text: String,
+/// }
+/// ```
+///
+/// A string repeating a single character has an entropy of 0 bits, and one made of `n` distinct characters
+/// used equally often has an entropy of `log2(n)` bits, so `abab` has 1 bit and `abcd` 2 bits.
+/// Short strings cannot reach high thresholds, so combine it with a minimum length. Empty strings are accepted.
+///
+/// Error code: `low_entropy`, with the `min` and `actual` entropies as parameters.
+pub fn min_entropy(value: &str, min: f64) -> Result<(), ValidationError> {
+    let actual = entropy(value);
+    if value.is_empty() || actual >= min {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("low_entropy");
+    error.message = Some(Cow::from("is too repetitive"));
+    error.add_param(Cow::from("min"), &min);
+    error.add_param(Cow::from("actual"), &actual);
+    Err(error)
+}
+
+/// Shannon entropy of the characters of a string, in bits per character.
+fn entropy(value: &str) -> f64 {
+    let mut counts = std::collections::HashMap::<char, usize>::new();
+    for c in value.chars() {
+        *counts.entry(c).or_default() += 1;
+    }
+    let total = value.chars().count() as f64;
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// A list of forbidden words, used as the validation context of `no_forbidden_words`.
+///
+/// Words are matched case-insensitively and only as whole words, so `class` does not match `ass`.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForbiddenWords(Arc<BTreeSet<String>>);
+
+impl ForbiddenWords {
+    /// Create a list from the given words.
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self(Arc::new(
+            words
+                .into_iter()
+                .map(|word| word.as_ref().to_lowercase())
+                .collect(),
+        ))
+    }
+
+    /// Returns the first forbidden word found in `text`.
+    pub fn find<'t>(&self, text: &'t str) -> Option<&'t str> {
+        words(text).find(|word| self.0.contains(&word.to_lowercase()))
+    }
+
+    /// Returns `text` with every forbidden word replaced by `*`s, for payloads which are masked rather than rejected.
+    pub fn mask(&self, text: &str) -> String {
+        let mut masked = String::with_capacity(text.len());
+        let mut end = 0;
+        for word in words(text).filter(|word| self.0.contains(&word.to_lowercase())) {
+            let start = word.as_ptr() as usize - text.as_ptr() as usize;
+            masked.push_str(&text[end..start]);
+            masked.push_str(&"*".repeat(word.chars().count()));
+            end = start + word.len();
+        }
+        masked.push_str(&text[end..]);
+        masked
+    }
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+}
+
+/// Validates that a string contains none of the `ForbiddenWords` given as validation context.
+///
+/// ```
+/// use validator::Validate;
+/// use axum_valid::helpers::ForbiddenWords;
+///
+/// #[derive(Validate)]
+/// #[validate(context = ForbiddenWords)]
+/// struct Comment {
+///     #[validate(custom(function = "axum_valid::helpers::no_forbidden_words", use_context))]
+///     body: String,
+/// }
+/// ```
+///
+/// Error code: `forbidden_word`, with the offending `word` as parameter.
+pub fn no_forbidden_words(value: &str, words: &ForbiddenWords) -> Result<(), ValidationError> {
+    let Some(word) = words.find(value) else {
+        return Ok(());
+    };
+    let mut error = ValidationError::new("forbidden_word");
+    error.message = Some(Cow::from("must not contain forbidden words"));
+    error.add_param(Cow::from("word"), &word);
+    Err(error)
+}
+
+/// Validates that a declared total equals the sum of its parts, e.g. the line items of an invoice.
+///
+/// Since it compares several fields, call it from a struct-level `schema` function of your own:
+///
+/// ```
+/// use validator::{Validate, ValidationError};
+///
+/// struct Line {
+///     amount: u64,
+/// }
+///
+/// #[derive(Validate)]
+/// #[validate(schema(function = "total_of_lines"))]
+/// struct Invoice {
+///     total: u64,
+///     lines: Vec<Line>,
+/// }
+///
+/// fn total_of_lines(invoice: &Invoice) -> Result<(), ValidationError> {
+///     axum_valid::helpers::total_matches(invoice.total, invoice.lines.iter().map(|line| line.amount))
+/// }
+/// ```
+///
+/// `schema` errors are reported under the `__all__` key, so the message names both values.
+///
+/// Error code: `total_mismatch`, with the `declared` total and the `actual` sum as parameters.
+pub fn total_matches<N, I>(declared: N, parts: I) -> Result<(), ValidationError>
+where
+    N: Sum + PartialEq + Display + Serialize,
+    I: IntoIterator<Item = N>,
+{
+    let actual: N = parts.into_iter().sum();
+    if actual == declared {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("total_mismatch");
+    error.message = Some(Cow::from(format!(
+        "declared total {declared} does not match the sum of its parts {actual}"
+    )));
+    error.add_param(Cow::from("declared"), &declared);
+    error.add_param(Cow::from("actual"), &actual);
+    Err(error)
+}
+
+/// Validates that the fields of a group are either all present or all absent, e.g. the parts of an address.
+///
+/// Since it looks at several fields, call it from a struct-level `schema` function of your own:
+///
+/// ```
+/// use serde::Serialize;
+/// use validator::{Validate, ValidationError};
+///
+/// #[derive(Validate, Serialize)]
+/// #[validate(schema(function = "address"))]
+/// struct Order {
+///     street: Option<String>,
+///     city: Option<String>,
+///     zip: Option<String>,
+/// }
+///
+/// fn address(order: &Order) -> Result<(), ValidationError> {
+///     axum_valid::helpers::all_or_none(order, &["street", "city", "zip"])
+/// }
+/// ```
+///
+/// Fields are looked up by their serialized names, and a field is absent if it is missing or `null`
+/// once serialized, e.g. an `Option` which is `None`.
+///
+/// `schema` errors are reported under the `__all__` key, so the message names the missing fields.
+///
+/// Error code: `all_or_none`, with the `group` and the `missing` fields as parameters.
+pub fn all_or_none<T: Serialize + ?Sized>(
+    value: &T,
+    group: &[&str],
+) -> Result<(), ValidationError> {
+    let present = present_fields(value, group);
+    if present.is_empty() || present.len() == group.len() {
+        return Ok(());
+    }
+    let missing = group
+        .iter()
+        .copied()
+        .filter(|name| !present.contains(name))
+        .collect::<Vec<_>>();
+    let mut error = ValidationError::new("all_or_none");
+    error.message = Some(Cow::from(format!(
+        "{} must be given together, missing: {}",
+        group.join(", "),
+        missing.join(", ")
+    )));
+    error.add_param(Cow::from("group"), &group);
+    error.add_param(Cow::from("missing"), &missing);
+    Err(error)
+}
+
+/// Validates that at least `n` fields of a group are present, e.g. several ways of contacting a customer.
+///
+/// Since it looks at several fields, call it from a struct-level `schema` function of your own:
+///
+/// ```
+/// use serde::Serialize;
+/// use validator::{Validate, ValidationError};
+///
+/// #[derive(Validate, Serialize)]
+/// #[validate(schema(function = "contact"))]
+/// struct Customer {
+///     email: Option<String>,
+///     phone: Option<String>,
+///     address: Option<String>,
+/// }
+///
+/// fn contact(customer: &Customer) -> Result<(), ValidationError> {
+///     axum_valid::helpers::at_least_n_of(customer, &["email", "phone", "address"], 2)
+/// }
+/// ```
+///
+/// Fields are looked up and considered absent as in `all_or_none`.
+///
+/// Error code: `at_least_n_of`, with the `group`, the `min` count and the `present` fields as parameters.
+pub fn at_least_n_of<T: Serialize + ?Sized>(
+    value: &T,
+    group: &[&str],
+    n: usize,
+) -> Result<(), ValidationError> {
+    let present = present_fields(value, group);
+    if present.len() >= n {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("at_least_n_of");
+    error.message = Some(Cow::from(format!(
+        "at least {n} of {} must be given, got {}",
+        group.join(", "),
+        present.len()
+    )));
+    error.add_param(Cow::from("group"), &group);
+    error.add_param(Cow::from("min"), &n);
+    error.add_param(Cow::from("present"), &present);
+    Err(error)
+}
+
+/// Validates that at most one field of a group is present, e.g. mutually exclusive ways of paying,
+/// while accepting none of them.
+///
+/// Since it looks at several fields, call it from a struct-level `schema` function of your own:
+///
+/// ```
+/// use serde::Serialize;
+/// use validator::{Validate, ValidationError};
+///
+/// #[derive(Validate, Serialize)]
+/// #[validate(schema(function = "payment"))]
+/// struct Checkout {
+///     card_token: Option<String>,
+///     voucher: Option<String>,
+/// }
+///
+/// fn payment(checkout: &Checkout) -> Result<(), ValidationError> {
+///     axum_valid::helpers::at_most_one_of(checkout, &["card_token", "voucher"])
+/// }
+/// ```
+///
+/// Fields are looked up and considered absent as in `all_or_none`.
+/// Combine it with `at_least_n_of(value, group, 1)` to require exactly one field.
+///
+/// Error code: `at_most_one_of`, with the `group` and the `present` fields as parameters.
+pub fn at_most_one_of<T: Serialize + ?Sized>(
+    value: &T,
+    group: &[&str],
+) -> Result<(), ValidationError> {
+    let present = present_fields(value, group);
+    if present.len() <= 1 {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("at_most_one_of");
+    error.message = Some(Cow::from(format!(
+        "at most one of {} may be given, got {}",
+        group.join(", "),
+        present.join(", ")
+    )));
+    error.add_param(Cow::from("group"), &group);
+    error.add_param(Cow::from("present"), &present);
+    Err(error)
+}
+
+/// Returns the fields of a group which are present in the serialized value, neither missing nor `null`.
+fn present_fields<'a, T: Serialize + ?Sized>(value: &T, group: &[&'a str]) -> Vec<&'a str> {
+    let fields = match serde_json::to_value(value) {
+        Ok(serde_json::Value::Object(fields)) => fields,
+        _ => return Vec::new(),
+    };
+    group
+        .iter()
+        .copied()
+        .filter(|name| fields.get(*name).is_some_and(|field| !field.is_null()))
+        .collect()
+}
+
+/// Validates that the elements of a slice are sorted in ascending order of `key`.
+/// Equal keys are allowed.
+///
+/// Since it takes a closure, call it from a function of your own:
+///
+/// ```
+/// use validator::{Validate, ValidationError};
+///
+/// #[derive(serde::Serialize)]
+/// struct Event {
+///     at: u64,
+/// }
+///
+/// fn time_ordered(events: &[Event]) -> Result<(), ValidationError> {
+///     axum_valid::helpers::sorted_by(events, |event| event.at)
+/// }
+///
+/// #[derive(Validate)]
+/// struct Timeline {
+///     #[validate(custom(function = "time_ordered"))]
+///     events: Vec<Event>,
+/// }
+/// ```
+///
+/// Error code: `sorted`, with the `index` of the first element which is out of order as parameter.
+pub fn sorted_by<T, K, F>(items: &[T], key: F) -> Result<(), ValidationError>
+where
+    K: PartialOrd,
+    F: Fn(&T) -> K,
+{
+    let keys = items.iter().map(key).collect::<Vec<_>>();
+    let Some(index) = keys
+        .windows(2)
+        .position(|pair| {
+            !matches!(
+                pair[0].partial_cmp(&pair[1]),
+                Some(Ordering::Less | Ordering::Equal)
+            )
+        })
+        .map(|position| position + 1)
+    else {
+        return Ok(());
+    };
+    let mut error = ValidationError::new("sorted");
+    error.message = Some(Cow::from(format!(
+        "must be sorted, element {index} is out of order"
+    )));
+    error.add_param(Cow::from("index"), &index);
+    Err(error)
+}
+
+/// Validates that a decimal is at most `max`, e.g. a monetary amount held in a `rust_decimal::Decimal`.
+///
+/// `validator`'s `range` only supports primitive numbers, and decimals cannot be const generic arguments,
+/// so call it from a function of your own:
+///
+/// ```
+/// use rust_decimal::Decimal;
+/// use validator::{Validate, ValidationError};
+///
+/// fn amount(value: &Decimal) -> Result<(), ValidationError> {
+///     axum_valid::helpers::decimal_min(value, Decimal::new(1, 2))?;
+///     axum_valid::helpers::decimal_max(value, Decimal::new(1_000_000, 2))
+/// }
+///
+/// #[derive(Validate)]
+/// struct Payment {
+///     #[validate(custom(function = "amount"))]
+///     amount: Decimal,
+/// }
+/// ```
+///
+/// The comparison is exact, e.g. `10000.001` is greater than `10000.00`.
+///
+/// Error code: `decimal_max`, with the `max` and `actual` values as parameters.
+#[cfg(feature = "decimal")]
+pub fn decimal_max(
+    value: &rust_decimal::Decimal,
+    max: rust_decimal::Decimal,
+) -> Result<(), ValidationError> {
+    if *value <= max {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("decimal_max");
+    error.message = Some(Cow::from(format!("must be at most {max}")));
+    error.add_param(Cow::from("max"), &max);
+    error.add_param(Cow::from("actual"), value);
+    Err(error)
+}
+
+/// Validates that a decimal is at least `min`. See `decimal_max`.
+///
+/// Error code: `decimal_min`, with the `min` and `actual` values as parameters.
+#[cfg(feature = "decimal")]
+pub fn decimal_min(
+    value: &rust_decimal::Decimal,
+    min: rust_decimal::Decimal,
+) -> Result<(), ValidationError> {
+    if *value >= min {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("decimal_min");
+    error.message = Some(Cow::from(format!("must be at least {min}")));
+    error.add_param(Cow::from("min"), &min);
+    error.add_param(Cow::from("actual"), value);
+    Err(error)
+}
+
+/// Validates that a string is at most `N` grapheme clusters long, i.e. its length as seen by users.
+///
+/// `length(max = N)` counts characters, so an emoji made of several code points, e.g. a flag or a family,
+/// or a letter followed by combining accents, counts as more than one. Grapheme clusters are counted
+/// as defined by Unicode (extended grapheme clusters), using the `unicode-segmentation` crate.
+///
+/// Error code: `max_graphemes`, with the `max` and `actual` grapheme counts as parameters.
+#[cfg(feature = "unicode-segmentation")]
+pub fn max_graphemes<const N: usize>(value: &str) -> Result<(), ValidationError> {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let actual = value.graphemes(true).count();
+    if actual <= N {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("max_graphemes");
+    error.message = Some(Cow::from(format!("must be at most {N} characters long")));
+    error.add_param(Cow::from("max"), &N);
+    error.add_param(Cow::from("actual"), &actual);
+    Err(error)
+}
+
+/// Validates that `end` is not before `start`, and at most `max` after it, e.g. the window of an event.
+///
+/// Since it compares two fields, call it from a struct-level `schema` function of your own:
+///
+/// ```
+/// use chrono::{DateTime, Duration, Utc};
+/// use validator::{Validate, ValidationError};
+///
+/// #[derive(Validate)]
+/// #[validate(schema(function = "window"))]
+/// struct Event {
+///     start: DateTime<Utc>,
+///     end: DateTime<Utc>,
+/// }
+///
+/// fn window(event: &Event) -> Result<(), ValidationError> {
+///     axum_valid::helpers::within_duration(event.start, event.end, Duration::hours(24))
+/// }
+/// ```
+///
+/// Any `chrono` values which can be subtracted into a `Duration` can be compared,
+/// e.g. `DateTime`, `NaiveDateTime` or `NaiveDate`.
+///
+/// Error codes: `end_before_start`, or `within_duration` with the `max` and `actual` durations in seconds
+/// as parameters.
+#[cfg(feature = "datetime-helpers")]
+pub fn within_duration<T>(start: T, end: T, max: chrono::Duration) -> Result<(), ValidationError>
+where
+    T: std::ops::Sub<Output = chrono::Duration>,
+{
+    let actual = end - start;
+    if actual < chrono::Duration::zero() {
+        let mut error = ValidationError::new("end_before_start");
+        error.message = Some(Cow::from("end must not be before start"));
+        return Err(error);
+    }
+    if actual <= max {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("within_duration");
+    error.message = Some(Cow::from(format!(
+        "end must be at most {} seconds after start, got {} seconds",
+        max.num_seconds(),
+        actual.num_seconds()
+    )));
+    error.add_param(Cow::from("max"), &max.num_seconds());
+    error.add_param(Cow::from("actual"), &actual.num_seconds());
+    Err(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use validator::Validate;
+
+    #[derive(Validate)]
+    struct Profile {
+        #[validate(length(max = 4), custom(function = "max_bytes::<4>"))]
+        bio: String,
+    }
+
+    #[test]
+    fn max_bytes_counts_bytes() {
+        assert!(max_bytes::<4>("abcd").is_ok());
+        assert!(max_bytes::<4>("abcde").is_err());
+
+        let ascii = Profile {
+            bio: String::from("abcd"),
+        };
+        assert!(ascii.validate().is_ok());
+
+        // 3 characters, 9 bytes
+        let multibyte = Profile {
+            bio: String::from("日本語"),
+        };
+        let errors = multibyte.validate().expect_err("multibyte string accepted");
+        let field_errors = errors.field_errors();
+        let error = &field_errors["bio"][0];
+        assert_eq!(error.code, "max_bytes");
+        assert_eq!(error.params["actual"], 9);
+    }
+
+    #[derive(Validate, serde::Deserialize)]
+    struct Contact {
+        #[validate(custom(function = "not_empty"))]
+        nickname: Option<String>,
+    }
+
+    #[test]
+    fn not_empty_tells_empty_from_missing() {
+        let contact = |body: &str| {
+            serde_json::from_str::<Contact>(body).expect("failed to deserialize contact")
+        };
+        assert!(contact("{}").validate().is_ok());
+        assert!(contact(r#"{ "nickname": null }"#).validate().is_ok());
+        assert!(contact(r#"{ "nickname": "ax" }"#).validate().is_ok());
+
+        let errors = contact(r#"{ "nickname": "" }"#)
+            .validate()
+            .expect_err("empty nickname accepted");
+        assert_eq!(errors.field_errors()["nickname"][0].code, "empty");
+    }
+
+    #[derive(Validate, serde::Deserialize)]
+    struct Signup {
+        #[validate(required, custom(function = "not_blank"))]
+        name: Option<String>,
+        #[validate(custom(function = "not_blank"))]
+        city: String,
+    }
+
+    #[test]
+    fn not_blank_rejects_whitespace_only() {
+        let signup = |body: &str| {
+            serde_json::from_str::<Signup>(body).expect("failed to deserialize signup")
+        };
+        assert!(signup(r#"{ "name": " ax ", "city": "Paris" }"#)
+            .validate()
+            .is_ok());
+
+        let errors = signup(r#"{ "name": " \t\n", "city": "   " }"#)
+            .validate()
+            .expect_err("blank fields accepted");
+        let field_errors = errors.field_errors();
+        assert_eq!(field_errors["name"][0].code, "blank");
+        assert_eq!(field_errors["city"][0].code, "blank");
+
+        let errors = signup(r#"{ "city": "Paris" }"#)
+            .validate()
+            .expect_err("missing name accepted");
+        assert_eq!(errors.field_errors()["name"][0].code, "required");
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn email_normalized_then_validated() {
+        use crate::Valid;
+        use axum::body::Body;
+        use axum::extract::{FromRequest, Request};
+        use axum::Json;
+
+        #[derive(Validate, serde::Deserialize)]
+        struct Subscriber {
+            #[serde(deserialize_with = "deserialize_email")]
+            #[validate(email)]
+            email: String,
+        }
+
+        let mut email = String::from("  Ada.Lovelace@Example.COM\t");
+        normalize_email(&mut email);
+        assert_eq!(email, "ada.lovelace@example.com");
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+        let Valid(Json(subscriber)) = Valid::<Json<Subscriber>>::from_request(
+            request(r#"{ "email": " Ada.Lovelace@Example.COM " }"#),
+            &(),
+        )
+        .await
+        .expect("padded email rejected");
+        assert_eq!(subscriber.email, "ada.lovelace@example.com");
+
+        assert!(Valid::<Json<Subscriber>>::from_request(
+            request(r#"{ "email": " Ada Lovelace " }"#),
+            &()
+        )
+        .await
+        .is_err());
+    }
+
+    fn username(value: &str) -> Result<(), ValidationError> {
+        charset(value, Charset::Alphanumeric)
+    }
+
+    #[derive(Validate, serde::Deserialize)]
+    struct Account {
+        #[validate(custom(function = "username"))]
+        username: String,
+    }
+
+    #[test]
+    fn charset_rejects_other_characters() {
+        let account = |body: &str| {
+            serde_json::from_str::<Account>(body).expect("failed to deserialize account")
+        };
+        assert!(account(r#"{ "username": "axum2024" }"#).validate().is_ok());
+
+        let errors = account(r#"{ "username": "josé" }"#)
+            .validate()
+            .expect_err("non-ASCII username accepted");
+        let field_errors = errors.field_errors();
+        let error = &field_errors["username"][0];
+        assert_eq!(error.code, "charset");
+        assert_eq!(error.params["charset"], "alphanumeric");
+        assert_eq!(error.params["invalid"], "é");
+        assert_eq!(
+            error.message.as_deref(),
+            Some("must only contain ASCII letters and digits")
+        );
+
+        assert!(charset("hello world", Charset::AsciiPrintable).is_ok());
+        assert!(charset("tab\there", Charset::AsciiPrintable).is_err());
+        assert!(charset("tab\there", Charset::Ascii).is_ok());
+        assert!(charset("12a", Charset::Digits).is_err());
+    }
+
+    fn color(value: &str) -> Result<(), ValidationError> {
+        one_of(value, &["red", "green", "blue"])
+    }
+
+    #[derive(Validate, serde::Deserialize)]
+    struct Paint {
+        #[validate(custom(function = "color"))]
+        color: String,
+        #[validate(custom(function = "color"))]
+        trim: Option<String>,
+    }
+
+    #[test]
+    fn one_of_lists_allowed_values() {
+        let paint =
+            |body: &str| serde_json::from_str::<Paint>(body).expect("failed to deserialize paint");
+        assert!(paint(r#"{ "color": "red" }"#).validate().is_ok());
+        assert!(paint(r#"{ "color": "red", "trim": "blue" }"#)
+            .validate()
+            .is_ok());
+
+        let errors = paint(r#"{ "color": "Red", "trim": "blue" }"#)
+            .validate()
+            .expect_err("unknown color accepted");
+        let field_errors = errors.field_errors();
+        assert!(!field_errors.contains_key("trim"));
+        let error = &field_errors["color"][0];
+        assert_eq!(error.code, "one_of");
+        assert_eq!(
+            error.params["allowed"],
+            serde_json::json!(["red", "green", "blue"])
+        );
+        assert_eq!(error.params["actual"], "Red");
+        assert_eq!(
+            error.message.as_deref(),
+            Some("must be one of: red, green, blue")
+        );
+    }
+
+    #[derive(Validate)]
+    struct Handle {
+        #[validate(custom(function = "max_scripts::<1>"))]
+        username: String,
+    }
+
+    #[test]
+    fn max_scripts_rejects_mixed_scripts() {
+        for username in ["paypal", "user_42", "Ελένη", "Борис", "José-María"] {
+            let handle = Handle {
+                username: String::from(username),
+            };
+            assert!(handle.validate().is_ok(), "{username} rejected");
+        }
+
+        // Cyrillic `а` in an otherwise Latin name
+        let spoofed = Handle {
+            username: String::from("pаypal"),
+        };
+        let errors = spoofed
+            .validate()
+            .expect_err("mixed-script username accepted");
+        let error = &errors.field_errors()["username"][0];
+        assert_eq!(error.code, "mixed_scripts");
+        assert_eq!(
+            error.params["scripts"],
+            serde_json::json!(["latin", "cyrillic"])
+        );
+        assert_eq!(error.params["max"], 1);
+
+        assert!(max_scripts::<3>("東京タワーへようこそ").is_ok());
+        assert!(max_scripts::<2>("東京タワーへようこそ").is_err());
+        assert_eq!(Script::of('7'), None);
+    }
+
+    #[test]
+    fn min_entropy_rejects_repetitive_strings() {
+        fn varied(value: &str) -> Result<(), ValidationError> {
+            min_entropy(value, 2.0)
+        }
+
+        #[derive(Validate)]
+        struct Comment {
+            #[validate(custom(function = "varied"))]
+            text: String,
+        }
+
+        let comment = |text: &str| Comment {
+            text: text.to_owned(),
+        };
+        assert!(comment("").validate().is_ok());
+        assert!(comment("abcd").validate().is_ok());
+        assert!(comment("Great article, thanks!").validate().is_ok());
+        assert!(comment("abababab").validate().is_err());
+
+        let errors = comment("aaaaaaaaaaaaaaaa")
+            .validate()
+            .expect_err("repeated characters accepted");
+        let field_errors = errors.field_errors();
+        let error = &field_errors["text"][0];
+        assert_eq!(error.code, "low_entropy");
+        assert_eq!(error.params["min"], 2.0);
+        assert_eq!(error.params["actual"], 0.0);
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn decimal_range_is_exact() {
+        use rust_decimal::Decimal;
+
+        fn amount(value: &Decimal) -> Result<(), ValidationError> {
+            decimal_min(value, Decimal::new(1, 2))?;
+            decimal_max(value, Decimal::new(1_000_000, 2))
+        }
+
+        #[derive(Validate, serde::Deserialize)]
+        struct Payment {
+            #[validate(custom(function = "amount"))]
+            amount: Decimal,
+        }
+
+        let payment = |body: &str| {
+            serde_json::from_str::<Payment>(body).expect("failed to deserialize payment")
+        };
+        assert!(payment(r#"{ "amount": "10000.00" }"#).validate().is_ok());
+        assert!(payment(r#"{ "amount": "0.01" }"#).validate().is_ok());
+
+        let errors = payment(r#"{ "amount": "10000.001" }"#)
+            .validate()
+            .expect_err("amount above maximum accepted");
+        let field_errors = errors.field_errors();
+        let error = &field_errors["amount"][0];
+        assert_eq!(error.code, "decimal_max");
+        assert_eq!(error.params["max"], "10000.00");
+        assert_eq!(error.params["actual"], "10000.001");
+        assert_eq!(error.message.as_deref(), Some("must be at most 10000.00"));
+
+        let errors = payment(r#"{ "amount": "0.009" }"#)
+            .validate()
+            .expect_err("amount below minimum accepted");
+        assert_eq!(errors.field_errors()["amount"][0].code, "decimal_min");
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn max_graphemes_counts_visible_length() {
+        #[derive(Validate, serde::Deserialize)]
+        struct Nickname {
+            #[validate(custom(function = "max_graphemes::<3>"))]
+            name: String,
+        }
+
+        let nickname = |name: &str| Nickname {
+            name: name.to_owned(),
+        };
+        // Three letters, each followed by several combining accents.
+        let accented = "a\u{301}\u{302}\u{303}b\u{301}\u{302}c\u{301}";
+        assert_eq!(accented.chars().count(), 9);
+        assert!(nickname(accented).validate().is_ok());
+        // A family emoji is a single grapheme made of seven code points.
+        assert!(
+            nickname("\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{200D}\u{1F466}ab")
+                .validate()
+                .is_ok()
+        );
+
+        let errors = nickname(&format!("{accented}d"))
+            .validate()
+            .expect_err("long nickname accepted");
+        let field_errors = errors.field_errors();
+        let error = &field_errors["name"][0];
+        assert_eq!(error.code, "max_graphemes");
+        assert_eq!(error.params["max"], 3);
+        assert_eq!(error.params["actual"], 4);
+    }
+
+    #[cfg(feature = "datetime-helpers")]
+    #[test]
+    fn within_duration_bounds_window() {
+        use chrono::{DateTime, Duration, Utc};
+
+        #[derive(Validate, serde::Deserialize)]
+        #[validate(schema(function = "window"))]
+        struct Event {
+            start: DateTime<Utc>,
+            end: DateTime<Utc>,
+        }
+
+        fn window(event: &Event) -> Result<(), ValidationError> {
+            within_duration(event.start, event.end, Duration::hours(24))
+        }
+
+        let event =
+            |body: &str| serde_json::from_str::<Event>(body).expect("failed to deserialize event");
+        assert!(
+            event(r#"{ "start": "2024-01-01T00:00:00Z", "end": "2024-01-02T00:00:00Z" }"#)
+                .validate()
+                .is_ok()
+        );
+
+        let errors = event(r#"{ "start": "2024-01-01T00:00:00Z", "end": "2024-01-02T00:00:01Z" }"#)
+            .validate()
+            .expect_err("long event accepted");
+        let error = &errors.field_errors()["__all__"][0];
+        assert_eq!(error.code, "within_duration");
+        assert_eq!(error.params["max"], 86400);
+        assert_eq!(error.params["actual"], 86401);
+
+        let errors = event(r#"{ "start": "2024-01-02T00:00:00Z", "end": "2024-01-01T00:00:00Z" }"#)
+            .validate()
+            .expect_err("reversed event accepted");
+        assert_eq!(errors.field_errors()["__all__"][0].code, "end_before_start");
+    }
+
+    #[derive(Validate)]
+    #[validate(context = ForbiddenWords)]
+    struct Comment {
+        #[validate(custom(function = "no_forbidden_words", use_context))]
+        title: String,
+        #[validate(custom(function = "no_forbidden_words", use_context))]
+        body: String,
+    }
+
+    #[test]
+    fn no_forbidden_words_reports_field() {
+        use validator::ValidateArgs;
+
+        let words = ForbiddenWords::new(["Spam", "scam"]);
+        assert_eq!(words.find("no SPAM, please"), Some("SPAM"));
+        assert_eq!(words.find("spammer"), None);
+        assert_eq!(words.mask("spam or scam?"), "**** or ****?");
+
+        let words_utf8 = ForbiddenWords::new(["Übel"]);
+        assert_eq!(words_utf8.find("ganz übel"), Some("übel"));
+        assert_eq!(words_utf8.mask("übel übel"), "**** ****");
+        assert_eq!(words_utf8.mask("sehr Übel, übler"), "sehr ****, übler");
+
+        let clean = Comment {
+            title: String::from("hello"),
+            body: String::from("a spammer wrote this"),
+        };
+        assert!(clean.validate_with_args(&words).is_ok());
+
+        let dirty = Comment {
+            title: String::from("hello"),
+            body: String::from("this is a scam!"),
+        };
+        let errors = dirty
+            .validate_with_args(&words)
+            .expect_err("forbidden word accepted");
+        let field_errors = errors.field_errors();
+        assert!(!field_errors.contains_key("title"));
+        assert_eq!(field_errors["body"][0].code, "forbidden_word");
+        assert_eq!(field_errors["body"][0].params["word"], "scam");
+    }
+
+    struct Line {
+        amount: u64,
+    }
+
+    #[derive(Validate)]
+    #[validate(schema(function = "total_of_lines"))]
+    struct Invoice {
+        total: u64,
+        lines: Vec<Line>,
+    }
+
+    fn total_of_lines(invoice: &Invoice) -> Result<(), ValidationError> {
+        total_matches(invoice.total, invoice.lines.iter().map(|line| line.amount))
+    }
+
+    #[test]
+    fn total_matches_names_mismatch() {
+        let invoice = |total| Invoice {
+            total,
+            lines: vec![Line { amount: 250 }, Line { amount: 750 }],
+        };
+        assert!(invoice(1000).validate().is_ok());
+
+        let errors = invoice(900).validate().expect_err("wrong total accepted");
+        let field_errors = errors.field_errors();
+        let error = &field_errors["__all__"][0];
+        assert_eq!(error.code, "total_mismatch");
+        assert_eq!(error.params["declared"], 900);
+        assert_eq!(error.params["actual"], 1000);
+        assert_eq!(
+            error.message.as_deref(),
+            Some("declared total 900 does not match the sum of its parts 1000")
+        );
+    }
+
+    #[derive(Validate, Serialize, serde::Deserialize)]
+    #[validate(schema(function = "address"))]
+    struct Order {
+        street: Option<String>,
+        city: Option<String>,
+        zip: Option<String>,
+    }
+
+    fn address(order: &Order) -> Result<(), ValidationError> {
+        all_or_none(order, &["street", "city", "zip"])
+    }
+
+    #[test]
+    fn all_or_none_rejects_partial_groups() {
+        let order =
+            |body: &str| serde_json::from_str::<Order>(body).expect("failed to deserialize order");
+        assert!(order("{}").validate().is_ok());
+        assert!(
+            order(r#"{ "street": "1 Main St", "city": "Springfield", "zip": "12345" }"#)
+                .validate()
+                .is_ok()
+        );
+
+        let errors = order(r#"{ "street": "1 Main St", "city": null }"#)
+            .validate()
+            .expect_err("partial address accepted");
+        let error = &errors.field_errors()["__all__"][0];
+        assert_eq!(error.code, "all_or_none");
+        assert_eq!(
+            error.message.as_deref(),
+            Some("street, city, zip must be given together, missing: city, zip")
+        );
+        assert_eq!(error.params["missing"], serde_json::json!(["city", "zip"]));
+    }
+
+    #[derive(Validate, Serialize, serde::Deserialize)]
+    #[validate(schema(function = "contact"))]
+    struct Customer {
+        email: Option<String>,
+        phone: Option<String>,
+        address: Option<String>,
+    }
+
+    fn contact(customer: &Customer) -> Result<(), ValidationError> {
+        at_least_n_of(customer, &["email", "phone", "address"], 2)
+    }
+
+    #[test]
+    fn at_least_n_of_counts_present_fields() {
+        let customer = |body: &str| {
+            serde_json::from_str::<Customer>(body).expect("failed to deserialize customer")
+        };
+        assert!(customer(r#"{ "email": "a@b.c", "phone": "555" }"#)
+            .validate()
+            .is_ok());
+
+        let errors = customer(r#"{ "email": "a@b.c", "phone": null }"#)
+            .validate()
+            .expect_err("single contact accepted");
+        let error = &errors.field_errors()["__all__"][0];
+        assert_eq!(error.code, "at_least_n_of");
+        assert_eq!(
+            error.message.as_deref(),
+            Some("at least 2 of email, phone, address must be given, got 1")
+        );
+        assert_eq!(error.params["min"], 2);
+        assert_eq!(error.params["present"], serde_json::json!(["email"]));
+    }
+
+    #[derive(Validate, Serialize, serde::Deserialize)]
+    #[validate(schema(function = "payment"))]
+    struct Checkout {
+        card_token: Option<String>,
+        voucher: Option<String>,
+        iban: Option<String>,
+    }
+
+    fn payment(checkout: &Checkout) -> Result<(), ValidationError> {
+        at_most_one_of(checkout, &["card_token", "voucher", "iban"])
+    }
+
+    #[test]
+    fn at_most_one_of_rejects_several_fields() {
+        let checkout = |body: &str| {
+            serde_json::from_str::<Checkout>(body).expect("failed to deserialize checkout")
+        };
+        assert!(checkout("{}").validate().is_ok());
+        assert!(checkout(r#"{ "voucher": "SPRING", "iban": null }"#)
+            .validate()
+            .is_ok());
+
+        let errors = checkout(r#"{ "card_token": "tok_1", "iban": "DE89" }"#)
+            .validate()
+            .expect_err("exclusive fields accepted together");
+        let error = &errors.field_errors()["__all__"][0];
+        assert_eq!(error.code, "at_most_one_of");
+        assert_eq!(
+            error.message.as_deref(),
+            Some("at most one of card_token, voucher, iban may be given, got card_token, iban")
+        );
+        assert_eq!(
+            error.params["present"],
+            serde_json::json!(["card_token", "iban"])
+        );
+    }
+
+    fn time_ordered(events: &[(u64, &str)]) -> Result<(), ValidationError> {
+        sorted_by(events, |(at, _)| *at)
+    }
+
+    #[derive(Validate)]
+    struct Timeline {
+        #[validate(custom(function = "time_ordered"))]
+        events: Vec<(u64, &'static str)>,
+    }
+
+    #[test]
+    fn sorted_by_reports_index() {
+        assert!(sorted_by(&[] as &[u64], |v| *v).is_ok());
+        assert!(sorted_by(&[1.0, 1.0, 2.5], |v| *v).is_ok());
+        assert!(sorted_by(&[1.0, f64::NAN], |v| *v).is_err());
+
+        let sorted = Timeline {
+            events: vec![(1, "created"), (1, "queued"), (5, "done")],
+        };
+        assert!(sorted.validate().is_ok());
+
+        let unsorted = Timeline {
+            events: vec![(1, "created"), (5, "done"), (3, "queued"), (2, "retried")],
+        };
+        let errors = unsorted.validate().expect_err("unsorted events accepted");
+        let field_errors = errors.field_errors();
+        let error = &field_errors["events"][0];
+        assert_eq!(error.code, "sorted");
+        assert_eq!(error.params["index"], 2);
+    }
+}
@@ -157,4 +157,47 @@ mod tests {
             builder.json(T::invalid())
         }
     }
+
+    #[cfg(feature = "validify")]
+    #[tokio::test]
+    async fn missing_fields_are_validation_errors() {
+        use crate::{ValidationRejection, Validified};
+        use axum::body::Body;
+        use axum::extract::{FromRequest, Request};
+        use validify::{Payload, Validify};
+
+        #[derive(Debug, Clone, serde::Deserialize, Validify, Payload)]
+        struct User {
+            #[modify(trim)]
+            #[validate(length(min = 1))]
+            name: String,
+            age: u8,
+        }
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+
+        let Validified(Json(user)) =
+            Validified::<Json<User>>::from_request(request(r#"{"name": " axum ", "age": 7}"#), &())
+                .await
+                .expect("complete body rejected");
+        assert_eq!(user.name, "axum");
+        assert_eq!(user.age, 7);
+
+        let rejection = Validified::<Json<User>>::from_request(request(r#"{"name": "axum"}"#), &())
+            .await
+            .expect_err("missing field accepted");
+        let ValidationRejection::Valid(errors) = rejection else {
+            panic!("missing field should be a validation error");
+        };
+        assert!(errors
+            .field_errors()
+            .iter()
+            .any(|error| error.field_name() == Some("age") && error.code() == "required"));
+    }
 }
@@ -0,0 +1,558 @@
+//! # JSON:API error documents
+//!
+//! ## Feature
+//!
+//! Enable the `json_api` feature to use `JsonApi<E>`.
+//!
+//! ## Usage
+//!
+//! APIs following [JSON:API](https://jsonapi.org/format/#errors) report errors in a top-level `errors` array,
+//! each error pointing at the offending member of the request document with `source.pointer`.
+//! `JsonApi<E>` wraps a validation extractor, e.g. `JsonApi<Valid<Json<T>>>`, and renders its validation
+//! errors as such a document:
+//!
+//! ```json
+//! {
+//!   "errors": [
+//!     {
+//!       "status": "400",
+//!       "code": "length",
+//!       "detail": "too long",
+//!       "source": { "pointer": "/data/attributes/title" }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! The document is sent with the `application/vnd.api+json` content type. `detail` is the message
+//! of the rule, and is omitted if the rule has none. Errors are sorted by pointer and code.
+//!
+//! `JsonApi<E>` reads its `JsonApiConfig` from the router state, so it must implement `FromRef<YourState>`.
+//! `JsonApiConfig::default()` prefixes pointers with `/data/attributes` and uses `VALIDATION_ERROR_STATUS`.
+//! Use `JsonApiConfig::with_pointer_prefix` for attributes not sent in a resource object,
+//! and `JsonApiConfig::with_status` to pick another status.
+//!
+//! Errors of `validator`, `garde` and `validify` can be rendered, depending on the enabled features.
+//! Other failures of the inner extractor, e.g. a malformed body, are rendered as usual.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::json_api::{JsonApi, JsonApiConfig};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Article {
+//!         #[validate(length(min = 1, max = 64))]
+//!         pub title: String,
+//!     }
+//!
+//!     pub async fn handler(JsonApi(Valid(Json(article))): JsonApi<Valid<Json<Article>>>) {
+//!         assert!(!article.title.is_empty());
+//!     }
+//!
+//!     pub fn router() -> Router {
+//!         Router::new()
+//!             .route("/articles", post(handler))
+//!             .with_state(JsonApiConfig::default())
+//!     }
+//! }
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! #     #[cfg(feature = "validator")]
+//! #     let router = validator_example::router();
+//! #     #[cfg(not(feature = "validator"))]
+//! #     let router = axum::Router::new();
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::{ValidationRejection, VALIDATION_ERROR_STATUS};
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, FromRequestParts, Request};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::request::Parts;
+use axum::http::{HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// The JSON:API media type.
+pub const JSON_API_CONTENT_TYPE: &str = "application/vnd.api+json";
+
+/// How `JsonApi` renders validation errors.
+#[derive(Debug, Clone)]
+pub struct JsonApiConfig {
+    pointer_prefix: Cow<'static, str>,
+    status: StatusCode,
+}
+
+impl Default for JsonApiConfig {
+    fn default() -> Self {
+        Self {
+            pointer_prefix: Cow::Borrowed("/data/attributes"),
+            status: VALIDATION_ERROR_STATUS,
+        }
+    }
+}
+
+impl JsonApiConfig {
+    /// Prefix of every `source.pointer`, e.g. `/data/attributes`. An empty prefix points at the document root.
+    pub fn with_pointer_prefix(mut self, prefix: impl Into<Cow<'static, str>>) -> Self {
+        self.pointer_prefix = prefix.into();
+        self
+    }
+
+    /// Status of the response, also reported in every error.
+    pub fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Appends a path, given as its segments, to the pointer prefix, escaping each segment.
+    pub fn pointer<'a>(&self, segments: impl IntoIterator<Item = &'a str>) -> String {
+        let mut pointer = self.pointer_prefix.trim_end_matches('/').to_owned();
+        for segment in segments {
+            pointer.push('/');
+            pointer.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+        }
+        pointer
+    }
+}
+
+/// Points at the member of the request document an error originates from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JsonApiSource {
+    /// A JSON pointer, e.g. `/data/attributes/title`.
+    pub pointer: String,
+}
+
+/// A JSON:API error object.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JsonApiError {
+    /// The HTTP status code, as a string.
+    pub status: String,
+    /// Code of the rule.
+    pub code: String,
+    /// Message of the rule, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// The offending member of the request document.
+    pub source: JsonApiSource,
+}
+
+impl Display for JsonApiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.detail {
+            Some(detail) => write!(f, "{}: {detail}", self.source.pointer),
+            None => write!(f, "{}: {}", self.source.pointer, self.code),
+        }
+    }
+}
+
+/// A JSON:API document holding validation errors.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JsonApiDocument {
+    /// The errors, sorted by pointer and code.
+    pub errors: Vec<JsonApiError>,
+    #[serde(skip)]
+    status: StatusCode,
+}
+
+impl JsonApiDocument {
+    /// Renders validation errors according to `config`.
+    pub fn new<V: JsonApiErrors + ?Sized>(errors: &V, config: &JsonApiConfig) -> Self {
+        let mut found = Vec::new();
+        errors.collect(config, &mut |pointer, code, detail| {
+            found.push(JsonApiError {
+                status: config.status.as_u16().to_string(),
+                code,
+                detail,
+                source: JsonApiSource { pointer },
+            })
+        });
+        found.sort_by(|a, b| (&a.source.pointer, &a.code).cmp(&(&b.source.pointer, &b.code)));
+        Self {
+            errors: found,
+            status: config.status,
+        }
+    }
+
+    /// Status of the response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+}
+
+impl Display for JsonApiDocument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .errors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Error for JsonApiDocument {}
+
+impl IntoResponse for JsonApiDocument {
+    fn into_response(self) -> Response {
+        let mut response = (self.status, Json(self)).into_response();
+        response.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static(JSON_API_CONTENT_TYPE),
+        );
+        response
+    }
+}
+
+/// Trait for validation errors which can be rendered as JSON:API errors.
+pub trait JsonApiErrors {
+    /// Calls `report` with the pointer, code and message of each error.
+    fn collect(
+        &self,
+        config: &JsonApiConfig,
+        report: &mut dyn FnMut(String, String, Option<String>),
+    );
+}
+
+#[cfg(feature = "validator")]
+impl JsonApiErrors for validator::ValidationErrors {
+    fn collect(
+        &self,
+        config: &JsonApiConfig,
+        report: &mut dyn FnMut(String, String, Option<String>),
+    ) {
+        crate::flatten_errors(self, &mut |path, errors| {
+            // Struct-level errors concern the object holding the fields.
+            let segments = path
+                .iter()
+                .filter(|segment| **segment != crate::ErrorPathSegment::Field("__all__"))
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            for error in errors {
+                report(
+                    config.pointer(segments.iter().map(String::as_str)),
+                    error.code.to_string(),
+                    error.message.as_ref().map(ToString::to_string),
+                );
+            }
+        });
+    }
+}
+
+#[cfg(feature = "garde")]
+impl JsonApiErrors for garde::Report {
+    fn collect(
+        &self,
+        config: &JsonApiConfig,
+        report: &mut dyn FnMut(String, String, Option<String>),
+    ) {
+        // garde errors have no code, only a message.
+        for (path, error) in self.iter() {
+            let path = path.to_string();
+            let segments = path
+                .split(['.', '['])
+                .map(|segment| segment.trim_end_matches(']'))
+                .filter(|segment| !segment.is_empty());
+            report(
+                config.pointer(segments),
+                String::from("invalid"),
+                Some(error.message().to_owned()),
+            );
+        }
+    }
+}
+
+#[cfg(feature = "garde")]
+impl JsonApiErrors for crate::GardeReport {
+    fn collect(
+        &self,
+        config: &JsonApiConfig,
+        report: &mut dyn FnMut(String, String, Option<String>),
+    ) {
+        self.0.collect(config, report)
+    }
+}
+
+#[cfg(feature = "validify")]
+impl JsonApiErrors for validify::ValidationErrors {
+    fn collect(
+        &self,
+        config: &JsonApiConfig,
+        report: &mut dyn FnMut(String, String, Option<String>),
+    ) {
+        // validify locations are already pointers, relative to the validated data.
+        for error in self.errors() {
+            report(
+                config.pointer(error.location().split('/').filter(|s| !s.is_empty())),
+                error.code(),
+                error.message(),
+            );
+        }
+    }
+}
+
+/// # `JsonApi` data extractor
+///
+/// `JsonApi` runs the inner validation extractor, rendering its validation errors as a JSON:API document.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonApi<E>(pub E);
+
+impl<E> Deref for JsonApi<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for JsonApi<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for JsonApi<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> JsonApi<E> {
+    /// Consumes the `JsonApi` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for JsonApi<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `JsonApiRejection` is returned when the `JsonApi` extractor fails.
+///
+#[derive(Debug)]
+pub enum JsonApiRejection<E> {
+    /// `Valid` variant captures the validation errors, rendered as a JSON:API document.
+    Valid(JsonApiDocument),
+    /// `Inner` variant represents the other errors that might occur within the inner extractor.
+    Inner(E),
+}
+
+impl<E: Display> Display for JsonApiRejection<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonApiRejection::Valid(document) => write!(f, "{document}"),
+            JsonApiRejection::Inner(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for JsonApiRejection<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            JsonApiRejection::Valid(document) => Some(document),
+            JsonApiRejection::Inner(error) => Some(error),
+        }
+    }
+}
+
+impl<E: IntoResponse> IntoResponse for JsonApiRejection<E> {
+    fn into_response(self) -> Response {
+        match self {
+            JsonApiRejection::Valid(document) => document.into_response(),
+            JsonApiRejection::Inner(error) => error.into_response(),
+        }
+    }
+}
+
+impl<E> JsonApiRejection<E> {
+    fn from_rejection<V: JsonApiErrors>(
+        rejection: ValidationRejection<V, E>,
+        config: &JsonApiConfig,
+    ) -> Self {
+        match rejection {
+            ValidationRejection::Valid(errors) => {
+                JsonApiRejection::Valid(JsonApiDocument::new(&errors, config))
+            }
+            ValidationRejection::Inner(error) => JsonApiRejection::Inner(error),
+        }
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, V, E> FromRequest<State> for JsonApi<Extractor>
+where
+    State: Send + Sync,
+    JsonApiConfig: FromRef<State>,
+    Extractor: FromRequest<State, Rejection = ValidationRejection<V, E>>,
+    V: JsonApiErrors,
+    E: IntoResponse,
+{
+    type Rejection = JsonApiRejection<E>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        Extractor::from_request(req, state)
+            .await
+            .map(JsonApi)
+            .map_err(|rejection| {
+                JsonApiRejection::from_rejection(rejection, &JsonApiConfig::from_ref(state))
+            })
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, V, E> FromRequestParts<State> for JsonApi<Extractor>
+where
+    State: Send + Sync,
+    JsonApiConfig: FromRef<State>,
+    Extractor: FromRequestParts<State, Rejection = ValidationRejection<V, E>>,
+    V: JsonApiErrors,
+    E: IntoResponse,
+{
+    type Rejection = JsonApiRejection<E>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        Extractor::from_request_parts(parts, state)
+            .await
+            .map(JsonApi)
+            .map_err(|rejection| {
+                JsonApiRejection::from_rejection(rejection, &JsonApiConfig::from_ref(state))
+            })
+    }
+}
+
+#[cfg(all(test, feature = "validator"))]
+mod tests {
+    use super::*;
+    use crate::Valid;
+    use axum::body::Body;
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+    use validator::Validate;
+
+    #[derive(Debug, Validate, Deserialize)]
+    struct Author {
+        #[validate(length(min = 1, message = "must not be empty"))]
+        name: String,
+    }
+
+    #[derive(Debug, Validate, Deserialize)]
+    struct Article {
+        #[validate(length(max = 8, message = "too long"))]
+        title: String,
+        #[validate(nested)]
+        authors: Vec<Author>,
+        #[validate(range(min = 0))]
+        rating: i32,
+    }
+
+    fn request(body: &str) -> Request {
+        Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_owned()))
+            .expect("invalid request")
+    }
+
+    async fn render(config: JsonApiConfig, body: &str) -> (StatusCode, String, Value) {
+        let rejection = JsonApi::<Valid<Json<Article>>>::from_request(request(body), &config)
+            .await
+            .expect_err("invalid article accepted");
+        let response = rejection.into_response();
+        let status = response.status();
+        let content_type = response.headers()[CONTENT_TYPE]
+            .to_str()
+            .expect("invalid content type")
+            .to_owned();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("failed to read body");
+        let document = serde_json::from_slice(&body).expect("invalid document");
+        (status, content_type, document)
+    }
+
+    #[tokio::test]
+    async fn json_api_document() {
+        let invalid = r#"{ "title": "too long a title", "authors": [{ "name": "Ada" }, { "name": "" }], "rating": -1 }"#;
+        let status = VALIDATION_ERROR_STATUS.as_u16().to_string();
+
+        let (code, content_type, document) = render(JsonApiConfig::default(), invalid).await;
+        assert_eq!(code, VALIDATION_ERROR_STATUS);
+        assert_eq!(content_type, JSON_API_CONTENT_TYPE);
+        assert_eq!(
+            document,
+            json!({
+                "errors": [
+                    {
+                        "status": status,
+                        "code": "length",
+                        "detail": "must not be empty",
+                        "source": { "pointer": "/data/attributes/authors/1/name" }
+                    },
+                    {
+                        "status": status,
+                        "code": "range",
+                        "source": { "pointer": "/data/attributes/rating" }
+                    },
+                    {
+                        "status": status,
+                        "code": "length",
+                        "detail": "too long",
+                        "source": { "pointer": "/data/attributes/title" }
+                    }
+                ]
+            })
+        );
+
+        let config = JsonApiConfig::default()
+            .with_pointer_prefix("")
+            .with_status(StatusCode::CONFLICT);
+        let (code, _, document) = render(config, invalid).await;
+        assert_eq!(code, StatusCode::CONFLICT);
+        assert_eq!(document["errors"][0]["status"], "409");
+        assert_eq!(
+            document["errors"][0]["source"]["pointer"],
+            "/authors/1/name"
+        );
+
+        let valid = r#"{ "title": "short", "authors": [], "rating": 1 }"#;
+        assert!(JsonApi::<Valid<Json<Article>>>::from_request(
+            request(valid),
+            &JsonApiConfig::default()
+        )
+        .await
+        .is_ok());
+        assert!(matches!(
+            JsonApi::<Valid<Json<Article>>>::from_request(request("{"), &JsonApiConfig::default())
+                .await,
+            Err(JsonApiRejection::Inner(_))
+        ));
+    }
+}
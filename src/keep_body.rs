@@ -0,0 +1,273 @@
+//! # Keeping the body of rejected requests
+//!
+//! ## Feature
+//!
+//! Enable the `keep_body` feature to use `KeepBody<E>`.
+//!
+//! ## Usage
+//!
+//! Validation rejections do not retain the request body, since it is consumed while extracting the data.
+//! To log the payload which caused a rejection, wrap your validation extractor with `KeepBody`,
+//! e.g. `KeepBody<Valid<Json<T>>>`:
+//!
+//! * The body is buffered before the inner extractor runs, and kept only if it rejects the request.
+//! * The rejection holds the body alongside the inner rejection, see `KeepBodyRejection::body`.
+//! * The response of the rejection carries the body as a `RejectedBody` extension,
+//!   so middleware can inspect it, e.g. with `axum::middleware::map_response`.
+//!
+//! The body is buffered once for the inner extractor, and shares its memory; the rejection only keeps it alive
+//! until the response is dropped. Nothing is buffered for extractors which are not wrapped, and nothing at all
+//! without the `keep_body` feature.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::middleware::map_response;
+//!     use axum::response::Response;
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::keep_body::{KeepBody, RejectedBody};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new()
+//!             .route("/json", post(handler))
+//!             .layer(map_response(log_rejected_body))
+//!     }
+//!
+//!     async fn log_rejected_body(response: Response) -> Response {
+//!         if let Some(RejectedBody(body)) = response.extensions().get::<RejectedBody>() {
+//!             eprintln!("rejected {}: {}", response.status(), String::from_utf8_lossy(body));
+//!         }
+//!         response
+//!     }
+//!
+//!     async fn handler(KeepBody(Valid(Json(parameter))): KeepBody<Valid<Json<Parameter>>>) {
+//!         assert!(parameter.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Parameter {
+//!         #[validate(range(min = 5, max = 10))]
+//!         pub v0: i32,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use axum::async_trait;
+use axum::body::Bytes;
+use axum::extract::rejection::BytesRejection;
+use axum::extract::{FromRequest, Request};
+use axum::response::{IntoResponse, Response};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// The body of a rejected request, carried by the response of a `KeepBodyRejection` as an extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedBody(pub Bytes);
+
+/// # `KeepBody` data extractor
+///
+/// `KeepBody` buffers the request body and delegates to the inner extractor,
+/// keeping the body in the rejection if the inner extractor fails.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeepBody<E>(pub E);
+
+impl<E> Deref for KeepBody<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for KeepBody<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for KeepBody<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> KeepBody<E> {
+    /// Consumes the `KeepBody` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for KeepBody<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `KeepBodyRejection` is returned when the `KeepBody` extractor fails.
+///
+#[derive(Debug)]
+pub enum KeepBodyRejection<E> {
+    /// `Body` variant represents errors that occur while buffering the request body.
+    Body(BytesRejection),
+    /// `Inner` variant holds the rejection of the inner extractor, along with the request body.
+    Inner {
+        /// The rejection of the inner extractor.
+        rejection: E,
+        /// The body of the rejected request.
+        body: Bytes,
+    },
+}
+
+impl<E> KeepBodyRejection<E> {
+    /// Returns the body of the rejected request, if it could be buffered.
+    pub fn body(&self) -> Option<&Bytes> {
+        match self {
+            KeepBodyRejection::Body(_) => None,
+            KeepBodyRejection::Inner { body, .. } => Some(body),
+        }
+    }
+}
+
+impl<E: Display> Display for KeepBodyRejection<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeepBodyRejection::Body(error) => write!(f, "{error}"),
+            KeepBodyRejection::Inner { rejection, .. } => write!(f, "{rejection}"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for KeepBodyRejection<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            KeepBodyRejection::Body(error) => Some(error),
+            KeepBodyRejection::Inner { rejection, .. } => Some(rejection),
+        }
+    }
+}
+
+impl<E: IntoResponse> IntoResponse for KeepBodyRejection<E> {
+    fn into_response(self) -> Response {
+        match self {
+            KeepBodyRejection::Body(error) => error.into_response(),
+            KeepBodyRejection::Inner { rejection, body } => {
+                let mut response = rejection.into_response();
+                response.extensions_mut().insert(RejectedBody(body));
+                response
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for KeepBody<Extractor>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = KeepBodyRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let (req, body) = crate::body::buffer(req, state)
+            .await
+            .map_err(KeepBodyRejection::Body)?;
+        Extractor::from_request(req, state)
+            .await
+            .map(KeepBody)
+            .map_err(|rejection| KeepBodyRejection::Inner { rejection, body })
+    }
+}
+
+#[cfg(all(test, feature = "validator", feature = "json"))]
+mod tests {
+    use super::*;
+    use crate::{Valid, ValidRejection};
+    use axum::body::Body;
+    use axum::Json;
+    use validator::Validate;
+
+    #[derive(Debug, Validate, serde::Deserialize)]
+    struct Parameter {
+        #[validate(range(min = 5, max = 10))]
+        v0: i32,
+    }
+
+    fn request(body: &'static str) -> Request {
+        Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .expect("invalid request")
+    }
+
+    #[tokio::test]
+    async fn keep_body_of_rejected_requests() {
+        let KeepBody(Valid(Json(parameter))) =
+            KeepBody::<Valid<Json<Parameter>>>::from_request(request(r#"{ "v0": 7 }"#), &())
+                .await
+                .expect("valid parameter rejected");
+        assert_eq!(parameter.v0, 7);
+
+        let payload = r#"{ "v0": 1, "note": "out of range" }"#;
+        let rejection = KeepBody::<Valid<Json<Parameter>>>::from_request(request(payload), &())
+            .await
+            .expect_err("invalid parameter accepted");
+        assert!(matches!(
+            &rejection,
+            KeepBodyRejection::Inner {
+                rejection: ValidRejection::Valid(_),
+                ..
+            }
+        ));
+        assert_eq!(
+            rejection.body().map(|body| &body[..]),
+            Some(payload.as_bytes())
+        );
+
+        let response = rejection.into_response();
+        assert_eq!(response.status(), crate::VALIDATION_ERROR_STATUS);
+        assert_eq!(
+            response.extensions().get::<RejectedBody>(),
+            Some(&RejectedBody(Bytes::from_static(payload.as_bytes())))
+        );
+
+        let rejection = KeepBody::<Valid<Json<Parameter>>>::from_request(request("{"), &())
+            .await
+            .expect_err("malformed parameter accepted");
+        assert!(matches!(
+            &rejection,
+            KeepBodyRejection::Inner {
+                rejection: ValidRejection::Inner(_),
+                body,
+            } if body == "{"
+        ));
+    }
+}
@@ -0,0 +1,345 @@
+//! # Case convention of error keys
+//!
+//! ## Feature
+//!
+//! Enable the `key_case` feature to use `KeyCased<E>`.
+//!
+//! ## Usage
+//!
+//! Validation errors are keyed by the names of the Rust fields, which are usually in snake_case,
+//! while clients may expect camelCase, or the other way round. `KeyCased<E>` wraps a validation extractor,
+//! e.g. `KeyCased<Valid<Json<T>>>`, and renders the field keys of its errors in a single case convention:
+//!
+//! 1. Make a `KeyCase` available from your router state
+//!    (implement `FromRef<YourState>` for it, or use it as the state directly).
+//! 2. In your handler function, wrap your validation extractor with `KeyCased`.
+//!
+//! Field keys are renamed at any depth, including the segments of paths such as `line_items[2].unit_price`,
+//! while the keys of the errors themselves (`code`, `message`, `params`) are left untouched, as is `__all__`.
+//! Errors rendered as a map from field to errors, such as `validator`'s or the pre-checks', are supported.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::key_case::{KeyCase, KeyCased};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new()
+//!             .route("/json", post(handler))
+//!             .with_state(KeyCase::Camel)
+//!     }
+//!
+//!     async fn handler(KeyCased(Valid(Json(user))): KeyCased<Valid<Json<User>>>) {
+//!         assert!(!user.first_name.is_empty());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     #[serde(rename_all = "camelCase")]
+//!     pub struct User {
+//!         // Reported as `firstName`, like in the request.
+//!         #[validate(length(min = 1, max = 32))]
+//!         pub first_name: String,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::ValidationRejection;
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use serde::Serialize;
+use serde_json::Value;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// Case convention of the field keys rendered by `KeyCased`.
+///
+/// `KeyCased` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCase {
+    /// `first_name`
+    Snake,
+    /// `firstName`
+    Camel,
+}
+
+impl KeyCase {
+    /// Converts a field key, or each segment of a field path, to this case convention.
+    pub fn convert(&self, key: &str) -> String {
+        let mut converted = String::with_capacity(key.len());
+        let mut rest = key;
+        while !rest.is_empty() {
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            let (word, tail) = rest.split_at(end);
+            // Keys such as `__all__` are not field names.
+            if word.starts_with('_') {
+                converted.push_str(word);
+            } else {
+                match self {
+                    KeyCase::Snake => to_snake(word, &mut converted),
+                    KeyCase::Camel => to_camel(word, &mut converted),
+                }
+            }
+            let separator = tail.chars().next().map_or(0, char::len_utf8);
+            converted.push_str(&tail[..separator]);
+            rest = &tail[separator..];
+        }
+        converted
+    }
+
+    /// Renames the field keys of serialized errors, leaving the error objects untouched.
+    fn rename(&self, value: Value) -> Value {
+        match value {
+            Value::Object(fields) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, value)| (self.convert(&key), self.rename(value)))
+                    .collect(),
+            ),
+            value => value,
+        }
+    }
+}
+
+fn to_snake(word: &str, out: &mut String) {
+    let chars = word.chars().collect::<Vec<_>>();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_is_lower)
+            {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_lowercase());
+    }
+}
+
+fn to_camel(word: &str, out: &mut String) {
+    let mut upper = false;
+    for c in word.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+}
+
+/// Validation errors whose field keys have been renamed by `KeyCased`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct RenamedErrors(pub Value);
+
+impl Display for RenamedErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for RenamedErrors {}
+
+/// # `KeyCased` data extractor
+///
+/// `KeyCased` runs the inner validation extractor, renaming the field keys of its validation errors
+/// according to the `KeyCase` taken from the state.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyCased<E>(pub E);
+
+impl<E> Deref for KeyCased<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for KeyCased<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for KeyCased<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> KeyCased<E> {
+    /// Consumes the `KeyCased` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for KeyCased<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `KeyCasedRejection` is returned when the `KeyCased` extractor fails.
+///
+pub type KeyCasedRejection<E> = ValidationRejection<RenamedErrors, E>;
+
+fn rename<V: Serialize, E>(
+    rejection: ValidationRejection<V, E>,
+    case: KeyCase,
+) -> KeyCasedRejection<E> {
+    match rejection {
+        ValidationRejection::Valid(errors) => {
+            let value = serde_json::to_value(&errors).unwrap_or(Value::Null);
+            ValidationRejection::Valid(RenamedErrors(case.rename(value)))
+        }
+        ValidationRejection::Inner(error) => ValidationRejection::Inner(error),
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, V, E> FromRequest<State> for KeyCased<Extractor>
+where
+    State: Send + Sync,
+    KeyCase: FromRef<State>,
+    Extractor: FromRequest<State, Rejection = ValidationRejection<V, E>>,
+    V: Serialize,
+    KeyCasedRejection<E>: axum::response::IntoResponse,
+{
+    type Rejection = KeyCasedRejection<E>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        Extractor::from_request(req, state)
+            .await
+            .map(KeyCased)
+            .map_err(|rejection| rename(rejection, KeyCase::from_ref(state)))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, V, E> FromRequestParts<State> for KeyCased<Extractor>
+where
+    State: Send + Sync,
+    KeyCase: FromRef<State>,
+    Extractor: FromRequestParts<State, Rejection = ValidationRejection<V, E>>,
+    V: Serialize,
+    KeyCasedRejection<E>: axum::response::IntoResponse,
+{
+    type Rejection = KeyCasedRejection<E>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        Extractor::from_request_parts(parts, state)
+            .await
+            .map(KeyCased)
+            .map_err(|rejection| rename(rejection, KeyCase::from_ref(state)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn convert_keys() {
+        assert_eq!(KeyCase::Camel.convert("first_name"), "firstName");
+        assert_eq!(KeyCase::Camel.convert("firstName"), "firstName");
+        assert_eq!(
+            KeyCase::Camel.convert("line_items[2].unit_price"),
+            "lineItems[2].unitPrice"
+        );
+        assert_eq!(KeyCase::Snake.convert("firstName"), "first_name");
+        assert_eq!(KeyCase::Snake.convert("HTTPStatus2xx"), "http_status2xx");
+        assert_eq!(
+            KeyCase::Snake.convert("lineItems[2].unitPrice"),
+            "line_items[2].unit_price"
+        );
+        assert_eq!(KeyCase::Snake.convert("__all__"), "__all__");
+        assert_eq!(KeyCase::Camel.convert("__all__"), "__all__");
+    }
+
+    #[cfg(feature = "validator")]
+    #[tokio::test]
+    async fn key_cased_errors() {
+        use crate::Valid;
+        use axum::body::Body;
+        use axum::response::IntoResponse;
+        use axum::Json;
+        use serde::Deserialize;
+        use validator::Validate;
+
+        #[derive(Debug, Validate, Deserialize)]
+        struct LineItem {
+            #[validate(range(min = 1))]
+            unit_price: u32,
+        }
+
+        #[derive(Debug, Validate, Deserialize)]
+        struct Order {
+            #[validate(length(min = 1))]
+            customer_name: String,
+            #[validate(nested)]
+            line_items: Vec<LineItem>,
+        }
+
+        let request = Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                r#"{ "customer_name": "", "line_items": [{ "unit_price": 0 }] }"#,
+            ))
+            .expect("invalid request");
+        let rejection = KeyCased::<Valid<Json<Order>>>::from_request(request, &KeyCase::Camel)
+            .await
+            .expect_err("invalid order accepted");
+        let response = rejection.into_response();
+        assert_eq!(response.status(), crate::VALIDATION_ERROR_STATUS);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("failed to read body");
+        let errors: Value = serde_json::from_slice(&body).expect("invalid errors");
+        assert_eq!(
+            errors,
+            json!({
+                "customerName": [{ "code": "length", "message": null, "params": { "min": 1, "value": "" } }],
+                "lineItems": { "0": { "unitPrice": [{ "code": "range", "message": null, "params": { "min": 1, "value": 0 } }] } }
+            })
+        );
+    }
+}
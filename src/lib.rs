@@ -1,21 +1,81 @@
 #![doc = include_str!("../README.md")]
 #![deny(unsafe_code, missing_docs, clippy::unwrap_used)]
 
+#[cfg(feature = "async_validate")]
+pub mod async_validate;
+#[cfg(feature = "audit")]
+pub mod audit;
+#[cfg(feature = "client_ip")]
+pub mod client_ip;
+#[cfg(feature = "deprecation")]
+pub mod deprecation;
+#[cfg(feature = "dynamic_rules")]
+pub mod dynamic_rules;
+#[cfg(feature = "encrypt_fields")]
+pub mod encrypt_fields;
+#[cfg(feature = "encrypted_query")]
+pub mod encrypted_query;
 #[cfg(feature = "extra")]
 pub mod extra;
+#[cfg(feature = "fallback")]
+pub mod fallback;
+#[cfg(feature = "field_metrics")]
+pub mod field_metrics;
 #[cfg(feature = "form")]
 pub mod form;
 #[cfg(feature = "garde")]
 pub mod garde;
+#[cfg(feature = "guard")]
+pub mod guard;
+#[cfg(feature = "help_links")]
+pub mod help_links;
+#[cfg(feature = "helpers")]
+pub mod helpers;
 #[cfg(feature = "json")]
 pub mod json;
+#[cfg(feature = "json_api")]
+pub mod json_api;
+#[cfg(feature = "keep_body")]
+pub mod keep_body;
+#[cfg(feature = "key_case")]
+pub mod key_case;
+#[cfg(feature = "live_validation")]
+pub mod live_validation;
+#[cfg(feature = "load_limits")]
+pub mod load_limits;
+#[cfg(feature = "merge_patch")]
+pub mod merge_patch;
+#[cfg(feature = "modify_passes")]
+pub mod modify_passes;
+#[cfg(feature = "modify_trace")]
+pub mod modify_trace;
 #[cfg(feature = "msgpack")]
 pub mod msgpack;
+#[cfg(feature = "negotiate")]
+pub mod negotiate;
 pub mod path;
+#[cfg(feature = "precheck")]
+pub mod precheck;
+#[cfg(feature = "problem_details")]
+pub mod problem_details;
+#[cfg(feature = "profile")]
+pub mod profile;
 #[cfg(feature = "query")]
 pub mod query;
+#[cfg(feature = "severity")]
+pub mod severity;
+#[cfg(feature = "status")]
+pub mod status;
+#[cfg(feature = "time_budget")]
+pub mod time_budget;
+#[cfg(feature = "timezone")]
+pub mod timezone;
 #[cfg(feature = "typed_header")]
 pub mod typed_header;
+#[cfg(feature = "unknown_fields")]
+pub mod unknown_fields;
+#[cfg(feature = "validation_cache")]
+pub mod validation_cache;
 #[cfg(feature = "validator")]
 pub mod validator;
 #[cfg(feature = "validify")]
@@ -34,6 +94,9 @@ pub mod typed_multipart;
 #[cfg(feature = "xml")]
 pub mod xml;
 
+#[cfg(any(feature = "precheck", feature = "guard", feature = "keep_body"))]
+mod body;
+
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use std::error::Error;
@@ -58,10 +121,13 @@ pub trait HasValidate {
 }
 
 #[cfg(feature = "validator")]
-pub use crate::validator::{HasValidateArgs, Valid, ValidEx, ValidRejection};
+pub use crate::validator::{
+    HasValidateArgs, OptionalValid, Valid, ValidAll, ValidAllRejection, ValidEx, ValidRejection,
+    ValidResult,
+};
 
 #[cfg(feature = "garde")]
-pub use crate::garde::{Garde, GardeRejection};
+pub use crate::garde::{Garde, GardeRejection, GardeReport};
 
 #[cfg(feature = "validify")]
 pub use crate::validify::{
@@ -104,9 +170,7 @@ impl<V: Error + 'static, E: Error + 'static> Error for ValidationRejection<V, E>
 impl<V: serde::Serialize, E: IntoResponse> IntoResponse for ValidationRejection<V, E> {
     fn into_response(self) -> Response {
         match self {
-            ValidationRejection::Valid(v) => {
-                (VALIDATION_ERROR_STATUS, axum::Json(v)).into_response()
-            }
+            ValidationRejection::Valid(v) => validation_errors_response(&v),
             ValidationRejection::Inner(e) => e.into_response(),
         }
     }
@@ -116,14 +180,182 @@ impl<V: serde::Serialize, E: IntoResponse> IntoResponse for ValidationRejection<
 impl<V: Display, E: IntoResponse> IntoResponse for ValidationRejection<V, E> {
     fn into_response(self) -> Response {
         match self {
-            ValidationRejection::Valid(v) => {
-                (VALIDATION_ERROR_STATUS, v.to_string()).into_response()
-            }
+            ValidationRejection::Valid(v) => validation_errors_response(&v),
             ValidationRejection::Inner(e) => e.into_response(),
         }
     }
 }
 
+/// Converts the rejection into a status code and a human-readable message,
+/// so validation extractors can be used with `?` in handlers returning `Result<_, (StatusCode, String)>`.
+///
+/// Validation errors yield `VALIDATION_ERROR_STATUS` and their message, one line per error sorted like
+/// the text response. Errors of the inner extractor keep the status of their response and their message.
+impl<V: Display, E: Display + IntoResponse> From<ValidationRejection<V, E>>
+    for (StatusCode, String)
+{
+    fn from(rejection: ValidationRejection<V, E>) -> Self {
+        match rejection {
+            ValidationRejection::Valid(errors) => {
+                (VALIDATION_ERROR_STATUS, sorted_lines(&errors.to_string()))
+            }
+            ValidationRejection::Inner(error) => {
+                let message = error.to_string();
+                (error.into_response().status(), message)
+            }
+        }
+    }
+}
+
+/// Renders validation errors as JSON with `VALIDATION_ERROR_STATUS`.
+///
+/// Errors are usually stored in hash maps, so object keys are sorted, and lists of errors are sorted by `code`,
+/// to render identical errors identically.
+#[cfg(feature = "into_json")]
+pub(crate) fn validation_errors_response<V: serde::Serialize>(errors: &V) -> Response {
+    match serde_json::to_value(errors) {
+        Ok(mut value) => {
+            sort_by_code(&mut value);
+            (VALIDATION_ERROR_STATUS, axum::Json(value)).into_response()
+        }
+        Err(_) => (VALIDATION_ERROR_STATUS, axum::Json(errors)).into_response(),
+    }
+}
+
+/// Renders validation errors as text with `VALIDATION_ERROR_STATUS`.
+///
+/// Errors are usually stored in hash maps, so lines (one per field path) are sorted
+/// to render identical errors identically.
+#[cfg(not(feature = "into_json"))]
+pub(crate) fn validation_errors_response<V: Display>(errors: &V) -> Response {
+    (VALIDATION_ERROR_STATUS, sorted_lines(&errors.to_string())).into_response()
+}
+
+/// Sorts the lines of rendered validation errors, which usually come from hash maps.
+fn sorted_lines(text: &str) -> String {
+    let mut lines = text.lines().collect::<Vec<_>>();
+    lines.sort_unstable();
+    lines.join("\n")
+}
+
+/// Sorts every list of error objects by their `code`, and the keys of every object.
+///
+/// Object keys are sorted explicitly, since `serde_json::Map` keeps their insertion order
+/// when any crate of the build enables the `preserve_order` feature of `serde_json`.
+///
+/// With the `dedup_errors` feature, errors of a list sharing their `code` and `message` are also collapsed
+/// into the first of them, since each list holds the errors of a single field.
+#[cfg(feature = "into_json")]
+pub(crate) fn sort_by_code(value: &mut serde_json::Value) {
+    use serde_json::Value;
+
+    match value {
+        Value::Array(items) => {
+            items.iter_mut().for_each(sort_by_code);
+            if items
+                .iter()
+                .all(|item| item.get("code").is_some_and(Value::is_string))
+            {
+                items.sort_by(|a, b| a["code"].as_str().cmp(&b["code"].as_str()));
+                #[cfg(feature = "dedup_errors")]
+                {
+                    let mut seen = Vec::with_capacity(items.len());
+                    items.retain(|item| {
+                        let key = (item.get("code").cloned(), item.get("message").cloned());
+                        let duplicate = seen.contains(&key);
+                        seen.push(key);
+                        !duplicate
+                    });
+                }
+            }
+        }
+        Value::Object(fields) => {
+            let sorted = std::mem::take(fields)
+                .into_iter()
+                .collect::<std::collections::BTreeMap<_, _>>();
+            *fields = sorted.into_iter().collect();
+            fields.values_mut().for_each(sort_by_code);
+        }
+        _ => {}
+    }
+}
+
+/// A step of the path leading to a failing field: a field of a struct, or an element of a list.
+#[cfg(feature = "validator")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ErrorPathSegment<'a> {
+    Field(&'a str),
+    Index(usize),
+}
+
+#[cfg(feature = "validator")]
+impl Display for ErrorPathSegment<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Field(field) => f.write_str(field),
+            Self::Index(index) => write!(f, "{index}"),
+        }
+    }
+}
+
+/// Calls `visit` with the path and the errors of every failing field of `errors`, in alphabetical order of fields.
+///
+/// Struct-level errors are visited as a field named `__all__` of the struct holding them.
+#[cfg(feature = "validator")]
+pub(crate) fn flatten_errors<'a>(
+    errors: &'a ::validator::ValidationErrors,
+    visit: &mut dyn FnMut(&[ErrorPathSegment<'a>], &'a [::validator::ValidationError]),
+) {
+    fn walk<'a>(
+        errors: &'a ::validator::ValidationErrors,
+        path: &mut Vec<ErrorPathSegment<'a>>,
+        visit: &mut dyn FnMut(&[ErrorPathSegment<'a>], &'a [::validator::ValidationError]),
+    ) {
+        use ::validator::ValidationErrorsKind;
+
+        let mut fields = errors.errors().iter().collect::<Vec<_>>();
+        fields.sort_unstable_by_key(|(field, _)| *field);
+        for (field, kind) in fields {
+            path.push(ErrorPathSegment::Field(field));
+            match kind {
+                ValidationErrorsKind::Field(errors) => visit(path, errors),
+                ValidationErrorsKind::Struct(errors) => walk(errors, path, visit),
+                ValidationErrorsKind::List(items) => {
+                    for (index, errors) in items {
+                        path.push(ErrorPathSegment::Index(*index));
+                        walk(errors, path, visit);
+                        path.pop();
+                    }
+                }
+            }
+            path.pop();
+        }
+    }
+
+    walk(errors, &mut Vec::new(), visit);
+}
+
+/// Formats a path as `items[1].price`, or as `items.price` if `indices` is `false`.
+#[cfg(feature = "validator")]
+pub(crate) fn format_error_path(path: &[ErrorPathSegment<'_>], indices: bool) -> String {
+    let mut formatted = String::new();
+    for segment in path {
+        match segment {
+            ErrorPathSegment::Field(field) => {
+                if !formatted.is_empty() {
+                    formatted.push('.');
+                }
+                formatted.push_str(field);
+            }
+            ErrorPathSegment::Index(index) if indices => {
+                formatted.push_str(&format!("[{index}]"));
+            }
+            ErrorPathSegment::Index(_) => {}
+        }
+    }
+    formatted
+}
+
 #[cfg(test)]
 mod tests {
     use axum::http::StatusCode;
@@ -155,6 +387,7 @@ mod tests {
         /// Use crate::VALIDATION_ERROR_STATUS by default.
         const INVALID_STATUS_CODE: StatusCode = crate::VALIDATION_ERROR_STATUS;
         /// If the response body can be serialized into JSON format
+        #[cfg_attr(not(feature = "into_json"), allow(dead_code))]
         const JSON_SERIALIZABLE: bool = true;
         /// Build a valid request, the server should return `200 OK`.
         fn set_valid_request(builder: RequestBuilder) -> RequestBuilder;
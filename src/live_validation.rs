@@ -0,0 +1,324 @@
+//! # Live validation feedback over server-sent events
+//!
+//! ## Feature
+//!
+//! Enable the `live_validation` feature to use `LiveValidation<T>`.
+//!
+//! ## Usage
+//!
+//! Long forms are easier to fill in when each field is checked as soon as it is typed, rather than when
+//! the whole form is submitted. `LiveValidation<T>` accepts a partially filled form as a JSON body,
+//! and turns it into a stream of [server-sent events](https://html.spec.whatwg.org/multipage/server-sent-events.html),
+//! one per field, which the client can render next to each field as the events arrive:
+//!
+//! 1. Declare the fields of your form as `Option`s, so that partial forms can be deserialized.
+//! 2. In your handler function, use `LiveValidation<T>` as the extractor and return `LiveValidation::into_sse`.
+//!
+//! Each field present in the body, in alphabetical order, yields a `field` event whose data is
+//! `{ "field": "email", "valid": false, "errors": [{ "path": "email", "code": "email", "message": null }] }`.
+//! Errors of nested fields are reported with the top-level field, named by their full path, e.g.
+//! `address.zip`. Struct-level errors, and errors of fields missing from the body, are not reported,
+//! since the form is not complete yet. A final `summary` event, `{ "valid": true }`, tells whether the
+//! fields sent so far are all valid.
+//!
+//! Bodies which are not JSON objects, or cannot be deserialized into `T`, are rejected as by `Json<T>`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use axum::response::IntoResponse;
+//! use axum::routing::post;
+//! use axum::Router;
+//! use axum_valid::live_validation::LiveValidation;
+//! use serde::Deserialize;
+//! use validator::Validate;
+//!
+//! #[derive(Validate, Deserialize)]
+//! pub struct SignUp {
+//!     #[validate(email)]
+//!     pub email: Option<String>,
+//!     #[validate(length(min = 12))]
+//!     pub password: Option<String>,
+//! }
+//!
+//! async fn feedback(live: LiveValidation<SignUp>) -> impl IntoResponse {
+//!     live.into_sse()
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! let router = Router::new().route("/sign-up/feedback", post(feedback));
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::ErrorPathSegment;
+use axum::async_trait;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{FromRequest, Request};
+use axum::response::sse::{Event, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use futures_util::stream::{self, Stream};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use validator::{Validate, ValidationErrors};
+
+/// A validation error of a field.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    /// Path of the offending field, e.g. `address.zip`.
+    pub path: String,
+    /// Code of the rule.
+    pub code: String,
+    /// Message of the rule, if any.
+    pub message: Option<String>,
+}
+
+/// The validation result of a top-level field, sent as a `field` event.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldResult {
+    /// Name of the field.
+    pub field: String,
+    /// `true` if the field and its nested fields are valid.
+    pub valid: bool,
+    /// The errors of the field and of its nested fields.
+    pub errors: Vec<FieldError>,
+}
+
+/// The result of all the fields, sent as the final `summary` event.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Summary {
+    /// `true` if every field present in the body is valid.
+    pub valid: bool,
+}
+
+/// # `LiveValidation` data extractor
+///
+/// `LiveValidation` deserializes a partially filled form from a JSON body, and validates its fields
+/// for live feedback. It holds the data, and the names of the top-level fields present in the body.
+///
+#[derive(Debug, Clone)]
+pub struct LiveValidation<T> {
+    /// The deserialized data.
+    pub data: T,
+    /// The top-level fields present in the body, in alphabetical order.
+    pub fields: Vec<String>,
+}
+
+impl<T: Validate> LiveValidation<T> {
+    /// Validates the data, returning the result of each field present in the body.
+    pub fn results(&self) -> Vec<FieldResult> {
+        let errors = self.data.validate().err().unwrap_or_default();
+        self.fields
+            .iter()
+            .map(|field| {
+                let mut found = Vec::new();
+                collect(&errors, field, &mut found);
+                FieldResult {
+                    field: field.clone(),
+                    valid: found.is_empty(),
+                    errors: found,
+                }
+            })
+            .collect()
+    }
+
+    /// Validates the data, returning a stream of `field` events followed by a `summary` event.
+    pub fn into_sse(self) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+        let results = self.results();
+        let summary = Summary {
+            valid: results.iter().all(|result| result.valid),
+        };
+        let events = results
+            .iter()
+            .map(|result| event("field", result))
+            .chain(std::iter::once(event("summary", &summary)))
+            .collect::<Vec<_>>();
+        Sse::new(stream::iter(events.into_iter().map(Ok)))
+    }
+}
+
+fn event<T: Serialize>(name: &str, data: &T) -> Event {
+    let event = Event::default().event(name);
+    match event.clone().json_data(data) {
+        Ok(event) => event,
+        Err(_) => event.data("null"),
+    }
+}
+
+/// Collects the errors of the fields nested in the top-level `field`.
+fn collect(errors: &ValidationErrors, field: &str, found: &mut Vec<FieldError>) {
+    crate::flatten_errors(errors, &mut |path, errors| {
+        if path.first() != Some(&ErrorPathSegment::Field(field)) {
+            return;
+        }
+        found.extend(errors.iter().map(|error| FieldError {
+            path: crate::format_error_path(path, true),
+            code: error.code.to_string(),
+            message: error.message.as_ref().map(ToString::to_string),
+        }))
+    });
+}
+
+/// `LiveValidationRejection` is returned when the `LiveValidation` extractor fails.
+///
+#[derive(Debug)]
+pub enum LiveValidationRejection {
+    /// The body is not valid JSON.
+    Json(JsonRejection),
+    /// The body is not a JSON object.
+    NotAnObject,
+    /// The body cannot be deserialized into the form.
+    Deserialize(serde_json::Error),
+}
+
+impl Display for LiveValidationRejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LiveValidationRejection::Json(error) => write!(f, "{error}"),
+            LiveValidationRejection::NotAnObject => {
+                write!(f, "Failed to deserialize the JSON body: expected an object")
+            }
+            LiveValidationRejection::Deserialize(error) => {
+                write!(
+                    f,
+                    "Failed to deserialize the JSON body into the target type: {error}"
+                )
+            }
+        }
+    }
+}
+
+impl Error for LiveValidationRejection {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LiveValidationRejection::Json(error) => Some(error),
+            LiveValidationRejection::NotAnObject => None,
+            LiveValidationRejection::Deserialize(error) => Some(error),
+        }
+    }
+}
+
+impl IntoResponse for LiveValidationRejection {
+    fn into_response(self) -> Response {
+        match self {
+            LiveValidationRejection::Json(error) => error.into_response(),
+            error => (
+                axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+                error.to_string(),
+            )
+                .into_response(),
+        }
+    }
+}
+
+#[async_trait]
+impl<State, T> FromRequest<State> for LiveValidation<T>
+where
+    State: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = LiveValidationRejection;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<Value>::from_request(req, state)
+            .await
+            .map_err(LiveValidationRejection::Json)?;
+        let fields = match &value {
+            Value::Object(fields) => fields.keys().cloned().collect(),
+            _ => return Err(LiveValidationRejection::NotAnObject),
+        };
+        let data = serde_json::from_value(value).map_err(LiveValidationRejection::Deserialize)?;
+        Ok(LiveValidation { data, fields })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use serde::Deserialize;
+
+    #[derive(Debug, Validate, Deserialize)]
+    struct Address {
+        #[validate(length(equal = 5))]
+        zip: String,
+    }
+
+    #[derive(Debug, Validate, Deserialize)]
+    struct SignUp {
+        #[validate(email)]
+        email: Option<String>,
+        #[validate(length(min = 12))]
+        password: Option<String>,
+        #[validate(nested)]
+        address: Option<Address>,
+    }
+
+    #[tokio::test]
+    async fn live_validation_events() {
+        let request = Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                r#"{ "password": "short", "email": "ada@example.com", "address": { "zip": "123" } }"#,
+            ))
+            .expect("invalid request");
+        let live = LiveValidation::<SignUp>::from_request(request, &())
+            .await
+            .expect("partial form rejected");
+        assert_eq!(live.fields, ["address", "email", "password"]);
+
+        let response = live.into_sse().into_response();
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("failed to read body");
+        let events = std::str::from_utf8(&body)
+            .expect("invalid event stream")
+            .split("\n\n")
+            .filter(|event| !event.is_empty())
+            .map(|event| {
+                let (name, data) = event.split_once('\n').expect("invalid event");
+                let data = data.strip_prefix("data: ").expect("missing data");
+                (
+                    name.trim_start_matches("event: ").to_owned(),
+                    serde_json::from_str::<Value>(data).expect("invalid data"),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(events.len(), 4);
+        assert_eq!(
+            events[0],
+            (
+                String::from("field"),
+                serde_json::json!({
+                    "field": "address",
+                    "valid": false,
+                    "errors": [{ "path": "address.zip", "code": "length", "message": null }]
+                })
+            )
+        );
+        assert_eq!(events[1].1["field"], "email");
+        assert_eq!(events[1].1["valid"], true);
+        assert_eq!(events[2].1["field"], "password");
+        assert_eq!(events[2].1["errors"][0]["code"], "length");
+        assert_eq!(
+            events[3],
+            (
+                String::from("summary"),
+                serde_json::json!({ "valid": false })
+            )
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! # Limits depending on the server load
+//!
+//! ## Feature
+//!
+//! Enable the `load_limits` feature to use `LoadGauge` and `LoadLimit`.
+//!
+//! ## Usage
+//!
+//! Some limits are a trade-off between convenience and capacity, e.g. the size of a batch: large batches
+//! are fine when the server is idle, but should be refused when it is busy. `LoadGauge` counts the requests
+//! being handled, and `LoadLimit` picks a limit according to that count, to be checked with `ValidAsync`:
+//!
+//! 1. Create a `LoadGauge`, make it available from your router state, and add the `track_load` middleware
+//!    to the routes whose requests should be counted, e.g. with `axum::middleware::from_fn_with_state`.
+//! 2. Implement `HasAsyncValidate` for your data type, with `State<LoadGauge>` in its `Context`,
+//!    checking the value against a `LoadLimit`, which returns a `LoadLimitError` when it is exceeded.
+//! 3. In your handler function, use `ValidAsync<E>` as the extractor, e.g. `ValidAsync<Json<T>>`.
+//!
+//! A `LoadLimitError` is rejected with `VALIDATION_ERROR_STATUS`, and names the field, the limit in force
+//! and the actual value, so clients can retry with smaller requests.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use axum::async_trait;
+//! use axum::extract::State;
+//! use axum::middleware::from_fn_with_state;
+//! use axum::routing::post;
+//! use axum::{Json, Router};
+//! use axum_valid::async_validate::{HasAsyncValidate, ValidAsync};
+//! use axum_valid::load_limits::{track_load, LoadGauge, LoadLimit, LoadLimitError};
+//! use serde::Deserialize;
+//!
+//! // Up to 1000 items, or 100 once 64 requests are being handled.
+//! const BATCH_SIZE: LoadLimit = LoadLimit::new(1000).reduced_to(100, 64);
+//!
+//! #[derive(Deserialize)]
+//! pub struct Batch {
+//!     pub items: Vec<String>,
+//! }
+//!
+//! #[async_trait]
+//! impl HasAsyncValidate for Batch {
+//!     type Context = State<LoadGauge>;
+//!     type Error = LoadLimitError;
+//!
+//!     async fn validate_async(&self, State(gauge): &Self::Context) -> Result<(), Self::Error> {
+//!         BATCH_SIZE.check(gauge, "items", self.items.len())
+//!     }
+//! }
+//!
+//! async fn handler(ValidAsync(Json(batch)): ValidAsync<Json<Batch>>) {
+//!     assert!(batch.items.len() <= 1000);
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! let gauge = LoadGauge::new();
+//! let router = Router::new()
+//!     .route("/batches", post(handler))
+//!     .layer(from_fn_with_state(gauge.clone(), track_load))
+//!     .with_state(gauge);
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::VALIDATION_ERROR_STATUS;
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Counts the requests being handled.
+#[derive(Debug, Clone, Default)]
+pub struct LoadGauge(Arc<AtomicUsize>);
+
+impl LoadGauge {
+    /// Create a `LoadGauge` counting no request.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of requests being handled.
+    pub fn current(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Counts a request until the returned `LoadGuard` is dropped.
+    pub fn enter(&self) -> LoadGuard {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        LoadGuard(self.0.clone())
+    }
+}
+
+/// Counts a request in a `LoadGauge` until it is dropped.
+#[derive(Debug)]
+pub struct LoadGuard(Arc<AtomicUsize>);
+
+impl Drop for LoadGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Middleware counting the requests being handled in a `LoadGauge`.
+pub async fn track_load(State(gauge): State<LoadGauge>, req: Request, next: Next) -> Response {
+    let _guard = gauge.enter();
+    next.run(req).await
+}
+
+/// A maximum which is reduced when the server is busy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadLimit {
+    normal: usize,
+    reduced: usize,
+    threshold: usize,
+}
+
+impl LoadLimit {
+    /// Create a `LoadLimit` allowing at most `max`, whatever the load.
+    pub const fn new(max: usize) -> Self {
+        Self {
+            normal: max,
+            reduced: max,
+            threshold: usize::MAX,
+        }
+    }
+
+    /// Allow at most `max` once `threshold` requests or more are being handled.
+    pub const fn reduced_to(mut self, max: usize, threshold: usize) -> Self {
+        self.reduced = max;
+        self.threshold = threshold;
+        self
+    }
+
+    /// Returns the maximum in force for the load measured by `gauge`.
+    pub fn max(&self, gauge: &LoadGauge) -> usize {
+        if gauge.current() >= self.threshold {
+            self.reduced
+        } else {
+            self.normal
+        }
+    }
+
+    /// Checks that the value of `field` does not exceed the maximum in force.
+    pub fn check(
+        &self,
+        gauge: &LoadGauge,
+        field: &'static str,
+        actual: usize,
+    ) -> Result<(), LoadLimitError> {
+        let max = self.max(gauge);
+        if actual <= max {
+            return Ok(());
+        }
+        Err(LoadLimitError { field, max, actual })
+    }
+}
+
+/// A value exceeding the `LoadLimit` in force.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadLimitError {
+    /// Name of the field.
+    pub field: &'static str,
+    /// Maximum in force.
+    pub max: usize,
+    /// Value of the field.
+    pub actual: usize,
+}
+
+impl Display for LoadLimitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: must be at most {} under the current load, got {}",
+            self.field, self.max, self.actual
+        )
+    }
+}
+
+impl Error for LoadLimitError {}
+
+impl IntoResponse for LoadLimitError {
+    fn into_response(self) -> Response {
+        (VALIDATION_ERROR_STATUS, self.to_string()).into_response()
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use crate::async_validate::{HasAsyncValidate, ValidAsync};
+    use axum::async_trait;
+    use axum::body::Body;
+    use axum::extract::FromRequest;
+    use axum::Json;
+    use serde::Deserialize;
+
+    const BATCH_SIZE: LoadLimit = LoadLimit::new(4).reduced_to(2, 3);
+
+    #[derive(Debug, Deserialize)]
+    struct Batch {
+        items: Vec<u32>,
+    }
+
+    #[async_trait]
+    impl HasAsyncValidate for Batch {
+        type Context = State<LoadGauge>;
+        type Error = LoadLimitError;
+
+        async fn validate_async(&self, State(gauge): &Self::Context) -> Result<(), Self::Error> {
+            BATCH_SIZE.check(gauge, "items", self.items.len())
+        }
+    }
+
+    fn request() -> Request {
+        Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{ "items": [1, 2, 3] }"#))
+            .expect("invalid request")
+    }
+
+    #[tokio::test]
+    async fn load_limit() {
+        let gauge = LoadGauge::new();
+        assert!(ValidAsync::<Json<Batch>>::from_request(request(), &gauge)
+            .await
+            .is_ok());
+
+        let guards = (0..3).map(|_| gauge.enter()).collect::<Vec<_>>();
+        assert_eq!(gauge.current(), 3);
+        let rejection = ValidAsync::<Json<Batch>>::from_request(request(), &gauge)
+            .await
+            .expect_err("large batch accepted under load");
+        assert_eq!(rejection.into_response().status(), VALIDATION_ERROR_STATUS);
+
+        drop(guards);
+        assert_eq!(gauge.current(), 0);
+        assert!(ValidAsync::<Json<Batch>>::from_request(request(), &gauge)
+            .await
+            .is_ok());
+    }
+}
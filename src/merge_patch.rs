@@ -0,0 +1,377 @@
+//! # Validation of partial updates merged onto their entity
+//!
+//! ## Feature
+//!
+//! Enable the `merge_patch` feature to use `MergePatchValid<E, P>`.
+//!
+//! ## Usage
+//!
+//! A partial update (e.g. the body of a `PATCH` request) can look fine on its own and still produce an invalid
+//! entity, e.g. when it moves a start date past the existing end date. `MergePatchValid` validates the merged
+//! result instead of the patch:
+//!
+//! 1. Write an extractor `P` loading the entity to update, e.g. from a store held in the state,
+//!    which implements `FromRequestParts` and `DerefMut` to the entity.
+//! 2. Implement `validator::Validate` for the entity, and `ApplyPatch<Entity>` for your patch type.
+//! 3. In your handler function, use `MergePatchValid<E, P>` as the extractor, e.g.
+//!    `MergePatchValid<Json<UserPatch>, LoadUser>`.
+//!
+//! `MergePatchValid` first extracts the entity with `P`, so a missing entity is rejected before the body is read,
+//! then runs the inner extractor `E`, applies the patch to the entity, and validates the merged entity.
+//! Both the patch and the merged entity are handed to the handler, which usually only has to save the latter.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use std::collections::HashMap;
+//! use std::ops::{Deref, DerefMut};
+//! use std::sync::Arc;
+//! use axum::async_trait;
+//! use axum::extract::{FromRef, FromRequestParts, Path};
+//! use axum::http::request::Parts;
+//! use axum::http::StatusCode;
+//! use axum::routing::patch;
+//! use axum::Json;
+//! use axum::Router;
+//! use axum_valid::merge_patch::{ApplyPatch, MergePatchValid};
+//! use serde::Deserialize;
+//! use validator::{Validate, ValidationError};
+//!
+//! #[derive(Clone, Default)]
+//! pub struct Store(Arc<HashMap<u32, Booking>>);
+//!
+//! pub fn router() -> Router {
+//!     Router::new()
+//!         .route("/bookings/:id", patch(handler))
+//!         .with_state(Store::default())
+//! }
+//!
+//! async fn handler(
+//!     MergePatchValid(Json(_patch), LoadBooking(booking)): MergePatchValid<
+//!         Json<BookingPatch>,
+//!         LoadBooking,
+//!     >,
+//! ) {
+//!     assert!(booking.validate().is_ok());
+//! }
+//!
+//! #[derive(Clone, Validate)]
+//! #[validate(schema(function = "ends_after_start"))]
+//! pub struct Booking {
+//!     pub starts_at: u64,
+//!     pub ends_at: u64,
+//! }
+//!
+//! fn ends_after_start(booking: &Booking) -> Result<(), ValidationError> {
+//!     if booking.ends_at > booking.starts_at {
+//!         Ok(())
+//!     } else {
+//!         Err(ValidationError::new("ends_after_start"))
+//!     }
+//! }
+//!
+//! #[derive(Deserialize)]
+//! pub struct BookingPatch {
+//!     pub starts_at: Option<u64>,
+//!     pub ends_at: Option<u64>,
+//! }
+//!
+//! impl ApplyPatch<Booking> for BookingPatch {
+//!     fn apply_to(&self, booking: &mut Booking) {
+//!         booking.starts_at = self.starts_at.unwrap_or(booking.starts_at);
+//!         booking.ends_at = self.ends_at.unwrap_or(booking.ends_at);
+//!     }
+//! }
+//!
+//! pub struct LoadBooking(Booking);
+//!
+//! impl Deref for LoadBooking {
+//!     type Target = Booking;
+//!
+//!     fn deref(&self) -> &Booking {
+//!         &self.0
+//!     }
+//! }
+//!
+//! impl DerefMut for LoadBooking {
+//!     fn deref_mut(&mut self) -> &mut Booking {
+//!         &mut self.0
+//!     }
+//! }
+//!
+//! #[async_trait]
+//! impl<S: Send + Sync> FromRequestParts<S> for LoadBooking
+//! where
+//!     Store: FromRef<S>,
+//! {
+//!     type Rejection = StatusCode;
+//!
+//!     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, StatusCode> {
+//!         let Path(id) = Path::<u32>::from_request_parts(parts, state)
+//!             .await
+//!             .map_err(|_| StatusCode::BAD_REQUEST)?;
+//!         let store = Store::from_ref(state);
+//!         store.0.get(&id).cloned().map(LoadBooking).ok_or(StatusCode::NOT_FOUND)
+//!     }
+//! }
+//! #
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router().into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::HasValidate;
+use axum::async_trait;
+use axum::extract::{FromRequest, FromRequestParts, Request};
+use axum::response::{IntoResponse, Response};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::DerefMut;
+use validator::{Validate, ValidationErrors};
+
+/// Trait for partial updates which can be applied to an entity.
+pub trait ApplyPatch<Entity: ?Sized> {
+    /// Apply the changes of the patch to the entity
+    fn apply_to(&self, entity: &mut Entity);
+}
+
+/// # `MergePatchValid` data extractor
+///
+/// `MergePatchValid` loads the entity with `P`, runs the inner extractor `E`, applies the patch to the entity,
+/// and validates the merged entity. It holds the inner extractor and the merged entity.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MergePatchValid<E, P>(pub E, pub P);
+
+impl<E, P> MergePatchValid<E, P> {
+    /// Consumes the `MergePatchValid` and returns the inner extractor and the merged entity.
+    pub fn into_inner(self) -> (E, P) {
+        (self.0, self.1)
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<E, P> aide::OperationInput for MergePatchValid<E, P>
+where
+    E: aide::OperationInput,
+    P: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        P::operation_input(ctx, operation);
+        E::operation_input(ctx, operation);
+    }
+}
+
+/// `MergePatchRejection` is returned when the `MergePatchValid` extractor fails.
+///
+#[derive(Debug)]
+pub enum MergePatchRejection<B, E> {
+    /// `Valid` variant captures the errors found in the merged entity.
+    Valid(ValidationErrors),
+    /// `Base` variant captures errors raised while loading the entity.
+    Base(B),
+    /// `Inner` variant represents potential errors that might occur within the inner extractor.
+    Inner(E),
+}
+
+impl<B: Display, E: Display> Display for MergePatchRejection<B, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergePatchRejection::Valid(errors) => write!(f, "{errors}"),
+            MergePatchRejection::Base(error) => write!(f, "{error}"),
+            MergePatchRejection::Inner(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl<B: Error + 'static, E: Error + 'static> Error for MergePatchRejection<B, E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MergePatchRejection::Valid(errors) => Some(errors),
+            MergePatchRejection::Base(error) => Some(error),
+            MergePatchRejection::Inner(error) => Some(error),
+        }
+    }
+}
+
+impl<B: IntoResponse, E: IntoResponse> IntoResponse for MergePatchRejection<B, E> {
+    fn into_response(self) -> Response {
+        match self {
+            MergePatchRejection::Valid(errors) => crate::validation_errors_response(&errors),
+            MergePatchRejection::Base(error) => error.into_response(),
+            MergePatchRejection::Inner(error) => error.into_response(),
+        }
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, Base> FromRequest<State> for MergePatchValid<Extractor, Base>
+where
+    State: Send + Sync,
+    Extractor: HasValidate + FromRequest<State>,
+    Extractor::Validate: ApplyPatch<Base::Target>,
+    Base: FromRequestParts<State> + DerefMut + Send,
+    Base::Target: Validate,
+{
+    type Rejection = MergePatchRejection<
+        <Base as FromRequestParts<State>>::Rejection,
+        <Extractor as FromRequest<State>>::Rejection,
+    >;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let (mut parts, body) = req.into_parts();
+        let mut base = Base::from_request_parts(&mut parts, state)
+            .await
+            .map_err(MergePatchRejection::Base)?;
+        let inner = Extractor::from_request(Request::from_parts(parts, body), state)
+            .await
+            .map_err(MergePatchRejection::Inner)?;
+        inner.get_validate().apply_to(&mut base);
+        base.validate().map_err(MergePatchRejection::Valid)?;
+        Ok(MergePatchValid(inner, base))
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::extract::State;
+    use axum::http::request::Parts;
+    use axum::http::StatusCode;
+    use axum::Json;
+    use serde::Deserialize;
+    use std::collections::HashMap;
+    use std::ops::Deref;
+    use std::sync::Arc;
+    use validator::ValidationError;
+
+    #[derive(Debug, Clone, PartialEq, Validate)]
+    #[validate(schema(function = "ends_after_start"))]
+    struct Booking {
+        #[validate(length(min = 1))]
+        room: String,
+        starts_at: u64,
+        ends_at: u64,
+    }
+
+    fn ends_after_start(booking: &Booking) -> Result<(), ValidationError> {
+        if booking.ends_at > booking.starts_at {
+            Ok(())
+        } else {
+            Err(ValidationError::new("ends_after_start"))
+        }
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct BookingPatch {
+        room: Option<String>,
+        starts_at: Option<u64>,
+        ends_at: Option<u64>,
+    }
+
+    impl ApplyPatch<Booking> for BookingPatch {
+        fn apply_to(&self, booking: &mut Booking) {
+            if let Some(room) = &self.room {
+                booking.room.clone_from(room);
+            }
+            booking.starts_at = self.starts_at.unwrap_or(booking.starts_at);
+            booking.ends_at = self.ends_at.unwrap_or(booking.ends_at);
+        }
+    }
+
+    type Store = Arc<HashMap<String, Booking>>;
+
+    #[derive(Debug)]
+    struct LoadBooking(Booking);
+
+    impl Deref for LoadBooking {
+        type Target = Booking;
+
+        fn deref(&self) -> &Self::Target {
+            &self.0
+        }
+    }
+
+    impl DerefMut for LoadBooking {
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            &mut self.0
+        }
+    }
+
+    #[async_trait]
+    impl FromRequestParts<Store> for LoadBooking {
+        type Rejection = StatusCode;
+
+        async fn from_request_parts(parts: &mut Parts, state: &Store) -> Result<Self, StatusCode> {
+            let State(store) = State::<Store>::from_request_parts(parts, state)
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            store
+                .get(parts.uri.path())
+                .cloned()
+                .map(LoadBooking)
+                .ok_or(StatusCode::NOT_FOUND)
+        }
+    }
+
+    #[tokio::test]
+    async fn merge_patch_valid() {
+        let store: Store = Arc::new(HashMap::from([(
+            String::from("/bookings/1"),
+            Booking {
+                room: String::from("blue"),
+                starts_at: 10,
+                ends_at: 20,
+            },
+        )]));
+        let request = |uri: &str, body: &'static str| {
+            Request::builder()
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+        type Extractor = MergePatchValid<Json<BookingPatch>, LoadBooking>;
+
+        let MergePatchValid(_, LoadBooking(booking)) =
+            Extractor::from_request(request("/bookings/1", r#"{ "starts_at": 15 }"#), &store)
+                .await
+                .expect("valid patch rejected");
+        assert_eq!(booking.starts_at, 15);
+        assert_eq!(booking.ends_at, 20);
+        assert_eq!(booking.room, "blue");
+
+        // The patch alone looks fine, but moves the start past the existing end.
+        let rejection =
+            Extractor::from_request(request("/bookings/1", r#"{ "starts_at": 25 }"#), &store)
+                .await
+                .expect_err("patch producing an invalid booking accepted");
+        assert!(matches!(
+            &rejection,
+            MergePatchRejection::Valid(errors) if errors.field_errors().contains_key("__all__")
+        ));
+        assert_eq!(
+            rejection.into_response().status(),
+            crate::VALIDATION_ERROR_STATUS
+        );
+
+        assert!(Extractor::from_request(
+            request("/bookings/1", r#"{ "starts_at": 25, "ends_at": 30 }"#),
+            &store
+        )
+        .await
+        .is_ok());
+
+        assert!(matches!(
+            Extractor::from_request(request("/bookings/2", r#"{ "starts_at": 15 }"#), &store).await,
+            Err(MergePatchRejection::Base(StatusCode::NOT_FOUND))
+        ));
+    }
+}
@@ -0,0 +1,281 @@
+//! # Repeated modification until convergence
+//!
+//! ## Feature
+//!
+//! Enable the `modify_passes` feature to use `Converged<E, N>`.
+//!
+//! ## Usage
+//!
+//! `validify` runs the modifiers of each field once, in declaration order. When a modifier's output can be
+//! changed again by a modifier, e.g. a custom modifier collapsing a single pair of spaces per run, one pass
+//! does not always produce normalized data. `Converged<E, N>` runs the modifiers again and again until a pass
+//! leaves the data unchanged, then validates it like `Validated<E>`:
+//!
+//! 1. Derive `Clone` and `PartialEq` for your data type, in addition to `Validify`.
+//! 2. In your handler function, use `Converged<E, N>` as the extractor, e.g. `Converged<Json<T>, 8>`,
+//!    where `N` is the maximum number of modification passes.
+//!
+//! Data which is already normalized takes one pass, and data normalized by idempotent modifiers two,
+//! since a pass only proves the data converged by changing nothing. Data still changing after `N` passes,
+//! e.g. because of a modifier appending to its field, is rejected with a schema error (code `not_converged`,
+//! with the number of passes as the `passes` parameter) instead of being modified forever.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use axum::routing::post;
+//! use axum::Json;
+//! use axum::Router;
+//! use axum_valid::modify_passes::Converged;
+//! use serde::Deserialize;
+//! use validify::Validify;
+//!
+//! fn collapse_spaces(name: &mut String) {
+//!     *name = name.replacen("  ", " ", 1);
+//! }
+//!
+//! #[derive(Clone, PartialEq, Validify, Deserialize)]
+//! pub struct Profile {
+//!     #[modify(trim, custom(collapse_spaces))]
+//!     #[validate(length(min = 1, max = 32))]
+//!     pub name: String,
+//! }
+//!
+//! async fn handler(Converged(Json(profile)): Converged<Json<Profile>, 8>) {
+//!     assert!(!profile.name.contains("  "));
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new().route("/profile", post(handler));
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::{HasModify, ValidifyRejection};
+use axum::async_trait;
+use axum::extract::{FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use validify::{Modify, Validate, ValidationError, ValidationErrors};
+
+/// Runs the modifiers of `data` until a pass leaves it unchanged, at most `passes` times, then validates it.
+pub fn modify_until_converged<T>(data: &mut T, passes: usize) -> Result<(), ValidationErrors>
+where
+    T: Modify + Validate + Clone + PartialEq,
+{
+    for _ in 0..passes {
+        let before = data.clone();
+        data.modify();
+        if *data == before {
+            return data.validate();
+        }
+    }
+    let mut errors = ValidationErrors::new();
+    errors.add(
+        ValidationError::new_schema("not_converged")
+            .with_param("passes", &passes)
+            .with_message(format!(
+                "modifications did not converge after {passes} passes"
+            )),
+    );
+    Err(errors)
+}
+
+/// # `Converged` data extractor
+///
+/// `Converged` modifies the data of the inner extractor until it stops changing, at most `N` times,
+/// then validates it.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Converged<E, const N: usize>(pub E);
+
+impl<E, const N: usize> Deref for Converged<E, N> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E, const N: usize> DerefMut for Converged<E, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display, const N: usize> Display for Converged<T, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E, const N: usize> Converged<E, N> {
+    /// Consumes the `Converged` and returns the modified and validated data within.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T, const N: usize> aide::OperationInput for Converged<T, N>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, const N: usize> FromRequest<State> for Converged<Extractor, N>
+where
+    State: Send + Sync,
+    Extractor: HasModify + FromRequest<State>,
+    Extractor::Modify: Validate + Clone + PartialEq,
+{
+    type Rejection = ValidifyRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let mut inner = Extractor::from_request(req, state)
+            .await
+            .map_err(ValidifyRejection::Inner)?;
+        modify_until_converged(inner.get_modify(), N)?;
+        Ok(Converged(inner))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, const N: usize> FromRequestParts<State> for Converged<Extractor, N>
+where
+    State: Send + Sync,
+    Extractor: HasModify + FromRequestParts<State>,
+    Extractor::Modify: Validate + Clone + PartialEq,
+{
+    type Rejection = ValidifyRejection<<Extractor as FromRequestParts<State>>::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let mut inner = Extractor::from_request_parts(parts, state)
+            .await
+            .map_err(ValidifyRejection::Inner)?;
+        modify_until_converged(inner.get_modify(), N)?;
+        Ok(Converged(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use validify::Validify;
+
+    fn collapse_spaces(name: &mut String) {
+        *name = name.replacen("  ", " ", 1);
+    }
+
+    fn exclaim(title: &mut String) {
+        title.push('!');
+    }
+
+    #[derive(Debug, Clone, PartialEq, Validify)]
+    struct Profile {
+        #[modify(trim, custom(collapse_spaces))]
+        #[validate(length(min = 1, max = 8))]
+        name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Validify)]
+    struct Post {
+        #[modify(custom(exclaim))]
+        title: String,
+    }
+
+    fn not_converged(errors: &ValidationErrors) -> bool {
+        errors
+            .schema_errors()
+            .iter()
+            .any(|error| error.code() == "not_converged")
+    }
+
+    #[test]
+    fn modify_until_stable() {
+        let mut profile = Profile {
+            name: String::from(" a     b "),
+        };
+        modify_until_converged(&mut profile, 8).expect("profile rejected");
+        assert_eq!(profile.name, "a b");
+
+        let mut profile = Profile {
+            name: String::from(" a     b "),
+        };
+        let errors = modify_until_converged(&mut profile, 2).expect_err("converged too soon");
+        assert!(not_converged(&errors));
+
+        let mut profile = Profile {
+            name: String::from("  "),
+        };
+        let errors = modify_until_converged(&mut profile, 8).expect_err("empty name accepted");
+        assert!(!not_converged(&errors));
+        assert!(!errors.field_errors().is_empty());
+
+        let mut post = Post {
+            title: String::from("Hi"),
+        };
+        let errors = modify_until_converged(&mut post, 4).expect_err("diverging title accepted");
+        assert!(not_converged(&errors));
+        assert_eq!(post.title, "Hi!!!!");
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn converged_rejects_diverging_data() {
+        use axum::body::Body;
+        use axum::response::IntoResponse;
+        use axum::Json;
+
+        #[derive(Debug, Clone, PartialEq, Validify, serde::Deserialize)]
+        struct Comment {
+            #[modify(trim, custom(collapse_spaces))]
+            #[validate(length(max = 16))]
+            text: String,
+            #[modify(custom(exclaim))]
+            tone: Option<String>,
+        }
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+
+        let Converged(Json(comment)) = Converged::<Json<Comment>, 8>::from_request(
+            request(r#"{ "text": " nice     post ", "tone": null }"#),
+            &(),
+        )
+        .await
+        .expect("converging comment rejected");
+        assert_eq!(comment.text, "nice post");
+
+        let rejection = Converged::<Json<Comment>, 8>::from_request(
+            request(r#"{ "text": "ok", "tone": "happy" }"#),
+            &(),
+        )
+        .await
+        .expect_err("diverging comment accepted");
+        assert!(matches!(
+            &rejection,
+            ValidifyRejection::Valid(errors) if not_converged(errors)
+        ));
+        assert_eq!(
+            rejection.into_response().status(),
+            crate::VALIDATION_ERROR_STATUS
+        );
+    }
+}
@@ -0,0 +1,368 @@
+//! # Traced modifications
+//!
+//! ## Feature
+//!
+//! Enable the `modify_trace` feature to use `TracedModified<E>`.
+//!
+//! ## Usage
+//!
+//! `TracedModified<E>` modifies data like `Modified<E>`, and also records which fields the `modify` attributes
+//! changed, to debug normalization:
+//!
+//! 1. Derive `Serialize` for your data type, in addition to `Validify`.
+//! 2. In your handler function, use `TracedModified<E>` as the extractor, e.g. `TracedModified<Json<T>>`.
+//!
+//! The data is serialized to JSON before and after the modification, and every changed field is recorded
+//! with its value before and after, as a `Modification`. `validify` runs the modifiers field by field,
+//! in declaration order, so the trace lists the fields in the order they were modified. A field whose value
+//! is unchanged by its modifiers, e.g. already trimmed, is not recorded.
+//!
+//! Fields are named by their path, e.g. `items[2].name`, following the serialized form of the data.
+//! Data that cannot be serialized to JSON yields an empty trace.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use axum::routing::post;
+//! use axum::Json;
+//! use axum::Router;
+//! use axum_valid::modify_trace::{ModifyTrace, TracedModified};
+//! use serde::{Deserialize, Serialize};
+//! use validify::Validify;
+//!
+//! #[derive(Validify, Deserialize, Serialize)]
+//! pub struct Signup {
+//!     #[modify(trim, lowercase)]
+//!     pub email: String,
+//!     #[modify(trim)]
+//!     pub name: String,
+//! }
+//!
+//! async fn handler(TracedModified(Json(signup), trace): TracedModified<Json<Signup>>) -> Json<ModifyTrace> {
+//!     assert_eq!(signup.email, signup.email.trim().to_lowercase());
+//!     Json(trace)
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new().route("/signup", post(handler));
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::HasModify;
+use axum::async_trait;
+use axum::extract::{FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use validify::Modify;
+
+/// A field changed by the modifiers.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Modification {
+    /// Path of the field, e.g. `items[2].name`. Empty when the whole data was replaced.
+    pub field: String,
+    /// Value of the field before modification.
+    pub before: Value,
+    /// Value of the field after modification.
+    pub after: Value,
+}
+
+impl Display for Modification {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.before, self.after)
+    }
+}
+
+/// The fields changed by the modifiers, in the order they were modified.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct ModifyTrace(pub Vec<Modification>);
+
+impl ModifyTrace {
+    /// Returns `true` if no field was changed.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over the modifications, in the order they were applied.
+    pub fn iter(&self) -> impl Iterator<Item = &Modification> {
+        self.0.iter()
+    }
+
+    /// Returns the modification of the given field, if it was changed.
+    pub fn get(&self, field: &str) -> Option<&Modification> {
+        self.0
+            .iter()
+            .find(|modification| modification.field == field)
+    }
+
+    /// Modifies `data`, recording the changed fields.
+    pub fn record<T: Modify + Serialize>(data: &mut T) -> Self {
+        let before = Tree::of(data);
+        data.modify();
+        let mut trace = Self::default();
+        if let (Some(before), Some(after)) = (before, Tree::of(data)) {
+            diff(before, after, String::new(), &mut trace.0);
+        }
+        trace
+    }
+}
+
+impl Display for ModifyTrace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, modification) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{modification}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A JSON document keeping the order of object keys, which `serde_json::Value` sorts.
+#[derive(Debug, PartialEq)]
+enum Tree {
+    Leaf(Value),
+    Seq(Vec<Tree>),
+    Map(Vec<(String, Tree)>),
+}
+
+impl Tree {
+    fn of<T: Serialize>(data: &T) -> Option<Self> {
+        let json = serde_json::to_string(data).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    fn into_value(self) -> Value {
+        match self {
+            Tree::Leaf(value) => value,
+            Tree::Seq(items) => Value::Array(items.into_iter().map(Tree::into_value).collect()),
+            Tree::Map(fields) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Tree {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TreeVisitor;
+
+        impl<'de> Visitor<'de> for TreeVisitor {
+            type Value = Tree;
+
+            fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+                write!(f, "any JSON value")
+            }
+
+            fn visit_bool<E>(self, v: bool) -> Result<Tree, E> {
+                Ok(Tree::Leaf(Value::from(v)))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Tree, E> {
+                Ok(Tree::Leaf(Value::from(v)))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Tree, E> {
+                Ok(Tree::Leaf(Value::from(v)))
+            }
+
+            fn visit_f64<E>(self, v: f64) -> Result<Tree, E> {
+                Ok(Tree::Leaf(Value::from(v)))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Tree, E> {
+                Ok(Tree::Leaf(Value::from(v)))
+            }
+
+            fn visit_unit<E>(self) -> Result<Tree, E> {
+                Ok(Tree::Leaf(Value::Null))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Tree, A::Error> {
+                let mut items = Vec::new();
+                while let Some(item) = seq.next_element()? {
+                    items.push(item);
+                }
+                Ok(Tree::Seq(items))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Tree, A::Error> {
+                let mut fields = Vec::new();
+                while let Some(field) = map.next_entry()? {
+                    fields.push(field);
+                }
+                Ok(Tree::Map(fields))
+            }
+        }
+
+        deserializer.deserialize_any(TreeVisitor)
+    }
+}
+
+/// Records the leaves that differ between `before` and `after`, in document order.
+fn diff(before: Tree, after: Tree, path: String, trace: &mut Vec<Modification>) {
+    match (before, after) {
+        (Tree::Map(before), Tree::Map(after))
+            if before.len() == after.len()
+                && before.iter().zip(&after).all(|((b, _), (a, _))| a == b) =>
+        {
+            for ((key, before), (_, after)) in before.into_iter().zip(after) {
+                let path = if path.is_empty() {
+                    key
+                } else {
+                    format!("{path}.{key}")
+                };
+                diff(before, after, path, trace);
+            }
+        }
+        (Tree::Seq(before), Tree::Seq(after)) if before.len() == after.len() => {
+            for (index, (before, after)) in before.into_iter().zip(after).enumerate() {
+                diff(before, after, format!("{path}[{index}]"), trace);
+            }
+        }
+        (before, after) if before != after => trace.push(Modification {
+            field: path,
+            before: before.into_value(),
+            after: after.into_value(),
+        }),
+        _ => {}
+    }
+}
+
+/// # `TracedModified` data extractor
+///
+/// `TracedModified` modifies the data of the inner extractor like `Modified`,
+/// and holds the inner extractor and the trace of the modifications.
+///
+#[derive(Debug, Clone, Default)]
+pub struct TracedModified<E>(pub E, pub ModifyTrace);
+
+impl<E> TracedModified<E> {
+    /// Consumes the `TracedModified` and returns the inner extractor and the trace.
+    pub fn into_inner(self) -> (E, ModifyTrace) {
+        (self.0, self.1)
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for TracedModified<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for TracedModified<Extractor>
+where
+    State: Send + Sync,
+    Extractor: HasModify + FromRequest<State>,
+    Extractor::Modify: Serialize,
+{
+    type Rejection = <Extractor as FromRequest<State>>::Rejection;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let mut inner = Extractor::from_request(req, state).await?;
+        let trace = ModifyTrace::record(inner.get_modify());
+        Ok(TracedModified(inner, trace))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequestParts<State> for TracedModified<Extractor>
+where
+    State: Send + Sync,
+    Extractor: HasModify + FromRequestParts<State>,
+    Extractor::Modify: Serialize,
+{
+    type Rejection = <Extractor as FromRequestParts<State>>::Rejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let mut inner = Extractor::from_request_parts(parts, state).await?;
+        let trace = ModifyTrace::record(inner.get_modify());
+        Ok(TracedModified(inner, trace))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::Json;
+    use serde::Deserialize;
+    use serde_json::json;
+    use validify::Validify;
+
+    #[derive(Debug, Validify, Deserialize, Serialize)]
+    struct Tag {
+        #[modify(trim, lowercase)]
+        name: String,
+    }
+
+    #[derive(Debug, Validify, Deserialize, Serialize)]
+    struct Signup {
+        #[modify(trim, lowercase)]
+        email: String,
+        #[modify(trim)]
+        name: String,
+        #[modify(trim)]
+        country: String,
+        #[validify]
+        tags: Vec<Tag>,
+    }
+
+    #[tokio::test]
+    async fn traced_modified() {
+        let request = Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({
+                    "email": " Jane@Example.COM",
+                    "name": "Jane ",
+                    "country": "FR",
+                    "tags": [{ "name": "rust" }, { "name": " Axum" }]
+                })
+                .to_string(),
+            ))
+            .expect("invalid request");
+
+        let TracedModified(Json(signup), trace) =
+            TracedModified::<Json<Signup>>::from_request(request, &())
+                .await
+                .expect("rejected");
+        assert_eq!(signup.email, "jane@example.com");
+        assert_eq!(
+            trace.iter().map(|m| m.field.as_str()).collect::<Vec<_>>(),
+            ["email", "name", "tags[1].name"]
+        );
+        assert_eq!(
+            trace.get("tags[1].name"),
+            Some(&Modification {
+                field: String::from("tags[1].name"),
+                before: json!(" Axum"),
+                after: json!("axum"),
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(&trace).expect("unserializable trace")[0],
+            json!({ "field": "email", "before": " Jane@Example.COM", "after": "jane@example.com" })
+        );
+    }
+}
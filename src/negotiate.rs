@@ -0,0 +1,394 @@
+//! # Content negotiation of validation errors
+//!
+//! ## Feature
+//!
+//! Enable the `negotiate` feature to use `Negotiated<E>`.
+//!
+//! ## Usage
+//!
+//! Validation errors are rendered as JSON by default. When an API serves clients using other formats,
+//! `Negotiated<E>` renders the validation errors of the inner validation extractor in the format preferred
+//! by the request's `Accept` header instead:
+//!
+//! * `application/json` (the default, also used for `*/*` and unsupported media types)
+//! * `application/yaml` (or `application/x-yaml`, `text/yaml`)
+//! * `application/msgpack` (or `application/x-msgpack`)
+//!
+//! The `Accept` header is read before the inner extractor runs, since the rejection cannot see the request.
+//! Media types are ranked by their `q` parameter, the first one listed winning ties; media types with `q=0`
+//! are never chosen.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Router;
+//!     use axum_serde::MsgPack;
+//!     use axum_valid::negotiate::Negotiated;
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/readings", post(handler))
+//!     }
+//!
+//!     async fn handler(Negotiated(Valid(MsgPack(reading))): Negotiated<Valid<MsgPack<Reading>>>) {
+//!         assert!(reading.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Reading {
+//!         #[validate(range(min = -50.0, max = 150.0))]
+//!         pub celsius: f64,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::{ValidationRejection, VALIDATION_ERROR_STATUS};
+use axum::async_trait;
+use axum::extract::{FromRequest, FromRequestParts, Request};
+use axum::http::header::ACCEPT;
+use axum::http::request::Parts;
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use axum_serde::{MsgPack, Yaml};
+use serde::Serialize;
+use serde_json::Value;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// Formats validation errors can be rendered in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ErrorFormat {
+    /// `application/json`
+    #[default]
+    Json,
+    /// `application/yaml`
+    Yaml,
+    /// `application/msgpack`
+    MsgPack,
+}
+
+impl ErrorFormat {
+    /// Returns the format of the given media type, ignoring its parameters.
+    pub fn from_media_type(media_type: &str) -> Option<Self> {
+        let essence = media_type.split(';').next().unwrap_or_default().trim();
+        match essence.to_ascii_lowercase().as_str() {
+            "application/json" | "application/*" | "*/*" => Some(ErrorFormat::Json),
+            "application/yaml" | "application/x-yaml" | "text/yaml" => Some(ErrorFormat::Yaml),
+            "application/msgpack" | "application/x-msgpack" => Some(ErrorFormat::MsgPack),
+            _ => None,
+        }
+    }
+
+    /// Returns the preferred format of the `Accept` header, `Json` when there is none.
+    pub fn from_accept(headers: &HeaderMap) -> Self {
+        let mut preferred = None::<(f32, Self)>;
+        let media_types = headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','));
+        for media_type in media_types {
+            let Some(format) = Self::from_media_type(media_type) else {
+                continue;
+            };
+            let quality = media_type
+                .split(';')
+                .skip(1)
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if quality > 0.0 && preferred.is_none_or(|(best, _)| quality > best) {
+                preferred = Some((quality, format));
+            }
+        }
+        preferred.map(|(_, format)| format).unwrap_or_default()
+    }
+
+    /// Renders `errors` in this format with `VALIDATION_ERROR_STATUS`.
+    pub fn render<V: Serialize + ?Sized>(self, errors: &V) -> Response {
+        let mut errors = serde_json::to_value(errors).unwrap_or(Value::Null);
+        crate::sort_by_code(&mut errors);
+        match self {
+            ErrorFormat::Json => (VALIDATION_ERROR_STATUS, Json(errors)).into_response(),
+            ErrorFormat::Yaml => (VALIDATION_ERROR_STATUS, Yaml(errors)).into_response(),
+            ErrorFormat::MsgPack => (VALIDATION_ERROR_STATUS, MsgPack(errors)).into_response(),
+        }
+    }
+}
+
+/// Validation errors along with the format they are rendered in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NegotiatedErrors {
+    /// The format preferred by the request.
+    pub format: ErrorFormat,
+    /// The validation errors.
+    pub errors: Value,
+}
+
+impl Display for NegotiatedErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.errors)
+    }
+}
+
+impl Error for NegotiatedErrors {}
+
+impl IntoResponse for NegotiatedErrors {
+    fn into_response(self) -> Response {
+        self.format.render(&self.errors)
+    }
+}
+
+/// # `Negotiated` data extractor
+///
+/// `Negotiated` runs the inner validation extractor, rendering its validation errors in the format
+/// preferred by the `Accept` header of the request.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Negotiated<E>(pub E);
+
+impl<E> Deref for Negotiated<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for Negotiated<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for Negotiated<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> Negotiated<E> {
+    /// Consumes the `Negotiated` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for Negotiated<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `NegotiatedRejection` is returned when the `Negotiated` extractor fails.
+///
+#[derive(Debug)]
+pub enum NegotiatedRejection<E> {
+    /// `Valid` variant captures the validation errors, rendered in the negotiated format.
+    Valid(NegotiatedErrors),
+    /// `Inner` variant represents the other errors that might occur within the inner extractor.
+    Inner(E),
+}
+
+impl<E: Display> Display for NegotiatedRejection<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NegotiatedRejection::Valid(errors) => write!(f, "{errors}"),
+            NegotiatedRejection::Inner(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for NegotiatedRejection<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NegotiatedRejection::Valid(errors) => Some(errors),
+            NegotiatedRejection::Inner(error) => Some(error),
+        }
+    }
+}
+
+impl<E: IntoResponse> IntoResponse for NegotiatedRejection<E> {
+    fn into_response(self) -> Response {
+        match self {
+            NegotiatedRejection::Valid(errors) => errors.into_response(),
+            NegotiatedRejection::Inner(error) => error.into_response(),
+        }
+    }
+}
+
+impl<E> NegotiatedRejection<E> {
+    fn from_rejection<V: Serialize>(
+        rejection: ValidationRejection<V, E>,
+        format: ErrorFormat,
+    ) -> Self {
+        match rejection {
+            ValidationRejection::Valid(errors) => NegotiatedRejection::Valid(NegotiatedErrors {
+                format,
+                errors: serde_json::to_value(&errors).unwrap_or(Value::Null),
+            }),
+            ValidationRejection::Inner(error) => NegotiatedRejection::Inner(error),
+        }
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, V, E> FromRequest<State> for Negotiated<Extractor>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State, Rejection = ValidationRejection<V, E>>,
+    V: Serialize,
+    E: IntoResponse,
+{
+    type Rejection = NegotiatedRejection<E>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let format = ErrorFormat::from_accept(req.headers());
+        Extractor::from_request(req, state)
+            .await
+            .map(Negotiated)
+            .map_err(|rejection| NegotiatedRejection::from_rejection(rejection, format))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, V, E> FromRequestParts<State> for Negotiated<Extractor>
+where
+    State: Send + Sync,
+    Extractor: FromRequestParts<State, Rejection = ValidationRejection<V, E>>,
+    V: Serialize,
+    E: IntoResponse,
+{
+    type Rejection = NegotiatedRejection<E>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let format = ErrorFormat::from_accept(&parts.headers);
+        Extractor::from_request_parts(parts, state)
+            .await
+            .map(Negotiated)
+            .map_err(|rejection| NegotiatedRejection::from_rejection(rejection, format))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn accept(value: &'static str) -> ErrorFormat {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static(value));
+        ErrorFormat::from_accept(&headers)
+    }
+
+    #[test]
+    fn preferred_format() {
+        assert_eq!(
+            ErrorFormat::from_accept(&HeaderMap::new()),
+            ErrorFormat::Json
+        );
+        assert_eq!(accept("application/msgpack"), ErrorFormat::MsgPack);
+        assert_eq!(accept("text/yaml; charset=utf-8"), ErrorFormat::Yaml);
+        assert_eq!(accept("text/html, */*;q=0.8"), ErrorFormat::Json);
+        assert_eq!(
+            accept("application/json;q=0.5, application/x-msgpack"),
+            ErrorFormat::MsgPack
+        );
+        assert_eq!(
+            accept("application/yaml, application/msgpack"),
+            ErrorFormat::Yaml
+        );
+        assert_eq!(
+            accept("application/msgpack;q=0, text/plain"),
+            ErrorFormat::Json
+        );
+    }
+
+    #[cfg(feature = "validator")]
+    #[tokio::test]
+    async fn negotiated_error_body() {
+        use crate::Valid;
+        use axum::body::Body;
+        use axum::http::header::CONTENT_TYPE;
+        use std::collections::BTreeMap;
+        use validator::Validate;
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        struct Reading {
+            #[validate(range(min = -50.0, max = 150.0))]
+            celsius: f64,
+        }
+
+        let request = |accept: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header(ACCEPT, accept)
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(r#"{ "celsius": 500 }"#))
+                .expect("invalid request")
+        };
+        let body = |response: Response| async {
+            axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .expect("failed to read body")
+        };
+
+        for (accept, content_type) in [
+            ("application/msgpack", "application/msgpack"),
+            ("application/yaml", "application/yaml"),
+            ("application/json", "application/json"),
+        ] {
+            let response = Negotiated::<Valid<Json<Reading>>>::from_request(request(accept), &())
+                .await
+                .expect_err("invalid reading accepted")
+                .into_response();
+            assert_eq!(response.status(), VALIDATION_ERROR_STATUS);
+            assert_eq!(response.headers()[CONTENT_TYPE], content_type);
+            let bytes = body(response).await;
+            let errors: BTreeMap<String, Value> = match content_type {
+                "application/msgpack" => rmp_serde::from_slice(&bytes).expect("invalid msgpack"),
+                "application/yaml" => serde_yaml::from_slice(&bytes).expect("invalid yaml"),
+                _ => serde_json::from_slice(&bytes).expect("invalid json"),
+            };
+            assert_eq!(errors["celsius"][0]["code"], "range");
+        }
+
+        assert!(matches!(
+            Negotiated::<Valid<Json<Reading>>>::from_request(
+                Request::builder()
+                    .uri("/")
+                    .header(ACCEPT, "application/msgpack")
+                    .body(Body::from("{}"))
+                    .expect("invalid request"),
+                &()
+            )
+            .await,
+            Err(NegotiatedRejection::Inner(_))
+        ));
+    }
+}
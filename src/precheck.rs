@@ -0,0 +1,396 @@
+//! # Pre-checks on raw JSON bodies
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use the pre-check extractors.
+//!
+//! A pre-check buffers the request body and inspects (or rewrites) the raw JSON document before delegating to an
+//! inner extractor, which is usually one of the validation extractors, e.g. `RejectNonFinite<Valid<Json<T>>>`.
+//! This catches problems that are lost or turned into opaque errors once the body has been deserialized.
+//!
+//! Failures are reported as field-level errors with `VALIDATION_ERROR_STATUS`, just like validation errors.
+//! Bodies that are not valid JSON are passed through untouched, so the inner extractor can reject them itself.
+//!
+//! ## Modules
+//!
+//! * [`big_int`] : `CoerceBigInts<E>`
+//! * [`control_chars`] : `StripControl<E>`
+//! * [`defaults`] : `FillNulls<E>`
+//! * [`deprecated_fields`] : `MigrateDeprecated<E>`
+//! * [`depth`] : `MaxDepth<E, N>`, `MaxArrayDepth<E, N>`
+//! * [`duplicate_keys`] : `RejectDuplicateKeys<E>`
+//! * [`empty_arrays`] : `NormalizeEmptyArrays<E>`
+//! * [`enum_case`] : `CaseInsensitiveEnums<E>`, `RejectUnknownVariants<E>`
+//! * [`field_count`] : `FieldCount<E, N>`
+//! * [`html`] : `SanitizeHtml<E>`
+//! * [`locale`] : `NormalizeLocale<E>`
+//! * [`non_finite`] : `RejectNonFinite<E>`
+//! * [`nullable`] : `RejectNulls<E>`
+//! * [`precision`] : `RejectPrecisionLoss<E>`
+//! * [`required`] : `RequireFields<E>`
+//! * [`reserved`] : `RejectReserved<E>`
+//! * [`string_bytes`] : `MaxStringBytes<E, N>`
+//! * [`string_count`] : `StringFieldCount<E, N>`
+//! * [`trim`] : `TrimAll<E>`
+//!
+
+pub mod big_int;
+pub mod control_chars;
+pub mod defaults;
+pub mod deprecated_fields;
+pub mod depth;
+pub mod duplicate_keys;
+pub mod empty_arrays;
+pub mod enum_case;
+pub mod field_count;
+pub mod html;
+pub mod locale;
+pub mod non_finite;
+pub mod nullable;
+pub mod precision;
+pub mod required;
+pub mod reserved;
+pub mod string_bytes;
+pub mod string_count;
+pub mod trim;
+
+pub use big_int::{BigIntFields, CoerceBigInts};
+pub use control_chars::{ControlChars, StripControl};
+pub use defaults::{DefaultProvider, Defaults, FillNulls};
+pub use deprecated_fields::{DeprecatedFields, MigrateDeprecated};
+pub use depth::{MaxArrayDepth, MaxDepth};
+pub use duplicate_keys::RejectDuplicateKeys;
+pub use empty_arrays::{EmptyArray, EmptyArrays, NormalizeEmptyArrays};
+pub use enum_case::{CaseInsensitiveEnums, EnumFields, RejectUnknownVariants};
+pub use field_count::FieldCount;
+pub use html::{HtmlFields, SanitizeHtml};
+pub use locale::{DateOrder, Locale, LocaleFields, NormalizeLocale};
+pub use non_finite::RejectNonFinite;
+pub use nullable::{NullableFields, RejectNulls};
+pub use precision::{Precision, PrecisionFields, RejectPrecisionLoss};
+pub use required::{RequireFields, RequiredFields};
+pub use reserved::{RejectReserved, ReservedFields};
+pub use string_bytes::MaxStringBytes;
+pub use string_count::StringFieldCount;
+pub use trim::TrimAll;
+
+use axum::extract::rejection::BytesRejection;
+use axum::extract::{FromRequest, Request};
+use axum::response::{IntoResponse, Response};
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt::Display;
+
+/// Path used for errors that concern the whole document rather than a single field.
+pub const ROOT_PATH: &str = "__all__";
+
+/// A field-level error found by a pre-check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrecheckError {
+    /// Path of the offending field, e.g. `items[2].price`, or [`ROOT_PATH`].
+    pub path: String,
+    /// Machine-readable error code.
+    pub code: Cow<'static, str>,
+    /// Human-readable error message.
+    pub message: Cow<'static, str>,
+}
+
+impl PrecheckError {
+    /// Create a new `PrecheckError`. An empty `path` refers to the whole document.
+    pub fn new(
+        path: impl Into<String>,
+        code: impl Into<Cow<'static, str>>,
+        message: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        let path = path.into();
+        Self {
+            path: if path.is_empty() {
+                String::from(ROOT_PATH)
+            } else {
+                path
+            },
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for PrecheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl Error for PrecheckError {}
+
+/// The errors found by a pre-check.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrecheckErrors(pub Vec<PrecheckError>);
+
+impl PrecheckErrors {
+    /// Returns `true` if no error was found.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Adds an error.
+    pub fn add(&mut self, error: PrecheckError) {
+        self.0.push(error);
+    }
+
+    /// Returns the errors found for the given path.
+    pub fn get<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a PrecheckError> + 'a {
+        self.0.iter().filter(move |error| error.path == path)
+    }
+}
+
+impl From<PrecheckError> for PrecheckErrors {
+    fn from(error: PrecheckError) -> Self {
+        Self(vec![error])
+    }
+}
+
+impl Display for PrecheckErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, error) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for PrecheckErrors {}
+
+/// Serialized like `validator`'s field errors: a map from path to the list of errors found at that path.
+#[cfg(feature = "into_json")]
+impl serde::Serialize for PrecheckErrors {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        use std::collections::BTreeMap;
+
+        #[derive(serde::Serialize)]
+        struct Entry<'a> {
+            code: &'a str,
+            message: &'a str,
+        }
+
+        let mut paths: BTreeMap<&str, Vec<Entry>> = BTreeMap::new();
+        for error in &self.0 {
+            paths.entry(&error.path).or_default().push(Entry {
+                code: &error.code,
+                message: &error.message,
+            });
+        }
+        let mut map = serializer.serialize_map(Some(paths.len()))?;
+        for (path, entries) in paths {
+            map.serialize_entry(path, &entries)?;
+        }
+        map.end()
+    }
+}
+
+impl IntoResponse for PrecheckErrors {
+    fn into_response(self) -> Response {
+        crate::validation_errors_response(&self)
+    }
+}
+
+/// `PrecheckRejection` is returned when a pre-check extractor fails.
+///
+#[derive(Debug)]
+pub enum PrecheckRejection<E> {
+    /// `Valid` variant captures the errors found by the pre-check.
+    Valid(PrecheckErrors),
+    /// `Body` variant represents errors that occur while buffering the request body.
+    Body(BytesRejection),
+    /// `Inner` variant represents potential errors that might occur within the inner extractor.
+    Inner(E),
+}
+
+impl<E: Display> Display for PrecheckRejection<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrecheckRejection::Valid(error) => write!(f, "{error}"),
+            PrecheckRejection::Body(error) => write!(f, "{error}"),
+            PrecheckRejection::Inner(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for PrecheckRejection<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PrecheckRejection::Valid(pe) => Some(pe),
+            PrecheckRejection::Body(be) => Some(be),
+            PrecheckRejection::Inner(e) => Some(e),
+        }
+    }
+}
+
+impl<E: IntoResponse> IntoResponse for PrecheckRejection<E> {
+    fn into_response(self) -> Response {
+        match self {
+            PrecheckRejection::Valid(pe) => pe.into_response(),
+            PrecheckRejection::Body(be) => be.into_response(),
+            PrecheckRejection::Inner(e) => e.into_response(),
+        }
+    }
+}
+
+/// Buffers the request body and runs `check` on it before handing the request to the inner extractor.
+pub(crate) async fn run<State, Extractor>(
+    req: Request,
+    state: &State,
+    check: impl FnOnce(&[u8]) -> PrecheckErrors,
+) -> Result<Extractor, PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+{
+    let (req, bytes) = crate::body::buffer(req, state)
+        .await
+        .map_err(PrecheckRejection::Body)?;
+    let errors = check(&bytes);
+    if !errors.is_empty() {
+        return Err(PrecheckRejection::Valid(errors));
+    }
+    Extractor::from_request(req, state)
+        .await
+        .map_err(PrecheckRejection::Inner)
+}
+
+/// Appends an object key to a field path.
+pub(crate) fn push_key(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_owned()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+/// Appends an array index to a field path.
+pub(crate) fn push_index(path: &str, index: usize) -> String {
+    format!("{path}[{index}]")
+}
+
+/// Rewrites the string values of a JSON document, copying every other byte as is, so numbers keep their
+/// exact text instead of being rounded through `serde_json::Value`.
+///
+/// `rewrite` is called with the path (e.g. `items[1].name`), the field (the path without array indices)
+/// and the text of each string value, and returns its replacement if it changed. Object keys are left
+/// untouched. Returns `None` if the document is not valid JSON, or if no string was replaced.
+pub(crate) fn rewrite_strings(
+    bytes: &[u8],
+    mut rewrite: impl FnMut(&str, &str, &str) -> Option<serde_json::Value>,
+) -> Option<Vec<u8>> {
+    serde_json::from_slice::<serde::de::IgnoredAny>(bytes).ok()?;
+    let mut strings = Strings {
+        bytes,
+        position: 0,
+        rewrite: &mut rewrite,
+        replacements: Vec::new(),
+    };
+    strings.value("", "")?;
+    if strings.replacements.is_empty() {
+        return None;
+    }
+    let mut rewritten = Vec::with_capacity(bytes.len());
+    let mut end = 0;
+    for (range, replacement) in strings.replacements {
+        rewritten.extend_from_slice(&bytes[end..range.start]);
+        serde_json::to_writer(&mut rewritten, &replacement).ok()?;
+        end = range.end;
+    }
+    rewritten.extend_from_slice(&bytes[end..]);
+    Some(rewritten)
+}
+
+/// Scans a valid JSON document, collecting the replacements of its string values.
+struct Strings<'a, 'r> {
+    bytes: &'a [u8],
+    position: usize,
+    rewrite: &'r mut dyn FnMut(&str, &str, &str) -> Option<serde_json::Value>,
+    replacements: Vec<(std::ops::Range<usize>, serde_json::Value)>,
+}
+
+impl Strings<'_, '_> {
+    /// Skips whitespace, returning the next byte.
+    fn peek(&mut self) -> Option<u8> {
+        while self.bytes.get(self.position)?.is_ascii_whitespace() {
+            self.position += 1;
+        }
+        self.bytes.get(self.position).copied()
+    }
+
+    /// Reads the string literal starting at the position, returning its range and its text.
+    fn string(&mut self) -> Option<(std::ops::Range<usize>, String)> {
+        let start = self.position;
+        self.position += 1;
+        loop {
+            match self.bytes.get(self.position)? {
+                b'\\' => self.position += 2,
+                b'"' => break,
+                _ => self.position += 1,
+            }
+        }
+        self.position += 1;
+        let text = serde_json::from_slice(&self.bytes[start..self.position]).ok()?;
+        Some((start..self.position, text))
+    }
+
+    fn value(&mut self, path: &str, field: &str) -> Option<()> {
+        match self.peek()? {
+            b'{' => {
+                self.position += 1;
+                loop {
+                    match self.peek()? {
+                        b'}' => break,
+                        b',' => self.position += 1,
+                        _ => {
+                            let (_, key) = self.string()?;
+                            // Skips the colon
+                            self.peek()?;
+                            self.position += 1;
+                            self.value(&push_key(path, &key), &push_key(field, &key))?;
+                        }
+                    }
+                }
+                self.position += 1;
+            }
+            b'[' => {
+                self.position += 1;
+                let mut index = 0;
+                loop {
+                    match self.peek()? {
+                        b']' => break,
+                        b',' => self.position += 1,
+                        _ => {
+                            self.value(&push_index(path, index), field)?;
+                            index += 1;
+                        }
+                    }
+                }
+                self.position += 1;
+            }
+            b'"' => {
+                let (range, text) = self.string()?;
+                if let Some(replacement) = (self.rewrite)(path, field, &text) {
+                    self.replacements.push((range, replacement));
+                }
+            }
+            _ => {
+                while self
+                    .bytes
+                    .get(self.position)
+                    .is_some_and(|b| !matches!(b, b',' | b']' | b'}') && !b.is_ascii_whitespace())
+                {
+                    self.position += 1;
+                }
+            }
+        }
+        Some(())
+    }
+}
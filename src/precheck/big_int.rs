@@ -0,0 +1,320 @@
+//! # Coercion of big integers sent as strings
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `CoerceBigInts<E>`.
+//!
+//! ## Usage
+//!
+//! JavaScript numbers lose precision above `2^53`, so JavaScript clients usually send big integers,
+//! such as 64-bit ids, as strings. `CoerceBigInts` turns those strings back into JSON numbers,
+//! so they can be deserialized into `u64` or `i64` fields and validated like any other number:
+//!
+//! 1. Build a `BigIntFields` policy listing the integer fields which may be sent as strings, and make it
+//!    available from your router state (implement `FromRef<YourState>` for it, or use it as the state directly).
+//! 2. In your handler function, wrap your extractor with `CoerceBigInts`, e.g. `CoerceBigInts<Valid<Json<T>>>`.
+//!
+//! Nested fields are named using dots, e.g. `owner.id`. Array indices are left out, so `items.id`
+//! matches the `id` field of every element of `items`, and `ids` matches every element of the `ids` array.
+//! Numbers are left untouched, while strings in those fields which are not integers fitting in
+//! 64 bits are reported with the `integer` code.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::{BigIntFields, CoerceBigInts};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new()
+//!             .route("/json", post(handler))
+//!             .with_state(BigIntFields::new(["id", "follower_ids"]))
+//!     }
+//!
+//!     async fn handler(CoerceBigInts(Valid(Json(user))): CoerceBigInts<Valid<Json<User>>>) {
+//!         assert!(user.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct User {
+//!         #[validate(range(min = 1))]
+//!         pub id: u64,
+//!         #[validate(length(max = 100))]
+//!         pub follower_ids: Vec<u64>,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::{push_index, push_key, PrecheckError, PrecheckErrors, PrecheckRejection};
+use axum::async_trait;
+use axum::body::Body;
+use axum::extract::{FromRef, FromRequest, Request};
+use axum::http::header::CONTENT_LENGTH;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// The set of integer fields `CoerceBigInts` accepts as strings.
+///
+/// `CoerceBigInts` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BigIntFields(Arc<BTreeSet<String>>);
+
+impl BigIntFields {
+    /// Create a policy for the given fields. Nested fields are named using dots, e.g. `owner.id`.
+    pub fn new<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self(Arc::new(fields.into_iter().map(Into::into).collect()))
+    }
+
+    /// Returns `true` if the policy accepts the given field as a string.
+    pub fn contains(&self, field: &str) -> bool {
+        self.0.contains(field)
+    }
+
+    /// Replaces the strings of the listed fields with numbers in place, returning `true` if any was replaced.
+    fn coerce(&self, document: &mut Value, errors: &mut PrecheckErrors) -> bool {
+        self.visit(document, "", "", errors)
+    }
+
+    /// `path` is reported in errors, `field` (which has no array indices) is matched against the policy.
+    fn visit(
+        &self,
+        value: &mut Value,
+        path: &str,
+        field: &str,
+        errors: &mut PrecheckErrors,
+    ) -> bool {
+        match value {
+            Value::String(s) if self.contains(field) => {
+                let number = s
+                    .trim()
+                    .parse::<u64>()
+                    .map(Value::from)
+                    .or_else(|_| s.trim().parse::<i64>().map(Value::from));
+                match number {
+                    Ok(number) => {
+                        *value = number;
+                        true
+                    }
+                    Err(_) => {
+                        errors.add(PrecheckError::new(
+                            path,
+                            "integer",
+                            "must be a 64-bit integer",
+                        ));
+                        false
+                    }
+                }
+            }
+            Value::Array(items) => {
+                items
+                    .iter_mut()
+                    .enumerate()
+                    .fold(false, |changed, (index, item)| {
+                        self.visit(item, &push_index(path, index), field, errors) | changed
+                    })
+            }
+            Value::Object(fields) => fields.iter_mut().fold(false, |changed, (key, value)| {
+                self.visit(value, &push_key(path, key), &push_key(field, key), errors) | changed
+            }),
+            _ => false,
+        }
+    }
+}
+
+/// # `CoerceBigInts` pre-check extractor
+///
+/// `CoerceBigInts` turns the strings of the integer fields listed in the `BigIntFields` policy
+/// taken from the state into JSON numbers, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CoerceBigInts<E>(pub E);
+
+impl<E> Deref for CoerceBigInts<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for CoerceBigInts<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for CoerceBigInts<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> CoerceBigInts<E> {
+    /// Consumes the `CoerceBigInts` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for CoerceBigInts<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for CoerceBigInts<Extractor>
+where
+    State: Send + Sync,
+    BigIntFields: FromRef<State>,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let policy = BigIntFields::from_ref(state);
+        let (req, bytes) = crate::body::buffer(req, state)
+            .await
+            .map_err(PrecheckRejection::Body)?;
+        let mut errors = PrecheckErrors::default();
+        let coerced = serde_json::from_slice::<Value>(&bytes)
+            .ok()
+            .and_then(|mut document| {
+                policy
+                    .coerce(&mut document, &mut errors)
+                    .then_some(document)
+            })
+            .and_then(|document| serde_json::to_vec(&document).ok());
+        if !errors.is_empty() {
+            return Err(PrecheckRejection::Valid(errors));
+        }
+        let req = match coerced {
+            Some(coerced) => {
+                let (mut parts, _) = req.into_parts();
+                parts.headers.remove(CONTENT_LENGTH);
+                Request::from_parts(parts, Body::from(coerced))
+            }
+            None => req,
+        };
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(PrecheckRejection::Inner)?;
+        Ok(CoerceBigInts(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn big_int_fields_coerce() {
+        let policy = BigIntFields::new(["id", "ids", "items.id"]);
+        let mut errors = PrecheckErrors::default();
+        let mut document = json!({
+            "id": "9007199254740993",
+            "ids": ["1", -2, "-3"],
+            "items": [{ "id": "18446744073709551615", "name": "42" }],
+            "name": "42"
+        });
+        assert!(policy.coerce(&mut document, &mut errors));
+        assert!(errors.is_empty());
+        assert_eq!(
+            document,
+            json!({
+                "id": 9007199254740993u64,
+                "ids": [1, -2, -3],
+                "items": [{ "id": u64::MAX, "name": "42" }],
+                "name": "42"
+            })
+        );
+        assert!(!policy.coerce(&mut document, &mut errors));
+
+        let mut document = json!({ "id": "12.5", "ids": ["1", "x"] });
+        policy.coerce(&mut document, &mut errors);
+        assert_eq!(errors.0.len(), 2);
+        assert_eq!(errors.get("id").count(), 1);
+        assert_eq!(errors.get("ids[1]").count(), 1);
+        assert!(errors.0.iter().all(|error| error.code == "integer"));
+    }
+
+    #[cfg(feature = "validator")]
+    #[tokio::test]
+    async fn coerce_big_ints_before_validation() {
+        use crate::Valid;
+        use axum::Json;
+        use validator::Validate;
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        struct User {
+            #[validate(range(min = 9007199254740993u64))]
+            id: u64,
+        }
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+        let policy = BigIntFields::new(["id"]);
+
+        let CoerceBigInts(Valid(Json(user))) = CoerceBigInts::<Valid<Json<User>>>::from_request(
+            request(r#"{ "id": "9007199254740993" }"#),
+            &policy,
+        )
+        .await
+        .expect("big integer string rejected");
+        assert_eq!(user.id, 9007199254740993);
+
+        assert!(matches!(
+            CoerceBigInts::<Valid<Json<User>>>::from_request(
+                request(r#"{ "id": "9007199254740992" }"#),
+                &policy
+            )
+            .await,
+            Err(PrecheckRejection::Inner(_))
+        ));
+
+        assert!(matches!(
+            CoerceBigInts::<Valid<Json<User>>>::from_request(request(r#"{ "id": "big" }"#), &policy)
+                .await,
+            Err(PrecheckRejection::Valid(errors)) if errors.get("id").count() == 1
+        ));
+    }
+}
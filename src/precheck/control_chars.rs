@@ -0,0 +1,268 @@
+//! # Stripping of control characters
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `StripControl<E>`.
+//!
+//! ## Usage
+//!
+//! Text pasted from other applications often carries invisible control characters (e.g. `\u{0}` or `\u{1b}`),
+//! which break downstream systems. `StripControl` removes them from every string value of the JSON body,
+//! at any depth, before the inner extractor deserializes and validates it:
+//!
+//! 1. Make a `ControlChars` setting available from your router state
+//!    (implement `FromRef<YourState>` for it, or use it as the state directly).
+//! 2. In your handler function, wrap your extractor with `StripControl`, e.g. `StripControl<Valid<Json<T>>>`.
+//!
+//! Tabs, line feeds and carriage returns are kept, so multi-line text is left intact.
+//! Object keys are left untouched. `ControlChars::disabled()` turns stripping off, e.g. from configuration,
+//! without changing the handlers. The body is only rewritten when at least one character was removed.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::{ControlChars, StripControl};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new()
+//!             .route("/json", post(handler))
+//!             .with_state(ControlChars::enabled())
+//!     }
+//!
+//!     async fn handler(StripControl(Valid(Json(comment))): StripControl<Valid<Json<Comment>>>) {
+//!         assert!(!comment.text.contains('\u{0}'));
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Comment {
+//!         #[validate(length(min = 1, max = 280))]
+//!         pub text: String,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::PrecheckRejection;
+use axum::async_trait;
+use axum::body::Body;
+use axum::extract::{FromRef, FromRequest, Request};
+use axum::http::header::CONTENT_LENGTH;
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// Whether `StripControl` removes control characters.
+///
+/// `StripControl` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ControlChars {
+    enabled: bool,
+}
+
+impl Default for ControlChars {
+    fn default() -> Self {
+        Self::enabled()
+    }
+}
+
+impl ControlChars {
+    /// Control characters are removed.
+    pub fn enabled() -> Self {
+        Self { enabled: true }
+    }
+
+    /// Control characters are kept, and bodies are passed through untouched.
+    pub fn disabled() -> Self {
+        Self { enabled: false }
+    }
+
+    /// Returns `true` if control characters are removed.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+/// # `StripControl` pre-check extractor
+///
+/// `StripControl` removes control characters from every string value of the JSON body,
+/// then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StripControl<E>(pub E);
+
+impl<E> Deref for StripControl<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for StripControl<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for StripControl<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> StripControl<E> {
+    /// Consumes the `StripControl` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for StripControl<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for StripControl<Extractor>
+where
+    State: Send + Sync,
+    ControlChars: FromRef<State>,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        if !ControlChars::from_ref(state).is_enabled() {
+            let inner = Extractor::from_request(req, state)
+                .await
+                .map_err(PrecheckRejection::Inner)?;
+            return Ok(StripControl(inner));
+        }
+        let (req, bytes) = crate::body::buffer(req, state)
+            .await
+            .map_err(PrecheckRejection::Body)?;
+        let stripped = serde_json::from_slice::<Value>(&bytes)
+            .ok()
+            .and_then(|mut document| strip_control(&mut document).then_some(document))
+            .and_then(|document| serde_json::to_vec(&document).ok());
+        let req = match stripped {
+            Some(stripped) => {
+                let (mut parts, _) = req.into_parts();
+                parts.headers.remove(CONTENT_LENGTH);
+                Request::from_parts(parts, Body::from(stripped))
+            }
+            None => req,
+        };
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(PrecheckRejection::Inner)?;
+        Ok(StripControl(inner))
+    }
+}
+
+/// Control characters, except tabs, line feeds and carriage returns.
+fn is_stripped(c: char) -> bool {
+    c.is_control() && !matches!(c, '\t' | '\n' | '\r')
+}
+
+/// Removes control characters from every string of a JSON document in place, returning `true` if any was changed.
+fn strip_control(value: &mut Value) -> bool {
+    match value {
+        Value::String(s) => {
+            if !s.contains(is_stripped) {
+                return false;
+            }
+            s.retain(|c| !is_stripped(c));
+            true
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .fold(false, |changed, v| strip_control(v) | changed),
+        Value::Object(fields) => fields
+            .values_mut()
+            .fold(false, |changed, v| strip_control(v) | changed),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn strip_control_strings() {
+        let mut document =
+            json!({ "a\u{0}": "b\u{0}c", "d": ["e\u{1b}[0m", { "f": "g\th\r\n" }], "i": 1 });
+        assert!(strip_control(&mut document));
+        assert_eq!(
+            document,
+            json!({ "a\u{0}": "bc", "d": ["e[0m", { "f": "g\th\r\n" }], "i": 1 })
+        );
+        assert!(!strip_control(&mut document));
+    }
+
+    #[cfg(feature = "validator")]
+    #[tokio::test]
+    async fn strip_control_before_validation() {
+        use crate::Valid;
+        use axum::Json;
+        use serde::Deserialize;
+        use validator::Validate;
+
+        #[derive(Debug, Validate, Deserialize)]
+        struct Comment {
+            #[validate(length(max = 5))]
+            text: String,
+        }
+
+        let request = || {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from("{ \"text\": \"hello\\u0000\\u0007\" }"))
+                .expect("invalid request")
+        };
+        let StripControl(Valid(Json(comment))) =
+            StripControl::<Valid<Json<Comment>>>::from_request(request(), &ControlChars::enabled())
+                .await
+                .expect("cleaned comment rejected");
+        assert_eq!(comment.text, "hello");
+
+        assert!(matches!(
+            StripControl::<Valid<Json<Comment>>>::from_request(
+                request(),
+                &ControlChars::disabled()
+            )
+            .await,
+            Err(PrecheckRejection::Inner(_))
+        ));
+    }
+}
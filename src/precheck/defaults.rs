@@ -0,0 +1,281 @@
+//! # Defaults for null fields
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `FillNulls<E>`.
+//!
+//! ## Usage
+//!
+//! `#[serde(default)]` fills missing fields from a fixed value known at compile time. When the default depends
+//! on configuration or on other state (e.g. the currency of the tenant), implement `DefaultProvider` instead,
+//! and let `FillNulls` replace the `null`s of the JSON body with its values before the inner extractor runs:
+//!
+//! 1. Implement `DefaultProvider` (a closure taking the field name works too), wrap it in `Defaults`, and make it
+//!    available from your router state (implement `FromRef<YourState>` for it, or use it as the state directly).
+//! 2. In your handler function, wrap your extractor with `FillNulls`, e.g. `FillNulls<Valid<Json<T>>>`.
+//!
+//! The filled values are validated like any other value. Fields are named using dots, e.g. `billing.currency`.
+//! Array indices are left out, so `items.unit` names the `unit` field of every element of `items`.
+//! `null`s for which the provider has no default are left untouched.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::{Defaults, FillNulls};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use serde_json::{json, Value};
+//!     use validator::Validate;
+//!
+//!     pub fn router(currency: &'static str) -> Router {
+//!         let defaults = Defaults::new(move |field: &str| match field {
+//!             "currency" => Some(json!(currency)),
+//!             _ => None,
+//!         });
+//!         Router::new()
+//!             .route("/invoices", post(handler))
+//!             .with_state(defaults)
+//!     }
+//!
+//!     async fn handler(FillNulls(Valid(Json(invoice))): FillNulls<Valid<Json<Invoice>>>) {
+//!         assert!(invoice.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Invoice {
+//!         #[validate(length(equal = 3))]
+//!         pub currency: String,
+//!         pub amount: u64,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router("EUR"));
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::{push_key, PrecheckRejection};
+use axum::async_trait;
+use axum::body::Body;
+use axum::extract::{FromRef, FromRequest, Request};
+use axum::http::header::CONTENT_LENGTH;
+use serde_json::Value;
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// Source of the values `FillNulls` puts in place of `null`s.
+pub trait DefaultProvider: Send + Sync + 'static {
+    /// The default value of the given field, if it has one
+    fn default_value(&self, field: &str) -> Option<Value>;
+}
+
+impl<F> DefaultProvider for F
+where
+    F: Fn(&str) -> Option<Value> + Send + Sync + 'static,
+{
+    fn default_value(&self, field: &str) -> Option<Value> {
+        self(field)
+    }
+}
+
+/// A shared `DefaultProvider`.
+///
+/// `FillNulls` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Clone)]
+pub struct Defaults(Arc<dyn DefaultProvider>);
+
+impl Defaults {
+    /// Create `Defaults` taking values from `provider`.
+    pub fn new(provider: impl DefaultProvider) -> Self {
+        Self(Arc::new(provider))
+    }
+
+    /// Replaces the `null`s of a JSON document in place, returning `true` if any was replaced.
+    /// `field` has no array indices.
+    fn fill(&self, value: &mut Value, field: &str) -> bool {
+        match value {
+            Value::Null if !field.is_empty() => match self.0.default_value(field) {
+                Some(default) => {
+                    *value = default;
+                    true
+                }
+                None => false,
+            },
+            Value::Array(items) => items
+                .iter_mut()
+                .fold(false, |changed, item| self.fill(item, field) | changed),
+            Value::Object(fields) => fields.iter_mut().fold(false, |changed, (key, value)| {
+                self.fill(value, &push_key(field, key)) | changed
+            }),
+            _ => false,
+        }
+    }
+}
+
+impl Debug for Defaults {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Defaults").finish_non_exhaustive()
+    }
+}
+
+/// # `FillNulls` pre-check extractor
+///
+/// `FillNulls` replaces the `null`s of the JSON body with the values of the `Defaults` taken from the state,
+/// then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FillNulls<E>(pub E);
+
+impl<E> Deref for FillNulls<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for FillNulls<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for FillNulls<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> FillNulls<E> {
+    /// Consumes the `FillNulls` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for FillNulls<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for FillNulls<Extractor>
+where
+    State: Send + Sync,
+    Defaults: FromRef<State>,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let defaults = Defaults::from_ref(state);
+        let (req, bytes) = crate::body::buffer(req, state)
+            .await
+            .map_err(PrecheckRejection::Body)?;
+        let filled = serde_json::from_slice::<Value>(&bytes)
+            .ok()
+            .and_then(|mut document| defaults.fill(&mut document, "").then_some(document))
+            .and_then(|document| serde_json::to_vec(&document).ok());
+        let req = match filled {
+            Some(filled) => {
+                let (mut parts, _) = req.into_parts();
+                parts.headers.remove(CONTENT_LENGTH);
+                Request::from_parts(parts, Body::from(filled))
+            }
+            None => req,
+        };
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(PrecheckRejection::Inner)?;
+        Ok(FillNulls(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn defaults(currency: &'static str) -> Defaults {
+        Defaults::new(move |field: &str| match field {
+            "currency" => Some(json!(currency)),
+            "items.quantity" => Some(json!(1)),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn fill_nulls() {
+        let mut document = json!({
+            "currency": null,
+            "note": null,
+            "items": [{ "quantity": null }, { "quantity": 3 }]
+        });
+        assert!(defaults("EUR").fill(&mut document, ""));
+        assert_eq!(
+            document,
+            json!({
+                "currency": "EUR",
+                "note": null,
+                "items": [{ "quantity": 1 }, { "quantity": 3 }]
+            })
+        );
+        assert!(!defaults("EUR").fill(&mut document, ""));
+        assert!(!defaults("EUR").fill(&mut Value::Null, ""));
+    }
+
+    #[cfg(feature = "validator")]
+    #[tokio::test]
+    async fn fill_nulls_before_validation() {
+        use crate::Valid;
+        use axum::Json;
+        use validator::Validate;
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        struct Invoice {
+            #[validate(length(equal = 3))]
+            currency: String,
+        }
+
+        let request = || {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{ "currency": null }"#))
+                .expect("invalid request")
+        };
+
+        let FillNulls(Valid(Json(invoice))) =
+            FillNulls::<Valid<Json<Invoice>>>::from_request(request(), &defaults("EUR"))
+                .await
+                .expect("filled currency rejected");
+        assert_eq!(invoice.currency, "EUR");
+
+        assert!(matches!(
+            FillNulls::<Valid<Json<Invoice>>>::from_request(request(), &defaults("EURO")).await,
+            Err(PrecheckRejection::Inner(crate::ValidRejection::Valid(_)))
+        ));
+    }
+}
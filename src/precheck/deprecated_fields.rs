@@ -0,0 +1,298 @@
+//! # Migration of deprecated fields
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `MigrateDeprecated<E>`.
+//!
+//! ## Usage
+//!
+//! When a field is renamed, clients keep sending the old name for a while. `MigrateDeprecated` copies the value
+//! of each deprecated field to the field replacing it before the body is deserialized, so only the replacement
+//! needs to be declared, and validated, in your data type:
+//!
+//! 1. Build a `DeprecatedFields` policy mapping each deprecated field to its replacement, and make it
+//!    available from your router state (implement `FromRef<YourState>` for it, or use it as the state directly).
+//! 2. In your handler function, wrap your extractor with `MigrateDeprecated`,
+//!    e.g. `MigrateDeprecated<Valid<Json<T>>>`.
+//!
+//! Deprecated fields are named using dots, e.g. `owner.fullname`. Array indices are left out, so `items.qty`
+//! matches the `qty` field of every element of `items`. The replacement is the name of a field of the same object,
+//! e.g. `name` for `owner.fullname`.
+//!
+//! The value is only copied when the replacement is absent, so a client sending both fields gets the replacement.
+//! The deprecated field is left in place, so it is ignored unless your data type denies unknown fields.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::{DeprecatedFields, MigrateDeprecated};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new()
+//!             .route("/json", post(handler))
+//!             .with_state(DeprecatedFields::new([("fullname", "name")]))
+//!     }
+//!
+//!     async fn handler(MigrateDeprecated(Valid(Json(user))): MigrateDeprecated<Valid<Json<User>>>) {
+//!         assert!(user.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct User {
+//!         #[validate(length(min = 1, max = 100))]
+//!         pub name: String,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::{push_key, PrecheckRejection};
+use axum::async_trait;
+use axum::body::Body;
+use axum::extract::{FromRef, FromRequest, Request};
+use axum::http::header::CONTENT_LENGTH;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// The deprecated fields `MigrateDeprecated` copies to their replacement.
+///
+/// `MigrateDeprecated` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeprecatedFields(Arc<BTreeMap<String, String>>);
+
+impl DeprecatedFields {
+    /// Create a policy from pairs of deprecated fields and their replacements.
+    /// Deprecated fields are named using dots, e.g. `owner.fullname`, and replaced by a field of the same object,
+    /// e.g. `name`.
+    pub fn new<I, D, R>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = (D, R)>,
+        D: Into<String>,
+        R: Into<String>,
+    {
+        Self(Arc::new(
+            fields
+                .into_iter()
+                .map(|(deprecated, replacement)| (deprecated.into(), replacement.into()))
+                .collect(),
+        ))
+    }
+
+    /// Returns the replacement of a deprecated field.
+    pub fn replacement(&self, field: &str) -> Option<&str> {
+        self.0.get(field).map(String::as_str)
+    }
+
+    /// Copies the deprecated fields to their absent replacements in place, returning `true` if any was copied.
+    fn migrate(&self, value: &mut Value, field: &str) -> bool {
+        match value {
+            Value::Array(items) => items
+                .iter_mut()
+                .fold(false, |changed, item| self.migrate(item, field) | changed),
+            Value::Object(fields) => {
+                let copies = fields
+                    .iter()
+                    .filter_map(|(key, value)| {
+                        self.replacement(&push_key(field, key))
+                            .filter(|replacement| !fields.contains_key(*replacement))
+                            .map(|replacement| (replacement.to_owned(), value.clone()))
+                    })
+                    .collect::<Vec<_>>();
+                let copied = !copies.is_empty();
+                for (replacement, value) in copies {
+                    fields.entry(replacement).or_insert(value);
+                }
+                fields.iter_mut().fold(copied, |changed, (key, value)| {
+                    self.migrate(value, &push_key(field, key)) | changed
+                })
+            }
+            _ => false,
+        }
+    }
+}
+
+/// # `MigrateDeprecated` pre-check extractor
+///
+/// `MigrateDeprecated` copies the deprecated fields listed in the `DeprecatedFields` policy
+/// taken from the state to their replacements, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MigrateDeprecated<E>(pub E);
+
+impl<E> Deref for MigrateDeprecated<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for MigrateDeprecated<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for MigrateDeprecated<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> MigrateDeprecated<E> {
+    /// Consumes the `MigrateDeprecated` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for MigrateDeprecated<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for MigrateDeprecated<Extractor>
+where
+    State: Send + Sync,
+    DeprecatedFields: FromRef<State>,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let policy = DeprecatedFields::from_ref(state);
+        let (req, bytes) = crate::body::buffer(req, state)
+            .await
+            .map_err(PrecheckRejection::Body)?;
+        let migrated = serde_json::from_slice::<Value>(&bytes)
+            .ok()
+            .and_then(|mut document| policy.migrate(&mut document, "").then_some(document))
+            .and_then(|document| serde_json::to_vec(&document).ok());
+        let req = match migrated {
+            Some(migrated) => {
+                let (mut parts, _) = req.into_parts();
+                parts.headers.remove(CONTENT_LENGTH);
+                Request::from_parts(parts, Body::from(migrated))
+            }
+            None => req,
+        };
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(PrecheckRejection::Inner)?;
+        Ok(MigrateDeprecated(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn deprecated_fields_migrate() {
+        let policy = DeprecatedFields::new([
+            ("fullname", "name"),
+            ("owner.fullname", "name"),
+            ("items.qty", "quantity"),
+        ]);
+        let mut document = json!({
+            "fullname": "Ada",
+            "owner": { "fullname": "Grace", "name": "Hopper" },
+            "items": [{ "qty": 1 }, { "quantity": 2 }],
+        });
+        assert!(policy.migrate(&mut document, ""));
+        assert_eq!(
+            document,
+            json!({
+                "fullname": "Ada",
+                "name": "Ada",
+                "owner": { "fullname": "Grace", "name": "Hopper" },
+                "items": [{ "qty": 1, "quantity": 1 }, { "quantity": 2 }],
+            })
+        );
+        assert!(!policy.migrate(&mut document, ""));
+        assert_eq!(policy.replacement("owner.fullname"), Some("name"));
+        assert_eq!(policy.replacement("name"), None);
+    }
+
+    #[cfg(feature = "validator")]
+    #[tokio::test]
+    async fn migrate_deprecated_before_validation() {
+        use crate::Valid;
+        use axum::Json;
+        use validator::Validate;
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        struct Order {
+            #[validate(range(min = 1, max = 10))]
+            quantity: u32,
+        }
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+        let policy = DeprecatedFields::new([("qty", "quantity")]);
+
+        let MigrateDeprecated(Valid(Json(order))) =
+            MigrateDeprecated::<Valid<Json<Order>>>::from_request(
+                request(r#"{ "qty": 3 }"#),
+                &policy,
+            )
+            .await
+            .expect("deprecated field rejected");
+        assert_eq!(order.quantity, 3);
+
+        assert!(matches!(
+            MigrateDeprecated::<Valid<Json<Order>>>::from_request(
+                request(r#"{ "qty": 30 }"#),
+                &policy
+            )
+            .await,
+            Err(PrecheckRejection::Inner(crate::ValidRejection::Valid(_)))
+        ));
+
+        let MigrateDeprecated(Valid(Json(order))) =
+            MigrateDeprecated::<Valid<Json<Order>>>::from_request(
+                request(r#"{ "qty": 30, "quantity": 4 }"#),
+                &policy,
+            )
+            .await
+            .expect("replacement overridden by the deprecated field");
+        assert_eq!(order.quantity, 4);
+    }
+}
@@ -0,0 +1,394 @@
+//! # Maximum nesting depth
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `MaxDepth<E, N>` and `MaxArrayDepth<E, N>`.
+//!
+//! ## Usage
+//!
+//! Deeply nested payloads are costly to deserialize and validate, and are rarely legitimate.
+//! Wrap your extractor with `MaxDepth`, e.g. `MaxDepth<Valid<Json<T>>, 8>`, to reject payloads whose objects
+//! and arrays are nested more than `N` levels deep, before they are deserialized.
+//!
+//! The root object or array is at depth 1, so `{ "a": { "b": [1] } }` is 3 levels deep.
+//! The error is reported at the path of the deepest object or array, e.g. `a.b[0].c`, with the `too_deep` code,
+//! so the offending branch of the payload can be found.
+//!
+//! Arrays of arrays are a cheap way to build deeply nested payloads, and are rarely expected even where
+//! objects are legitimately nested. `MaxArrayDepth`, e.g. `MaxArrayDepth<Valid<Json<T>>, 2>`, rejects payloads
+//! with more than `N` arrays directly nested in one another, whatever the depth of the objects around them:
+//! `{ "a": [[1], [{ "b": [2] }]] }` has an array depth of 2. The error is reported at the path of the innermost
+//! array with the `array_too_deep` code.
+//!
+//! `serde_json` rejects documents nested more than 128 levels deep as malformed, so they are passed
+//! through to the inner extractor, which rejects them itself.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::MaxDepth;
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/json", post(handler))
+//!     }
+//!
+//!     async fn handler(MaxDepth(Valid(Json(comment))): MaxDepth<Valid<Json<Comment>>, 8>) {
+//!         assert!(comment.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Comment {
+//!         #[validate(length(min = 1, max = 1000))]
+//!         pub text: String,
+//!         #[validate(nested)]
+//!         pub replies: Vec<Comment>,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::{
+    push_index, push_key, run, PrecheckError, PrecheckErrors, PrecheckRejection,
+};
+use axum::async_trait;
+use axum::extract::{FromRequest, Request};
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// # `MaxDepth` pre-check extractor
+///
+/// `MaxDepth` rejects JSON bodies nested more than `N` levels deep,
+/// then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaxDepth<E, const N: usize>(pub E);
+
+impl<E, const N: usize> Deref for MaxDepth<E, N> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E, const N: usize> DerefMut for MaxDepth<E, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display, const N: usize> Display for MaxDepth<T, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E, const N: usize> MaxDepth<E, N> {
+    /// Consumes the `MaxDepth` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T, const N: usize> aide::OperationInput for MaxDepth<T, N>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, const N: usize> FromRequest<State> for MaxDepth<Extractor, N>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let inner = run(req, state, |bytes| {
+            let Ok(document) = serde_json::from_slice::<Value>(bytes) else {
+                return PrecheckErrors::default();
+            };
+            let (depth, path) = deepest(&document, String::new());
+            if depth <= N {
+                return PrecheckErrors::default();
+            }
+            PrecheckError::new(
+                path,
+                "too_deep",
+                format!("must be nested at most {N} levels deep, found {depth}"),
+            )
+            .into()
+        })
+        .await?;
+        Ok(MaxDepth(inner))
+    }
+}
+
+/// # `MaxArrayDepth` pre-check extractor
+///
+/// `MaxArrayDepth` rejects JSON bodies with more than `N` arrays directly nested in one another,
+/// then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaxArrayDepth<E, const N: usize>(pub E);
+
+impl<E, const N: usize> Deref for MaxArrayDepth<E, N> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E, const N: usize> DerefMut for MaxArrayDepth<E, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display, const N: usize> Display for MaxArrayDepth<T, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E, const N: usize> MaxArrayDepth<E, N> {
+    /// Consumes the `MaxArrayDepth` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T, const N: usize> aide::OperationInput for MaxArrayDepth<T, N>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, const N: usize> FromRequest<State> for MaxArrayDepth<Extractor, N>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let inner = run(req, state, |bytes| {
+            let Ok(document) = serde_json::from_slice::<Value>(bytes) else {
+                return PrecheckErrors::default();
+            };
+            let (_, (depth, path)) = deepest_array(&document, String::new());
+            if depth <= N {
+                return PrecheckErrors::default();
+            }
+            PrecheckError::new(
+                path,
+                "array_too_deep",
+                format!("arrays must be nested at most {N} levels deep, found {depth}"),
+            )
+            .into()
+        })
+        .await?;
+        Ok(MaxArrayDepth(inner))
+    }
+}
+
+/// Returns the depth of a JSON value, and the path of its first deepest object or array.
+fn deepest(value: &Value, path: String) -> (usize, String) {
+    let children: Box<dyn Iterator<Item = (&Value, String)>> = match value {
+        Value::Array(items) => Box::new(
+            items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| (item, push_index(&path, index))),
+        ),
+        Value::Object(fields) => Box::new(
+            fields
+                .iter()
+                .map(|(key, field)| (field, push_key(&path, key))),
+        ),
+        _ => return (0, path),
+    };
+    let mut found = (1, path.clone());
+    for (child, child_path) in children {
+        let (depth, deepest_path) = deepest(child, child_path);
+        if depth + 1 > found.0 {
+            found = (depth + 1, deepest_path);
+        }
+    }
+    found
+}
+
+/// Returns the number of arrays directly nested from a JSON value, and the largest such number within it,
+/// each with the path of its first innermost array.
+fn deepest_array(value: &Value, path: String) -> ((usize, String), (usize, String)) {
+    match value {
+        Value::Array(items) => {
+            let mut chain = (1, path.clone());
+            let mut found = (0, String::new());
+            for (index, item) in items.iter().enumerate() {
+                let (item_chain, item_found) = deepest_array(item, push_index(&path, index));
+                if item_chain.0 + 1 > chain.0 {
+                    chain = (item_chain.0 + 1, item_chain.1);
+                }
+                if item_found.0 > found.0 {
+                    found = item_found;
+                }
+            }
+            if chain.0 > found.0 {
+                found = chain.clone();
+            }
+            (chain, found)
+        }
+        Value::Object(fields) => {
+            let mut found = (0, String::new());
+            for (key, field) in fields {
+                let (_, field_found) = deepest_array(field, push_key(&path, key));
+                if field_found.0 > found.0 {
+                    found = field_found;
+                }
+            }
+            ((0, path), found)
+        }
+        _ => ((0, path.clone()), (0, path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::precheck::ROOT_PATH;
+    use axum::body::Body;
+    use axum::Json;
+    use serde_json::json;
+
+    #[test]
+    fn deepest_path() {
+        assert_eq!(deepest(&json!(1), String::new()), (0, String::new()));
+        assert_eq!(deepest(&json!([]), String::new()), (1, String::new()));
+        assert_eq!(
+            deepest(
+                &json!({ "a": { "b": [1] }, "c": { "d": [{ "e": {} }] } }),
+                String::new()
+            ),
+            (5, String::from("c.d[0].e"))
+        );
+    }
+
+    #[tokio::test]
+    async fn max_depth() {
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+
+        assert!(
+            MaxDepth::<Json<Value>, 3>::from_request(request(r#"{ "a": { "b": [1] } }"#), &())
+                .await
+                .is_ok()
+        );
+
+        let rejection = MaxDepth::<Json<Value>, 3>::from_request(
+            request(r#"{ "a": { "b": [1] }, "c": [{ "d": { "e": 1 } }] }"#),
+            &(),
+        )
+        .await
+        .expect_err("too deep body accepted");
+        assert!(matches!(
+            rejection,
+            PrecheckRejection::Valid(errors)
+                if errors.get("c[0].d").any(|error| error.code == "too_deep")
+        ));
+
+        assert!(matches!(
+            MaxDepth::<Json<Value>, 0>::from_request(request("[]"), &()).await,
+            Err(PrecheckRejection::Valid(errors)) if errors.get(ROOT_PATH).count() == 1
+        ));
+    }
+
+    #[test]
+    fn deepest_array_path() {
+        let found = |value: Value| deepest_array(&value, String::new()).1;
+        assert_eq!(found(json!(1)), (0, String::new()));
+        assert_eq!(found(json!({ "a": { "b": 1 } })), (0, String::new()));
+        assert_eq!(found(json!([])), (1, String::new()));
+        assert_eq!(
+            found(json!({ "a": [[1], [{ "b": [2] }]] })),
+            (2, String::from("a[0]"))
+        );
+        assert_eq!(
+            found(json!({ "a": [1, [[]]], "b": [{ "c": [[[[2]]]] }] })),
+            (4, String::from("b[0].c[0][0][0]"))
+        );
+    }
+
+    #[tokio::test]
+    async fn max_array_depth() {
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+
+        assert!(MaxArrayDepth::<Json<Value>, 2>::from_request(
+            request(r#"{ "a": { "b": { "c": [[1], [2]] } } }"#),
+            &()
+        )
+        .await
+        .is_ok());
+
+        let rejection = MaxArrayDepth::<Json<Value>, 2>::from_request(
+            request(r#"{ "matrix": [[1, 2], [3, [[4]]]] }"#),
+            &(),
+        )
+        .await
+        .expect_err("deeply nested arrays accepted");
+        assert!(matches!(
+            rejection,
+            PrecheckRejection::Valid(errors)
+                if errors.get("matrix[1][1][0]").any(|error| error.code == "array_too_deep")
+        ));
+
+        assert!(matches!(
+            MaxArrayDepth::<Json<Value>, 1>::from_request(request("[[]]"), &()).await,
+            Err(PrecheckRejection::Valid(errors)) if errors.get("[0]").count() == 1
+        ));
+    }
+}
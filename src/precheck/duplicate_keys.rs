@@ -0,0 +1,289 @@
+//! # Rejection of duplicate keys
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `RejectDuplicateKeys<E>`.
+//!
+//! ## Usage
+//!
+//! JSON objects with a repeated key are ambiguous: `serde` silently keeps the last value, while other parsers
+//! may keep the first one. Wrap your extractor with `RejectDuplicateKeys`, e.g. `RejectDuplicateKeys<Valid<Json<T>>>`,
+//! to turn every repeated key into a field-level error (code `duplicate_key`) naming the offending field,
+//! before the body is deserialized.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::RejectDuplicateKeys;
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/json", post(handler))
+//!     }
+//!
+//!     async fn handler(
+//!         RejectDuplicateKeys(Valid(Json(transfer))): RejectDuplicateKeys<Valid<Json<Transfer>>>,
+//!     ) {
+//!         assert!(transfer.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Transfer {
+//!         #[validate(range(min = 1))]
+//!         pub amount: u64,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::{
+    push_index, push_key, run, PrecheckError, PrecheckErrors, PrecheckRejection,
+};
+use axum::async_trait;
+use axum::extract::{FromRequest, Request};
+use serde::de::{DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// # `RejectDuplicateKeys` pre-check extractor
+///
+/// `RejectDuplicateKeys` rejects JSON bodies containing objects with repeated keys,
+/// then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RejectDuplicateKeys<E>(pub E);
+
+impl<E> Deref for RejectDuplicateKeys<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for RejectDuplicateKeys<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for RejectDuplicateKeys<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> RejectDuplicateKeys<E> {
+    /// Consumes the `RejectDuplicateKeys` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for RejectDuplicateKeys<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for RejectDuplicateKeys<Extractor>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let inner = run(req, state, find_duplicate_keys).await?;
+        Ok(RejectDuplicateKeys(inner))
+    }
+}
+
+/// Parses a JSON document, reporting every repeated key. Malformed documents are not reported.
+fn find_duplicate_keys(input: &[u8]) -> PrecheckErrors {
+    let mut errors = PrecheckErrors::default();
+    let mut deserializer = serde_json::Deserializer::from_slice(input);
+    let scanned = Scan {
+        path: String::new(),
+        errors: &mut errors,
+    }
+    .deserialize(&mut deserializer)
+    .and_then(|()| deserializer.end());
+    match scanned {
+        Ok(()) => errors,
+        Err(_) => PrecheckErrors::default(),
+    }
+}
+
+/// Walks a JSON value without building it, `path` being the path of the value.
+struct Scan<'a> {
+    path: String,
+    errors: &'a mut PrecheckErrors,
+}
+
+impl<'de> DeserializeSeed<'de> for Scan<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Scan<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "any JSON value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut index = 0;
+        while seq
+            .next_element_seed(Scan {
+                path: push_index(&self.path, index),
+                errors: self.errors,
+            })?
+            .is_some()
+        {
+            index += 1;
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut keys = HashSet::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let path = push_key(&self.path, &key);
+            if !keys.insert(key) {
+                self.errors.add(PrecheckError::new(
+                    path.clone(),
+                    "duplicate_key",
+                    "must not be repeated",
+                ));
+            }
+            map.next_value_seed(Scan {
+                path,
+                errors: self.errors,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use axum::Json;
+
+    #[test]
+    fn find_duplicate_keys_paths() {
+        assert!(find_duplicate_keys(br#"{"a": 1, "b": {"a": 2}}"#).is_empty());
+        assert!(find_duplicate_keys(br#"{"a": 1, "a": "#).is_empty());
+
+        let errors = find_duplicate_keys(
+            br#"{"a": 1, "a": 2, "b": [{"c": 1}, {"c": 2, "c": 3, "c": 4}], "d\"e": 0, "d\"e": 1}"#,
+        );
+        assert_eq!(errors.get("a").count(), 1);
+        assert_eq!(errors.get("b[1].c").count(), 2);
+        assert_eq!(errors.get("d\"e").count(), 1);
+        assert!(errors.0.iter().all(|error| error.code == "duplicate_key"));
+    }
+
+    #[tokio::test]
+    async fn reject_duplicate_keys() {
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+
+        let RejectDuplicateKeys(Json(value)) =
+            RejectDuplicateKeys::<Json<serde_json::Value>>::from_request(
+                request(r#"{"v": 1}"#),
+                &(),
+            )
+            .await
+            .expect("body without duplicate keys rejected");
+        assert_eq!(value, serde_json::json!({ "v": 1 }));
+
+        let rejection = RejectDuplicateKeys::<Json<serde_json::Value>>::from_request(
+            request(r#"{"v": 1, "v": 2}"#),
+            &(),
+        )
+        .await
+        .expect_err("duplicate keys accepted");
+        assert!(matches!(
+            &rejection,
+            PrecheckRejection::Valid(errors) if errors.get("v").any(|error| error.code == "duplicate_key")
+        ));
+        assert_eq!(
+            rejection.into_response().status(),
+            crate::VALIDATION_ERROR_STATUS
+        );
+
+        let rejection =
+            RejectDuplicateKeys::<Json<serde_json::Value>>::from_request(request("{"), &())
+                .await
+                .expect_err("malformed body accepted");
+        assert_eq!(
+            rejection.into_response().status(),
+            StatusCode::BAD_REQUEST,
+            "malformed body should be rejected by the inner extractor"
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! # Normalization of empty arrays
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `NormalizeEmptyArrays<E>`.
+//!
+//! ## Usage
+//!
+//! Clients often send `[]`, `null` or nothing at all for the same thing, while the server may have to tell
+//! "no items" from "not provided". `NormalizeEmptyArrays` rewrites these fields consistently before the
+//! inner extractor runs, so validation and downstream logic only see one of them:
+//!
+//! 1. Build an `EmptyArrays` policy, choosing for each field whether an empty array becomes `null`
+//!    (`EmptyArrays::to_null`) or whether `null` and a missing field become an empty array (`EmptyArrays::to_empty`),
+//!    and make it available from your router state (implement `FromRef<YourState>` for it,
+//!    or use it as the state directly).
+//! 2. In your handler function, wrap your extractor with `NormalizeEmptyArrays`,
+//!    e.g. `NormalizeEmptyArrays<Valid<Json<T>>>`.
+//!
+//! Nested fields are named using dots, e.g. `filter.tags`. Array indices are left out, so `items.tags`
+//! applies to the `tags` field of every element of `items`. A missing field is only added when its parent object
+//! is present.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::{EmptyArrays, NormalizeEmptyArrays};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new()
+//!             .route("/search", post(handler))
+//!             .with_state(EmptyArrays::default().to_null("tags").to_empty("sort"))
+//!     }
+//!
+//!     async fn handler(
+//!         NormalizeEmptyArrays(Valid(Json(search))): NormalizeEmptyArrays<Valid<Json<Search>>>,
+//!     ) {
+//!         assert!(search.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Search {
+//!         /// `None` means any tag, `Some` at least one of the tags.
+//!         #[validate(length(min = 1, max = 8))]
+//!         pub tags: Option<Vec<String>>,
+//!         #[validate(length(max = 3))]
+//!         pub sort: Vec<String>,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::{push_key, PrecheckRejection};
+use axum::async_trait;
+use axum::body::Body;
+use axum::extract::{FromRef, FromRequest, Request};
+use axum::http::header::CONTENT_LENGTH;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// How `NormalizeEmptyArrays` normalizes a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmptyArray {
+    /// An empty array becomes `null`.
+    ToNull,
+    /// `null` and a missing field become an empty array.
+    ToEmpty,
+}
+
+/// The fields `NormalizeEmptyArrays` normalizes, and how.
+///
+/// `NormalizeEmptyArrays` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmptyArrays(Arc<BTreeMap<String, EmptyArray>>);
+
+impl EmptyArrays {
+    /// Normalize the given field as specified. Nested fields are named using dots, e.g. `filter.tags`.
+    pub fn field(mut self, field: impl Into<String>, normalization: EmptyArray) -> Self {
+        Arc::make_mut(&mut self.0).insert(field.into(), normalization);
+        self
+    }
+
+    /// Turn an empty array into `null` in the given field.
+    pub fn to_null(self, field: impl Into<String>) -> Self {
+        self.field(field, EmptyArray::ToNull)
+    }
+
+    /// Turn `null` or a missing field into an empty array in the given field.
+    pub fn to_empty(self, field: impl Into<String>) -> Self {
+        self.field(field, EmptyArray::ToEmpty)
+    }
+
+    /// Returns how the given field is normalized, if it is.
+    pub fn get(&self, field: &str) -> Option<EmptyArray> {
+        self.0.get(field).copied()
+    }
+
+    /// Normalizes the fields of a JSON document in place, returning `true` if any was changed.
+    /// `field` has no array indices.
+    fn normalize(&self, value: &mut Value, field: &str) -> bool {
+        match value {
+            Value::Array(items) => items
+                .iter_mut()
+                .fold(false, |changed, item| self.normalize(item, field) | changed),
+            Value::Object(fields) => {
+                let mut changed = false;
+                for (path, _) in self
+                    .0
+                    .iter()
+                    .filter(|(_, normalization)| **normalization == EmptyArray::ToEmpty)
+                {
+                    let key = match path.rsplit_once('.') {
+                        Some((parent, key)) if parent == field => key,
+                        None if field.is_empty() => path.as_str(),
+                        _ => continue,
+                    };
+                    if !fields.contains_key(key) {
+                        fields.insert(key.to_owned(), Value::Array(Vec::new()));
+                        changed = true;
+                    }
+                }
+                for (key, value) in fields.iter_mut() {
+                    let field = push_key(field, key);
+                    changed |= match (self.get(&field), &*value) {
+                        (Some(EmptyArray::ToNull), Value::Array(items)) if items.is_empty() => {
+                            *value = Value::Null;
+                            true
+                        }
+                        (Some(EmptyArray::ToEmpty), Value::Null) => {
+                            *value = Value::Array(Vec::new());
+                            true
+                        }
+                        _ => self.normalize(value, &field),
+                    };
+                }
+                changed
+            }
+            _ => false,
+        }
+    }
+}
+
+/// # `NormalizeEmptyArrays` pre-check extractor
+///
+/// `NormalizeEmptyArrays` normalizes the empty arrays of the fields listed in the `EmptyArrays` policy
+/// taken from the state, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NormalizeEmptyArrays<E>(pub E);
+
+impl<E> Deref for NormalizeEmptyArrays<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for NormalizeEmptyArrays<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for NormalizeEmptyArrays<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> NormalizeEmptyArrays<E> {
+    /// Consumes the `NormalizeEmptyArrays` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for NormalizeEmptyArrays<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for NormalizeEmptyArrays<Extractor>
+where
+    State: Send + Sync,
+    EmptyArrays: FromRef<State>,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let policy = EmptyArrays::from_ref(state);
+        let (req, bytes) = crate::body::buffer(req, state)
+            .await
+            .map_err(PrecheckRejection::Body)?;
+        let normalized = serde_json::from_slice::<Value>(&bytes)
+            .ok()
+            .and_then(|mut document| policy.normalize(&mut document, "").then_some(document))
+            .and_then(|document| serde_json::to_vec(&document).ok());
+        let req = match normalized {
+            Some(normalized) => {
+                let (mut parts, _) = req.into_parts();
+                parts.headers.remove(CONTENT_LENGTH);
+                Request::from_parts(parts, Body::from(normalized))
+            }
+            None => req,
+        };
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(PrecheckRejection::Inner)?;
+        Ok(NormalizeEmptyArrays(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn normalize_empty_arrays() {
+        let policy = EmptyArrays::default()
+            .to_null("tags")
+            .to_empty("sort")
+            .to_null("items.labels")
+            .to_empty("filter.ids");
+
+        let mut document = json!({
+            "tags": [],
+            "items": [{ "labels": [] }, { "labels": ["a"] }],
+            "filter": { "ids": null }
+        });
+        assert!(policy.normalize(&mut document, ""));
+        assert_eq!(
+            document,
+            json!({
+                "tags": null,
+                "sort": [],
+                "items": [{ "labels": null }, { "labels": ["a"] }],
+                "filter": { "ids": [] }
+            })
+        );
+        assert!(!policy.normalize(&mut document, ""));
+
+        let mut document = json!({ "tags": ["a"], "sort": ["name"], "other": [] });
+        assert!(!policy.normalize(&mut document, ""));
+    }
+
+    #[cfg(feature = "validator")]
+    #[tokio::test]
+    async fn normalize_before_validation() {
+        use crate::Valid;
+        use axum::Json;
+        use validator::Validate;
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        struct Search {
+            #[validate(length(min = 1))]
+            tags: Option<Vec<String>>,
+            #[validate(length(max = 2))]
+            sort: Vec<String>,
+        }
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+        let policy = EmptyArrays::default().to_null("tags").to_empty("sort");
+
+        for body in [
+            r#"{ "tags": [] }"#,
+            r#"{ "tags": null, "sort": null }"#,
+            "{}",
+        ] {
+            let NormalizeEmptyArrays(Valid(Json(search))) =
+                NormalizeEmptyArrays::<Valid<Json<Search>>>::from_request(request(body), &policy)
+                    .await
+                    .expect("normalized search rejected");
+            assert_eq!(search.tags, None);
+            assert!(search.sort.is_empty());
+        }
+
+        assert!(matches!(
+            NormalizeEmptyArrays::<Valid<Json<Search>>>::from_request(
+                request(r#"{ "tags": [], "sort": ["a", "b", "c"] }"#),
+                &policy
+            )
+            .await,
+            Err(PrecheckRejection::Inner(_))
+        ));
+    }
+}
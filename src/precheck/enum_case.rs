@@ -0,0 +1,546 @@
+//! # Case-insensitive enum values
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `CaseInsensitiveEnums<E>`.
+//!
+//! ## Usage
+//!
+//! Clients often disagree on the case of enum values, sending `ASC`, `Asc` or `asc` for the same variant,
+//! while `serde` only accepts the exact spelling. `CaseInsensitiveEnums` rewrites the values of the fields
+//! listed in an `EnumFields` policy to the expected spelling before the body is deserialized:
+//!
+//! 1. Build an `EnumFields` policy listing each enum field with the spelling of its variants, and make it
+//!    available from your router state (implement `FromRef<YourState>` for it, or use it as the state directly).
+//! 2. In your handler function, wrap your extractor with `CaseInsensitiveEnums`,
+//!    e.g. `CaseInsensitiveEnums<Valid<Json<T>>>`.
+//!
+//! Values matching no variant are left untouched, so the inner extractor rejects them as usual.
+//! A field holding an array of enum values has each of its elements rewritten.
+//!
+//! Nested fields are named using dots, e.g. `sort.order`. Array indices are left out, so `items.kind`
+//! names the `kind` field of every element of `items`.
+//!
+//! ### Suggestions for unknown values
+//!
+//! The error `serde` reports for an unknown variant is an opaque deserialization error. Wrap your extractor
+//! with `RejectUnknownVariants` instead, e.g. `RejectUnknownVariants<Valid<Json<T>>>`, to report the string
+//! values of the `EnumFields` which are not one of their variants as field-level errors (code
+//! `unknown_variant`). The message suggests the closest variant by edit distance, e.g.
+//! `unknown variant "dsec", did you mean "desc"?`, or lists the variants when none is close.
+//! The variants are matched exactly, so combine both extractors to accept other cases as well:
+//! `CaseInsensitiveEnums<RejectUnknownVariants<Valid<Json<T>>>>`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::{CaseInsensitiveEnums, EnumFields};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new()
+//!             .route("/search", post(handler))
+//!             .with_state(EnumFields::default().field("order", ["asc", "desc"]))
+//!     }
+//!
+//!     async fn handler(
+//!         CaseInsensitiveEnums(Valid(Json(search))): CaseInsensitiveEnums<Valid<Json<Search>>>,
+//!     ) {
+//!         assert!(!search.query.is_empty());
+//!     }
+//!
+//!     #[derive(Deserialize)]
+//!     #[serde(rename_all = "lowercase")]
+//!     pub enum Order {
+//!         Asc,
+//!         Desc,
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Search {
+//!         #[validate(length(min = 1, max = 64))]
+//!         pub query: String,
+//!         pub order: Order,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::{
+    push_index, push_key, run, PrecheckError, PrecheckErrors, PrecheckRejection,
+};
+use axum::async_trait;
+use axum::body::Body;
+use axum::extract::{FromRef, FromRequest, Request};
+use axum::http::header::CONTENT_LENGTH;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// The enum fields `CaseInsensitiveEnums` matches case-insensitively, with the spelling of their variants.
+///
+/// `CaseInsensitiveEnums` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnumFields(Arc<BTreeMap<String, Vec<String>>>);
+
+impl EnumFields {
+    /// Match the given field case-insensitively against `variants`, spelled as the inner extractor expects them.
+    /// Nested fields are named using dots, e.g. `sort.order`.
+    pub fn field<I, S>(mut self, field: impl Into<String>, variants: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Arc::make_mut(&mut self.0)
+            .insert(field.into(), variants.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Returns the spelling of the variant of the given field matching `value` case-insensitively, if any.
+    pub fn variant(&self, field: &str, value: &str) -> Option<&str> {
+        self.0
+            .get(field)?
+            .iter()
+            .find(|variant| variant.to_lowercase() == value.to_lowercase())
+            .map(String::as_str)
+    }
+
+    /// Returns the variant of the given field closest to `value`, if one is close enough to be a likely typo.
+    pub fn suggest(&self, field: &str, value: &str) -> Option<&str> {
+        let value = value.to_lowercase();
+        self.0
+            .get(field)?
+            .iter()
+            .map(|variant| (edit_distance(&variant.to_lowercase(), &value), variant))
+            .filter(|(distance, variant)| *distance <= variant.chars().count().div_ceil(3).max(1))
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, variant)| variant.as_str())
+    }
+
+    /// Returns the errors of the string values of a JSON document which are not variants of their field.
+    /// `path` is reported in errors, `field` (which has no array indices) is matched against the policy.
+    fn check(&self, value: &Value, path: &str, field: &str, errors: &mut PrecheckErrors) {
+        match value {
+            Value::String(s) => {
+                let Some(variants) = self.0.get(field) else {
+                    return;
+                };
+                if variants.contains(s) {
+                    return;
+                }
+                let message = match self.suggest(field, s) {
+                    Some(variant) => format!("unknown variant {s:?}, did you mean {variant:?}?"),
+                    None => format!(
+                        "unknown variant {s:?}, expected one of {}",
+                        variants
+                            .iter()
+                            .map(|variant| format!("{variant:?}"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                };
+                errors.add(PrecheckError::new(path, "unknown_variant", message));
+            }
+            Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    self.check(item, &push_index(path, index), field, errors);
+                }
+            }
+            Value::Object(fields) => {
+                for (key, value) in fields {
+                    self.check(value, &push_key(path, key), &push_key(field, key), errors);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Rewrites the enum values of a JSON document in place, returning `true` if any was changed.
+    /// `field` has no array indices.
+    fn normalize(&self, value: &mut Value, field: &str) -> bool {
+        match value {
+            Value::String(s) => match self.variant(field, s) {
+                Some(variant) if variant != s => {
+                    *s = variant.to_owned();
+                    true
+                }
+                _ => false,
+            },
+            Value::Array(items) => items
+                .iter_mut()
+                .fold(false, |changed, item| self.normalize(item, field) | changed),
+            Value::Object(fields) => fields.iter_mut().fold(false, |changed, (key, value)| {
+                self.normalize(value, &push_key(field, key)) | changed
+            }),
+            _ => false,
+        }
+    }
+}
+
+/// Returns the Levenshtein distance between two strings, in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// # `CaseInsensitiveEnums` pre-check extractor
+///
+/// `CaseInsensitiveEnums` rewrites the values of the fields listed in the `EnumFields` policy taken from the
+/// state to the spelling of the variant they match case-insensitively, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CaseInsensitiveEnums<E>(pub E);
+
+impl<E> Deref for CaseInsensitiveEnums<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for CaseInsensitiveEnums<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for CaseInsensitiveEnums<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> CaseInsensitiveEnums<E> {
+    /// Consumes the `CaseInsensitiveEnums` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for CaseInsensitiveEnums<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for CaseInsensitiveEnums<Extractor>
+where
+    State: Send + Sync,
+    EnumFields: FromRef<State>,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let policy = EnumFields::from_ref(state);
+        let (req, bytes) = crate::body::buffer(req, state)
+            .await
+            .map_err(PrecheckRejection::Body)?;
+        let normalized = serde_json::from_slice::<Value>(&bytes)
+            .ok()
+            .and_then(|mut document| policy.normalize(&mut document, "").then_some(document))
+            .and_then(|document| serde_json::to_vec(&document).ok());
+        let req = match normalized {
+            Some(normalized) => {
+                let (mut parts, _) = req.into_parts();
+                parts.headers.remove(CONTENT_LENGTH);
+                Request::from_parts(parts, Body::from(normalized))
+            }
+            None => req,
+        };
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(PrecheckRejection::Inner)?;
+        Ok(CaseInsensitiveEnums(inner))
+    }
+}
+
+/// # `RejectUnknownVariants` pre-check extractor
+///
+/// `RejectUnknownVariants` rejects JSON bodies with values of the fields listed in the `EnumFields` policy
+/// taken from the state which are not one of their variants, suggesting the closest one,
+/// then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RejectUnknownVariants<E>(pub E);
+
+impl<E> Deref for RejectUnknownVariants<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for RejectUnknownVariants<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for RejectUnknownVariants<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> RejectUnknownVariants<E> {
+    /// Consumes the `RejectUnknownVariants` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for RejectUnknownVariants<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for RejectUnknownVariants<Extractor>
+where
+    State: Send + Sync,
+    EnumFields: FromRef<State>,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let policy = EnumFields::from_ref(state);
+        let inner = run(req, state, |bytes| {
+            let mut errors = PrecheckErrors::default();
+            if let Ok(document) = serde_json::from_slice::<Value>(bytes) {
+                policy.check(&document, "", "", &mut errors);
+            }
+            errors
+        })
+        .await?;
+        Ok(RejectUnknownVariants(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn normalize_enum_values() {
+        let policy = EnumFields::default()
+            .field("order", ["asc", "desc"])
+            .field("items.kind", ["Book", "EBook"]);
+
+        let mut document = json!({
+            "order": "ASC",
+            "items": [{ "kind": "ebook" }, { "kind": ["BOOK", "Magazine"] }],
+            "other": "DESC"
+        });
+        assert!(policy.normalize(&mut document, ""));
+        assert_eq!(
+            document,
+            json!({
+                "order": "asc",
+                "items": [{ "kind": "EBook" }, { "kind": ["Book", "Magazine"] }],
+                "other": "DESC"
+            })
+        );
+        assert!(!policy.normalize(&mut document, ""));
+    }
+
+    #[cfg(feature = "validator")]
+    #[tokio::test]
+    async fn match_enums_before_validation() {
+        use crate::{Valid, ValidRejection};
+        use axum::Json;
+        use validator::Validate;
+
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        #[serde(rename_all = "lowercase")]
+        enum Order {
+            Asc,
+            Desc,
+        }
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        struct Search {
+            #[validate(length(min = 1))]
+            query: String,
+            order: Order,
+        }
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+        let policy = EnumFields::default().field("order", ["asc", "desc"]);
+
+        let CaseInsensitiveEnums(Valid(Json(search))) =
+            CaseInsensitiveEnums::<Valid<Json<Search>>>::from_request(
+                request(r#"{ "query": "axum", "order": "DESC" }"#),
+                &policy,
+            )
+            .await
+            .expect("mixed-case enum rejected");
+        assert_eq!(search.order, Order::Desc);
+
+        let rejection = CaseInsensitiveEnums::<Valid<Json<Search>>>::from_request(
+            request(r#"{ "query": "", "order": "Asc" }"#),
+            &policy,
+        )
+        .await
+        .expect_err("empty query accepted");
+        assert!(matches!(
+            rejection,
+            PrecheckRejection::Inner(ValidRejection::Valid(_))
+        ));
+
+        let rejection = CaseInsensitiveEnums::<Valid<Json<Search>>>::from_request(
+            request(r#"{ "query": "axum", "order": "random" }"#),
+            &policy,
+        )
+        .await
+        .expect_err("unknown variant accepted");
+        assert!(matches!(
+            rejection,
+            PrecheckRejection::Inner(ValidRejection::Inner(_))
+        ));
+    }
+
+    #[test]
+    fn suggest_closest_variant() {
+        assert_eq!(edit_distance("desc", "dsec"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+
+        let policy = EnumFields::default()
+            .field("order", ["asc", "desc"])
+            .field("items.status", ["pending", "shipped", "delivered"]);
+        assert_eq!(policy.suggest("order", "dsec"), Some("desc"));
+        assert_eq!(policy.suggest("order", "ASC"), Some("asc"));
+        assert_eq!(policy.suggest("order", "random"), None);
+        assert_eq!(policy.suggest("items.status", "shiped"), Some("shipped"));
+        assert_eq!(policy.suggest("other", "asc"), None);
+
+        let mut errors = PrecheckErrors::default();
+        policy.check(
+            &json!({
+                "order": "asc",
+                "items": [{ "status": "pending" }, { "status": "delivred" }, { "status": "lost" }]
+            }),
+            "",
+            "",
+            &mut errors,
+        );
+        assert_eq!(errors.0.len(), 2);
+        assert_eq!(
+            errors
+                .get("items[1].status")
+                .next()
+                .map(|error| &*error.message),
+            Some(r#"unknown variant "delivred", did you mean "delivered"?"#)
+        );
+        assert_eq!(
+            errors
+                .get("items[2].status")
+                .next()
+                .map(|error| &*error.message),
+            Some(r#"unknown variant "lost", expected one of "pending", "shipped", "delivered""#)
+        );
+    }
+
+    #[tokio::test]
+    async fn reject_unknown_variants_with_suggestion() {
+        use axum::response::IntoResponse;
+        use axum::Json;
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+        let policy = EnumFields::default().field("order", ["asc", "desc"]);
+
+        let RejectUnknownVariants(Json(value)) =
+            RejectUnknownVariants::<Json<Value>>::from_request(
+                request(r#"{ "order": "desc" }"#),
+                &policy,
+            )
+            .await
+            .expect("known variant rejected");
+        assert_eq!(value, json!({ "order": "desc" }));
+
+        let rejection = RejectUnknownVariants::<Json<Value>>::from_request(
+            request(r#"{ "order": "decs" }"#),
+            &policy,
+        )
+        .await
+        .expect_err("typo accepted");
+        assert!(matches!(
+            &rejection,
+            PrecheckRejection::Valid(errors) if errors.get("order").any(|error| {
+                error.code == "unknown_variant" && error.message.contains(r#"did you mean "desc""#)
+            })
+        ));
+        assert_eq!(
+            rejection.into_response().status(),
+            crate::VALIDATION_ERROR_STATUS
+        );
+
+        let CaseInsensitiveEnums(RejectUnknownVariants(Json(value))) =
+            CaseInsensitiveEnums::<RejectUnknownVariants<Json<Value>>>::from_request(
+                request(r#"{ "order": "ASC" }"#),
+                &policy,
+            )
+            .await
+            .expect("mixed-case variant rejected");
+        assert_eq!(value, json!({ "order": "asc" }));
+    }
+}
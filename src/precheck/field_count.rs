@@ -0,0 +1,191 @@
+//! # Maximum number of fields
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `FieldCount<E, N>`.
+//!
+//! ## Usage
+//!
+//! The body limit bounds the size of a payload, but not how much work it takes to process it.
+//! Wrap your extractor with `FieldCount`, e.g. `FieldCount<Valid<Json<T>>, 256>`, to reject payloads with
+//! more than `N` object keys in total, counting the keys of every nested object, before they are deserialized.
+//!
+//! The error is reported for the whole document with the `too_many_fields` code.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use std::collections::HashMap;
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::FieldCount;
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/json", post(handler))
+//!     }
+//!
+//!     async fn handler(FieldCount(Valid(Json(labels))): FieldCount<Valid<Json<Labels>>, 64>) {
+//!         assert!(labels.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Labels {
+//!         #[validate(length(max = 32))]
+//!         pub labels: HashMap<String, String>,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::{run, PrecheckError, PrecheckErrors, PrecheckRejection, ROOT_PATH};
+use axum::async_trait;
+use axum::extract::{FromRequest, Request};
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// # `FieldCount` pre-check extractor
+///
+/// `FieldCount` rejects JSON bodies with more than `N` object keys in total,
+/// then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FieldCount<E, const N: usize>(pub E);
+
+impl<E, const N: usize> Deref for FieldCount<E, N> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E, const N: usize> DerefMut for FieldCount<E, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display, const N: usize> Display for FieldCount<T, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E, const N: usize> FieldCount<E, N> {
+    /// Consumes the `FieldCount` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T, const N: usize> aide::OperationInput for FieldCount<T, N>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, const N: usize> FromRequest<State> for FieldCount<Extractor, N>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let inner = run(req, state, |bytes| {
+            let count = serde_json::from_slice(bytes).map_or(0, |document| count_fields(&document));
+            if count <= N {
+                return PrecheckErrors::default();
+            }
+            PrecheckError::new(
+                ROOT_PATH,
+                "too_many_fields",
+                format!("must have at most {N} fields, found {count}"),
+            )
+            .into()
+        })
+        .await?;
+        Ok(FieldCount(inner))
+    }
+}
+
+/// Counts the object keys of a JSON document, at any depth.
+fn count_fields(value: &Value) -> usize {
+    match value {
+        Value::Array(items) => items.iter().map(count_fields).sum(),
+        Value::Object(fields) => fields.len() + fields.values().map(count_fields).sum::<usize>(),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::Json;
+    use serde_json::json;
+
+    #[test]
+    fn count_nested_fields() {
+        assert_eq!(count_fields(&json!([1, "a", null])), 0);
+        assert_eq!(
+            count_fields(&json!({ "a": 1, "b": { "c": [{ "d": 1 }, { "e": 2 }] } })),
+            5
+        );
+    }
+
+    #[tokio::test]
+    async fn field_count() {
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+
+        assert!(FieldCount::<Json<Value>, 3>::from_request(
+            request(r#"{ "a": { "b": 1, "c": 2 } }"#),
+            &()
+        )
+        .await
+        .is_ok());
+
+        let rejection = FieldCount::<Json<Value>, 3>::from_request(
+            request(r#"{ "a": { "b": 1, "c": 2 }, "d": 3 }"#),
+            &(),
+        )
+        .await
+        .expect_err("too many fields accepted");
+        assert!(matches!(
+            rejection,
+            PrecheckRejection::Valid(errors)
+                if errors.get(ROOT_PATH).any(|error| error.code == "too_many_fields")
+        ));
+    }
+}
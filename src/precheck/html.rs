@@ -0,0 +1,384 @@
+//! # HTML sanitization
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `SanitizeHtml<E>`.
+//!
+//! ## Usage
+//!
+//! Most text fields must be plain text, while a few (e.g. a biography) may contain limited formatting.
+//! `SanitizeHtml` removes HTML from every string of the JSON body, except in the fields listed in an
+//! `HtmlFields` policy, where a small set of tags is kept:
+//!
+//! 1. Build an `HtmlFields` policy listing the fields allowing HTML, and make it available from your router state
+//!    (implement `FromRef<YourState>` for it, or use it as the state directly).
+//! 2. In your handler function, wrap your extractor with `SanitizeHtml`, e.g. `SanitizeHtml<Valid<Json<T>>>`.
+//!
+//! The body is rewritten before the inner extractor runs, so validation (e.g. length limits) applies to the
+//! sanitized text. Only the sanitized strings are rewritten: numbers and the rest of the document keep their
+//! exact text. Tags are removed, but their text is kept, except for `script` and `style` elements and
+//! comments, which are removed entirely. Allowed tags are kept without their attributes.
+//! Tags formed by the text around removed ones, e.g. in `<<b>script>`, are removed as well.
+//!
+//! Nested fields are named using dots, e.g. `profile.bio`. Array indices are left out, so `posts.body`
+//! allows HTML in the `body` field of every element of `posts`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::{HtmlFields, SanitizeHtml};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new()
+//!             .route("/profile", post(handler))
+//!             .with_state(HtmlFields::new(["bio"]))
+//!     }
+//!
+//!     async fn handler(SanitizeHtml(Valid(Json(profile))): SanitizeHtml<Valid<Json<Profile>>>) {
+//!         assert!(!profile.name.contains('<'));
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Profile {
+//!         #[validate(length(min = 1, max = 32))]
+//!         pub name: String,
+//!         #[validate(length(max = 1024))]
+//!         pub bio: String,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::PrecheckRejection;
+use axum::async_trait;
+use axum::body::Body;
+use axum::extract::{FromRef, FromRequest, Request};
+use axum::http::header::CONTENT_LENGTH;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// Tags kept by default in the fields allowing HTML.
+pub const DEFAULT_ALLOWED_TAGS: &[&str] = &["b", "br", "em", "i", "li", "ol", "p", "strong", "ul"];
+
+/// The fields `SanitizeHtml` allows limited HTML in, and the tags allowed there.
+///
+/// `SanitizeHtml` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HtmlFields {
+    fields: Arc<BTreeSet<String>>,
+    tags: Arc<BTreeSet<String>>,
+}
+
+impl Default for HtmlFields {
+    fn default() -> Self {
+        Self::new(Vec::<String>::new())
+    }
+}
+
+impl HtmlFields {
+    /// Create a policy allowing the `DEFAULT_ALLOWED_TAGS` in the given fields.
+    /// Nested fields are named using dots, e.g. `profile.bio`.
+    pub fn new<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            fields: Arc::new(fields.into_iter().map(Into::into).collect()),
+            tags: Arc::new(
+                DEFAULT_ALLOWED_TAGS
+                    .iter()
+                    .map(|tag| tag.to_string())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Replace the allowed tags. Tags are matched case-insensitively.
+    pub fn with_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.tags = Arc::new(
+            tags.into_iter()
+                .map(|tag| tag.as_ref().to_ascii_lowercase())
+                .collect(),
+        );
+        self
+    }
+
+    /// Returns `true` if the policy allows HTML in the given field.
+    pub fn contains(&self, field: &str) -> bool {
+        self.fields.contains(field)
+    }
+
+    /// Sanitizes every string of a JSON document, returning the rewritten document if any was changed.
+    fn sanitize(&self, bytes: &[u8]) -> Option<Vec<u8>> {
+        crate::precheck::rewrite_strings(bytes, |_, field, text| {
+            let allowed = self.contains(field).then_some(self.tags.as_ref());
+            sanitize(text, allowed).map(Value::String)
+        })
+    }
+}
+
+/// Removes the tags of `text` except the `allowed` ones, which are kept without their attributes.
+/// Returns `None` if there was nothing to remove.
+///
+/// Removing a tag can join the text around it into a new one, e.g. `<<b>script>`, so tags are removed
+/// until none is left.
+fn sanitize(text: &str, allowed: Option<&BTreeSet<String>>) -> Option<String> {
+    if !text.contains('<') {
+        return None;
+    }
+    let mut sanitized = remove_tags(text, allowed);
+    loop {
+        let next = remove_tags(&sanitized, allowed);
+        if next == sanitized {
+            break;
+        }
+        sanitized = next;
+    }
+    (sanitized != text).then_some(sanitized)
+}
+
+/// Removes the tags of `text` once, except the `allowed` ones, which are kept without their attributes.
+fn remove_tags(text: &str, allowed: Option<&BTreeSet<String>>) -> String {
+    let mut sanitized = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        sanitized.push_str(&rest[..start]);
+        let markup = &rest[start..];
+        if let Some(comment) = markup.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some((name, closing, len)) = tag(markup) else {
+            sanitized.push('<');
+            rest = &markup[1..];
+            continue;
+        };
+        rest = &markup[len..];
+        if !closing && (name == "script" || name == "style") {
+            let end = format!("</{name}");
+            rest = rest
+                .to_ascii_lowercase()
+                .find(&end)
+                .and_then(|position| {
+                    let tail = &rest[position..];
+                    tail.find('>').map(|close| &tail[close + 1..])
+                })
+                .unwrap_or("");
+        } else if allowed.is_some_and(|allowed| allowed.contains(&name)) {
+            sanitized.push_str(if closing { "</" } else { "<" });
+            sanitized.push_str(&name);
+            sanitized.push('>');
+        }
+    }
+    sanitized.push_str(rest);
+    sanitized
+}
+
+/// Parses the tag at the start of `markup`, returning its lowercase name, whether it is a closing tag,
+/// and its length. A tag without a closing `>` extends to the end of `markup`.
+fn tag(markup: &str) -> Option<(String, bool, usize)> {
+    let inner = &markup[1..];
+    let (closing, inner) = match inner.strip_prefix('/') {
+        Some(inner) => (true, inner),
+        None => (false, inner),
+    };
+    let name_len = inner
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(inner.len());
+    if name_len == 0 || !inner.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let name = inner[..name_len].to_ascii_lowercase();
+    let len = markup.find('>').map_or(markup.len(), |end| end + 1);
+    Some((name, closing, len))
+}
+
+/// # `SanitizeHtml` pre-check extractor
+///
+/// `SanitizeHtml` removes HTML from every string value of the JSON body, except for the tags allowed in the
+/// fields of the `HtmlFields` policy taken from the state, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SanitizeHtml<E>(pub E);
+
+impl<E> Deref for SanitizeHtml<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for SanitizeHtml<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for SanitizeHtml<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> SanitizeHtml<E> {
+    /// Consumes the `SanitizeHtml` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for SanitizeHtml<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for SanitizeHtml<Extractor>
+where
+    State: Send + Sync,
+    HtmlFields: FromRef<State>,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let policy = HtmlFields::from_ref(state);
+        let (req, bytes) = crate::body::buffer(req, state)
+            .await
+            .map_err(PrecheckRejection::Body)?;
+        let req = match policy.sanitize(&bytes) {
+            Some(sanitized) => {
+                let (mut parts, _) = req.into_parts();
+                parts.headers.remove(CONTENT_LENGTH);
+                Request::from_parts(parts, Body::from(sanitized))
+            }
+            None => req,
+        };
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(PrecheckRejection::Inner)?;
+        Ok(SanitizeHtml(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Json;
+    use serde_json::json;
+
+    #[test]
+    fn sanitize_text() {
+        let tags = HtmlFields::default().tags;
+        let plain = |text| sanitize(text, None);
+        let html = |text| sanitize(text, Some(&tags));
+
+        assert_eq!(plain("1 < 2 and 3>2"), None);
+        assert_eq!(
+            plain("<b>bold</b> <a href='x'>link</a>").as_deref(),
+            Some("bold link")
+        );
+        assert_eq!(
+            plain("a<script>alert('<b>')</script>b<!-- c -->d<STYLE>p {}</Style>e").as_deref(),
+            Some("abde")
+        );
+        assert_eq!(
+            plain("unterminated <img src=x onerror=alert(1)").as_deref(),
+            Some("unterminated ")
+        );
+
+        assert_eq!(
+            html(r#"<P class="x">Hi <B onclick="evil()">there</B><br/><img src=x></p>"#).as_deref(),
+            Some("<p>Hi <b>there</b><br></p>")
+        );
+        assert_eq!(html("<b>bold</b>"), None);
+
+        // Text left around removed tags cannot form new ones
+        assert_eq!(plain("<<b>script>alert</script>").as_deref(), Some(""));
+        assert_eq!(plain("<<x>b>bold").as_deref(), Some("bold"));
+        assert_eq!(html("<<x>b>bold</b>").as_deref(), Some("<b>bold</b>"));
+        assert_eq!(html("<<x>img src=x onerror=alert(1)>").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn sanitize_keeps_numbers() {
+        let policy = HtmlFields::new(["bio"]);
+        let document =
+            r#"{"id": 123456789012345678901234567890, "bio": "<p>x</p>", "name": "<b>y</b>"}"#;
+        assert_eq!(
+            String::from_utf8(
+                policy
+                    .sanitize(document.as_bytes())
+                    .expect("nothing sanitized")
+            ),
+            Ok(
+                r#"{"id": 123456789012345678901234567890, "bio": "<p>x</p>", "name": "y"}"#
+                    .to_owned()
+            )
+        );
+        assert_eq!(policy.sanitize(br#"{"bio": "<p>x</p>", "v": 0.1}"#), None);
+    }
+
+    #[tokio::test]
+    async fn sanitize_html_per_field() {
+        let request = Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                r#"{
+                    "name": "<b>axum</b>",
+                    "bio": "<p>Hello <em>world</em><script>alert(1)</script></p>",
+                    "posts": [{ "title": "<i>one</i>", "body": "<i>one</i>" }]
+                }"#,
+            ))
+            .expect("invalid request");
+        let policy = HtmlFields::new(["bio", "posts.body"]);
+        let SanitizeHtml(Json(value)) = SanitizeHtml::<Json<Value>>::from_request(request, &policy)
+            .await
+            .expect("body rejected");
+        assert_eq!(
+            value,
+            json!({
+                "name": "axum",
+                "bio": "<p>Hello <em>world</em></p>",
+                "posts": [{ "title": "one", "body": "<i>one</i>" }]
+            })
+        );
+    }
+}
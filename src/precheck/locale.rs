@@ -0,0 +1,559 @@
+//! # Locale-aware numbers and dates
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `NormalizeLocale<E>`.
+//!
+//! ## Usage
+//!
+//! Forms filled in by people in different locales send numbers like `1.000,50` or `1,000.50`,
+//! and dates like `31/12/2024` or `12/31/2024`. `NormalizeLocale` parses them according to the locale
+//! of the request, and rewrites them as JSON numbers and ISO 8601 dates (`2024-12-31`), so they can be
+//! deserialized and validated like any other value:
+//!
+//! 1. Build a `LocaleFields` policy with the default `Locale`, listing the number and date fields which
+//!    may be sent formatted, and make it available from your router state (implement `FromRef<YourState>`
+//!    for it, or use it as the state directly).
+//! 2. In your handler function, wrap your extractor with `NormalizeLocale`, e.g. `NormalizeLocale<Valid<Json<T>>>`.
+//!
+//! The locale is the preferred language of the `Accept-Language` header which `Locale::from_language` knows,
+//! or the default locale of the policy. Group separators (`.`, `,`, `'` or spaces, except the decimal
+//! separator) must separate groups of 3 digits. ISO 8601 dates are accepted in every locale.
+//!
+//! Nested fields are named using dots, e.g. `invoice.total`. Array indices are left out, so `items.price`
+//! matches the `price` field of every element of `items`. Numbers are left untouched, while strings in those
+//! fields which cannot be parsed are reported with the `number` or `date` code. Only the parsed strings are
+//! rewritten: the other numbers and the rest of the document keep their exact text.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::{Locale, LocaleFields, NormalizeLocale};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         let policy = LocaleFields::new(Locale::EN_US)
+//!             .numbers(["amount"])
+//!             .dates(["due"]);
+//!         Router::new()
+//!             .route("/invoices", post(handler))
+//!             .with_state(policy)
+//!     }
+//!
+//!     async fn handler(NormalizeLocale(Valid(Json(invoice))): NormalizeLocale<Valid<Json<Invoice>>>) {
+//!         assert!(invoice.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Invoice {
+//!         #[validate(range(min = 0.01))]
+//!         pub amount: f64,
+//!         #[validate(length(equal = 10))]
+//!         pub due: String,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::{PrecheckError, PrecheckErrors, PrecheckRejection};
+use axum::async_trait;
+use axum::body::Body;
+use axum::extract::{FromRef, FromRequest, Request};
+use axum::http::header::{ACCEPT_LANGUAGE, CONTENT_LENGTH};
+use axum::http::HeaderMap;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// Order of the day, month and year in a date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateOrder {
+    /// e.g. `31/12/2024`
+    DayMonthYear,
+    /// e.g. `12/31/2024`
+    MonthDayYear,
+    /// e.g. `2024/12/31`
+    YearMonthDay,
+}
+
+/// How numbers and dates are formatted in a locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    /// Separator between the integer and the fractional part of numbers.
+    pub decimal_separator: char,
+    /// Order of the parts of dates.
+    pub date_order: DateOrder,
+}
+
+impl Locale {
+    /// `1,000.5` and `12/31/2024`
+    pub const EN_US: Locale = Locale::new('.', DateOrder::MonthDayYear);
+    /// `1,000.5` and `31/12/2024`
+    pub const EN_GB: Locale = Locale::new('.', DateOrder::DayMonthYear);
+    /// `1.000,5` and `31.12.2024`
+    pub const DE: Locale = Locale::new(',', DateOrder::DayMonthYear);
+    /// `1,000.5` and `2024/12/31`
+    pub const JA: Locale = Locale::new('.', DateOrder::YearMonthDay);
+
+    /// Create a `Locale`.
+    pub const fn new(decimal_separator: char, date_order: DateOrder) -> Self {
+        Self {
+            decimal_separator,
+            date_order,
+        }
+    }
+
+    /// Returns the locale of a language tag, e.g. `de-CH`, if it is known.
+    ///
+    /// Only the language matters, except for English, which is month-first in the US (and for a bare `en`)
+    /// and day-first elsewhere.
+    pub fn from_language(tag: &str) -> Option<Self> {
+        let tag = tag.trim().to_ascii_lowercase();
+        let mut subtags = tag.split(['-', '_']);
+        let language = subtags.next()?;
+        let region = subtags.next();
+        match language {
+            "en" => match region {
+                None | Some("us") => Some(Self::EN_US),
+                Some(_) => Some(Self::EN_GB),
+            },
+            "ja" | "zh" | "ko" => Some(Self::JA),
+            "hu" | "lt" => Some(Self::new(',', DateOrder::YearMonthDay)),
+            "ga" | "he" | "hi" | "ms" | "th" => Some(Self::EN_GB),
+            "bg" | "ca" | "cs" | "da" | "de" | "el" | "es" | "et" | "fi" | "fr" | "hr" | "id"
+            | "it" | "lv" | "nb" | "nl" | "nn" | "no" | "pl" | "pt" | "ro" | "ru" | "sk" | "sl"
+            | "sr" | "sv" | "tr" | "uk" | "vi" => Some(Self::DE),
+            _ => None,
+        }
+    }
+
+    /// Returns the locale of the most preferred language of an `Accept-Language` header which is known.
+    pub fn from_accept_language(header: &str) -> Option<Self> {
+        let mut languages = header
+            .split(',')
+            .filter_map(|item| {
+                let mut parts = item.split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+                (quality > 0.0).then_some((tag, quality))
+            })
+            .collect::<Vec<_>>();
+        languages.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        languages
+            .into_iter()
+            .find_map(|(tag, _)| Self::from_language(tag))
+    }
+
+    /// Parses a number formatted in this locale.
+    pub fn parse_number(&self, s: &str) -> Option<Value> {
+        let s = s.trim();
+        let (sign, digits) = match s.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (integer, fraction) = match digits.split_once(self.decimal_separator) {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (digits, None),
+        };
+        let groups = integer
+            .split(|c: char| c != self.decimal_separator && is_group_separator(c))
+            .collect::<Vec<_>>();
+        let grouped = groups.len() > 1;
+        let well_grouped = groups.iter().enumerate().all(|(index, group)| {
+            let len_ok = match (grouped, index) {
+                (false, _) => !group.is_empty(),
+                (true, 0) => (1..=3).contains(&group.len()),
+                (true, _) => group.len() == 3,
+            };
+            len_ok && group.bytes().all(|b| b.is_ascii_digit())
+        });
+        if !well_grouped {
+            return None;
+        }
+        let integer = groups.concat();
+        match fraction {
+            None => match format!("{sign}{integer}").parse::<i64>() {
+                Ok(number) => Some(Value::from(number)),
+                Err(_) if sign.is_empty() => integer.parse::<u64>().ok().map(Value::from),
+                Err(_) => None,
+            },
+            Some(fraction)
+                if !fraction.is_empty() && fraction.bytes().all(|b| b.is_ascii_digit()) =>
+            {
+                format!("{sign}{integer}.{fraction}")
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|number| number.is_finite())
+                    .map(Value::from)
+            }
+            Some(_) => None,
+        }
+    }
+
+    /// Parses a date formatted in this locale, or in ISO 8601, into an ISO 8601 date.
+    pub fn parse_date(&self, s: &str) -> Option<String> {
+        let parts = s.trim().split(['/', '.', '-']).collect::<Vec<_>>();
+        let [a, b, c] = parts[..] else {
+            return None;
+        };
+        let order = if a.len() == 4 {
+            DateOrder::YearMonthDay
+        } else {
+            self.date_order
+        };
+        let (year, month, day) = match order {
+            DateOrder::DayMonthYear => (c, b, a),
+            DateOrder::MonthDayYear => (c, a, b),
+            DateOrder::YearMonthDay => (a, b, c),
+        };
+        let number = |s: &str, digits: std::ops::RangeInclusive<usize>| {
+            (digits.contains(&s.len()) && s.bytes().all(|b| b.is_ascii_digit()))
+                .then(|| s.parse::<u32>().ok())
+                .flatten()
+        };
+        let (year, month, day) = (
+            number(year, 4..=4)?,
+            number(month, 1..=2)?,
+            number(day, 1..=2)?,
+        );
+        let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        let days = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap => 29,
+            2 => 28,
+            _ => return None,
+        };
+        (1..=days)
+            .contains(&day)
+            .then(|| format!("{year:04}-{month:02}-{day:02}"))
+    }
+}
+
+fn is_group_separator(c: char) -> bool {
+    matches!(c, '.' | ',' | '\'' | ' ' | '\u{a0}' | '\u{202f}')
+}
+
+/// The default locale, and the number and date fields `NormalizeLocale` parses.
+///
+/// `NormalizeLocale` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocaleFields {
+    default: Locale,
+    numbers: Arc<BTreeSet<String>>,
+    dates: Arc<BTreeSet<String>>,
+}
+
+impl LocaleFields {
+    /// Create a policy without fields, using `default` when the request has no known language.
+    pub fn new(default: Locale) -> Self {
+        Self {
+            default,
+            numbers: Arc::default(),
+            dates: Arc::default(),
+        }
+    }
+
+    /// Adds number fields. Nested fields are named using dots, e.g. `invoice.total`.
+    pub fn numbers<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Arc::make_mut(&mut self.numbers).extend(fields.into_iter().map(Into::into));
+        self
+    }
+
+    /// Adds date fields. Nested fields are named using dots, e.g. `invoice.due`.
+    pub fn dates<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Arc::make_mut(&mut self.dates).extend(fields.into_iter().map(Into::into));
+        self
+    }
+
+    /// Returns the locale of a request, from its `Accept-Language` header or the default one.
+    pub fn locale(&self, headers: &HeaderMap) -> Locale {
+        headers
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Locale::from_accept_language)
+            .unwrap_or(self.default)
+    }
+
+    /// Replaces the formatted strings of the listed fields, returning the rewritten document if any was replaced.
+    fn normalize(
+        &self,
+        locale: &Locale,
+        bytes: &[u8],
+        errors: &mut PrecheckErrors,
+    ) -> Option<Vec<u8>> {
+        // `path` is reported in errors, `field` (which has no array indices) is matched against the policy.
+        crate::precheck::rewrite_strings(bytes, |path, field, text| {
+            if self.numbers.contains(field) {
+                let number = locale.parse_number(text);
+                if number.is_none() {
+                    errors.add(PrecheckError::new(path, "number", "must be a number"));
+                }
+                number
+            } else if self.dates.contains(field) {
+                match locale.parse_date(text) {
+                    Some(date) => (date != text).then_some(Value::String(date)),
+                    None => {
+                        errors.add(PrecheckError::new(path, "date", "must be a date"));
+                        None
+                    }
+                }
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// # `NormalizeLocale` pre-check extractor
+///
+/// `NormalizeLocale` parses the strings of the number and date fields listed in the `LocaleFields` policy
+/// taken from the state according to the locale of the request, rewrites them as JSON numbers
+/// and ISO 8601 dates, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NormalizeLocale<E>(pub E);
+
+impl<E> Deref for NormalizeLocale<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for NormalizeLocale<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for NormalizeLocale<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> NormalizeLocale<E> {
+    /// Consumes the `NormalizeLocale` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for NormalizeLocale<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for NormalizeLocale<Extractor>
+where
+    State: Send + Sync,
+    LocaleFields: FromRef<State>,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let policy = LocaleFields::from_ref(state);
+        let locale = policy.locale(req.headers());
+        let (req, bytes) = crate::body::buffer(req, state)
+            .await
+            .map_err(PrecheckRejection::Body)?;
+        let mut errors = PrecheckErrors::default();
+        let normalized = policy.normalize(&locale, &bytes, &mut errors);
+        if !errors.is_empty() {
+            return Err(PrecheckRejection::Valid(errors));
+        }
+        let req = match normalized {
+            Some(normalized) => {
+                let (mut parts, _) = req.into_parts();
+                parts.headers.remove(CONTENT_LENGTH);
+                Request::from_parts(parts, Body::from(normalized))
+            }
+            None => req,
+        };
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(PrecheckRejection::Inner)?;
+        Ok(NormalizeLocale(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_number() {
+        assert_eq!(Locale::DE.parse_number("1.000,50"), Some(json!(1000.5)));
+        assert_eq!(Locale::DE.parse_number("-12,5"), Some(json!(-12.5)));
+        assert_eq!(Locale::DE.parse_number("1 000 000"), Some(json!(1_000_000)));
+        assert_eq!(Locale::EN_US.parse_number("1,000.50"), Some(json!(1000.5)));
+        assert_eq!(Locale::EN_US.parse_number("+42"), Some(json!(42)));
+        assert_eq!(Locale::EN_US.parse_number("1,5"), None);
+        assert_eq!(Locale::DE.parse_number("1.000.50"), None);
+        assert_eq!(Locale::DE.parse_number("1,"), None);
+        assert_eq!(Locale::DE.parse_number("abc"), None);
+        assert_eq!(Locale::DE.parse_number(""), None);
+    }
+
+    #[test]
+    fn parse_date() {
+        assert_eq!(
+            Locale::DE.parse_date("31.12.2024").as_deref(),
+            Some("2024-12-31")
+        );
+        assert_eq!(
+            Locale::EN_US.parse_date("2/29/2024").as_deref(),
+            Some("2024-02-29")
+        );
+        assert_eq!(
+            Locale::EN_US.parse_date("2024-12-31").as_deref(),
+            Some("2024-12-31")
+        );
+        assert_eq!(Locale::EN_US.parse_date("31/12/2024"), None);
+        assert_eq!(Locale::DE.parse_date("29.02.2023"), None);
+        assert_eq!(Locale::DE.parse_date("31.12.24"), None);
+    }
+
+    #[test]
+    fn locale_from_accept_language() {
+        assert_eq!(
+            Locale::from_accept_language("de-CH,de;q=0.9,en;q=0.8"),
+            Some(Locale::DE)
+        );
+        assert_eq!(
+            Locale::from_accept_language("xx, en-GB;q=0.5, fr;q=0.7"),
+            Some(Locale::DE)
+        );
+        assert_eq!(
+            Locale::from_accept_language("fr;q=0, en-AU"),
+            Some(Locale::EN_GB)
+        );
+        assert_eq!(Locale::from_accept_language("*"), None);
+    }
+
+    #[test]
+    fn normalize_keeps_numbers() {
+        let policy = LocaleFields::new(Locale::DE).numbers(["items.price"]);
+        let document =
+            r#"{"id": 123456789012345678901234567890, "items": [{"price": "1.000,50"}]}"#;
+        let mut errors = PrecheckErrors::default();
+        assert_eq!(
+            String::from_utf8(
+                policy
+                    .normalize(&Locale::DE, document.as_bytes(), &mut errors)
+                    .expect("nothing normalized")
+            ),
+            Ok(
+                r#"{"id": 123456789012345678901234567890, "items": [{"price": 1000.5}]}"#
+                    .to_owned()
+            )
+        );
+        assert!(errors.is_empty());
+
+        let document = r#"{"items": [{"price": 1}, {"price": "x"}]}"#;
+        assert_eq!(
+            policy.normalize(&Locale::DE, document.as_bytes(), &mut errors),
+            None
+        );
+        assert_eq!(errors.get("items[1].price").count(), 1);
+    }
+
+    #[cfg(feature = "validator")]
+    #[tokio::test]
+    async fn normalize_locale_before_validation() {
+        use crate::Valid;
+        use axum::Json;
+        use validator::Validate;
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        struct Invoice {
+            #[validate(range(min = 1000.0))]
+            amount: f64,
+            due: String,
+        }
+
+        let request = |language: &str, body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .header(ACCEPT_LANGUAGE, language)
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+        let policy = LocaleFields::new(Locale::EN_US)
+            .numbers(["amount"])
+            .dates(["due"]);
+
+        let NormalizeLocale(Valid(Json(invoice))) =
+            NormalizeLocale::<Valid<Json<Invoice>>>::from_request(
+                request("de-DE", r#"{ "amount": "1.000,50", "due": "31.12.2024" }"#),
+                &policy,
+            )
+            .await
+            .expect("German invoice rejected");
+        assert_eq!(invoice.amount, 1000.5);
+        assert_eq!(invoice.due, "2024-12-31");
+
+        assert!(matches!(
+            NormalizeLocale::<Valid<Json<Invoice>>>::from_request(
+                request("en-US", r#"{ "amount": "999.99", "due": "12/31/2024" }"#),
+                &policy
+            )
+            .await,
+            Err(PrecheckRejection::Inner(crate::ValidRejection::Valid(_)))
+        ));
+
+        assert!(matches!(
+            NormalizeLocale::<Valid<Json<Invoice>>>::from_request(
+                request("en-US", r#"{ "amount": "1.000,50", "due": "31/12/2024" }"#),
+                &policy
+            )
+            .await,
+            Err(PrecheckRejection::Valid(errors))
+                if errors.get("amount").count() == 1 && errors.get("due").count() == 1
+        ));
+    }
+}
@@ -0,0 +1,300 @@
+//! # Rejection of non-finite numbers
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `RejectNonFinite<E>`.
+//!
+//! ## Usage
+//!
+//! JSON has no representation for `NaN` or infinities, but some clients and parsers emit bare `NaN`,
+//! `Infinity` or `-Infinity` tokens, or numbers such as `1e400` that overflow to infinity.
+//! Wrap your extractor with `RejectNonFinite`, e.g. `RejectNonFinite<Valid<Json<T>>>`, to turn these into
+//! field-level errors (code `non_finite`) naming the offending field, before normal validation runs.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::RejectNonFinite;
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/json", post(handler))
+//!     }
+//!
+//!     async fn handler(
+//!         RejectNonFinite(Valid(Json(measurement))): RejectNonFinite<Valid<Json<Measurement>>>,
+//!     ) {
+//!         assert!(measurement.value.is_finite());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Measurement {
+//!         #[validate(range(min = -100.0, max = 100.0))]
+//!         pub value: f64,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::{
+    push_index, push_key, run, PrecheckError, PrecheckErrors, PrecheckRejection,
+};
+use axum::async_trait;
+use axum::extract::{FromRequest, Request};
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// # `RejectNonFinite` pre-check extractor
+///
+/// `RejectNonFinite` rejects JSON bodies containing `NaN` or infinite numbers,
+/// then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RejectNonFinite<E>(pub E);
+
+impl<E> Deref for RejectNonFinite<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for RejectNonFinite<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for RejectNonFinite<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> RejectNonFinite<E> {
+    /// Consumes the `RejectNonFinite` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for RejectNonFinite<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for RejectNonFinite<Extractor>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let inner = run(req, state, |bytes| {
+            let error = find_non_finite(bytes)
+                .map(|path| PrecheckError::new(path, "non_finite", "must be a finite number"));
+            PrecheckErrors(error.into_iter().collect())
+        })
+        .await?;
+        Ok(RejectNonFinite(inner))
+    }
+}
+
+pub(super) enum Frame {
+    Object {
+        key: Option<String>,
+        expect_key: bool,
+    },
+    Array {
+        index: usize,
+    },
+}
+
+pub(super) fn current_path(stack: &[Frame]) -> String {
+    stack.iter().fold(String::new(), |path, frame| match frame {
+        Frame::Object { key: Some(key), .. } => push_key(&path, key),
+        Frame::Object { key: None, .. } => path,
+        Frame::Array { index } => push_index(&path, *index),
+    })
+}
+
+/// Scans a (possibly non-standard) JSON document for non-finite number tokens,
+/// returning the path of the first one found.
+fn find_non_finite(input: &[u8]) -> Option<String> {
+    let mut found = None;
+    visit_bare_tokens(input, |stack, token| {
+        let non_finite = token.parse::<f64>().is_ok_and(|number| !number.is_finite());
+        if non_finite {
+            found = Some(current_path(stack));
+        }
+        non_finite
+    });
+    found
+}
+
+/// Scans a (possibly non-standard) JSON document, calling `visit` with the enclosing containers of every token
+/// which is not a string or a punctuation mark, i.e. numbers, literals and non-standard tokens such as `NaN`.
+/// The scan stops as soon as `visit` returns `true`.
+pub(super) fn visit_bare_tokens(input: &[u8], mut visit: impl FnMut(&[Frame], &str) -> bool) {
+    let mut stack = Vec::new();
+    let mut i = 0;
+    while i < input.len() {
+        match input[i] {
+            b'{' => stack.push(Frame::Object {
+                key: None,
+                expect_key: true,
+            }),
+            b'[' => stack.push(Frame::Array { index: 0 }),
+            b'}' | b']' => {
+                stack.pop();
+            }
+            b',' => match stack.last_mut() {
+                Some(Frame::Array { index }) => *index += 1,
+                Some(Frame::Object { expect_key, .. }) => *expect_key = true,
+                None => {}
+            },
+            b':' => {
+                if let Some(Frame::Object { expect_key, .. }) = stack.last_mut() {
+                    *expect_key = false;
+                }
+            }
+            b'"' => {
+                let start = i + 1;
+                i = start;
+                while i < input.len() && input[i] != b'"' {
+                    i += if input[i] == b'\\' { 2 } else { 1 };
+                }
+                if let Some(Frame::Object {
+                    key,
+                    expect_key: true,
+                }) = stack.last_mut()
+                {
+                    let end = i.min(input.len());
+                    *key = Some(String::from_utf8_lossy(&input[start..end]).into_owned());
+                }
+            }
+            c if c.is_ascii_whitespace() => {}
+            _ => {
+                let start = i;
+                while i < input.len()
+                    && !matches!(input[i], b',' | b':' | b'}' | b']')
+                    && !input[i].is_ascii_whitespace()
+                {
+                    i += 1;
+                }
+                let stop =
+                    std::str::from_utf8(&input[start..i]).is_ok_and(|token| visit(&stack, token));
+                if stop {
+                    return;
+                }
+                continue;
+            }
+        }
+        i += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::precheck::ROOT_PATH;
+    use axum::body::Body;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use axum::Json;
+
+    #[test]
+    fn find_non_finite_paths() {
+        assert_eq!(find_non_finite(br#"{"a": 1.5, "b": [1, 2e10]}"#), None);
+        assert_eq!(find_non_finite(br#"{"a": "NaN"}"#), None);
+        assert_eq!(
+            find_non_finite(br#"{"a": 1, "b": NaN}"#),
+            Some(String::from("b"))
+        );
+        assert_eq!(
+            find_non_finite(br#"{"a": {"b": [0, -Infinity]}}"#),
+            Some(String::from("a.b[1]"))
+        );
+        assert_eq!(
+            find_non_finite(br#"{"a\"b": [{"c": 1e400}]}"#),
+            Some(String::from(r#"a\"b[0].c"#))
+        );
+        assert_eq!(find_non_finite(b"Infinity"), Some(String::new()));
+    }
+
+    #[tokio::test]
+    async fn reject_non_finite() {
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+
+        let RejectNonFinite(Json(value)) =
+            RejectNonFinite::<Json<serde_json::Value>>::from_request(request(r#"{"v": 1}"#), &())
+                .await
+                .expect("finite body rejected");
+        assert_eq!(value, serde_json::json!({ "v": 1 }));
+
+        let rejection =
+            RejectNonFinite::<Json<serde_json::Value>>::from_request(request(r#"{"v": NaN}"#), &())
+                .await
+                .expect_err("NaN accepted");
+        assert!(matches!(
+            &rejection,
+            PrecheckRejection::Valid(errors) if errors.get("v").any(|error| error.code == "non_finite")
+        ));
+        assert_eq!(
+            rejection.into_response().status(),
+            crate::VALIDATION_ERROR_STATUS
+        );
+
+        let rejection =
+            RejectNonFinite::<Json<serde_json::Value>>::from_request(request("NaN"), &())
+                .await
+                .expect_err("NaN accepted");
+        assert!(matches!(
+            rejection,
+            PrecheckRejection::Valid(errors) if errors.get(ROOT_PATH).count() == 1
+        ));
+
+        let rejection = RejectNonFinite::<Json<serde_json::Value>>::from_request(request("{"), &())
+            .await
+            .expect_err("malformed body accepted");
+        assert_eq!(
+            rejection.into_response().status(),
+            StatusCode::BAD_REQUEST,
+            "malformed body should be rejected by the inner extractor"
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! # Allowlist of nullable fields
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `RejectNulls<E>`.
+//!
+//! ## Usage
+//!
+//! `Option<T>` fields accept both a missing field and an explicit `null`. When only some fields may be
+//! explicitly `null` (e.g. `null` clears a value in a partial update), declare them in a `NullableFields`
+//! policy and reject `null` everywhere else:
+//!
+//! 1. Build a `NullableFields` policy and make it available from your router state
+//!    (implement `FromRef<YourState>` for it, or use it as the state directly).
+//! 2. In your handler function, wrap your extractor with `RejectNulls`, e.g. `RejectNulls<Valid<Json<T>>>`.
+//!
+//! Nested fields are named using dots, e.g. `address.zip`. Array indices are left out, so `items.note`
+//! allows `null` in the `note` field of every element of `items`. Each `null` found elsewhere is reported
+//! with the `not_nullable` code, at its full path (e.g. `items[2].price`).
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::patch;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::{NullableFields, RejectNulls};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new()
+//!             .route("/json", patch(handler))
+//!             .with_state(NullableFields::new(["nickname"]))
+//!     }
+//!
+//!     async fn handler(RejectNulls(Valid(Json(update))): RejectNulls<Valid<Json<UserUpdate>>>) {
+//!         assert!(update.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct UserUpdate {
+//!         #[validate(length(min = 1, max = 32))]
+//!         pub name: Option<String>,
+//!         #[validate(length(min = 1, max = 32))]
+//!         pub nickname: Option<String>,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::{
+    push_index, push_key, run, PrecheckError, PrecheckErrors, PrecheckRejection,
+};
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, Request};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// The set of fields `RejectNulls` allows to be `null`.
+///
+/// `RejectNulls` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NullableFields(Arc<BTreeSet<String>>);
+
+impl NullableFields {
+    /// Create a policy allowing `null` in the given fields. Nested fields are named using dots, e.g. `address.zip`.
+    pub fn new<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self(Arc::new(fields.into_iter().map(Into::into).collect()))
+    }
+
+    /// Returns `true` if the policy allows `null` in the given field.
+    pub fn contains(&self, field: &str) -> bool {
+        self.0.contains(field)
+    }
+
+    /// Returns the `null`s of a JSON document which are not allowed.
+    fn check(&self, document: &Value) -> PrecheckErrors {
+        let mut errors = PrecheckErrors::default();
+        self.visit(document, "", "", &mut errors);
+        errors
+    }
+
+    /// `path` is reported in errors, `field` (which has no array indices) is matched against the policy.
+    fn visit(&self, value: &Value, path: &str, field: &str, errors: &mut PrecheckErrors) {
+        match value {
+            Value::Null if !path.is_empty() && !self.contains(field) => {
+                errors.add(PrecheckError::new(path, "not_nullable", "must not be null"));
+            }
+            Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    self.visit(item, &push_index(path, index), field, errors);
+                }
+            }
+            Value::Object(fields) => {
+                for (key, value) in fields {
+                    self.visit(value, &push_key(path, key), &push_key(field, key), errors);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// # `RejectNulls` pre-check extractor
+///
+/// `RejectNulls` rejects JSON bodies containing `null` in a field which is not listed in the
+/// `NullableFields` policy taken from the state, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RejectNulls<E>(pub E);
+
+impl<E> Deref for RejectNulls<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for RejectNulls<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for RejectNulls<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> RejectNulls<E> {
+    /// Consumes the `RejectNulls` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for RejectNulls<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for RejectNulls<Extractor>
+where
+    State: Send + Sync,
+    NullableFields: FromRef<State>,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let policy = NullableFields::from_ref(state);
+        let inner = run(req, state, |bytes| match serde_json::from_slice(bytes) {
+            Ok(document) => policy.check(&document),
+            Err(_) => PrecheckErrors::default(),
+        })
+        .await?;
+        Ok(RejectNulls(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::Json;
+    use serde_json::json;
+
+    #[test]
+    fn nullable_fields_check() {
+        let policy = NullableFields::new(["nickname", "items.note"]);
+        assert!(policy
+            .check(&json!({ "nickname": null, "items": [{ "note": null }, { "note": "x" }] }))
+            .is_empty());
+
+        let errors = policy.check(&json!({
+            "name": null,
+            "items": [{ "note": null, "price": null }],
+            "address": { "nickname": null }
+        }));
+        assert_eq!(errors.0.len(), 3);
+        assert_eq!(errors.get("name").count(), 1);
+        assert_eq!(errors.get("items[0].price").count(), 1);
+        assert_eq!(errors.get("address.nickname").count(), 1);
+        assert!(errors.0.iter().all(|error| error.code == "not_nullable"));
+
+        assert!(policy.check(&Value::Null).is_empty());
+    }
+
+    #[tokio::test]
+    async fn reject_nulls() {
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+        let policy = NullableFields::new(["nickname"]);
+
+        assert!(RejectNulls::<Json<Value>>::from_request(
+            request(r#"{ "name": "axum", "nickname": null }"#),
+            &policy
+        )
+        .await
+        .is_ok());
+
+        let rejection =
+            RejectNulls::<Json<Value>>::from_request(request(r#"{ "name": null }"#), &policy)
+                .await
+                .expect_err("null in non-nullable field accepted");
+        assert!(matches!(
+            rejection,
+            PrecheckRejection::Valid(errors) if errors.get("name").count() == 1
+        ));
+    }
+}
@@ -0,0 +1,361 @@
+//! # Rejection of numbers losing precision
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `RejectPrecisionLoss<E>`.
+//!
+//! ## Usage
+//!
+//! A JSON number is silently rounded when it is deserialized into a type which can't represent it,
+//! e.g. `0.12345678901234567890` into an `f64`, or `10.005` into an amount with two decimal places.
+//! For financial fields, rounding is worse than rejecting the request, so `RejectPrecisionLoss` reports
+//! those numbers as field-level errors (code `precision`) before the body is deserialized:
+//!
+//! 1. Build a `PrecisionFields` policy listing each numeric field with the `Precision` of its target type,
+//!    and make it available from your router state (implement `FromRef<YourState>` for it,
+//!    or use it as the state directly).
+//! 2. In your handler function, wrap your extractor with `RejectPrecisionLoss`,
+//!    e.g. `RejectPrecisionLoss<Valid<Json<T>>>`.
+//!
+//! A number is considered lossless for a floating point type if it is the shortest decimal representation of
+//! the float it is parsed into, so `0.1` is accepted for an `f64` field, while `0.10000000000000001` is not.
+//! Trailing zeros are ignored, so `10.50` has two decimal places.
+//!
+//! Nested fields are named using dots, e.g. `payment.amount`. Array indices are left out, so `lines.price`
+//! names the `price` field of every element of `lines`, and `amounts` names every element of `amounts`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::{Precision, PrecisionFields, RejectPrecisionLoss};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new()
+//!             .route("/payment", post(handler))
+//!             .with_state(
+//!                 PrecisionFields::default()
+//!                     .field("amount", Precision::Decimal(2))
+//!                     .field("rate", Precision::F64),
+//!             )
+//!     }
+//!
+//!     async fn handler(
+//!         RejectPrecisionLoss(Valid(Json(payment))): RejectPrecisionLoss<Valid<Json<Payment>>>,
+//!     ) {
+//!         assert!(payment.amount > 0.0);
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Payment {
+//!         #[validate(range(exclusive_min = 0.0))]
+//!         pub amount: f64,
+//!         #[validate(range(min = 0.0, max = 1.0))]
+//!         pub rate: f64,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::non_finite::{current_path, visit_bare_tokens, Frame};
+use crate::precheck::{push_key, run, PrecheckError, PrecheckErrors, PrecheckRejection};
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, Request};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// The precision a numeric field can hold without rounding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    /// The field is deserialized into an `f32`.
+    F32,
+    /// The field is deserialized into an `f64`.
+    F64,
+    /// The field holds at most the given number of decimal places, e.g. `2` for most currency amounts.
+    Decimal(u32),
+}
+
+impl Precision {
+    /// Returns `true` if the JSON number `token` can't be represented with this precision.
+    /// Tokens which are not JSON numbers are never reported.
+    pub fn loses(&self, token: &str) -> bool {
+        let Some(decimal) = Decimal::parse(token) else {
+            return false;
+        };
+        match self {
+            Precision::F32 => token.parse::<f32>().map_or(true, |float| {
+                Decimal::parse(&format!("{float:e}")) != Some(decimal)
+            }),
+            Precision::F64 => token.parse::<f64>().map_or(true, |float| {
+                Decimal::parse(&format!("{float:e}")) != Some(decimal)
+            }),
+            Precision::Decimal(places) => decimal.exponent < -i64::from(*places),
+        }
+    }
+}
+
+/// The value of a JSON number, as `digits * 10^exponent` without leading or trailing zeros in `digits`.
+#[derive(Debug, PartialEq, Eq)]
+struct Decimal {
+    negative: bool,
+    digits: String,
+    exponent: i64,
+}
+
+impl Decimal {
+    fn parse(token: &str) -> Option<Self> {
+        let (negative, unsigned) = match token.strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, token),
+        };
+        let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()?),
+            None => (unsigned, 0),
+        };
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if integer.is_empty()
+            || !integer.bytes().all(|c| c.is_ascii_digit())
+            || !fraction.bytes().all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+        let digits = format!("{integer}{fraction}");
+        let trimmed = digits.trim_end_matches('0');
+        let exponent = exponent - fraction.len() as i64 + (digits.len() - trimmed.len()) as i64;
+        let digits = trimmed.trim_start_matches('0');
+        Some(if digits.is_empty() {
+            Decimal {
+                negative: false,
+                digits: String::new(),
+                exponent: 0,
+            }
+        } else {
+            Decimal {
+                negative,
+                digits: digits.to_owned(),
+                exponent,
+            }
+        })
+    }
+}
+
+/// The numeric fields `RejectPrecisionLoss` checks, and their precision.
+///
+/// `RejectPrecisionLoss` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrecisionFields(Arc<BTreeMap<String, Precision>>);
+
+impl PrecisionFields {
+    /// Check the given field against `precision`. Nested fields are named using dots, e.g. `payment.amount`.
+    pub fn field(mut self, field: impl Into<String>, precision: Precision) -> Self {
+        Arc::make_mut(&mut self.0).insert(field.into(), precision);
+        self
+    }
+
+    /// Returns the precision of the given field, if it is checked.
+    pub fn get(&self, field: &str) -> Option<Precision> {
+        self.0.get(field).copied()
+    }
+
+    /// Returns the errors of the numbers of a JSON document which would lose precision.
+    fn check(&self, input: &[u8]) -> PrecheckErrors {
+        let mut errors = PrecheckErrors::default();
+        visit_bare_tokens(input, |stack, token| {
+            let loses = self
+                .get(&current_field(stack))
+                .is_some_and(|precision| precision.loses(token));
+            if loses {
+                errors.add(PrecheckError::new(
+                    current_path(stack),
+                    "precision",
+                    "cannot be represented without losing precision",
+                ));
+            }
+            false
+        });
+        errors
+    }
+}
+
+/// Returns the field of the current token, without array indices.
+fn current_field(stack: &[Frame]) -> String {
+    stack
+        .iter()
+        .fold(String::new(), |field, frame| match frame {
+            Frame::Object { key: Some(key), .. } => push_key(&field, key),
+            _ => field,
+        })
+}
+
+/// # `RejectPrecisionLoss` pre-check extractor
+///
+/// `RejectPrecisionLoss` rejects JSON bodies with numbers which can't be represented with the precision of
+/// their field in the `PrecisionFields` policy taken from the state, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RejectPrecisionLoss<E>(pub E);
+
+impl<E> Deref for RejectPrecisionLoss<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for RejectPrecisionLoss<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for RejectPrecisionLoss<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> RejectPrecisionLoss<E> {
+    /// Consumes the `RejectPrecisionLoss` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for RejectPrecisionLoss<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for RejectPrecisionLoss<Extractor>
+where
+    State: Send + Sync,
+    PrecisionFields: FromRef<State>,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let policy = PrecisionFields::from_ref(state);
+        let inner = run(req, state, |bytes| policy.check(bytes)).await?;
+        Ok(RejectPrecisionLoss(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use axum::Json;
+
+    #[test]
+    fn detect_precision_loss() {
+        assert!(!Precision::F64.loses("0.1"));
+        assert!(!Precision::F64.loses("-1.5e3"));
+        assert!(!Precision::F64.loses("9007199254740992"));
+        assert!(Precision::F64.loses("9007199254740993"));
+        assert!(Precision::F64.loses("0.12345678901234567890"));
+        assert!(Precision::F64.loses("1e400"));
+        assert!(!Precision::F32.loses("0.5"));
+        assert!(Precision::F32.loses("0.123456789"));
+        assert!(!Precision::Decimal(2).loses("10.50"));
+        assert!(!Precision::Decimal(2).loses("1.5e1"));
+        assert!(Precision::Decimal(2).loses("10.005"));
+        assert!(Precision::Decimal(0).loses("1.5"));
+        assert!(!Precision::F64.loses("true"));
+        assert!(!Precision::F64.loses("NaN"));
+    }
+
+    #[test]
+    fn check_precision_paths() {
+        let policy = PrecisionFields::default()
+            .field("amount", Precision::Decimal(2))
+            .field("lines.price", Precision::F64);
+        assert!(policy
+            .check(br#"{"amount": 1.25, "lines": [{"price": 0.1}], "other": 0.001}"#)
+            .is_empty());
+        let errors = policy.check(
+            br#"{"amount": 1.255, "lines": [{"price": 1}, {"price": 0.10000000000000001}]}"#,
+        );
+        assert_eq!(errors.0.len(), 2);
+        assert_eq!(errors.get("amount").count(), 1);
+        assert_eq!(errors.get("lines[1].price").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn reject_precision_loss() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Payment {
+            amount: f64,
+        }
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+        let policy = PrecisionFields::default().field("amount", Precision::F64);
+
+        let RejectPrecisionLoss(Json(payment)) =
+            RejectPrecisionLoss::<Json<Payment>>::from_request(
+                request(r#"{"amount": 12.5}"#),
+                &policy,
+            )
+            .await
+            .expect("exact amount rejected");
+        assert_eq!(payment.amount, 12.5);
+
+        let rejection = RejectPrecisionLoss::<Json<Payment>>::from_request(
+            request(r#"{"amount": 1234567890.123456789}"#),
+            &policy,
+        )
+        .await
+        .expect_err("rounded amount accepted");
+        assert!(matches!(
+            &rejection,
+            PrecheckRejection::Valid(errors) if errors.get("amount").any(|error| error.code == "precision")
+        ));
+        assert_eq!(
+            rejection.into_response().status(),
+            crate::VALIDATION_ERROR_STATUS
+        );
+
+        let rejection = RejectPrecisionLoss::<Json<Payment>>::from_request(request("{"), &policy)
+            .await
+            .expect_err("malformed body accepted");
+        assert_eq!(rejection.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,231 @@
+//! # Policy-driven required fields
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `RequireFields<E>`.
+//!
+//! ## Usage
+//!
+//! Some fields are only required in certain deployments, e.g. a field that is optional in development
+//! but required in production. Declare such fields as `Option<T>` in your data type, and let the
+//! `RequiredFields` policy decide which of them must be present:
+//!
+//! 1. Build a `RequiredFields` policy from your configuration and make it available from your router state
+//!    (implement `FromRef<YourState>` for it, or use it as the state directly).
+//! 2. In your handler function, wrap your extractor with `RequireFields`, e.g. `RequireFields<Valid<Json<T>>>`.
+//!
+//! A field listed in the policy is missing if it is absent or `null`. Nested fields are named using dots,
+//! e.g. `address.zip`. Each missing field is reported with the `required` code.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::{RequireFields, RequiredFields};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router(production: bool) -> Router {
+//!         let policy = if production {
+//!             RequiredFields::new(["phone"])
+//!         } else {
+//!             RequiredFields::default()
+//!         };
+//!         Router::new()
+//!             .route("/json", post(handler))
+//!             .with_state(policy)
+//!     }
+//!
+//!     async fn handler(RequireFields(Valid(Json(user))): RequireFields<Valid<Json<User>>>) {
+//!         assert!(user.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct User {
+//!         #[validate(length(min = 1, max = 32))]
+//!         pub name: String,
+//!         #[validate(length(min = 6, max = 16))]
+//!         pub phone: Option<String>,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router(true));
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::{run, PrecheckError, PrecheckErrors, PrecheckRejection};
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, Request};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// The set of fields `RequireFields` requires to be present and not `null`.
+///
+/// `RequireFields` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequiredFields(Arc<BTreeSet<String>>);
+
+impl RequiredFields {
+    /// Create a policy requiring the given fields. Nested fields are named using dots, e.g. `address.zip`.
+    pub fn new<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self(Arc::new(fields.into_iter().map(Into::into).collect()))
+    }
+
+    /// Returns `true` if the policy requires the given field.
+    pub fn contains(&self, field: &str) -> bool {
+        self.0.contains(field)
+    }
+
+    /// Returns the missing fields of a JSON document.
+    fn check(&self, document: &Value) -> PrecheckErrors {
+        let missing = self.0.iter().filter(|field| {
+            field
+                .split('.')
+                .try_fold(document, |value, key| value.get(key))
+                .is_none_or(Value::is_null)
+        });
+        PrecheckErrors(
+            missing
+                .map(|field| PrecheckError::new(field.as_str(), "required", "is required"))
+                .collect(),
+        )
+    }
+}
+
+/// # `RequireFields` pre-check extractor
+///
+/// `RequireFields` checks that the JSON body contains every field of the `RequiredFields`
+/// policy taken from the state, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequireFields<E>(pub E);
+
+impl<E> Deref for RequireFields<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for RequireFields<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for RequireFields<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> RequireFields<E> {
+    /// Consumes the `RequireFields` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for RequireFields<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for RequireFields<Extractor>
+where
+    State: Send + Sync,
+    RequiredFields: FromRef<State>,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let policy = RequiredFields::from_ref(state);
+        let inner = run(req, state, |bytes| match serde_json::from_slice(bytes) {
+            Ok(document) => policy.check(&document),
+            Err(_) => PrecheckErrors::default(),
+        })
+        .await?;
+        Ok(RequireFields(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::Json;
+    use serde_json::json;
+
+    #[test]
+    fn required_fields_check() {
+        let policy = RequiredFields::new(["phone", "address.zip"]);
+        assert!(policy.contains("phone"));
+        assert!(policy
+            .check(&json!({ "phone": "123456", "address": { "zip": "10001" } }))
+            .is_empty());
+
+        let errors = policy.check(&json!({ "phone": null, "address": {} }));
+        assert_eq!(errors.get("phone").count(), 1);
+        assert_eq!(errors.get("address.zip").count(), 1);
+        assert!(errors.0.iter().all(|error| error.code == "required"));
+    }
+
+    #[tokio::test]
+    async fn require_fields_per_environment() {
+        let request = || {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{ "name": "axum" }"#))
+                .expect("invalid request")
+        };
+
+        let development = RequiredFields::default();
+        assert!(
+            RequireFields::<Json<Value>>::from_request(request(), &development)
+                .await
+                .is_ok()
+        );
+
+        let production = RequiredFields::new(["phone"]);
+        let rejection = RequireFields::<Json<Value>>::from_request(request(), &production)
+            .await
+            .expect_err("missing required field accepted");
+        assert!(matches!(
+            rejection,
+            PrecheckRejection::Valid(errors) if errors.get("phone").count() == 1
+        ));
+    }
+}
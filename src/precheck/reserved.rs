@@ -0,0 +1,254 @@
+//! # Reserved fields
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `RejectReserved<E>`.
+//!
+//! ## Usage
+//!
+//! Some fields are set by the server and must not be sent by clients, e.g. `id` or `created_at` when creating
+//! a resource. Since serde ignores unknown fields by default, they would otherwise be silently dropped, or
+//! worse, deserialized into a type shared with responses. Declare them in a `ReservedFields` policy to reject
+//! payloads which include them:
+//!
+//! 1. Build a `ReservedFields` policy and make it available from the state of the routes it applies to
+//!    (implement `FromRef<YourState>` for it, or use it as the state directly). Routes with different reserved
+//!    fields use different states, e.g. by nesting routers.
+//! 2. In your handler function, wrap your extractor with `RejectReserved`, e.g. `RejectReserved<Valid<Json<T>>>`.
+//!
+//! A reserved field is rejected whenever it is present, even if it is `null`. Nested fields are named using dots,
+//! e.g. `owner.id`. Array indices are left out, so `items.id` is reserved in every element of `items`.
+//! Each reserved field found is reported with the `reserved` code, at its full path (e.g. `items[2].id`).
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::{RejectReserved, ReservedFields};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new()
+//!             .route("/articles", post(handler))
+//!             .with_state(ReservedFields::new(["id", "created_at", "author.id"]))
+//!     }
+//!
+//!     async fn handler(
+//!         RejectReserved(Valid(Json(article))): RejectReserved<Valid<Json<NewArticle>>>,
+//!     ) {
+//!         assert!(article.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct NewArticle {
+//!         #[validate(length(min = 1, max = 128))]
+//!         pub title: String,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::{
+    push_index, push_key, run, PrecheckError, PrecheckErrors, PrecheckRejection,
+};
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, Request};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// The set of fields `RejectReserved` rejects.
+///
+/// `RejectReserved` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReservedFields(Arc<BTreeSet<String>>);
+
+impl ReservedFields {
+    /// Create a policy reserving the given fields. Nested fields are named using dots, e.g. `owner.id`.
+    pub fn new<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self(Arc::new(fields.into_iter().map(Into::into).collect()))
+    }
+
+    /// Returns `true` if the policy reserves the given field.
+    pub fn contains(&self, field: &str) -> bool {
+        self.0.contains(field)
+    }
+
+    /// Returns the reserved fields present in a JSON document.
+    fn check(&self, document: &Value) -> PrecheckErrors {
+        let mut errors = PrecheckErrors::default();
+        self.visit(document, "", "", &mut errors);
+        errors
+    }
+
+    /// `path` is reported in errors, `field` (which has no array indices) is matched against the policy.
+    fn visit(&self, value: &Value, path: &str, field: &str, errors: &mut PrecheckErrors) {
+        match value {
+            Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    self.visit(item, &push_index(path, index), field, errors);
+                }
+            }
+            Value::Object(fields) => {
+                for (key, value) in fields {
+                    let (path, field) = (push_key(path, key), push_key(field, key));
+                    if self.contains(&field) {
+                        errors.add(PrecheckError::new(
+                            path,
+                            "reserved",
+                            "is reserved and must not be set",
+                        ));
+                    } else {
+                        self.visit(value, &path, &field, errors);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// # `RejectReserved` pre-check extractor
+///
+/// `RejectReserved` rejects JSON bodies containing a field listed in the `ReservedFields` policy
+/// taken from the state, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RejectReserved<E>(pub E);
+
+impl<E> Deref for RejectReserved<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for RejectReserved<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for RejectReserved<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> RejectReserved<E> {
+    /// Consumes the `RejectReserved` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for RejectReserved<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for RejectReserved<Extractor>
+where
+    State: Send + Sync,
+    ReservedFields: FromRef<State>,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let policy = ReservedFields::from_ref(state);
+        let inner = run(req, state, |bytes| match serde_json::from_slice(bytes) {
+            Ok(document) => policy.check(&document),
+            Err(_) => PrecheckErrors::default(),
+        })
+        .await?;
+        Ok(RejectReserved(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::Json;
+    use serde_json::json;
+
+    #[test]
+    fn reserved_fields_check() {
+        let policy = ReservedFields::new(["id", "created_at", "items.id"]);
+        assert!(policy
+            .check(&json!({ "name": "axum", "items": [{ "name": "x" }], "owner": { "id": 1 } }))
+            .is_empty());
+
+        let errors = policy.check(&json!({
+            "id": null,
+            "created_at": "2024-01-01T00:00:00Z",
+            "items": [{ "name": "x" }, { "id": 7 }]
+        }));
+        assert_eq!(errors.0.len(), 3);
+        assert_eq!(errors.get("id").count(), 1);
+        assert_eq!(errors.get("created_at").count(), 1);
+        assert_eq!(errors.get("items[1].id").count(), 1);
+        assert!(errors.0.iter().all(|error| error.code == "reserved"));
+    }
+
+    #[tokio::test]
+    async fn reject_reserved_per_route() {
+        let request = || {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{ "id": 42, "title": "axum" }"#))
+                .expect("invalid request")
+        };
+
+        let create = ReservedFields::new(["id", "created_at"]);
+        let rejection = RejectReserved::<Json<Value>>::from_request(request(), &create)
+            .await
+            .expect_err("reserved field accepted");
+        assert!(matches!(
+            rejection,
+            PrecheckRejection::Valid(errors) if errors.get("id").count() == 1
+        ));
+
+        let import = ReservedFields::new(["created_at"]);
+        assert!(
+            RejectReserved::<Json<Value>>::from_request(request(), &import)
+                .await
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! # Maximum combined size of strings
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `MaxStringBytes<E, N>`.
+//!
+//! ## Usage
+//!
+//! Size limits on single fields do not bound the memory of a whole payload, which may hold many large strings.
+//! Wrap your extractor with `MaxStringBytes`, e.g. `MaxStringBytes<Valid<Json<T>>, 65536>`, to reject payloads
+//! whose strings take more than `N` bytes in total, at any depth, before they are deserialized.
+//!
+//! Both string values and object keys are counted, by the size of their UTF-8 encoding once escape sequences
+//! are decoded, i.e. the memory they take once deserialized into `String`s.
+//!
+//! The error is reported for the whole document with the `too_many_string_bytes` code.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::MaxStringBytes;
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/json", post(handler))
+//!     }
+//!
+//!     async fn handler(
+//!         MaxStringBytes(Valid(Json(notes))): MaxStringBytes<Valid<Json<Notes>>, 65536>,
+//!     ) {
+//!         assert!(notes.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Notes {
+//!         #[validate(length(max = 256))]
+//!         pub notes: Vec<String>,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::{run, PrecheckError, PrecheckErrors, PrecheckRejection, ROOT_PATH};
+use axum::async_trait;
+use axum::extract::{FromRequest, Request};
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// # `MaxStringBytes` pre-check extractor
+///
+/// `MaxStringBytes` rejects JSON bodies whose strings take more than `N` bytes in total,
+/// then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaxStringBytes<E, const N: usize>(pub E);
+
+impl<E, const N: usize> Deref for MaxStringBytes<E, N> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E, const N: usize> DerefMut for MaxStringBytes<E, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display, const N: usize> Display for MaxStringBytes<T, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E, const N: usize> MaxStringBytes<E, N> {
+    /// Consumes the `MaxStringBytes` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T, const N: usize> aide::OperationInput for MaxStringBytes<T, N>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, const N: usize> FromRequest<State> for MaxStringBytes<Extractor, N>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let inner = run(req, state, |bytes| {
+            let total = serde_json::from_slice(bytes).map_or(0, |document| string_bytes(&document));
+            if total <= N {
+                return PrecheckErrors::default();
+            }
+            PrecheckError::new(
+                ROOT_PATH,
+                "too_many_string_bytes",
+                format!("strings must take at most {N} bytes in total, found {total}"),
+            )
+            .into()
+        })
+        .await?;
+        Ok(MaxStringBytes(inner))
+    }
+}
+
+/// Sums the bytes of the string values and object keys of a JSON document, at any depth.
+fn string_bytes(value: &Value) -> usize {
+    match value {
+        Value::String(string) => string.len(),
+        Value::Array(items) => items.iter().map(string_bytes).sum(),
+        Value::Object(fields) => fields
+            .iter()
+            .map(|(key, value)| key.len() + string_bytes(value))
+            .sum(),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::Json;
+    use serde_json::json;
+
+    #[test]
+    fn sum_nested_string_bytes() {
+        assert_eq!(string_bytes(&json!("abc")), 3);
+        assert_eq!(string_bytes(&json!("é")), 2);
+        assert_eq!(
+            string_bytes(&json!({ "a": "xy", "b": { "c": ["z", 1, null, { "d": "" }] } })),
+            7
+        );
+    }
+
+    #[tokio::test]
+    async fn max_string_bytes() {
+        let request = |body: String| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+        let notes = |count: usize| {
+            let notes = vec!["x".repeat(400); count];
+            json!({ "notes": notes }).to_string()
+        };
+
+        assert!(
+            MaxStringBytes::<Json<Value>, 1024>::from_request(request(notes(2)), &())
+                .await
+                .is_ok()
+        );
+
+        let rejection = MaxStringBytes::<Json<Value>, 1024>::from_request(request(notes(3)), &())
+            .await
+            .expect_err("too many string bytes accepted");
+        assert!(matches!(
+            rejection,
+            PrecheckRejection::Valid(errors)
+                if errors.get(ROOT_PATH).any(|error| error.code == "too_many_string_bytes")
+        ));
+    }
+}
@@ -0,0 +1,201 @@
+//! # Maximum number of string fields
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `StringFieldCount<E, N>`.
+//!
+//! ## Usage
+//!
+//! Each string of a payload is a separate allocation once deserialized, so map-like objects with many small
+//! string values cost much more memory than their size on the wire suggests. Wrap your extractor with
+//! `StringFieldCount`, e.g. `StringFieldCount<Valid<Json<T>>, 256>`, to reject payloads with more than `N`
+//! string values in total, at any depth, before they are deserialized.
+//!
+//! Unlike `FieldCount`, which counts object keys, `StringFieldCount` counts string values, including the
+//! elements of arrays, and ignores numbers, booleans and `null`s. Both can be combined.
+//!
+//! The error is reported for the whole document with the `too_many_strings` code.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use std::collections::HashMap;
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::StringFieldCount;
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/json", post(handler))
+//!     }
+//!
+//!     async fn handler(
+//!         StringFieldCount(Valid(Json(labels))): StringFieldCount<Valid<Json<Labels>>, 64>,
+//!     ) {
+//!         assert!(labels.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Labels {
+//!         #[validate(length(max = 32))]
+//!         pub labels: HashMap<String, String>,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::{run, PrecheckError, PrecheckErrors, PrecheckRejection, ROOT_PATH};
+use axum::async_trait;
+use axum::extract::{FromRequest, Request};
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// # `StringFieldCount` pre-check extractor
+///
+/// `StringFieldCount` rejects JSON bodies with more than `N` string values in total,
+/// then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StringFieldCount<E, const N: usize>(pub E);
+
+impl<E, const N: usize> Deref for StringFieldCount<E, N> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E, const N: usize> DerefMut for StringFieldCount<E, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display, const N: usize> Display for StringFieldCount<T, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E, const N: usize> StringFieldCount<E, N> {
+    /// Consumes the `StringFieldCount` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T, const N: usize> aide::OperationInput for StringFieldCount<T, N>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, const N: usize> FromRequest<State> for StringFieldCount<Extractor, N>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let inner = run(req, state, |bytes| {
+            let count =
+                serde_json::from_slice(bytes).map_or(0, |document| count_strings(&document));
+            if count <= N {
+                return PrecheckErrors::default();
+            }
+            PrecheckError::new(
+                ROOT_PATH,
+                "too_many_strings",
+                format!("must have at most {N} string values, found {count}"),
+            )
+            .into()
+        })
+        .await?;
+        Ok(StringFieldCount(inner))
+    }
+}
+
+/// Counts the string values of a JSON document, at any depth.
+fn count_strings(value: &Value) -> usize {
+    match value {
+        Value::String(_) => 1,
+        Value::Array(items) => items.iter().map(count_strings).sum(),
+        Value::Object(fields) => fields.values().map(count_strings).sum(),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::Json;
+    use serde_json::json;
+
+    #[test]
+    fn count_nested_strings() {
+        assert_eq!(count_strings(&json!("a")), 1);
+        assert_eq!(
+            count_strings(
+                &json!({ "a": "x", "b": { "c": ["y", 1, null, { "d": "z" }] }, "e": true })
+            ),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn string_field_count() {
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+
+        assert!(StringFieldCount::<Json<Value>, 2>::from_request(
+            request(r#"{ "a": "x", "b": { "c": "y", "d": 1, "e": 2, "f": null } }"#),
+            &()
+        )
+        .await
+        .is_ok());
+
+        let rejection = StringFieldCount::<Json<Value>, 2>::from_request(
+            request(r#"{ "a": "x", "b": { "c": "y", "d": ["z"] } }"#),
+            &(),
+        )
+        .await
+        .expect_err("too many strings accepted");
+        assert!(matches!(
+            rejection,
+            PrecheckRejection::Valid(errors)
+                if errors.get(ROOT_PATH).any(|error| error.code == "too_many_strings")
+        ));
+    }
+}
@@ -0,0 +1,193 @@
+//! # Trimming of all strings
+//!
+//! ## Feature
+//!
+//! Enable the `precheck` feature to use `TrimAll<E>`.
+//!
+//! ## Usage
+//!
+//! `validify`'s `modify(trim)` trims a single field, so every string field needs its own annotation.
+//! Wrap your extractor with `TrimAll`, e.g. `TrimAll<Validated<Json<T>>>` or `TrimAll<Valid<Json<T>>>`,
+//! to trim leading and trailing whitespace from every string value of the JSON body, at any depth,
+//! before the inner extractor deserializes and validates it. Object keys are left untouched.
+//!
+//! The body is only rewritten when at least one string was trimmed, and only the trimmed strings are
+//! rewritten: numbers and the rest of the document keep their exact text.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::precheck::TrimAll;
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/json", post(handler))
+//!     }
+//!
+//!     async fn handler(TrimAll(Valid(Json(user))): TrimAll<Valid<Json<User>>>) {
+//!         assert_eq!(user.name, user.name.trim());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct User {
+//!         #[validate(length(min = 1, max = 32))]
+//!         pub name: String,
+//!         pub tags: Vec<String>,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::precheck::PrecheckRejection;
+use axum::async_trait;
+use axum::body::Body;
+use axum::extract::{FromRequest, Request};
+use axum::http::header::CONTENT_LENGTH;
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// # `TrimAll` pre-check extractor
+///
+/// `TrimAll` trims every string value of the JSON body, then delegates to the inner extractor.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TrimAll<E>(pub E);
+
+impl<E> Deref for TrimAll<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for TrimAll<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for TrimAll<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> TrimAll<E> {
+    /// Consumes the `TrimAll` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for TrimAll<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for TrimAll<Extractor>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = PrecheckRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let (req, bytes) = crate::body::buffer(req, state)
+            .await
+            .map_err(PrecheckRejection::Body)?;
+        let req = match trim_all(&bytes) {
+            Some(trimmed) => {
+                let (mut parts, _) = req.into_parts();
+                parts.headers.remove(CONTENT_LENGTH);
+                Request::from_parts(parts, Body::from(trimmed))
+            }
+            None => req,
+        };
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(PrecheckRejection::Inner)?;
+        Ok(TrimAll(inner))
+    }
+}
+
+/// Trims every string of a JSON document, returning the rewritten document if any was changed.
+fn trim_all(bytes: &[u8]) -> Option<Vec<u8>> {
+    crate::precheck::rewrite_strings(bytes, |_, _, text| {
+        let trimmed = text.trim();
+        (trimmed.len() != text.len()).then(|| Value::from(trimmed))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Json;
+    use serde_json::json;
+
+    #[test]
+    fn trim_all_strings() {
+        let document = json!({ " key ": " a ", "b": [" c", { "d": "e " }], "f": 1 }).to_string();
+        let trimmed = trim_all(document.as_bytes()).expect("nothing trimmed");
+        assert_eq!(
+            serde_json::from_slice::<Value>(&trimmed).expect("invalid JSON"),
+            json!({ " key ": "a", "b": ["c", { "d": "e" }], "f": 1 })
+        );
+        assert_eq!(trim_all(&trimmed), None);
+        assert_eq!(trim_all(b"{ \"a\": \" b"), None);
+    }
+
+    #[test]
+    fn trim_all_keeps_numbers() {
+        let document = r#"{"id": 123456789012345678901234567890, "price": 0.10000000000000000000001,
+            "name": " \"axum\"\u0020", "tags": [1e400, " a"]}"#;
+        assert_eq!(
+            String::from_utf8(trim_all(document.as_bytes()).expect("nothing trimmed")),
+            Ok(
+                r#"{"id": 123456789012345678901234567890, "price": 0.10000000000000000000001,
+            "name": "\"axum\"", "tags": [1e400, "a"]}"#
+                    .to_owned()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn trim_all_before_inner() {
+        let request = Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{ "name": "  axum ", "tags": [" web"] }"#))
+            .expect("invalid request");
+        let TrimAll(Json(value)) = TrimAll::<Json<Value>>::from_request(request, &())
+            .await
+            .expect("body rejected");
+        assert_eq!(value, json!({ "name": "axum", "tags": ["web"] }));
+    }
+}
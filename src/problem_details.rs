@@ -0,0 +1,385 @@
+//! # Problem details for validation errors
+//!
+//! ## Feature
+//!
+//! Enable the `problem_details` feature to use `Problem<E>`.
+//!
+//! ## Usage
+//!
+//! [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807) defines a standard format for error responses.
+//! `Problem<E>` wraps a validation extractor, e.g. `Problem<Valid<Json<T>>>`, and renders its validation
+//! errors as such a problem document, with the validation errors in an `errors` extension member:
+//!
+//! ```json
+//! {
+//!   "type": "about:blank",
+//!   "title": "Validation failed",
+//!   "status": 400,
+//!   "instance": "/articles",
+//!   "errors": { "title": [{ "code": "length", "message": null, "params": { "max": 64, "value": "..." } }] }
+//! }
+//! ```
+//!
+//! The document is sent with the `application/problem+json` content type and `VALIDATION_ERROR_STATUS`.
+//! `instance` is the path of the request. `errors` holds the validation errors serialized as usual,
+//! so errors of `validator`, `garde` and `validify` can all be rendered, depending on the enabled features.
+//! Other failures of the inner extractor, e.g. a malformed body, are rendered as usual.
+//!
+//! Only the extractors wrapped with `Problem` are affected, so other routes keep rendering errors as before.
+//! `Problem<E>` reads its `ProblemConfig` from the router state, so it must implement `FromRef<YourState>`.
+//! Use `ProblemConfig::with_type` and `ProblemConfig::with_title` to describe the problem type of your API.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::problem_details::{Problem, ProblemConfig};
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Article {
+//!         #[validate(length(min = 1, max = 64))]
+//!         pub title: String,
+//!     }
+//!
+//!     pub async fn handler(Problem(Valid(Json(article))): Problem<Valid<Json<Article>>>) {
+//!         assert!(!article.title.is_empty());
+//!     }
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/articles", post(handler)).with_state(
+//!             ProblemConfig::default()
+//!                 .with_type("https://example.com/problems/validation")
+//!                 .with_title("Your request is not valid"),
+//!         )
+//!     }
+//! }
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! #     #[cfg(feature = "validator")]
+//! #     let router = validator_example::router();
+//! #     #[cfg(not(feature = "validator"))]
+//! #     let router = axum::Router::new();
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::{ValidationRejection, VALIDATION_ERROR_STATUS};
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, FromRequestParts, Request};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::request::Parts;
+use axum::http::HeaderValue;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// The problem details media type.
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+/// How `Problem` describes validation errors.
+#[derive(Debug, Clone)]
+pub struct ProblemConfig {
+    problem_type: Cow<'static, str>,
+    title: Cow<'static, str>,
+}
+
+impl Default for ProblemConfig {
+    fn default() -> Self {
+        Self {
+            problem_type: Cow::Borrowed("about:blank"),
+            title: Cow::Borrowed("Validation failed"),
+        }
+    }
+}
+
+impl ProblemConfig {
+    /// URI identifying the problem type, `about:blank` by default.
+    pub fn with_type(mut self, problem_type: impl Into<Cow<'static, str>>) -> Self {
+        self.problem_type = problem_type.into();
+        self
+    }
+
+    /// Short summary of the problem type, `Validation failed` by default.
+    pub fn with_title(mut self, title: impl Into<Cow<'static, str>>) -> Self {
+        self.title = title.into();
+        self
+    }
+}
+
+/// A problem details document holding validation errors.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProblemDocument {
+    /// URI identifying the problem type.
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    /// Short summary of the problem type.
+    pub title: String,
+    /// The HTTP status code.
+    pub status: u16,
+    /// Path of the request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// The validation errors.
+    pub errors: Value,
+}
+
+impl ProblemDocument {
+    /// Describes validation errors according to `config`.
+    pub fn new<V: Serialize + ?Sized>(
+        errors: &V,
+        instance: Option<String>,
+        config: &ProblemConfig,
+    ) -> Self {
+        let mut errors = serde_json::to_value(errors).unwrap_or(Value::Null);
+        crate::sort_by_code(&mut errors);
+        Self {
+            problem_type: config.problem_type.to_string(),
+            title: config.title.to_string(),
+            status: VALIDATION_ERROR_STATUS.as_u16(),
+            instance,
+            errors,
+        }
+    }
+}
+
+impl Display for ProblemDocument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.title, self.errors)
+    }
+}
+
+impl Error for ProblemDocument {}
+
+impl IntoResponse for ProblemDocument {
+    fn into_response(self) -> Response {
+        let mut response = (VALIDATION_ERROR_STATUS, Json(self)).into_response();
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_CONTENT_TYPE));
+        response
+    }
+}
+
+/// # `Problem` data extractor
+///
+/// `Problem` runs the inner validation extractor, rendering its validation errors as a problem details document.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Problem<E>(pub E);
+
+impl<E> Deref for Problem<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for Problem<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for Problem<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> Problem<E> {
+    /// Consumes the `Problem` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for Problem<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `ProblemRejection` is returned when the `Problem` extractor fails.
+///
+#[derive(Debug)]
+pub enum ProblemRejection<E> {
+    /// `Valid` variant captures the validation errors, rendered as a problem details document.
+    Valid(ProblemDocument),
+    /// `Inner` variant represents the other errors that might occur within the inner extractor.
+    Inner(E),
+}
+
+impl<E: Display> Display for ProblemRejection<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProblemRejection::Valid(document) => write!(f, "{document}"),
+            ProblemRejection::Inner(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for ProblemRejection<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ProblemRejection::Valid(document) => Some(document),
+            ProblemRejection::Inner(error) => Some(error),
+        }
+    }
+}
+
+impl<E: IntoResponse> IntoResponse for ProblemRejection<E> {
+    fn into_response(self) -> Response {
+        match self {
+            ProblemRejection::Valid(document) => document.into_response(),
+            ProblemRejection::Inner(error) => error.into_response(),
+        }
+    }
+}
+
+impl<E> ProblemRejection<E> {
+    fn from_rejection<V: Serialize>(
+        rejection: ValidationRejection<V, E>,
+        instance: String,
+        config: &ProblemConfig,
+    ) -> Self {
+        match rejection {
+            ValidationRejection::Valid(errors) => {
+                ProblemRejection::Valid(ProblemDocument::new(&errors, Some(instance), config))
+            }
+            ValidationRejection::Inner(error) => ProblemRejection::Inner(error),
+        }
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, V, E> FromRequest<State> for Problem<Extractor>
+where
+    State: Send + Sync,
+    ProblemConfig: FromRef<State>,
+    Extractor: FromRequest<State, Rejection = ValidationRejection<V, E>>,
+    V: Serialize,
+    E: IntoResponse,
+{
+    type Rejection = ProblemRejection<E>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let instance = req.uri().path().to_owned();
+        Extractor::from_request(req, state)
+            .await
+            .map(Problem)
+            .map_err(|rejection| {
+                ProblemRejection::from_rejection(
+                    rejection,
+                    instance,
+                    &ProblemConfig::from_ref(state),
+                )
+            })
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, V, E> FromRequestParts<State> for Problem<Extractor>
+where
+    State: Send + Sync,
+    ProblemConfig: FromRef<State>,
+    Extractor: FromRequestParts<State, Rejection = ValidationRejection<V, E>>,
+    V: Serialize,
+    E: IntoResponse,
+{
+    type Rejection = ProblemRejection<E>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let instance = parts.uri.path().to_owned();
+        Extractor::from_request_parts(parts, state)
+            .await
+            .map(Problem)
+            .map_err(|rejection| {
+                ProblemRejection::from_rejection(
+                    rejection,
+                    instance,
+                    &ProblemConfig::from_ref(state),
+                )
+            })
+    }
+}
+
+#[cfg(all(test, feature = "validator"))]
+mod tests {
+    use super::*;
+    use crate::Valid;
+    use axum::body::Body;
+    use serde::Deserialize;
+    use validator::Validate;
+
+    #[derive(Debug, Validate, Deserialize)]
+    struct Article {
+        #[validate(length(max = 8, message = "too long"))]
+        title: String,
+    }
+
+    fn request(body: &'static str) -> Request {
+        Request::builder()
+            .uri("/articles?draft=true")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .expect("invalid request")
+    }
+
+    #[tokio::test]
+    async fn problem_document() {
+        let config = ProblemConfig::default().with_type("https://example.com/problems/validation");
+        let rejection = Problem::<Valid<Json<Article>>>::from_request(
+            request(r#"{ "title": "too long a title" }"#),
+            &config,
+        )
+        .await
+        .expect_err("invalid article accepted");
+        let response = rejection.into_response();
+        assert_eq!(response.status(), VALIDATION_ERROR_STATUS);
+        assert_eq!(response.headers()[CONTENT_TYPE], PROBLEM_CONTENT_TYPE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("failed to read body");
+        let document: Value = serde_json::from_slice(&body).expect("invalid document");
+        assert_eq!(document["type"], "https://example.com/problems/validation");
+        assert_eq!(document["title"], "Validation failed");
+        assert_eq!(document["status"], VALIDATION_ERROR_STATUS.as_u16());
+        assert_eq!(document["instance"], "/articles");
+        assert_eq!(document["errors"]["title"][0]["code"], "length");
+        assert_eq!(document["errors"]["title"][0]["message"], "too long");
+
+        let config = ProblemConfig::default();
+        assert!(Problem::<Valid<Json<Article>>>::from_request(
+            request(r#"{ "title": "short" }"#),
+            &config
+        )
+        .await
+        .is_ok());
+        assert!(matches!(
+            Problem::<Valid<Json<Article>>>::from_request(request("{"), &config).await,
+            Err(ProblemRejection::Inner(_))
+        ));
+    }
+}
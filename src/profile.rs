@@ -0,0 +1,369 @@
+//! # Validation by content-type profile
+//!
+//! ## Feature
+//!
+//! Enable the `profile` feature to use `ValidProfile<E>`.
+//!
+//! ## Usage
+//!
+//! Clients can select the version of a payload with the `profile` parameter of the `Content-Type` header,
+//! e.g. `application/json; profile=v2`, and each version has its own validation rules.
+//!
+//! 1. Register a validator for each profile in a `Profiles<T>`, where `T` is your data type, and make it
+//!    available from your router state (implement `FromRef<YourState>` for it, or use it as the state directly).
+//!    Requests without a `profile` parameter use the default validator, if one is registered.
+//! 2. In your handler function, use `ValidProfile<E>` as the extractor, e.g. `ValidProfile<Json<T>>`.
+//!
+//! Requests with a profile that has no validator are rejected with `415 Unsupported Media Type`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use axum::routing::post;
+//! use axum::Json;
+//! use axum::Router;
+//! use axum_valid::profile::{Profiles, ValidProfile};
+//! use serde::Deserialize;
+//! use validator::{Validate, ValidationErrors};
+//!
+//! #[derive(Validate, Deserialize)]
+//! pub struct Order {
+//!     #[validate(range(min = 1, max = 100))]
+//!     pub quantity: u32,
+//!     pub coupon: Option<String>,
+//! }
+//!
+//! // v2 also requires a coupon.
+//! fn validate_v2(order: &Order) -> Result<(), ValidationErrors> {
+//!     order.validate()?;
+//!     let mut errors = ValidationErrors::new();
+//!     if order.coupon.is_none() {
+//!         errors.add("coupon", validator::ValidationError::new("required"));
+//!     }
+//!     if errors.is_empty() { Ok(()) } else { Err(errors) }
+//! }
+//!
+//! async fn handler(ValidProfile(Json(order)): ValidProfile<Json<Order>>) {
+//!     assert!(order.validate().is_ok());
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! let profiles = Profiles::<Order>::new()
+//!     .default_profile(Order::validate)
+//!     .profile("v1", Order::validate)
+//!     .profile("v2", validate_v2);
+//! let router = Router::new()
+//!     .route("/orders", post(handler))
+//!     .with_state(profiles);
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::HasValidate;
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, Request};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use validator::ValidationErrors;
+
+type Validator<T> = Arc<dyn Fn(&T) -> Result<(), ValidationErrors> + Send + Sync>;
+
+/// Validators of a data type `T`, by content-type profile.
+///
+/// `ValidProfile` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+pub struct Profiles<T> {
+    profiles: HashMap<String, Validator<T>>,
+    default: Option<Validator<T>>,
+}
+
+impl<T> Profiles<T> {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self {
+            profiles: HashMap::new(),
+            default: None,
+        }
+    }
+
+    /// Register the validator of `profile`.
+    pub fn profile<F>(mut self, profile: impl Into<String>, validator: F) -> Self
+    where
+        F: Fn(&T) -> Result<(), ValidationErrors> + Send + Sync + 'static,
+    {
+        self.profiles.insert(profile.into(), Arc::new(validator));
+        self
+    }
+
+    /// Register the validator used when the `Content-Type` header has no `profile` parameter.
+    pub fn default_profile<F>(mut self, validator: F) -> Self
+    where
+        F: Fn(&T) -> Result<(), ValidationErrors> + Send + Sync + 'static,
+    {
+        self.default = Some(Arc::new(validator));
+        self
+    }
+
+    fn get(&self, profile: Option<&str>) -> Option<&Validator<T>> {
+        match profile {
+            Some(profile) => self.profiles.get(profile),
+            None => self.default.as_ref(),
+        }
+    }
+}
+
+impl<T> Default for Profiles<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for Profiles<T> {
+    fn clone(&self) -> Self {
+        Self {
+            profiles: self.profiles.clone(),
+            default: self.default.clone(),
+        }
+    }
+}
+
+impl<T> Debug for Profiles<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Profiles")
+            .field("profiles", &self.profiles.keys().collect::<Vec<_>>())
+            .field("default", &self.default.is_some())
+            .finish()
+    }
+}
+
+/// Returns the `profile` parameter of the `Content-Type` header.
+fn content_type_profile(headers: &HeaderMap) -> Option<String> {
+    let content_type = headers.get(CONTENT_TYPE)?.to_str().ok()?;
+    content_type.split(';').skip(1).find_map(|parameter| {
+        let (name, value) = parameter.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("profile")
+            .then(|| value.trim().trim_matches('"').to_owned())
+    })
+}
+
+/// # `ValidProfile` data extractor
+///
+/// `ValidProfile` runs the inner extractor, then validates its data with the validator
+/// registered in `Profiles` for the `profile` parameter of the `Content-Type` header.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidProfile<E>(pub E);
+
+impl<E> Deref for ValidProfile<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for ValidProfile<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for ValidProfile<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> ValidProfile<E> {
+    /// Consumes the `ValidProfile` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for ValidProfile<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `ValidProfileRejection` is returned when the `ValidProfile` extractor fails.
+///
+#[derive(Debug)]
+pub enum ValidProfileRejection<E> {
+    /// `Valid` variant captures errors returned by the validator of the profile.
+    Valid(ValidationErrors),
+    /// `UnknownProfile` variant is returned when no validator is registered for the profile (`None` for the default one).
+    UnknownProfile(Option<String>),
+    /// `Inner` variant represents potential errors that might occur within the inner extractor.
+    Inner(E),
+}
+
+impl<E: Display> Display for ValidProfileRejection<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidProfileRejection::Valid(errors) => write!(f, "{errors}"),
+            ValidProfileRejection::UnknownProfile(Some(profile)) => {
+                write!(f, "Unsupported profile `{profile}`")
+            }
+            ValidProfileRejection::UnknownProfile(None) => write!(f, "Missing profile"),
+            ValidProfileRejection::Inner(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for ValidProfileRejection<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ValidProfileRejection::Valid(errors) => Some(errors),
+            ValidProfileRejection::UnknownProfile(_) => None,
+            ValidProfileRejection::Inner(error) => Some(error),
+        }
+    }
+}
+
+impl<E: IntoResponse> IntoResponse for ValidProfileRejection<E> {
+    fn into_response(self) -> Response {
+        match self {
+            ValidProfileRejection::Valid(errors) => crate::validation_errors_response(&errors),
+            ValidProfileRejection::UnknownProfile(profile) => {
+                let message = match profile {
+                    Some(profile) => format!("Unsupported profile `{profile}`"),
+                    None => String::from("Missing profile"),
+                };
+                (StatusCode::UNSUPPORTED_MEDIA_TYPE, message).into_response()
+            }
+            ValidProfileRejection::Inner(error) => error.into_response(),
+        }
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for ValidProfile<Extractor>
+where
+    State: Send + Sync,
+    Extractor: HasValidate + FromRequest<State>,
+    Extractor::Validate: 'static,
+    Profiles<Extractor::Validate>: FromRef<State>,
+{
+    type Rejection = ValidProfileRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let profile = content_type_profile(req.headers());
+        let profiles = Profiles::<Extractor::Validate>::from_ref(state);
+        let Some(validator) = profiles.get(profile.as_deref()) else {
+            return Err(ValidProfileRejection::UnknownProfile(profile));
+        };
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(ValidProfileRejection::Inner)?;
+        validator(inner.get_validate()).map_err(ValidProfileRejection::Valid)?;
+        Ok(ValidProfile(inner))
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::Json;
+    use serde::Deserialize;
+    use validator::{Validate, ValidationError};
+
+    #[derive(Debug, Validate, Deserialize)]
+    struct Order {
+        #[validate(range(min = 1, max = 100))]
+        quantity: u32,
+        coupon: Option<String>,
+    }
+
+    fn validate_v2(order: &Order) -> Result<(), ValidationErrors> {
+        order.validate()?;
+        let mut errors = ValidationErrors::new();
+        if order.coupon.is_none() {
+            errors.add("coupon", ValidationError::new("required"));
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    #[test]
+    fn parse_content_type_profile() {
+        let headers = |content_type: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(CONTENT_TYPE, content_type.parse().expect("invalid header"));
+            headers
+        };
+        assert_eq!(content_type_profile(&headers("application/json")), None);
+        assert_eq!(
+            content_type_profile(&headers("application/json; charset=utf-8; Profile=\"v2\"")),
+            Some(String::from("v2"))
+        );
+    }
+
+    #[tokio::test]
+    async fn valid_profile() {
+        let profiles = Profiles::<Order>::new()
+            .default_profile(Order::validate)
+            .profile("v2", validate_v2);
+        let request = |content_type: &str| {
+            Request::builder()
+                .uri("/")
+                .header(CONTENT_TYPE, content_type)
+                .body(Body::from(r#"{ "quantity": 3 }"#))
+                .expect("invalid request")
+        };
+
+        assert!(
+            ValidProfile::<Json<Order>>::from_request(request("application/json"), &profiles)
+                .await
+                .is_ok()
+        );
+
+        let rejection = ValidProfile::<Json<Order>>::from_request(
+            request("application/json; profile=v2"),
+            &profiles,
+        )
+        .await
+        .expect_err("v2 order without coupon accepted");
+        assert!(matches!(
+            &rejection,
+            ValidProfileRejection::Valid(errors) if errors.field_errors().contains_key("coupon")
+        ));
+        assert_eq!(
+            rejection.into_response().status(),
+            crate::VALIDATION_ERROR_STATUS
+        );
+
+        let rejection = ValidProfile::<Json<Order>>::from_request(
+            request("application/json; profile=v3"),
+            &profiles,
+        )
+        .await
+        .expect_err("unknown profile accepted");
+        assert_eq!(
+            rejection.into_response().status(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+    }
+}
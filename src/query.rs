@@ -158,4 +158,32 @@ mod tests {
             builder.query(&T::invalid())
         }
     }
+
+    #[cfg(feature = "validify")]
+    #[tokio::test]
+    async fn modified_query_trims_parameters() {
+        use crate::Modified;
+        use axum::extract::FromRequestParts;
+        use axum::http::Request;
+        use validify::Validify;
+
+        #[derive(Debug, serde::Deserialize, Validify)]
+        struct Search {
+            #[modify(trim, lowercase)]
+            q: String,
+            page: u32,
+        }
+
+        let (mut parts, _) = Request::builder()
+            .uri("/search?q=%20%20Axum%20Valid%09&page=2")
+            .body(())
+            .expect("invalid request")
+            .into_parts();
+        let Modified(Query(search)) =
+            Modified::<Query<Search>>::from_request_parts(&mut parts, &())
+                .await
+                .expect("query rejected");
+        assert_eq!(search.q, "axum valid");
+        assert_eq!(search.page, 2);
+    }
 }
@@ -0,0 +1,391 @@
+//! # Validation errors grouped by severity
+//!
+//! ## Feature
+//!
+//! Enable the `severity` feature to use `Graded<E>`.
+//!
+//! ## Usage
+//!
+//! Some rules only flag suspicious data, e.g. a very short description, which should be reported
+//! without rejecting the request. `Graded<E>` validates like `Valid<E>`, but splits the failures into
+//! errors and warnings:
+//!
+//! 1. Implement `RuleSeverity` for your data type, listing the rules which only raise warnings.
+//!    Every other rule raises errors.
+//! 2. In your handler function, use `Graded<E>` as the extractor, e.g. `Graded<Json<T>>`.
+//!
+//! Requests with errors are rejected with `VALIDATION_ERROR_STATUS` and a JSON body of the form
+//! `{ "errors": [...], "warnings": [...] }`. Requests with warnings only are accepted,
+//! and the warnings are handed to the handler, e.g. to return them alongside the result.
+//!
+//! Each issue names the offending `field` by its path, e.g. `items[2].name`, its `code`, and its `message`
+//! and `params` if any. Rules are identified by their field, without array indices, and their code,
+//! e.g. `("items.name", "length")`. Struct-level rules use the `__all__` field.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use axum::routing::post;
+//! use axum::Json;
+//! use axum::Router;
+//! use axum_valid::severity::{Graded, RuleSeverity};
+//! use serde::Deserialize;
+//! use validator::Validate;
+//!
+//! #[derive(Validate, Deserialize)]
+//! pub struct Listing {
+//!     #[validate(length(min = 1, max = 64))]
+//!     pub title: String,
+//!     #[validate(length(min = 32, code = "short_description"))]
+//!     pub description: String,
+//! }
+//!
+//! impl RuleSeverity for Listing {
+//!     const WARNINGS: &'static [(&'static str, &'static str)] =
+//!         &[("description", "short_description")];
+//! }
+//!
+//! async fn handler(Graded(Json(listing), warnings): Graded<Json<Listing>>) -> Json<usize> {
+//!     assert!(!listing.title.is_empty());
+//!     Json(warnings.len())
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new().route("/listings", post(handler));
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::{HasValidate, VALIDATION_ERROR_STATUS};
+use axum::async_trait;
+use axum::extract::{FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use validator::{Validate, ValidationErrors};
+
+/// Trait for data types with rules which raise warnings rather than errors.
+pub trait RuleSeverity {
+    /// Rules which raise warnings, as `(field, code)` pairs. Fields are named without array indices.
+    const WARNINGS: &'static [(&'static str, &'static str)];
+}
+
+/// How serious a validation failure is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The request is rejected.
+    Error,
+    /// The request is accepted, and the failure reported.
+    Warning,
+}
+
+/// A validation failure.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Issue {
+    /// Path of the offending field, e.g. `items[2].name`.
+    pub field: String,
+    /// Code of the rule.
+    pub code: String,
+    /// Message of the rule, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Parameters of the rule.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, Value>,
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.message {
+            Some(message) => write!(f, "{}: {message}", self.field),
+            None => write!(f, "{}: {}", self.field, self.code),
+        }
+    }
+}
+
+/// The validation failures of a request, grouped by severity.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Issues {
+    /// Failures which reject the request.
+    pub errors: Vec<Issue>,
+    /// Failures which are only reported.
+    pub warnings: Vec<Issue>,
+}
+
+impl Issues {
+    /// Groups validation errors using the `WARNINGS` of `T`, sorting each group by field and code.
+    pub fn grade<T: RuleSeverity + ?Sized>(errors: &ValidationErrors) -> Self {
+        let mut issues = Issues::default();
+        collect::<T>(errors, &mut issues);
+        for group in [&mut issues.errors, &mut issues.warnings] {
+            group.sort_by(|a, b| (&a.field, &a.code).cmp(&(&b.field, &b.code)));
+        }
+        issues
+    }
+}
+
+impl Display for Issues {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .errors
+            .iter()
+            .map(|issue| format!("error: {issue}"))
+            .chain(
+                self.warnings
+                    .iter()
+                    .map(|issue| format!("warning: {issue}")),
+            )
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Error for Issues {}
+
+impl IntoResponse for Issues {
+    fn into_response(self) -> Response {
+        (VALIDATION_ERROR_STATUS, Json(self)).into_response()
+    }
+}
+
+/// Issues report the path of the field, while rules are matched against its path without array indices.
+fn collect<T: RuleSeverity + ?Sized>(errors: &ValidationErrors, issues: &mut Issues) {
+    crate::flatten_errors(errors, &mut |path, errors| {
+        let field = crate::format_error_path(path, false);
+        for error in errors {
+            let issue = Issue {
+                field: crate::format_error_path(path, true),
+                code: error.code.to_string(),
+                message: error.message.as_ref().map(ToString::to_string),
+                params: error
+                    .params
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.clone()))
+                    .collect(),
+            };
+            if T::WARNINGS.contains(&(field.as_str(), &error.code)) {
+                issues.warnings.push(issue);
+            } else {
+                issues.errors.push(issue);
+            }
+        }
+    });
+}
+
+/// # `Graded` data extractor
+///
+/// `Graded` runs the inner extractor and validates its data, rejecting the request if any rule raises
+/// an error. It holds the inner extractor and the warnings.
+///
+#[derive(Debug, Clone, Default)]
+pub struct Graded<E>(pub E, pub Vec<Issue>);
+
+impl<E> Graded<E> {
+    /// Consumes the `Graded` and returns the inner extractor and the warnings.
+    pub fn into_inner(self) -> (E, Vec<Issue>) {
+        (self.0, self.1)
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for Graded<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `GradedRejection` is returned when the `Graded` extractor fails.
+///
+#[derive(Debug)]
+pub enum GradedRejection<E> {
+    /// `Valid` variant captures the validation failures, when at least one of them is an error.
+    Valid(Issues),
+    /// `Inner` variant represents potential errors that might occur within the inner extractor.
+    Inner(E),
+}
+
+impl<E: Display> Display for GradedRejection<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GradedRejection::Valid(issues) => write!(f, "{issues}"),
+            GradedRejection::Inner(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for GradedRejection<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GradedRejection::Valid(issues) => Some(issues),
+            GradedRejection::Inner(error) => Some(error),
+        }
+    }
+}
+
+impl<E: IntoResponse> IntoResponse for GradedRejection<E> {
+    fn into_response(self) -> Response {
+        match self {
+            GradedRejection::Valid(issues) => issues.into_response(),
+            GradedRejection::Inner(error) => error.into_response(),
+        }
+    }
+}
+
+fn grade<T: Validate + RuleSeverity>(data: &T) -> Result<Vec<Issue>, Issues> {
+    let Err(errors) = data.validate() else {
+        return Ok(Vec::new());
+    };
+    let issues = Issues::grade::<T>(&errors);
+    if issues.errors.is_empty() {
+        Ok(issues.warnings)
+    } else {
+        Err(issues)
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for Graded<Extractor>
+where
+    State: Send + Sync,
+    Extractor: HasValidate + FromRequest<State>,
+    Extractor::Validate: Validate + RuleSeverity,
+{
+    type Rejection = GradedRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(GradedRejection::Inner)?;
+        let warnings = grade(inner.get_validate()).map_err(GradedRejection::Valid)?;
+        Ok(Graded(inner, warnings))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequestParts<State> for Graded<Extractor>
+where
+    State: Send + Sync,
+    Extractor: HasValidate + FromRequestParts<State>,
+    Extractor::Validate: Validate + RuleSeverity,
+{
+    type Rejection = GradedRejection<<Extractor as FromRequestParts<State>>::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let inner = Extractor::from_request_parts(parts, state)
+            .await
+            .map_err(GradedRejection::Inner)?;
+        let warnings = grade(inner.get_validate()).map_err(GradedRejection::Valid)?;
+        Ok(Graded(inner, warnings))
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Validate, Deserialize)]
+    struct Tag {
+        #[validate(length(min = 3, code = "short_tag"))]
+        name: String,
+        #[validate(range(max = 10))]
+        weight: u32,
+    }
+
+    #[derive(Debug, Validate, Deserialize)]
+    struct Listing {
+        #[validate(length(min = 1, max = 16))]
+        title: String,
+        #[validate(length(min = 8, code = "short_description"))]
+        description: String,
+        #[validate(nested)]
+        tags: Vec<Tag>,
+    }
+
+    impl RuleSeverity for Listing {
+        const WARNINGS: &'static [(&'static str, &'static str)] = &[
+            ("description", "short_description"),
+            ("tags.name", "short_tag"),
+        ];
+    }
+
+    fn request(body: serde_json::Value) -> Request {
+        Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .expect("invalid request")
+    }
+
+    #[tokio::test]
+    async fn graded_separates_warnings_from_errors() {
+        let Graded(Json(listing), warnings) = Graded::<Json<Listing>>::from_request(
+            request(json!({ "title": "bike", "description": "red", "tags": [{ "name": "ok", "weight": 1 }] })),
+            &(),
+        )
+        .await
+        .expect("warnings only rejected");
+        assert_eq!(listing.title, "bike");
+        let warnings = warnings
+            .iter()
+            .map(|issue| (issue.field.as_str(), issue.code.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            warnings,
+            [
+                ("description", "short_description"),
+                ("tags[0].name", "short_tag")
+            ]
+        );
+
+        let rejection = Graded::<Json<Listing>>::from_request(
+            request(json!({ "title": "", "description": "red", "tags": [{ "name": "", "weight": 11 }] })),
+            &(),
+        )
+        .await
+        .expect_err("errors accepted");
+        let response = rejection.into_response();
+        assert_eq!(response.status(), VALIDATION_ERROR_STATUS);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("failed to read response");
+        let body: Value = serde_json::from_slice(&body).expect("response is not JSON");
+        let codes = |group: &str| {
+            body[group]
+                .as_array()
+                .expect("missing group")
+                .iter()
+                .map(|issue| format!("{} {}", issue["field"], issue["code"]))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            codes("errors"),
+            [r#""tags[0].weight" "range""#, r#""title" "length""#]
+        );
+        assert_eq!(
+            codes("warnings"),
+            [
+                r#""description" "short_description""#,
+                r#""tags[0].name" "short_tag""#
+            ]
+        );
+        assert_eq!(body["errors"][0]["params"]["max"], 10.0);
+    }
+}
@@ -0,0 +1,322 @@
+//! # Per-extractor validation error status
+//!
+//! ## Feature
+//!
+//! Enable the `status` feature to use `WithStatus<E, S>`.
+//!
+//! ## Usage
+//!
+//! Validation errors are rendered with `VALIDATION_ERROR_STATUS`, which the `422` feature switches from
+//! `400 Bad Request` to `422 Unprocessable Entity` for the whole crate. To choose the status of a single route
+//! instead, wrap its validation extractor with `WithStatus<E, S>`, where `S` implements `ValidationStatus`:
+//!
+//! * `BadRequest` renders validation errors with `400 Bad Request`.
+//! * `UnprocessableEntity` renders them with `422 Unprocessable Entity`.
+//! * Any other type implementing `ValidationStatus` picks its own status.
+//!
+//! `Valid422<E>` is a shorthand for `WithStatus<Valid<E>, UnprocessableEntity>`. The body of the response is the
+//! same whatever the status, and rejections of the inner extractor which are not validation errors are unchanged.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::status::Valid422;
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/json", post(handler))
+//!     }
+//!
+//!     async fn handler(parameter: Valid422<Json<Parameter>>) {
+//!         assert!(parameter.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Parameter {
+//!         #[validate(range(min = 5, max = 10))]
+//!         pub v0: i32,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::{validation_errors_response, ValidationRejection};
+use axum::async_trait;
+use axum::extract::{FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+/// The status code validation errors are rendered with.
+pub trait ValidationStatus {
+    /// The status code of validation error responses, `VALIDATION_ERROR_STATUS` by default.
+    const VALIDATION_ERROR_STATUS: StatusCode = crate::VALIDATION_ERROR_STATUS;
+}
+
+/// Renders validation errors with `400 Bad Request`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BadRequest;
+
+impl ValidationStatus for BadRequest {
+    const VALIDATION_ERROR_STATUS: StatusCode = StatusCode::BAD_REQUEST;
+}
+
+/// Renders validation errors with `422 Unprocessable Entity`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnprocessableEntity;
+
+impl ValidationStatus for UnprocessableEntity {
+    const VALIDATION_ERROR_STATUS: StatusCode = StatusCode::UNPROCESSABLE_ENTITY;
+}
+
+/// # `WithStatus` data extractor
+///
+/// `WithStatus` runs the inner validation extractor, rendering its validation errors with the status of `S`.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WithStatus<E, S>(pub E, pub PhantomData<S>);
+
+/// `Valid` rendering validation errors with `422 Unprocessable Entity`.
+#[cfg(feature = "validator")]
+pub type Valid422<E> = WithStatus<crate::Valid<E>, UnprocessableEntity>;
+
+impl<E, S: ValidationStatus> ValidationStatus for WithStatus<E, S> {
+    const VALIDATION_ERROR_STATUS: StatusCode = S::VALIDATION_ERROR_STATUS;
+}
+
+impl<E, S> Deref for WithStatus<E, S> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E, S> DerefMut for WithStatus<E, S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display, S> Display for WithStatus<T, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E, S> WithStatus<E, S> {
+    /// Consumes the `WithStatus` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T, S> aide::OperationInput for WithStatus<T, S>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `WithStatusRejection` is returned when the `WithStatus` extractor fails.
+///
+/// It holds the rejection of the inner extractor, rendering its validation errors with the status of `S`.
+///
+pub struct WithStatusRejection<V, E, S>(pub ValidationRejection<V, E>, pub PhantomData<S>);
+
+impl<V, E, S> WithStatusRejection<V, E, S> {
+    /// Consumes the `WithStatusRejection` and returns the rejection of the inner extractor.
+    pub fn into_inner(self) -> ValidationRejection<V, E> {
+        self.0
+    }
+}
+
+impl<V: Debug, E: Debug, S> Debug for WithStatusRejection<V, E, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<V: Display, E: Display, S> Display for WithStatusRejection<V, E, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<V: Error + 'static, E: Error + 'static, S> Error for WithStatusRejection<V, E, S> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.source()
+    }
+}
+
+#[cfg(feature = "into_json")]
+impl<V: serde::Serialize, E: IntoResponse, S: ValidationStatus> IntoResponse
+    for WithStatusRejection<V, E, S>
+{
+    fn into_response(self) -> Response {
+        match self.0 {
+            ValidationRejection::Valid(v) => {
+                let mut response = validation_errors_response(&v);
+                *response.status_mut() = S::VALIDATION_ERROR_STATUS;
+                response
+            }
+            ValidationRejection::Inner(e) => e.into_response(),
+        }
+    }
+}
+
+#[cfg(not(feature = "into_json"))]
+impl<V: Display, E: IntoResponse, S: ValidationStatus> IntoResponse
+    for WithStatusRejection<V, E, S>
+{
+    fn into_response(self) -> Response {
+        match self.0 {
+            ValidationRejection::Valid(v) => {
+                let mut response = validation_errors_response(&v);
+                *response.status_mut() = S::VALIDATION_ERROR_STATUS;
+                response
+            }
+            ValidationRejection::Inner(e) => e.into_response(),
+        }
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, V, E, S> FromRequest<State> for WithStatus<Extractor, S>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State, Rejection = ValidationRejection<V, E>>,
+    WithStatusRejection<V, E, S>: IntoResponse,
+    S: ValidationStatus,
+{
+    type Rejection = WithStatusRejection<V, E, S>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        Extractor::from_request(req, state)
+            .await
+            .map(|inner| WithStatus(inner, PhantomData))
+            .map_err(|rejection| WithStatusRejection(rejection, PhantomData))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, V, E, S> FromRequestParts<State> for WithStatus<Extractor, S>
+where
+    State: Send + Sync,
+    Extractor: FromRequestParts<State, Rejection = ValidationRejection<V, E>>,
+    WithStatusRejection<V, E, S>: IntoResponse,
+    S: ValidationStatus,
+{
+    type Rejection = WithStatusRejection<V, E, S>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        Extractor::from_request_parts(parts, state)
+            .await
+            .map(|inner| WithStatus(inner, PhantomData))
+            .map_err(|rejection| WithStatusRejection(rejection, PhantomData))
+    }
+}
+
+#[cfg(all(test, feature = "validator", feature = "json"))]
+mod tests {
+    use super::*;
+    use crate::Valid;
+    use axum::body::Body;
+    use axum::Json;
+    use validator::Validate;
+
+    #[derive(Debug, Validate, serde::Deserialize)]
+    struct Parameter {
+        #[validate(range(min = 5, max = 10))]
+        v0: i32,
+    }
+
+    fn request(body: &'static str) -> Request {
+        Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .expect("invalid request")
+    }
+
+    async fn rejection_response<T>(body: &'static str) -> (StatusCode, axum::body::Bytes)
+    where
+        T: FromRequest<()>,
+    {
+        let response = T::from_request(request(body), &())
+            .await
+            .err()
+            .expect("invalid parameter accepted")
+            .into_response();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("failed to read body");
+        (status, body)
+    }
+
+    #[tokio::test]
+    async fn status_per_extractor() {
+        assert_eq!(
+            <Valid422<Json<Parameter>> as ValidationStatus>::VALIDATION_ERROR_STATUS,
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+
+        let invalid = r#"{ "v0": 1 }"#;
+        let (status, body) = rejection_response::<Valid<Json<Parameter>>>(invalid).await;
+        assert_eq!(status, crate::VALIDATION_ERROR_STATUS);
+        let (bad_request, bad_request_body) =
+            rejection_response::<WithStatus<Valid<Json<Parameter>>, BadRequest>>(invalid).await;
+        assert_eq!(bad_request, StatusCode::BAD_REQUEST);
+        let (unprocessable, unprocessable_body) =
+            rejection_response::<Valid422<Json<Parameter>>>(invalid).await;
+        assert_eq!(unprocessable, StatusCode::UNPROCESSABLE_ENTITY);
+        // Without `into_json`, parameters of validation errors are displayed in hash map order.
+        #[cfg(feature = "into_json")]
+        {
+            assert_eq!(bad_request_body, body);
+            assert_eq!(unprocessable_body, body);
+        }
+        #[cfg(not(feature = "into_json"))]
+        {
+            assert_eq!(bad_request_body.len(), body.len());
+            assert_eq!(unprocessable_body.len(), body.len());
+        }
+
+        let (status, _) = rejection_response::<Valid422<Json<Parameter>>>("{").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        assert!(
+            Valid422::<Json<Parameter>>::from_request(request(r#"{ "v0": 7 }"#), &())
+                .await
+                .is_ok()
+        );
+    }
+}
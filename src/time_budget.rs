@@ -0,0 +1,299 @@
+//! # Time budget of extraction
+//!
+//! ## Feature
+//!
+//! Enable the `time_budget` feature to use `TimeBudget<E, MS>`.
+//!
+//! ## Usage
+//!
+//! Pathological inputs, e.g. deeply nested documents, bodies trickling in slowly or values with expensive rules,
+//! can keep a request busy for long before it is rejected. `TimeBudget<E, MS>` bounds the wall-clock time
+//! the inner extractor may take, including the validation of validation extractors, to `MS` milliseconds:
+//!
+//! 1. In your handler function, wrap your extractor with `TimeBudget`, e.g. `TimeBudget<Valid<Json<T>>, 100>`.
+//!
+//! An extraction which is still waiting, e.g. for the body, when the budget runs out is abandoned.
+//! Deserialization and validation run synchronously, so they cannot be interrupted: an extraction which
+//! exceeds the budget while parsing is rejected as soon as it completes, and the handler is not called.
+//! Either way, the request is rejected with `408 Request Timeout`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::time_budget::TimeBudget;
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/json", post(handler))
+//!     }
+//!
+//!     async fn handler(TimeBudget(Valid(Json(document))): TimeBudget<Valid<Json<Document>>, 100>) {
+//!         assert!(document.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Document {
+//!         #[validate(length(max = 10000))]
+//!         pub sections: Vec<String>,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use axum::async_trait;
+use axum::extract::{FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// # `TimeBudget` data extractor
+///
+/// `TimeBudget` runs the inner extractor, and rejects the request when it takes more than `MS` milliseconds.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeBudget<E, const MS: u64>(pub E);
+
+impl<E, const MS: u64> Deref for TimeBudget<E, MS> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E, const MS: u64> DerefMut for TimeBudget<E, MS> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display, const MS: u64> Display for TimeBudget<T, MS> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E, const MS: u64> TimeBudget<E, MS> {
+    /// Consumes the `TimeBudget` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+
+    /// Runs the extraction, abandoning it once the budget is spent.
+    async fn run<T, R, F>(extraction: F) -> Result<T, TimeBudgetRejection<R>>
+    where
+        F: Future<Output = Result<T, R>>,
+    {
+        let budget = Duration::from_millis(MS);
+        let start = Instant::now();
+        let inner = tokio::time::timeout(budget, extraction)
+            .await
+            .map_err(|_| TimeBudgetRejection::Exceeded(budget))?
+            .map_err(TimeBudgetRejection::Inner)?;
+        if start.elapsed() > budget {
+            return Err(TimeBudgetRejection::Exceeded(budget));
+        }
+        Ok(inner)
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T, const MS: u64> aide::OperationInput for TimeBudget<T, MS>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `TimeBudgetRejection` is returned when the `TimeBudget` extractor fails.
+///
+#[derive(Debug)]
+pub enum TimeBudgetRejection<E> {
+    /// `Exceeded` variant holds the budget the extraction did not fit in.
+    Exceeded(Duration),
+    /// `Inner` variant represents errors of the inner extractor which completed in time.
+    Inner(E),
+}
+
+impl<E: Display> Display for TimeBudgetRejection<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeBudgetRejection::Exceeded(budget) => write!(
+                f,
+                "Request took more than {} ms to extract",
+                budget.as_millis()
+            ),
+            TimeBudgetRejection::Inner(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for TimeBudgetRejection<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TimeBudgetRejection::Exceeded(_) => None,
+            TimeBudgetRejection::Inner(error) => Some(error),
+        }
+    }
+}
+
+impl<E: IntoResponse> IntoResponse for TimeBudgetRejection<E> {
+    fn into_response(self) -> Response {
+        match self {
+            TimeBudgetRejection::Exceeded(budget) => (
+                StatusCode::REQUEST_TIMEOUT,
+                format!(
+                    "Request took more than {} ms to extract",
+                    budget.as_millis()
+                ),
+            )
+                .into_response(),
+            TimeBudgetRejection::Inner(error) => error.into_response(),
+        }
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, const MS: u64> FromRequest<State> for TimeBudget<Extractor, MS>
+where
+    State: Send + Sync,
+    Extractor: FromRequest<State>,
+{
+    type Rejection = TimeBudgetRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        Self::run(Extractor::from_request(req, state))
+            .await
+            .map(TimeBudget)
+    }
+}
+
+#[async_trait]
+impl<State, Extractor, const MS: u64> FromRequestParts<State> for TimeBudget<Extractor, MS>
+where
+    State: Send + Sync,
+    Extractor: FromRequestParts<State>,
+{
+    type Rejection = TimeBudgetRejection<<Extractor as FromRequestParts<State>>::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        Self::run(Extractor::from_request_parts(parts, state))
+            .await
+            .map(TimeBudget)
+    }
+}
+
+#[cfg(all(test, feature = "validator", feature = "json"))]
+mod tests {
+    use super::*;
+    use crate::{Valid, ValidRejection};
+    use axum::body::Body;
+    use axum::Json;
+    use serde::Deserialize;
+    use validator::Validate;
+
+    #[derive(Debug, Validate, Deserialize)]
+    struct Document {
+        #[validate(length(min = 1))]
+        text: String,
+    }
+
+    type Budgeted = TimeBudget<Valid<Json<Document>>, 20>;
+
+    fn request(body: Body) -> Request {
+        Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(body)
+            .expect("invalid request")
+    }
+
+    /// An extraction which overruns the budget before completing, like a costly validation.
+    #[derive(Debug)]
+    struct Costly;
+
+    #[async_trait]
+    impl FromRequestParts<()> for Costly {
+        type Rejection = StatusCode;
+
+        async fn from_request_parts(_: &mut Parts, _: &()) -> Result<Self, Self::Rejection> {
+            tokio::time::advance(Duration::from_millis(50)).await;
+            Ok(Costly)
+        }
+    }
+
+    /// An extraction which never ends, e.g. waiting for a stalled body.
+    struct Stalled;
+
+    #[async_trait]
+    impl FromRequestParts<()> for Stalled {
+        type Rejection = StatusCode;
+
+        async fn from_request_parts(_: &mut Parts, _: &()) -> Result<Self, Self::Rejection> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pathological_payloads_are_aborted() {
+        let TimeBudget(Valid(Json(document))) =
+            Budgeted::from_request(request(Body::from(r#"{ "text": "short" }"#)), &())
+                .await
+                .expect("cheap document rejected");
+        assert_eq!(document.text, "short");
+
+        assert!(matches!(
+            Budgeted::from_request(request(Body::from(r#"{ "text": 1 }"#)), &()).await,
+            Err(TimeBudgetRejection::Inner(ValidRejection::Inner(_)))
+        ));
+
+        // An extraction which completes after the budget is spent is rejected
+        let (mut parts, _) = request(Body::empty()).into_parts();
+        let rejection = TimeBudget::<Costly, 20>::from_request_parts(&mut parts, &())
+            .await
+            .expect_err("costly extraction accepted");
+        assert!(
+            matches!(rejection, TimeBudgetRejection::Exceeded(budget) if budget.as_millis() == 20)
+        );
+        assert_eq!(
+            rejection.into_response().status(),
+            StatusCode::REQUEST_TIMEOUT
+        );
+
+        // An extraction which never ends is abandoned once the budget is spent
+        let start = Instant::now();
+        assert!(matches!(
+            TimeBudget::<Stalled, 20>::from_request_parts(&mut parts, &()).await,
+            Err(TimeBudgetRejection::Exceeded(_))
+        ));
+        assert_eq!(start.elapsed(), Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,279 @@
+//! # Default timezone for naive datetimes
+//!
+//! ## Feature
+//!
+//! Enable the `timezone` feature to use `Localized<E>`.
+//!
+//! ## Usage
+//!
+//! Clients sometimes send naive datetimes (without offset), which must be interpreted in a configured timezone
+//! before they can be compared with absolute bounds.
+//!
+//! 1. Make a `DefaultTimezone` available from your router state (implement `FromRef<YourState>` for it,
+//!    or use it as the state directly). Any `chrono::TimeZone` can be used, e.g. `chrono_tz::Tz`.
+//! 2. Implement `Localize` for your data type, converting its naive datetimes, e.g. with `to_utc`.
+//! 3. In your handler function, wrap the inner extractor with `Localized` inside the validation extractor,
+//!    e.g. `Valid<Localized<Json<T>>>` or `Validated<Localized<Json<T>>>`.
+//!
+//! `Localized` runs the inner extractor and modifies its data with `Localize`, like a `validify` `modify` step;
+//! the outer extractor then validates the localized data.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Json;
+//!     use axum::Router;
+//!     use axum_valid::timezone::{to_utc, DefaultTimezone, Localize, Localized};
+//!     use axum_valid::Valid;
+//!     use chrono::{FixedOffset, NaiveDateTime, TimeZone, Utc};
+//!     use serde::Deserialize;
+//!     use validator::{Validate, ValidationError};
+//!
+//!     pub fn router() -> Router {
+//!         let paris_winter = FixedOffset::east_opt(3600).expect("invalid offset");
+//!         Router::new()
+//!             .route("/meetings", post(handler))
+//!             .with_state(DefaultTimezone(paris_winter))
+//!     }
+//!
+//!     async fn handler(Valid(Localized(Json(meeting))): Valid<Localized<Json<Meeting>>>) {
+//!         assert!(meeting.validate().is_ok());
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Meeting {
+//!         /// Local time when received, UTC once localized.
+//!         #[validate(custom(function = "in_2024"))]
+//!         pub starts_at: NaiveDateTime,
+//!     }
+//!
+//!     impl Localize for Meeting {
+//!         type TimeZone = FixedOffset;
+//!
+//!         fn localize(&mut self, timezone: &FixedOffset) {
+//!             if let Some(utc) = to_utc(self.starts_at, timezone) {
+//!                 self.starts_at = utc.naive_utc();
+//!             }
+//!         }
+//!     }
+//!
+//!     fn in_2024(starts_at: &NaiveDateTime) -> Result<(), ValidationError> {
+//!         let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap().naive_utc();
+//!         let end = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap().naive_utc();
+//!         if (start..end).contains(starts_at) {
+//!             Ok(())
+//!         } else {
+//!             Err(ValidationError::new("in_2024"))
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::HasValidate;
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use chrono::{DateTime, LocalResult, NaiveDateTime, TimeZone, Utc};
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// Timezone in which naive datetimes are interpreted.
+///
+/// `Localized` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DefaultTimezone<Tz>(pub Tz);
+
+/// Trait for data types whose naive datetimes must be localized before validation.
+pub trait Localize {
+    /// Type of the default timezone
+    type TimeZone: TimeZone;
+    /// Interpret the naive datetimes of the data in `timezone`
+    fn localize(&mut self, timezone: &Self::TimeZone);
+}
+
+/// Interprets a naive datetime in `timezone` and converts it to UTC.
+///
+/// When the local time is ambiguous (e.g. when clocks go back), the earliest instant is used.
+/// When it does not exist (e.g. when clocks go forward), `None` is returned.
+pub fn to_utc<Tz: TimeZone>(naive: NaiveDateTime, timezone: &Tz) -> Option<DateTime<Utc>> {
+    match timezone.from_local_datetime(&naive) {
+        LocalResult::Single(local) | LocalResult::Ambiguous(local, _) => {
+            Some(local.with_timezone(&Utc))
+        }
+        LocalResult::None => None,
+    }
+}
+
+/// # `Localized` extractor
+///
+/// `Localized` runs the inner extractor, then localizes its data with the `DefaultTimezone`
+/// taken from the state.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Localized<E>(pub E);
+
+impl<E> Deref for Localized<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for Localized<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for Localized<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> Localized<E> {
+    /// Consumes the `Localized` and returns the inner extractor.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for Localized<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+impl<E: HasValidate> HasValidate for Localized<E> {
+    type Validate = E::Validate;
+    fn get_validate(&self) -> &Self::Validate {
+        self.0.get_validate()
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for Localized<Extractor>
+where
+    State: Send + Sync,
+    Extractor: DerefMut + FromRequest<State>,
+    Extractor::Target: Localize,
+    DefaultTimezone<<Extractor::Target as Localize>::TimeZone>: FromRef<State>,
+{
+    type Rejection = <Extractor as FromRequest<State>>::Rejection;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let mut inner = Extractor::from_request(req, state).await?;
+        let DefaultTimezone(timezone) = DefaultTimezone::from_ref(state);
+        inner.localize(&timezone);
+        Ok(Localized(inner))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequestParts<State> for Localized<Extractor>
+where
+    State: Send + Sync,
+    Extractor: DerefMut + FromRequestParts<State>,
+    Extractor::Target: Localize,
+    DefaultTimezone<<Extractor::Target as Localize>::TimeZone>: FromRef<State>,
+{
+    type Rejection = <Extractor as FromRequestParts<State>>::Rejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let mut inner = Extractor::from_request_parts(parts, state).await?;
+        let DefaultTimezone(timezone) = DefaultTimezone::from_ref(state);
+        inner.localize(&timezone);
+        Ok(Localized(inner))
+    }
+}
+
+#[cfg(all(test, feature = "validator", feature = "json"))]
+mod tests {
+    use super::*;
+    use crate::Valid;
+    use axum::body::Body;
+    use axum::Json;
+    use chrono::FixedOffset;
+    use serde::Deserialize;
+    use validator::{Validate, ValidationError};
+
+    #[derive(Debug, Validate, Deserialize)]
+    struct Meeting {
+        #[validate(custom(function = "in_2024"))]
+        starts_at: NaiveDateTime,
+    }
+
+    impl Localize for Meeting {
+        type TimeZone = FixedOffset;
+
+        fn localize(&mut self, timezone: &FixedOffset) {
+            if let Some(utc) = to_utc(self.starts_at, timezone) {
+                self.starts_at = utc.naive_utc();
+            }
+        }
+    }
+
+    fn in_2024(starts_at: &NaiveDateTime) -> Result<(), ValidationError> {
+        let year = |year| {
+            Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0)
+                .single()
+                .expect("invalid date")
+                .naive_utc()
+        };
+        if (year(2024)..year(2025)).contains(starts_at) {
+            Ok(())
+        } else {
+            Err(ValidationError::new("in_2024"))
+        }
+    }
+
+    #[tokio::test]
+    async fn localized_before_validation() {
+        let request = || {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{ "starts_at": "2024-01-01T00:30:00" }"#))
+                .expect("invalid request")
+        };
+        let timezone =
+            |hours| DefaultTimezone(FixedOffset::east_opt(hours * 3600).expect("invalid offset"));
+
+        // 00:30 at UTC-02:00 is 02:30 UTC, in 2024.
+        let Valid(Localized(Json(meeting))) =
+            Valid::<Localized<Json<Meeting>>>::from_request(request(), &timezone(-2))
+                .await
+                .expect("localized datetime rejected");
+        assert_eq!(meeting.starts_at.to_string(), "2024-01-01 02:30:00");
+
+        // 00:30 at UTC+02:00 is 22:30 UTC on the previous day, in 2023.
+        assert!(
+            Valid::<Localized<Json<Meeting>>>::from_request(request(), &timezone(2))
+                .await
+                .is_err()
+        );
+    }
+}
@@ -194,4 +194,76 @@ mod tests {
             builder.headers(headers)
         }
     }
+
+    #[cfg(feature = "validator")]
+    mod rate_limit {
+        use crate::{Valid, ValidRejection};
+        use axum::extract::FromRequestParts;
+        use axum::http::{HeaderName, HeaderValue, Request};
+        use axum_extra::headers::{Error, Header};
+        use axum_extra::typed_header::{TypedHeader, TypedHeaderRejection};
+        use validator::Validate;
+
+        static RATE_LIMIT: HeaderName = HeaderName::from_static("x-rate-limit");
+
+        #[derive(Debug, Clone, Validate)]
+        struct RateLimit {
+            #[validate(range(min = 1, max = 1000))]
+            per_minute: u32,
+        }
+
+        impl Header for RateLimit {
+            fn name() -> &'static HeaderName {
+                &RATE_LIMIT
+            }
+
+            fn decode<'i, I>(values: &mut I) -> Result<Self, Error>
+            where
+                I: Iterator<Item = &'i HeaderValue>,
+            {
+                let value = values.next().ok_or_else(Error::invalid)?;
+                let per_minute = value
+                    .to_str()
+                    .ok()
+                    .and_then(|value| value.parse().ok())
+                    .ok_or_else(Error::invalid)?;
+                Ok(RateLimit { per_minute })
+            }
+
+            fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+                values.extend(std::iter::once(self.per_minute.into()));
+            }
+        }
+
+        async fn extract(
+            value: &'static str,
+        ) -> Result<Valid<TypedHeader<RateLimit>>, ValidRejection<TypedHeaderRejection>> {
+            let (mut parts, _) = Request::builder()
+                .header(&RATE_LIMIT, value)
+                .body(())
+                .expect("invalid request")
+                .into_parts();
+            Valid::<TypedHeader<RateLimit>>::from_request_parts(&mut parts, &()).await
+        }
+
+        #[tokio::test]
+        async fn valid_typed_header_rejections() {
+            let Valid(TypedHeader(rate_limit)) =
+                extract("60").await.expect("valid header rejected");
+            assert_eq!(rate_limit.per_minute, 60);
+            assert!(matches!(
+                extract("0").await,
+                Err(ValidRejection::Valid(errors)) if errors.field_errors().contains_key("per_minute")
+            ));
+        }
+
+        #[tokio::test]
+        async fn malformed_typed_header_is_inner_rejection() {
+            // A malformed value is rejected by `TypedHeader` itself, before validation
+            assert!(matches!(
+                extract("sixty").await,
+                Err(ValidRejection::Inner(rejection)) if !rejection.is_missing()
+            ));
+        }
+    }
 }
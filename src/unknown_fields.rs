@@ -0,0 +1,329 @@
+//! # Unknown fields of JSON bodies
+//!
+//! ## Feature
+//!
+//! Enable the `unknown_fields` feature to use `TolerantJson<T>`.
+//!
+//! ## Usage
+//!
+//! To stay compatible with newer clients, servers usually ignore the fields they do not know yet.
+//! `TolerantJson<T>` deserializes a JSON body like `Json<T>`, ignoring unknown fields, but collects their names
+//! into `UnknownFields`, so they can be logged or counted to follow the evolution of the schema.
+//!
+//! In your handler function, use `TolerantJson<T>` inside a validation extractor, e.g. `Valid<TolerantJson<T>>`.
+//! Only the known fields are validated, and unknown fields never cause a rejection.
+//!
+//! Unknown fields are named by their path, e.g. `address.building` or `items[2].color`.
+//! Types using `#[serde(deny_unknown_fields)]` still reject them, as `Json<T>` does.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! #[cfg(feature = "validator")]
+//! mod validator_example {
+//!     use axum::routing::post;
+//!     use axum::Router;
+//!     use axum_valid::unknown_fields::TolerantJson;
+//!     use axum_valid::Valid;
+//!     use serde::Deserialize;
+//!     use validator::Validate;
+//!
+//!     pub fn router() -> Router {
+//!         Router::new().route("/json", post(handler))
+//!     }
+//!
+//!     async fn handler(Valid(TolerantJson(parameter, unknown)): Valid<TolerantJson<Parameter>>) {
+//!         assert!(parameter.validate().is_ok());
+//!         for field in unknown.iter() {
+//!             println!("unknown field `{field}`");
+//!         }
+//!     }
+//!
+//!     #[derive(Validate, Deserialize)]
+//!     pub struct Parameter {
+//!         #[validate(range(min = 5, max = 10))]
+//!         pub v0: i32,
+//!         #[validate(length(min = 1, max = 10))]
+//!         pub v1: String,
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use axum::Router;
+//! #     use tokio::net::TcpListener;
+//! #     let router = Router::new();
+//! #     #[cfg(feature = "validator")]
+//! #     let router = router.nest("/validator", validator_example::router());
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::HasValidate;
+#[cfg(feature = "validator")]
+use crate::HasValidateArgs;
+use axum::async_trait;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{FromRequest, Request};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::de::DeserializeOwned;
+use serde_ignored::Path;
+use serde_json::Value;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+#[cfg(feature = "validator")]
+use validator::ValidateArgs;
+
+/// Paths of the fields of a JSON body which were ignored during deserialization.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnknownFields(pub Vec<String>);
+
+impl UnknownFields {
+    /// Returns `true` if the body had no unknown field.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the paths of the unknown fields, in the order of the body.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+}
+
+impl Display for UnknownFields {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.join(", "))
+    }
+}
+
+/// # `TolerantJson` data extractor
+///
+/// `TolerantJson` deserializes the JSON body into `T` and collects the fields which `T` ignored.
+///
+#[derive(Debug, Clone, Default)]
+pub struct TolerantJson<T>(pub T, pub UnknownFields);
+
+impl<T> Deref for TolerantJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for TolerantJson<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> TolerantJson<T> {
+    /// Consumes the `TolerantJson` and returns the data and its unknown fields.
+    pub fn into_inner(self) -> (T, UnknownFields) {
+        (self.0, self.1)
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for TolerantJson<T>
+where
+    Json<T>: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        Json::<T>::operation_input(ctx, operation);
+    }
+}
+
+/// `TolerantJsonRejection` is returned when the `TolerantJson` extractor fails.
+///
+#[derive(Debug)]
+pub enum TolerantJsonRejection {
+    /// `Json` variant captures errors raised while reading or parsing the body.
+    Json(JsonRejection),
+    /// `Data` variant captures errors raised while deserializing the body into the target type.
+    Data(serde_json::Error),
+}
+
+impl Display for TolerantJsonRejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TolerantJsonRejection::Json(error) => write!(f, "{error}"),
+            TolerantJsonRejection::Data(error) => write!(
+                f,
+                "Failed to deserialize the JSON body into the target type: {error}"
+            ),
+        }
+    }
+}
+
+impl Error for TolerantJsonRejection {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TolerantJsonRejection::Json(error) => Some(error),
+            TolerantJsonRejection::Data(error) => Some(error),
+        }
+    }
+}
+
+impl IntoResponse for TolerantJsonRejection {
+    fn into_response(self) -> Response {
+        match self {
+            TolerantJsonRejection::Json(error) => error.into_response(),
+            TolerantJsonRejection::Data(_) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, self.to_string()).into_response()
+            }
+        }
+    }
+}
+
+/// Formats a path like the pre-checks do, e.g. `items[2].color`.
+fn field_path(path: &Path) -> String {
+    match path {
+        Path::Root => String::new(),
+        Path::Seq { parent, index } => format!("{}[{index}]", field_path(parent)),
+        Path::Map { parent, key } => match field_path(parent) {
+            parent if parent.is_empty() => key.clone(),
+            parent => format!("{parent}.{key}"),
+        },
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => field_path(parent),
+    }
+}
+
+#[async_trait]
+impl<State, T> FromRequest<State> for TolerantJson<T>
+where
+    State: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = TolerantJsonRejection;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<Value>::from_request(req, state)
+            .await
+            .map_err(TolerantJsonRejection::Json)?;
+        let mut unknown = UnknownFields::default();
+        let data = serde_ignored::deserialize(value, |path| unknown.0.push(field_path(&path)))
+            .map_err(TolerantJsonRejection::Data)?;
+        Ok(TolerantJson(data, unknown))
+    }
+}
+
+impl<T> HasValidate for TolerantJson<T> {
+    type Validate = T;
+    fn get_validate(&self) -> &T {
+        &self.0
+    }
+}
+
+#[cfg(feature = "validator")]
+impl<'v, T: ValidateArgs<'v>> HasValidateArgs<'v> for TolerantJson<T> {
+    type ValidateArgs = T;
+    fn get_validate_args(&self) -> &Self::ValidateArgs {
+        &self.0
+    }
+}
+
+#[cfg(feature = "validify")]
+impl<T: validify::Modify> crate::HasModify for TolerantJson<T> {
+    type Modify = T;
+
+    fn get_modify(&mut self) -> &mut Self::Modify {
+        &mut self.0
+    }
+}
+
+#[cfg(all(test, feature = "validator"))]
+mod tests {
+    use super::*;
+    use crate::{Valid, ValidRejection};
+    use axum::body::Body;
+    use serde::Deserialize;
+    use validator::Validate;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Item {
+        #[validate(length(min = 1))]
+        name: String,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Order {
+        #[validate(range(min = 1))]
+        quantity: u32,
+        #[validate(nested)]
+        items: Vec<Item>,
+        note: Option<Item>,
+    }
+
+    fn request(body: &'static str) -> Request {
+        Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .expect("invalid request")
+    }
+
+    #[tokio::test]
+    async fn tolerant_json_collects_unknown_fields() {
+        let Valid(TolerantJson(order, unknown)) = Valid::<TolerantJson<Order>>::from_request(
+            request(
+                r#"{
+                    "quantity": 2,
+                    "channel": "mobile",
+                    "items": [{ "name": "a" }, { "name": "b", "color": "red" }],
+                    "note": { "name": "gift", "wrapping": true }
+                }"#,
+            ),
+            &(),
+        )
+        .await
+        .expect("unknown fields rejected");
+        assert_eq!(order.quantity, 2);
+        assert_eq!(order.note.map(|note| note.name).as_deref(), Some("gift"));
+        assert_eq!(
+            unknown.iter().collect::<Vec<_>>(),
+            ["channel", "items[1].color", "note.wrapping"]
+        );
+
+        let Valid(TolerantJson(_, unknown)) = Valid::<TolerantJson<Order>>::from_request(
+            request(r#"{ "quantity": 1, "items": [] }"#),
+            &(),
+        )
+        .await
+        .expect("valid order rejected");
+        assert!(unknown.is_empty());
+
+        assert!(matches!(
+            Valid::<TolerantJson<Order>>::from_request(
+                request(r#"{ "quantity": 0, "items": [], "channel": "mobile" }"#),
+                &()
+            )
+            .await,
+            Err(ValidRejection::Valid(_))
+        ));
+
+        let rejection = Valid::<TolerantJson<Order>>::from_request(
+            request(r#"{ "quantity": "two", "items": [] }"#),
+            &(),
+        )
+        .await
+        .expect_err("invalid type accepted");
+        assert!(matches!(
+            rejection,
+            ValidRejection::Inner(TolerantJsonRejection::Data(_))
+        ));
+        assert_eq!(
+            rejection.into_response().status(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+}
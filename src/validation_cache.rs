@@ -0,0 +1,463 @@
+//! # Validation outcomes cached by data hash
+//!
+//! ## Feature
+//!
+//! Enable the `validation_cache` feature to use `CachedValid<E>`.
+//!
+//! ## Usage
+//!
+//! Retried requests often carry the exact same data. `CachedValid<E>` validates like `Valid<E>`, but remembers
+//! the outcome of the validation, keyed by the SHA-256 digest of the extracted data, and reuses it for identical
+//! data instead of validating again:
+//!
+//! 1. Make a `ValidationCache` available from your router state (implement `FromRef<YourState>` for it,
+//!    or use it as the state directly). Clones share the same entries and metrics.
+//! 2. Derive `Serialize` for your data type.
+//! 3. In your handler function, use `CachedValid<E>` as the extractor, e.g. `CachedValid<Json<T>>`.
+//!
+//! The digest is computed over the data serialized as JSON, whichever part of the request it was extracted from,
+//! so two requests only share an outcome when they carry the same data. Fields skipped by serialization are not
+//! part of the digest, so they must not affect validation. Data which cannot be serialized is validated every time.
+//!
+//! Both successful and failed outcomes are cached. Entries are also keyed by the validated type, so routes
+//! sharing a cache never reuse each other's outcomes. Once the cache is full, the least recently used entry
+//! is evicted. `ValidationCache::hits` and `ValidationCache::misses` count how often a cached outcome was reused.
+//!
+//! Only use it with rules that depend on the data alone: rules reading the clock or external state may
+//! give another outcome on retry.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use axum::routing::post;
+//! use axum::Json;
+//! use axum::Router;
+//! use axum_valid::validation_cache::{CachedValid, ValidationCache};
+//! use serde::{Deserialize, Serialize};
+//! use std::num::NonZeroUsize;
+//! use validator::Validate;
+//!
+//! #[derive(Validate, Serialize, Deserialize)]
+//! pub struct Payment {
+//!     #[validate(length(min = 1, max = 64))]
+//!     pub reference: String,
+//!     #[validate(range(min = 1))]
+//!     pub amount: u64,
+//! }
+//!
+//! async fn handler(CachedValid(Json(payment)): CachedValid<Json<Payment>>) {
+//!     assert!(payment.validate().is_ok());
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! #     use std::net::SocketAddr;
+//! #     use tokio::net::TcpListener;
+//! let cache = ValidationCache::new(NonZeroUsize::new(1024).expect("zero capacity"));
+//! let router = Router::new()
+//!     .route("/payments", post(handler))
+//!     .with_state(cache);
+//! #     let listener = TcpListener::bind(&SocketAddr::from(([0u8, 0, 0, 0], 0u16))).await?;
+//! #     axum::serve(listener, router.into_make_service())
+//! #         .await?;
+//! #     Ok(())
+//! # }
+//! ```
+
+use crate::HasValidate;
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::response::{IntoResponse, Response};
+use lru::LruCache;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::any::TypeId;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::num::NonZeroUsize;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use validator::{Validate, ValidationErrors};
+
+type Key = (TypeId, [u8; 32]);
+
+#[derive(Debug)]
+struct Entries {
+    outcomes: Mutex<LruCache<Key, Result<(), ValidationErrors>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// Validation outcomes of the most recently seen data.
+///
+/// `CachedValid` reads it from the router state, so it must implement `FromRef<YourState>`.
+///
+#[derive(Debug, Clone)]
+pub struct ValidationCache(Arc<Entries>);
+
+impl ValidationCache {
+    /// Create a `ValidationCache` holding up to `capacity` outcomes.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self(Arc::new(Entries {
+            outcomes: Mutex::new(LruCache::new(capacity)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }))
+    }
+
+    /// Number of validations skipped because the outcome was cached.
+    pub fn hits(&self) -> u64 {
+        self.0.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of validations run because the outcome was not cached.
+    pub fn misses(&self) -> u64 {
+        self.0.misses.load(Ordering::Relaxed)
+    }
+
+    /// Number of cached outcomes.
+    pub fn len(&self) -> usize {
+        self.outcomes().len()
+    }
+
+    /// Returns `true` if no outcome is cached.
+    pub fn is_empty(&self) -> bool {
+        self.outcomes().is_empty()
+    }
+
+    /// Removes all cached outcomes. Metrics are kept.
+    pub fn clear(&self) {
+        self.outcomes().clear();
+    }
+
+    fn outcomes(&self) -> std::sync::MutexGuard<'_, LruCache<Key, Result<(), ValidationErrors>>> {
+        self.0
+            .outcomes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Returns the cached outcome for `data`, or validates it and caches the outcome.
+    fn validate<T: Validate + Serialize + 'static>(
+        &self,
+        data: &T,
+    ) -> Result<(), ValidationErrors> {
+        let Some(digest) = digest(data) else {
+            return data.validate();
+        };
+        let key = (TypeId::of::<T>(), digest);
+        if let Some(outcome) = self.outcomes().get(&key).cloned() {
+            self.0.hits.fetch_add(1, Ordering::Relaxed);
+            return outcome;
+        }
+        self.0.misses.fetch_add(1, Ordering::Relaxed);
+        let outcome = data.validate();
+        self.outcomes().put(key, outcome.clone());
+        outcome
+    }
+}
+
+/// # `CachedValid` data extractor
+///
+/// `CachedValid` validates the data of the inner extractor like `Valid`, reusing the outcome
+/// cached in the `ValidationCache` for identical data.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CachedValid<E>(pub E);
+
+impl<E> Deref for CachedValid<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for CachedValid<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for CachedValid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> CachedValid<E> {
+    /// Consumes the `CachedValid` and returns the validated data within.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for CachedValid<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// `CachedValidRejection` is returned when the `CachedValid` extractor fails.
+///
+#[derive(Debug)]
+pub enum CachedValidRejection<E> {
+    /// `Valid` variant captures errors related to the validation logic, whether cached or not.
+    Valid(ValidationErrors),
+    /// `Inner` variant represents potential errors that might occur within the inner extractor.
+    Inner(E),
+}
+
+impl<E: Display> Display for CachedValidRejection<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CachedValidRejection::Valid(errors) => write!(f, "{errors}"),
+            CachedValidRejection::Inner(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for CachedValidRejection<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CachedValidRejection::Valid(ve) => Some(ve),
+            CachedValidRejection::Inner(e) => Some(e),
+        }
+    }
+}
+
+impl<E: IntoResponse> IntoResponse for CachedValidRejection<E> {
+    fn into_response(self) -> Response {
+        match self {
+            CachedValidRejection::Valid(ve) => crate::validation_errors_response(&ve),
+            CachedValidRejection::Inner(e) => e.into_response(),
+        }
+    }
+}
+
+/// Digest of the data serialized as JSON, or `None` if it cannot be serialized.
+fn digest<T: Serialize + ?Sized>(data: &T) -> Option<[u8; 32]> {
+    let json = serde_json::to_vec(data).ok()?;
+    Some(Sha256::digest(json).into())
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for CachedValid<Extractor>
+where
+    State: Send + Sync,
+    ValidationCache: FromRef<State>,
+    Extractor: HasValidate + FromRequest<State>,
+    Extractor::Validate: Validate + Serialize + 'static,
+{
+    type Rejection = CachedValidRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let cache = ValidationCache::from_ref(state);
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(CachedValidRejection::Inner)?;
+        cache
+            .validate(inner.get_validate())
+            .map_err(CachedValidRejection::Valid)?;
+        Ok(CachedValid(inner))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequestParts<State> for CachedValid<Extractor>
+where
+    State: Send + Sync,
+    ValidationCache: FromRef<State>,
+    Extractor: HasValidate + FromRequestParts<State>,
+    Extractor::Validate: Validate + Serialize + 'static,
+{
+    type Rejection = CachedValidRejection<<Extractor as FromRequestParts<State>>::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let cache = ValidationCache::from_ref(state);
+        let inner = Extractor::from_request_parts(parts, state)
+            .await
+            .map_err(CachedValidRejection::Inner)?;
+        cache
+            .validate(inner.get_validate())
+            .map_err(CachedValidRejection::Valid)?;
+        Ok(CachedValid(inner))
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    #[cfg(feature = "typed_header")]
+    use axum::http::{HeaderName, HeaderValue};
+    use axum::Json;
+    #[cfg(feature = "typed_header")]
+    use axum_extra::headers::{Error, Header};
+    use serde::Deserialize;
+
+    #[derive(Debug, Validate, Serialize, Deserialize)]
+    struct Payment {
+        #[validate(range(min = 1))]
+        amount: u64,
+    }
+
+    #[cfg(feature = "typed_header")]
+    static AMOUNT: HeaderName = HeaderName::from_static("x-amount");
+
+    #[cfg(feature = "typed_header")]
+    impl Header for Payment {
+        fn name() -> &'static HeaderName {
+            &AMOUNT
+        }
+
+        fn decode<'i, I>(values: &mut I) -> Result<Self, Error>
+        where
+            I: Iterator<Item = &'i HeaderValue>,
+        {
+            let amount = values
+                .next()
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(Error::invalid)?;
+            Ok(Payment { amount })
+        }
+
+        fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+            values.extend(std::iter::once(self.amount.into()));
+        }
+    }
+
+    fn request(body: &'static str) -> Request {
+        Request::builder()
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .expect("invalid request")
+    }
+
+    #[tokio::test]
+    async fn cached_valid() {
+        let cache = ValidationCache::new(NonZeroUsize::new(2).expect("zero capacity"));
+
+        for _ in 0..3 {
+            let CachedValid(Json(payment)) =
+                CachedValid::<Json<Payment>>::from_request(request(r#"{"amount":5}"#), &cache)
+                    .await
+                    .expect("valid payment rejected");
+            assert_eq!(payment.amount, 5);
+        }
+        assert_eq!((cache.misses(), cache.hits()), (1, 2));
+
+        for _ in 0..2 {
+            assert!(matches!(
+                CachedValid::<Json<Payment>>::from_request(request(r#"{"amount":0}"#), &cache)
+                    .await,
+                Err(CachedValidRejection::Valid(_))
+            ));
+        }
+        assert_eq!((cache.misses(), cache.hits()), (2, 3));
+        assert_eq!(cache.len(), 2);
+
+        // Differently formatted bodies carrying the same data share the outcome
+        CachedValid::<Json<Payment>>::from_request(request(r#"{ "amount": 5 }"#), &cache)
+            .await
+            .expect("valid payment rejected");
+        assert_eq!((cache.misses(), cache.hits()), (2, 4));
+
+        CachedValid::<Json<Payment>>::from_request(request(r#"{"amount":7}"#), &cache)
+            .await
+            .expect("valid payment rejected");
+        assert_eq!((cache.misses(), cache.hits()), (3, 4));
+        assert_eq!(cache.len(), 2);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[cfg(feature = "query")]
+    #[tokio::test]
+    async fn cached_valid_query_keyed_by_uri() {
+        use axum::extract::Query;
+
+        let cache = ValidationCache::new(NonZeroUsize::new(4).expect("zero capacity"));
+        let extract = |uri: &'static str| {
+            let (mut parts, _) = Request::builder()
+                .uri(uri)
+                .body(())
+                .expect("invalid request")
+                .into_parts();
+            let cache = cache.clone();
+            async move { CachedValid::<Query<Payment>>::from_request_parts(&mut parts, &cache).await }
+        };
+
+        let CachedValid(Query(payment)) =
+            extract("/?amount=5").await.expect("valid payment rejected");
+        assert_eq!(payment.amount, 5);
+        assert!(matches!(
+            extract("/?amount=0").await,
+            Err(CachedValidRejection::Valid(_))
+        ));
+        assert_eq!((cache.misses(), cache.hits()), (2, 0));
+
+        extract("/?amount=5").await.expect("valid payment rejected");
+        assert_eq!((cache.misses(), cache.hits()), (2, 1));
+    }
+
+    #[cfg(feature = "form")]
+    #[tokio::test]
+    async fn cached_valid_form_query_keyed_by_uri() {
+        use axum::Form;
+
+        let cache = ValidationCache::new(NonZeroUsize::new(4).expect("zero capacity"));
+        let request = |uri: &'static str| {
+            Request::builder()
+                .uri(uri)
+                .body(Body::empty())
+                .expect("invalid request")
+        };
+
+        CachedValid::<Form<Payment>>::from_request(request("/?amount=5"), &cache)
+            .await
+            .expect("valid payment rejected");
+        assert!(matches!(
+            CachedValid::<Form<Payment>>::from_request(request("/?amount=0"), &cache).await,
+            Err(CachedValidRejection::Valid(_))
+        ));
+        assert_eq!((cache.misses(), cache.hits()), (2, 0));
+    }
+
+    #[cfg(feature = "typed_header")]
+    #[tokio::test]
+    async fn cached_valid_typed_header_keyed_by_value() {
+        use axum_extra::typed_header::TypedHeader;
+
+        let cache = ValidationCache::new(NonZeroUsize::new(4).expect("zero capacity"));
+        let extract = |amount: &'static str| {
+            let (mut parts, _) = Request::builder()
+                .uri("/")
+                .header(&AMOUNT, amount)
+                .body(())
+                .expect("invalid request")
+                .into_parts();
+            let cache = cache.clone();
+            async move {
+                CachedValid::<TypedHeader<Payment>>::from_request_parts(&mut parts, &cache).await
+            }
+        };
+
+        let CachedValid(TypedHeader(payment)) = extract("5").await.expect("valid payment rejected");
+        assert_eq!(payment.amount, 5);
+        assert!(matches!(
+            extract("0").await,
+            Err(CachedValidRejection::Valid(_))
+        ));
+        assert_eq!((cache.misses(), cache.hits()), (2, 0));
+    }
+}
@@ -2,19 +2,31 @@
 //!
 //! ## Feature
 //!
-//! Enable the `validator` feature (enabled by default) to use `Valid<E>` and `ValidEx<E, A>`.
+//! Enable the `validator` feature (enabled by default) to use `Valid<E>`, `ValidEx<E, A>`, `OptionalValid<E>`, `ValidResult<E>`
+//! and `ValidAll<E>`.
+//!
+//! Enable the `validate_response` feature to also return `Valid<T>` from handlers: the data is validated on the
+//! way out, and a response with invalid data is replaced by the validation errors with a `500` status,
+//! since it is a bug of the handler rather than of the request.
 //!
 
 #[cfg(test)]
-pub mod test;
+mod test;
 
-use crate::{HasValidate, ValidationRejection};
+use crate::{ErrorPathSegment, HasValidate, ValidationRejection};
 use axum::async_trait;
+use axum::body::HttpBody;
 use axum::extract::{FromRef, FromRequest, FromRequestParts, Request};
+use axum::http::header::CONTENT_LENGTH;
 use axum::http::request::Parts;
+#[cfg(feature = "validate_response")]
+use axum::http::StatusCode;
+#[cfg(feature = "validate_response")]
+use axum::response::{IntoResponse, Response};
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::ops::{Deref, DerefMut};
-use validator::{Validate, ValidateArgs, ValidationErrors};
+use validator::{Validate, ValidateArgs, ValidationError, ValidationErrors};
 
 /// # `Valid` data extractor
 ///
@@ -55,6 +67,56 @@ impl<E> Valid<E> {
     pub fn into_inner(self) -> E {
         self.0
     }
+
+    /// Maps the inner value with `f`, keeping it wrapped in `Valid`.
+    ///
+    /// The result is not validated again, so `f` should preserve what was checked, e.g. convert the
+    /// extracted payload into a data transfer object:
+    ///
+    /// ```
+    /// use axum_valid::Valid;
+    ///
+    /// struct Profile {
+    ///     name: String,
+    /// }
+    ///
+    /// struct ProfileDto {
+    ///     display_name: String,
+    /// }
+    ///
+    /// impl Profile {
+    ///     fn into_dto(self) -> ProfileDto {
+    ///         ProfileDto {
+    ///             display_name: self.name,
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let valid = Valid(Profile {
+    ///     name: String::from("axum"),
+    /// });
+    /// let dto: Valid<ProfileDto> = valid.map(|p| p.into_dto());
+    /// assert_eq!(dto.display_name, "axum");
+    /// ```
+    pub fn map<U>(self, f: impl FnOnce(E) -> U) -> Valid<U> {
+        Valid(f(self.0))
+    }
+
+    /// Maps the inner value with the fallible `f`, keeping it wrapped in `Valid` on success.
+    ///
+    /// ```
+    /// use axum_valid::Valid;
+    ///
+    /// let valid = Valid(String::from("42"));
+    /// let number: Result<Valid<u32>, _> = valid.try_map(|s| s.parse::<u32>());
+    /// assert_eq!(number.map(Valid::into_inner), Ok(42));
+    /// ```
+    pub fn try_map<U, Error>(
+        self,
+        f: impl FnOnce(E) -> Result<U, Error>,
+    ) -> Result<Valid<U>, Error> {
+        f(self.0).map(Valid)
+    }
 }
 
 #[cfg(feature = "aide")]
@@ -67,6 +129,46 @@ where
     }
 }
 
+#[cfg(feature = "validate_response")]
+impl<E> IntoResponse for Valid<E>
+where
+    E: IntoResponse + HasValidate,
+    E::Validate: Validate,
+{
+    fn into_response(self) -> Response {
+        match self.get_validate().validate() {
+            Ok(()) => self.0.into_response(),
+            Err(errors) => {
+                let mut response = crate::validation_errors_response(&errors);
+                *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                response
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "aide", feature = "validate_response"))]
+impl<T> aide::OperationOutput for Valid<T>
+where
+    T: aide::OperationOutput,
+{
+    type Inner = T::Inner;
+
+    fn operation_response(
+        ctx: &mut aide::gen::GenContext,
+        operation: &mut aide::openapi::Operation,
+    ) -> Option<aide::openapi::Response> {
+        T::operation_response(ctx, operation)
+    }
+
+    fn inferred_responses(
+        ctx: &mut aide::gen::GenContext,
+        operation: &mut aide::openapi::Operation,
+    ) -> Vec<(Option<u16>, aide::openapi::Response)> {
+        T::inferred_responses(ctx, operation)
+    }
+}
+
 /// # `ValidEx` data extractor
 ///
 /// `ValidEx` can be incorporated with extractors from various modules, similar to `Valid`.
@@ -111,6 +213,19 @@ impl<E> ValidEx<E> {
     pub fn into_inner(self) -> E {
         self.0
     }
+
+    /// Maps the validated value with `f`, keeping it wrapped in `ValidEx`, like `Valid::map`.
+    pub fn map<U>(self, f: impl FnOnce(E) -> U) -> ValidEx<U> {
+        ValidEx(f(self.0))
+    }
+
+    /// Maps the validated value with the fallible `f`, keeping it wrapped in `ValidEx` on success.
+    pub fn try_map<U, Error>(
+        self,
+        f: impl FnOnce(E) -> Result<U, Error>,
+    ) -> Result<ValidEx<U>, Error> {
+        f(self.0).map(ValidEx)
+    }
 }
 
 #[cfg(feature = "aide")]
@@ -123,7 +238,232 @@ where
     }
 }
 
-/// `ValidRejection` is returned when the `Valid` or `ValidEx` extractor fails.
+/// # `OptionalValid` data extractor
+///
+/// `OptionalValid` extracts and validates an optional request body, e.g. `OptionalValid<Json<T>>`:
+///
+/// - A request without a body yields `OptionalValid(None)`, and nothing is validated.
+/// - A request with a body yields `OptionalValid(Some(_))` once the body is extracted and validated like
+///   `Valid` would do, so a malformed or invalid body is rejected rather than treated as absent.
+///
+/// A body is absent when the request declares it empty, with `Content-Length: 0` or a body known to be empty.
+/// The body is not read to find out, so a chunked (or HTTP/2) request without a length is treated as having
+/// a body even if it sends no data, and is handed to the inner extractor, which usually rejects it.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OptionalValid<E>(pub Option<E>);
+
+impl<E> Deref for OptionalValid<E> {
+    type Target = Option<E>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for OptionalValid<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<E> OptionalValid<E> {
+    /// Consumes the `OptionalValid` and returns the validated data within, if any.
+    pub fn into_inner(self) -> Option<E> {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for OptionalValid<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// Returns `true` if the request declares an empty body, without reading it.
+fn has_empty_body(req: &Request) -> bool {
+    let declared_empty = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.trim() == "0");
+    declared_empty || req.body().size_hint().exact() == Some(0)
+}
+
+/// # `ValidResult` data extractor
+///
+/// `ValidResult` extracts and validates the data like `Valid`, but hands validation errors to the handler
+/// instead of rejecting the request, e.g. to report them as data in service-to-service calls:
+///
+/// - Valid data yields `ValidResult(Ok(Valid(_)))`.
+/// - Invalid data yields `ValidResult(Err(errors))`.
+///
+/// Errors of the inner extractor, e.g. a malformed body, still reject the request with the inner rejection,
+/// since there is nothing to validate.
+///
+#[derive(Debug, Clone)]
+pub struct ValidResult<E>(pub Result<Valid<E>, ValidationErrors>);
+
+impl<E> Deref for ValidResult<E> {
+    type Target = Result<Valid<E>, ValidationErrors>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for ValidResult<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<E> ValidResult<E> {
+    /// Consumes the `ValidResult` and returns the validated data, or the validation errors.
+    pub fn into_inner(self) -> Result<Valid<E>, ValidationErrors> {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for ValidResult<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// Turns the validation errors of a `Valid` rejection into a `ValidResult`, keeping the other errors.
+fn into_valid_result<E, R>(
+    result: Result<Valid<E>, ValidRejection<R>>,
+) -> Result<ValidResult<E>, R> {
+    match result {
+        Ok(valid) => Ok(ValidResult(Ok(valid))),
+        Err(ValidRejection::Valid(errors)) => Ok(ValidResult(Err(errors))),
+        Err(ValidRejection::Inner(rejection)) => Err(rejection),
+    }
+}
+
+/// # `ValidAll` data extractor
+///
+/// `ValidAll` validates every element of a collection extracted by the inner extractor,
+/// e.g. `ValidAll<Json<Vec<T>>>`, reporting the errors of each element under its index.
+///
+/// With the `into_json` feature, the errors are rendered as a JSON object mapping paths such as `[3].name`
+/// to the errors found there.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidAll<E>(pub E);
+
+impl<E> Deref for ValidAll<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for ValidAll<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Display> Display for ValidAll<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<E> ValidAll<E> {
+    /// Consumes the `ValidAll` and returns the validated data within.
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+#[cfg(feature = "aide")]
+impl<T> aide::OperationInput for ValidAll<T>
+where
+    T: aide::OperationInput,
+{
+    fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+        T::operation_input(ctx, operation);
+    }
+}
+
+/// The validation errors of the elements of a collection, by index, found by `ValidAll`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ValidAllErrors(pub BTreeMap<usize, ValidationErrors>);
+
+impl ValidAllErrors {
+    /// Returns the errors found at each path, e.g. `[3].name` or `[0].items[1].price`.
+    pub fn paths(&self) -> BTreeMap<String, &[ValidationError]> {
+        let mut paths = BTreeMap::new();
+        for (index, errors) in &self.0 {
+            crate::flatten_errors(errors, &mut |path, errors| {
+                let path = [&[ErrorPathSegment::Index(*index)], path].concat();
+                paths.insert(crate::format_error_path(&path, true), errors);
+            });
+        }
+        paths
+    }
+}
+
+impl Display for ValidAllErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut first = true;
+        for (path, errors) in self.paths() {
+            for error in errors {
+                if !first {
+                    writeln!(f)?;
+                }
+                first = false;
+                write!(f, "{path}: {error}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidAllErrors {}
+
+#[cfg(feature = "into_json")]
+impl serde::Serialize for ValidAllErrors {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.paths().serialize(serializer)
+    }
+}
+
+/// `ValidAllRejection` is returned when the `ValidAll` extractor fails.
+///
+pub type ValidAllRejection<E> = ValidationRejection<ValidAllErrors, E>;
+
+/// Validates every item, keeping the errors of each failing one under its index.
+fn validate_all<I>(items: I) -> Result<(), ValidAllErrors>
+where
+    I: IntoIterator,
+    I::Item: Validate,
+{
+    let errors = items
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, item)| item.validate().err().map(|errors| (index, errors)))
+        .collect::<BTreeMap<_, _>>();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ValidAllErrors(errors))
+    }
+}
+
+/// `ValidRejection` is returned when the `Valid`, `ValidEx` or `OptionalValid` extractor fails.
 ///
 pub type ValidRejection<E> = ValidationRejection<ValidationErrors, E>;
 
@@ -221,8 +561,92 @@ where
     }
 }
 
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for OptionalValid<Extractor>
+where
+    State: Send + Sync,
+    Extractor: HasValidate + FromRequest<State>,
+    Extractor::Validate: Validate,
+{
+    type Rejection = ValidRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        if has_empty_body(&req) {
+            return Ok(OptionalValid(None));
+        }
+        let Valid(inner) = Valid::<Extractor>::from_request(req, state).await?;
+        Ok(OptionalValid(Some(inner)))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for ValidResult<Extractor>
+where
+    State: Send + Sync,
+    Extractor: HasValidate + FromRequest<State>,
+    Extractor::Validate: Validate,
+{
+    type Rejection = <Extractor as FromRequest<State>>::Rejection;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        into_valid_result(Valid::<Extractor>::from_request(req, state).await)
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequestParts<State> for ValidResult<Extractor>
+where
+    State: Send + Sync,
+    Extractor: HasValidate + FromRequestParts<State>,
+    Extractor::Validate: Validate,
+{
+    type Rejection = <Extractor as FromRequestParts<State>>::Rejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        into_valid_result(Valid::<Extractor>::from_request_parts(parts, state).await)
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequest<State> for ValidAll<Extractor>
+where
+    State: Send + Sync,
+    Extractor: HasValidate + FromRequest<State>,
+    for<'a> &'a Extractor::Validate: IntoIterator,
+    for<'a> <&'a Extractor::Validate as IntoIterator>::Item: Validate,
+{
+    type Rejection = ValidAllRejection<<Extractor as FromRequest<State>>::Rejection>;
+
+    async fn from_request(req: Request, state: &State) -> Result<Self, Self::Rejection> {
+        let inner = Extractor::from_request(req, state)
+            .await
+            .map_err(ValidAllRejection::Inner)?;
+        validate_all(inner.get_validate()).map_err(ValidAllRejection::Valid)?;
+        Ok(ValidAll(inner))
+    }
+}
+
+#[async_trait]
+impl<State, Extractor> FromRequestParts<State> for ValidAll<Extractor>
+where
+    State: Send + Sync,
+    Extractor: HasValidate + FromRequestParts<State>,
+    for<'a> &'a Extractor::Validate: IntoIterator,
+    for<'a> <&'a Extractor::Validate as IntoIterator>::Item: Validate,
+{
+    type Rejection = ValidAllRejection<<Extractor as FromRequestParts<State>>::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let inner = Extractor::from_request_parts(parts, state)
+            .await
+            .map_err(ValidAllRejection::Inner)?;
+        validate_all(inner.get_validate()).map_err(ValidAllRejection::Valid)?;
+        Ok(ValidAll(inner))
+    }
+}
+
 #[cfg(test)]
-pub mod tests {
+mod tests {
     use super::*;
     use std::error::Error;
     use std::fmt::Formatter;
@@ -283,6 +707,427 @@ pub mod tests {
         assert_eq!(ve.v, v);
     }
 
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn valid_ex_arguments_from_state() {
+        use axum::body::Body;
+        use axum::extract::FromRef;
+        use axum::Json;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        #[derive(Clone, Default)]
+        struct AppState {
+            derived: Arc<AtomicUsize>,
+            validated: Arc<AtomicUsize>,
+        }
+
+        struct Limits {
+            max: u32,
+            validated: Arc<AtomicUsize>,
+        }
+
+        impl FromRef<AppState> for Limits {
+            fn from_ref(state: &AppState) -> Self {
+                state.derived.fetch_add(1, Ordering::SeqCst);
+                Limits {
+                    max: 10,
+                    validated: state.validated.clone(),
+                }
+            }
+        }
+
+        fn below_max(quantity: u32, limits: &Limits) -> Result<(), ValidationError> {
+            limits.validated.fetch_add(1, Ordering::SeqCst);
+            if quantity > limits.max {
+                return Err(ValidationError::new("max"));
+            }
+            Ok(())
+        }
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        #[validate(context = Limits)]
+        struct Order {
+            #[validate(custom(function = "below_max", use_context))]
+            quantity: u32,
+        }
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+        let state = AppState::default();
+
+        let ValidEx(Json(order)) =
+            ValidEx::<Json<Order>>::from_request(request(r#"{ "quantity": 3 }"#), &state)
+                .await
+                .expect("valid order rejected");
+        assert_eq!(order.quantity, 3);
+        assert!(matches!(
+            ValidEx::<Json<Order>>::from_request(request(r#"{ "quantity": 30 }"#), &state).await,
+            Err(ValidRejection::Valid(_))
+        ));
+        assert_eq!(state.derived.load(Ordering::SeqCst), 2);
+        assert_eq!(state.validated.load(Ordering::SeqCst), 2);
+
+        assert!(matches!(
+            ValidEx::<Json<Order>>::from_request(request("{"), &state).await,
+            Err(ValidRejection::Inner(_))
+        ));
+        assert_eq!(state.derived.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            state.validated.load(Ordering::SeqCst),
+            2,
+            "validation should not run after a parse failure"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn optional_valid_skips_missing_body() {
+        use axum::body::Body;
+        use axum::Json;
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        struct Filter {
+            #[validate(range(min = 1, max = 100))]
+            limit: u32,
+        }
+
+        let request = |body: &'static str| {
+            let builder = Request::builder().uri("/");
+            match body {
+                "" => builder.body(Body::empty()),
+                body => builder
+                    .header("content-type", "application/json")
+                    .body(Body::from(body)),
+            }
+            .expect("invalid request")
+        };
+
+        let OptionalValid(filter) = OptionalValid::<Json<Filter>>::from_request(request(""), &())
+            .await
+            .expect("missing body rejected");
+        assert!(filter.is_none());
+
+        let OptionalValid(filter) =
+            OptionalValid::<Json<Filter>>::from_request(request(r#"{ "limit": 10 }"#), &())
+                .await
+                .expect("valid body rejected");
+        assert_eq!(filter.map(|Json(filter)| filter.limit), Some(10));
+
+        assert!(matches!(
+            OptionalValid::<Json<Filter>>::from_request(request(r#"{ "limit": 1000 }"#), &()).await,
+            Err(ValidRejection::Valid(_))
+        ));
+        assert!(matches!(
+            OptionalValid::<Json<Filter>>::from_request(request("{"), &()).await,
+            Err(ValidRejection::Inner(_))
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn optional_valid_treats_chunked_body_as_present() -> anyhow::Result<()> {
+        use axum::routing::post;
+        use axum::{Json, Router};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::{TcpListener, TcpStream};
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        struct Filter {
+            #[validate(range(min = 1, max = 100))]
+            limit: u32,
+        }
+
+        let router = Router::new().route(
+            "/",
+            post(
+                |OptionalValid(filter): OptionalValid<Json<Filter>>| async move {
+                    filter.is_some().to_string()
+                },
+            ),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        // An empty chunked body has no length, so it is not known to be empty
+        let mut stream = TcpStream::connect(address).await?;
+        stream
+            .write_all(
+                b"POST / HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+                  Transfer-Encoding: chunked\r\nConnection: close\r\n\r\n0\r\n\r\n",
+            )
+            .await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        assert!(
+            response.starts_with("HTTP/1.1 400"),
+            "unexpected response: {response}"
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn valid_result_hands_errors_to_handler() {
+        use axum::body::Body;
+        use axum::Json;
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        struct Transfer {
+            #[validate(range(min = 1))]
+            amount: u64,
+        }
+
+        async fn handler(ValidResult(transfer): ValidResult<Json<Transfer>>) -> String {
+            match transfer {
+                Ok(Valid(Json(transfer))) => transfer.amount.to_string(),
+                Err(errors) => errors.field_errors().keys().copied().collect(),
+            }
+        }
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+        let extract = |body| ValidResult::<Json<Transfer>>::from_request(request(body), &());
+
+        let transfer = extract(r#"{ "amount": 5 }"#)
+            .await
+            .expect("valid transfer rejected");
+        assert_eq!(handler(transfer).await, "5");
+
+        let transfer = extract(r#"{ "amount": 0 }"#)
+            .await
+            .expect("invalid transfer rejected before the handler");
+        assert!(matches!(&transfer.0, Err(errors) if errors.field_errors().contains_key("amount")));
+        assert_eq!(handler(transfer).await, "amount");
+
+        assert!(extract("{").await.is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn valid_all_reports_failing_indices() {
+        use axum::body::Body;
+        use axum::response::IntoResponse;
+        use axum::Json;
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        struct Item {
+            #[validate(length(min = 1))]
+            name: String,
+            #[validate(range(max = 10))]
+            quantity: u32,
+        }
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+
+        let ValidAll(Json(items)) = ValidAll::<Json<Vec<Item>>>::from_request(
+            request(r#"[{ "name": "a", "quantity": 1 }, { "name": "b", "quantity": 2 }]"#),
+            &(),
+        )
+        .await
+        .expect("valid items rejected");
+        assert_eq!(items.len(), 2);
+
+        let rejection = ValidAll::<Json<Vec<Item>>>::from_request(
+            request(
+                r#"[
+                    { "name": "a", "quantity": 1 },
+                    { "name": "", "quantity": 2 },
+                    { "name": "c", "quantity": 3 },
+                    { "name": "", "quantity": 40 }
+                ]"#,
+            ),
+            &(),
+        )
+        .await
+        .expect_err("invalid items accepted");
+        let ValidAllRejection::Valid(errors) = &rejection else {
+            panic!("unexpected rejection: {rejection:?}");
+        };
+        assert_eq!(errors.0.keys().copied().collect::<Vec<_>>(), [1, 3]);
+        assert_eq!(
+            errors.paths().into_keys().collect::<Vec<_>>(),
+            ["[1].name", "[3].name", "[3].quantity"]
+        );
+        assert!(errors.to_string().contains("[3].name: "));
+
+        let response = rejection.into_response();
+        assert_eq!(response.status(), crate::VALIDATION_ERROR_STATUS);
+        #[cfg(feature = "into_json")]
+        {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .expect("failed to read body");
+            let body: serde_json::Value = serde_json::from_slice(&body).expect("invalid json");
+            assert_eq!(body["[1].name"][0]["code"], "length");
+            assert_eq!(body["[3].quantity"][0]["code"], "range");
+            assert!(body.get("[0].name").is_none());
+        }
+
+        assert!(matches!(
+            ValidAll::<Json<Vec<Item>>>::from_request(request("{"), &()).await,
+            Err(ValidAllRejection::Inner(_))
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn rejection_into_status_and_message() {
+        use axum::body::Body;
+        use axum::http::StatusCode;
+        use axum::Json;
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        struct Parameter {
+            #[validate(range(min = 5, max = 10))]
+            v0: i32,
+        }
+
+        async fn handler(request: Request) -> Result<i32, (StatusCode, String)> {
+            let Valid(Json(parameter)) =
+                Valid::<Json<Parameter>>::from_request(request, &()).await?;
+            Ok(parameter.v0)
+        }
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .uri("/")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("invalid request")
+        };
+
+        assert_eq!(handler(request(r#"{ "v0": 7 }"#)).await, Ok(7));
+
+        let (status, invalid) = handler(request(r#"{ "v0": 1 }"#))
+            .await
+            .expect_err("invalid parameter accepted");
+        assert_eq!(status, crate::VALIDATION_ERROR_STATUS);
+        assert!(invalid.starts_with("v0: "), "{invalid}");
+
+        let (status, malformed) = handler(request("{"))
+            .await
+            .expect_err("malformed parameter accepted");
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(malformed.contains("Failed to parse"), "{malformed}");
+        assert_ne!(invalid, malformed);
+    }
+
+    #[cfg(all(feature = "validate_response", feature = "json"))]
+    #[tokio::test]
+    async fn validate_response() {
+        use axum::http::StatusCode;
+        use axum::response::IntoResponse;
+        use axum::Json;
+
+        #[derive(Debug, Validate, serde::Serialize)]
+        struct User {
+            #[validate(length(min = 1))]
+            name: String,
+        }
+
+        let body = |response: axum::response::Response| async {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .expect("failed to read body");
+            String::from_utf8(bytes.to_vec()).expect("body is not UTF-8")
+        };
+
+        let response = Valid(Json(User {
+            name: String::from("axum"),
+        }))
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body(response).await, r#"{"name":"axum"}"#);
+
+        let response = Valid(Json(User {
+            name: String::new(),
+        }))
+        .into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = body(response).await;
+        assert!(body.contains("name") && body.contains("length"), "{body}");
+    }
+
+    #[tokio::test]
+    async fn deterministic_error_rendering() {
+        use axum::response::IntoResponse;
+
+        let fields = ["d", "a", "c", "e", "b", "f", "h", "g"];
+        let errors = |fields: &mut dyn Iterator<Item = &&'static str>| {
+            let mut errors = ValidationErrors::new();
+            for field in fields {
+                errors.add(field, ValidationError::new("length"));
+                errors.add(field, ValidationError::new("email"));
+            }
+            errors
+        };
+        let render = |errors: ValidationErrors| async {
+            let response = ValidRejection::<String>::Valid(errors).into_response();
+            assert_eq!(response.status(), crate::VALIDATION_ERROR_STATUS);
+            axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .expect("failed to read body")
+        };
+
+        let first = render(errors(&mut fields.iter())).await;
+        let second = render(errors(&mut fields.iter().rev())).await;
+        assert_eq!(first, second);
+
+        let body = String::from_utf8(first.to_vec()).expect("body is not UTF-8");
+        #[cfg(feature = "into_json")]
+        let marker = |field: &str| format!("\"{field}\":");
+        #[cfg(not(feature = "into_json"))]
+        let marker = |field: &str| format!("\n{field}: ");
+        let body = format!("\n{body}");
+        let positions = ["a", "b", "h"].map(|field| body.find(&marker(field)));
+        assert!(positions.iter().all(Option::is_some), "{body}");
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]), "{body}");
+        #[cfg(feature = "into_json")]
+        assert!(body.find("email") < body.find("length"), "{body}");
+    }
+
+    #[cfg(feature = "dedup_errors")]
+    #[tokio::test]
+    async fn duplicate_errors_are_collapsed() {
+        use axum::response::IntoResponse;
+        use std::borrow::Cow;
+
+        let mut errors = ValidationErrors::new();
+        errors.add("name", ValidationError::new("length"));
+        errors.add("name", ValidationError::new("length"));
+        errors.add(
+            "name",
+            ValidationError::new("length").with_message(Cow::from("too short")),
+        );
+        errors.add("email", ValidationError::new("length"));
+
+        let response = ValidRejection::<String>::Valid(errors).into_response();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("failed to read body");
+        let body = serde_json::from_slice::<serde_json::Value>(&body).expect("body is not JSON");
+        assert_eq!(body["name"].as_array().map(Vec::len), Some(2), "{body}");
+        assert_eq!(body["email"].as_array().map(Vec::len), Some(1), "{body}");
+    }
+
     #[test]
     fn display_error() {
         // ValidRejection::Valid Display
@@ -305,7 +1150,7 @@ pub mod tests {
         );
 
         // ValidRejection::Valid Error
-        let vr = ValidRejection::<io::Error>::Inner(io::Error::new(io::ErrorKind::Other, TEST));
+        let vr = ValidRejection::<io::Error>::Inner(io::Error::other(TEST));
         assert!(
             matches!(vr.source(), Some(source) if source.downcast_ref::<io::Error>().is_some())
         );
@@ -6,7 +6,7 @@
 //!
 
 #[cfg(test)]
-pub mod test;
+mod test;
 
 use crate::{HasValidate, ValidationRejection};
 use axum::async_trait;
@@ -54,6 +54,19 @@ impl<E> Validated<E> {
     pub fn into_inner(self) -> E {
         self.0
     }
+
+    /// Maps the validated value with `f`, keeping it wrapped in `Validated`, like `Valid::map`.
+    pub fn map<U>(self, f: impl FnOnce(E) -> U) -> Validated<U> {
+        Validated(f(self.0))
+    }
+
+    /// Maps the validated value with the fallible `f`, keeping it wrapped in `Validated` on success.
+    pub fn try_map<U, Error>(
+        self,
+        f: impl FnOnce(E) -> Result<U, Error>,
+    ) -> Result<Validated<U>, Error> {
+        f(self.0).map(Validated)
+    }
 }
 
 #[cfg(feature = "aide")]
@@ -113,6 +126,19 @@ impl<E> Modified<E> {
     pub fn into_inner(self) -> E {
         self.0
     }
+
+    /// Maps the modified value with `f`, keeping it wrapped in `Modified`, like `Valid::map`.
+    pub fn map<U>(self, f: impl FnOnce(E) -> U) -> Modified<U> {
+        Modified(f(self.0))
+    }
+
+    /// Maps the modified value with the fallible `f`, keeping it wrapped in `Modified` on success.
+    pub fn try_map<U, Error>(
+        self,
+        f: impl FnOnce(E) -> Result<U, Error>,
+    ) -> Result<Modified<U>, Error> {
+        f(self.0).map(Modified)
+    }
 }
 
 impl<E: IntoResponse + HasModify> IntoResponse for Modified<E> {
@@ -193,6 +219,19 @@ impl<E> Validified<E> {
     pub fn into_inner(self) -> E {
         self.0
     }
+
+    /// Maps the modified and validated value with `f`, keeping it wrapped in `Validified`, like `Valid::map`.
+    pub fn map<U>(self, f: impl FnOnce(E) -> U) -> Validified<U> {
+        Validified(f(self.0))
+    }
+
+    /// Maps the modified and validated value with the fallible `f`, keeping it wrapped in `Validified` on success.
+    pub fn try_map<U, Error>(
+        self,
+        f: impl FnOnce(E) -> Result<U, Error>,
+    ) -> Result<Validified<U>, Error> {
+        f(self.0).map(Validified)
+    }
 }
 
 #[cfg(feature = "aide")]
@@ -242,6 +281,19 @@ impl<E> ValidifiedByRef<E> {
     pub fn into_inner(self) -> E {
         self.0
     }
+
+    /// Maps the modified and validated value with `f`, keeping it wrapped in `ValidifiedByRef`, like `Valid::map`.
+    pub fn map<U>(self, f: impl FnOnce(E) -> U) -> ValidifiedByRef<U> {
+        ValidifiedByRef(f(self.0))
+    }
+
+    /// Maps the modified and validated value with the fallible `f`, keeping it wrapped in `ValidifiedByRef` on success.
+    pub fn try_map<U, Error>(
+        self,
+        f: impl FnOnce(E) -> Result<U, Error>,
+    ) -> Result<ValidifiedByRef<U>, Error> {
+        f(self.0).map(ValidifiedByRef)
+    }
 }
 
 #[cfg(feature = "aide")]
@@ -517,13 +569,33 @@ mod tests {
         );
 
         // ValidifyRejection::Valid Error
-        let vr =
-            ValidifyRejection::<io::Error>::Inner(io::Error::new(io::ErrorKind::Other, VALIDIFY));
+        let vr = ValidifyRejection::<io::Error>::Inner(io::Error::other(VALIDIFY));
         assert!(
             matches!(vr.source(), Some(source) if source.downcast_ref::<io::Error>().is_some())
         );
     }
 
+    #[test]
+    fn rejection_into_status_and_message() {
+        use axum::extract::rejection::MissingJsonContentType;
+        use axum::http::StatusCode;
+
+        let mut report = ValidationErrors::new();
+        report.add(validify::ValidationError::new_schema(VALIDIFY));
+        let message = report.to_string();
+        let (status, valid) = <(StatusCode, String)>::from(ValidifyRejection::<
+            MissingJsonContentType,
+        >::Valid(report));
+        assert_eq!(status, crate::VALIDATION_ERROR_STATUS);
+        assert_eq!(valid, message.trim_end());
+
+        let (status, inner) = <(StatusCode, String)>::from(ValidifyRejection::Inner(
+            MissingJsonContentType::default(),
+        ));
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_ne!(valid, inner);
+    }
+
     #[test]
     fn modified_into_response() {
         use validify::Validify;
@@ -28,6 +28,7 @@ pub struct ParametersValidify {
     v1: String,
 }
 
+#[cfg(feature = "extra_protobuf")]
 #[derive(Clone, Validify, Eq, PartialEq)]
 #[cfg_attr(feature = "extra_protobuf", derive(Message))]
 #[cfg_attr(
@@ -64,12 +65,14 @@ static INVALID_PARAMETERS: Lazy<ParametersValidify> = Lazy::new(|| ParametersVal
     v1: String::from("ABCDEFGHIJKLMN"),
 });
 
+#[cfg(feature = "extra_protobuf")]
 static VALID_PARAMETERS_WITHOUT_PAYLOAD: Lazy<ParametersValidifyWithoutPayload> =
     Lazy::new(|| ParametersValidifyWithoutPayload {
         v0: 5,
         v1: String::from("ABCDEFG"),
     });
 
+#[cfg(feature = "extra_protobuf")]
 static INVALID_PARAMETERS_WITHOUT_PAYLOAD: Lazy<ParametersValidifyWithoutPayload> =
     Lazy::new(|| ParametersValidifyWithoutPayload {
         v0: 6,
@@ -90,6 +93,7 @@ impl ValidTestParameter for ParametersValidify {
     }
 }
 
+#[cfg(feature = "extra_protobuf")]
 impl ValidTestParameter for ParametersValidifyWithoutPayload {
     fn valid() -> &'static Self {
         VALID_PARAMETERS_WITHOUT_PAYLOAD.deref()
@@ -175,4 +175,29 @@ mod tests {
                 )
         }
     }
+
+    #[cfg(feature = "validator")]
+    #[tokio::test]
+    async fn malformed_body_is_inner_rejection() {
+        use crate::Valid;
+        use axum::body::Body;
+        use axum::extract::{FromRequest, Request};
+        use validator::Validate;
+
+        #[derive(Debug, Validate, serde::Deserialize)]
+        struct Envelope {
+            #[validate(length(min = 1))]
+            body: String,
+        }
+
+        let request = Request::builder()
+            .uri("/")
+            .header("content-type", "application/xml")
+            .body(Body::from("<Envelope><body>unclosed</Envelope>"))
+            .expect("invalid request");
+        assert!(matches!(
+            Valid::<Xml<Envelope>>::from_request(request, &()).await,
+            Err(crate::ValidRejection::Inner(_))
+        ));
+    }
 }